@@ -0,0 +1,14 @@
+-- Quiet hours and do-not-disturb rules for reminders
+
+-- Set while a reminder is held back by quiet hours or DND, NULL otherwise
+ALTER TABLE reminders ADD COLUMN deferred_until TEXT; -- ISO 8601 format
+
+CREATE INDEX idx_reminders_deferred ON reminders(deferred_until);
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('quiet_hours_enabled', '0'),
+    ('quiet_hours_start', '22:00'), -- HH:MM, local time
+    ('quiet_hours_end', '07:00'), -- may be earlier than start to span midnight
+    ('dnd_during_pomodoro', '1'), -- mute while a Pomodoro work session runs
+    ('dnd_override_priority', '1'), -- priority values up to this one break through DND, 0 disables
+    ('dnd_suppressed_mode', 'DIGEST'); -- 'DEFER' delivers one by one, 'DIGEST' batches them
@@ -0,0 +1,47 @@
+-- Pluggable delivery channels for reminders
+
+CREATE TABLE delivery_channels (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    channel_type TEXT NOT NULL CHECK (channel_type IN ('DESKTOP', 'EMAIL', 'WEBHOOK')),
+    config TEXT NOT NULL, -- JSON, shape depends on channel_type
+    is_enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Channels chosen for a single reminder take precedence over the item's category
+CREATE TABLE reminder_channels (
+    reminder_id INTEGER NOT NULL REFERENCES reminders(id) ON DELETE CASCADE,
+    channel_id INTEGER NOT NULL REFERENCES delivery_channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (reminder_id, channel_id)
+);
+
+CREATE TABLE category_channels (
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    channel_id INTEGER NOT NULL REFERENCES delivery_channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (category_id, channel_id)
+);
+
+-- One row per delivery attempt
+CREATE TABLE reminder_delivery_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reminder_id INTEGER NOT NULL REFERENCES reminders(id) ON DELETE CASCADE,
+    channel_id INTEGER REFERENCES delivery_channels(id) ON DELETE SET NULL, -- NULL for the built-in desktop channel
+    channel_type TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('SENT', 'FAILED')),
+    error TEXT,
+    attempted_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_delivery_log_reminder ON reminder_delivery_log(reminder_id, channel_id);
+
+CREATE TRIGGER delivery_channels_updated_at AFTER UPDATE ON delivery_channels
+BEGIN
+    UPDATE delivery_channels SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('delivery_max_attempts', '5'),
+    ('delivery_backoff_seconds', '60'); -- doubled after every failed attempt
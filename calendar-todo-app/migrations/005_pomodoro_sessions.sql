@@ -0,0 +1,22 @@
+-- Persistent Pomodoro engine state so a running session survives app restarts
+
+CREATE TABLE pomodoro_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_type TEXT NOT NULL CHECK (item_type IN ('EVENT', 'TASK', 'CATEGORY', 'MANUAL')),
+    item_id INTEGER,
+    phase TEXT NOT NULL CHECK (phase IN ('WORK', 'SHORT_BREAK', 'LONG_BREAK')),
+    state TEXT NOT NULL CHECK (state IN ('RUNNING', 'PAUSED', 'STOPPED')),
+    phase_started_at TEXT NOT NULL, -- ISO 8601, when the phase last started or resumed running
+    elapsed_seconds INTEGER NOT NULL DEFAULT 0, -- time spent in the phase before phase_started_at
+    completed_work_intervals INTEGER NOT NULL DEFAULT 0,
+    time_entry_id INTEGER REFERENCES time_tracking(id) ON DELETE SET NULL, -- open entry of the current work phase
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_pomodoro_sessions_state ON pomodoro_sessions(state);
+
+CREATE TRIGGER pomodoro_sessions_updated_at AFTER UPDATE ON pomodoro_sessions
+BEGIN
+    UPDATE pomodoro_sessions SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
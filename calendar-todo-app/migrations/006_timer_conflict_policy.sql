@@ -0,0 +1,4 @@
+-- Single running timer policy for time tracking
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('timer_conflict_policy', 'AUTO_STOP'); -- 'AUTO_STOP' stops the running timer, 'REJECT' refuses to start another
@@ -0,0 +1,30 @@
+-- Task estimates and category time budgets
+
+ALTER TABLE tasks ADD COLUMN estimated_minutes INTEGER CHECK (estimated_minutes IS NULL OR estimated_minutes > 0);
+
+CREATE TABLE category_budgets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    period TEXT NOT NULL CHECK (period IN ('WEEKLY', 'MONTHLY')),
+    budget_minutes INTEGER NOT NULL CHECK (budget_minutes > 0),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (category_id, period)
+);
+
+-- Thresholds already announced, so each crossing is only notified once
+CREATE TABLE budget_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    scope TEXT NOT NULL CHECK (scope IN ('TASK', 'CATEGORY')),
+    scope_id INTEGER NOT NULL,
+    period TEXT NOT NULL, -- 'WEEKLY' or 'MONTHLY' for categories, 'ESTIMATE' for tasks
+    period_start TEXT NOT NULL, -- YYYY-MM-DD, empty for task estimates
+    threshold INTEGER NOT NULL CHECK (threshold IN (80, 100)),
+    alerted_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (scope, scope_id, period, period_start, threshold)
+);
+
+CREATE TRIGGER category_budgets_updated_at AFTER UPDATE ON category_budgets
+BEGIN
+    UPDATE category_budgets SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
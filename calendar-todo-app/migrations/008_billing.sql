@@ -0,0 +1,12 @@
+-- Billable clients and rounding rules for invoice exports
+
+-- Categories and participants both act as clients; rates are in cents per hour
+ALTER TABLE categories ADD COLUMN is_billable BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE categories ADD COLUMN hourly_rate_cents INTEGER CHECK (hourly_rate_cents IS NULL OR hourly_rate_cents >= 0);
+ALTER TABLE participants ADD COLUMN is_billable BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE participants ADD COLUMN hourly_rate_cents INTEGER CHECK (hourly_rate_cents IS NULL OR hourly_rate_cents >= 0);
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('billing_rounding_minutes', '0'), -- round to the nearest 6 or 15 minutes, 0 bills exact time
+    ('billing_rounding_mode', 'ENTRY'), -- 'ENTRY' rounds every entry, 'DAY' rounds each day's total per item
+    ('billing_currency', 'USD');
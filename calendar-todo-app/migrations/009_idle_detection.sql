@@ -0,0 +1,21 @@
+-- Idle gaps detected while a timer was running
+
+CREATE TABLE idle_periods (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    time_entry_id INTEGER REFERENCES time_tracking(id) ON DELETE SET NULL, -- entry running when idling began
+    resumed_entry_id INTEGER REFERENCES time_tracking(id) ON DELETE SET NULL, -- entry started on return after a pause
+    item_type TEXT NOT NULL,
+    item_id INTEGER,
+    timer_type TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('PAUSE', 'TRIM')),
+    idle_started_at TEXT NOT NULL, -- ISO 8601, last user input before the gap
+    idle_ended_at TEXT, -- ISO 8601, NULL while the user is still away
+    resolution TEXT CHECK (resolution IN ('KEPT', 'DISCARDED')), -- NULL until the user decides
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_idle_periods_open ON idle_periods(idle_ended_at, resolution);
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('idle_threshold_minutes', '10'),
+    ('idle_action', 'PAUSE'); -- 'PAUSE' stops the timer while away, 'TRIM' keeps it running and cuts the gap if discarded
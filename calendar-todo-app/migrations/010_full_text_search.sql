@@ -0,0 +1,89 @@
+-- Full-text search index for events, tasks and notes
+
+-- Notes are created and searched by title, but the initial schema never had the column
+ALTER TABLE notes ADD COLUMN title TEXT NOT NULL DEFAULT '';
+
+-- External-content FTS5 tables: the text lives in the source tables and the
+-- triggers below keep the index in step with every insert, update and delete
+CREATE VIRTUAL TABLE events_fts USING fts5(
+    title, description, location,
+    content = 'events', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    title, description,
+    content = 'tasks', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE notes_fts USING fts5(
+    title, content,
+    content = 'notes', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location)
+    VALUES (NEW.id, NEW.title, NEW.description, NEW.location);
+END;
+
+CREATE TRIGGER events_fts_delete AFTER DELETE ON events
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location)
+    VALUES ('delete', OLD.id, OLD.title, OLD.description, OLD.location);
+END;
+
+CREATE TRIGGER events_fts_update AFTER UPDATE OF title, description, location ON events
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location)
+    VALUES ('delete', OLD.id, OLD.title, OLD.description, OLD.location);
+    INSERT INTO events_fts (rowid, title, description, location)
+    VALUES (NEW.id, NEW.title, NEW.description, NEW.location);
+END;
+
+CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (rowid, title, description)
+    VALUES (NEW.id, NEW.title, NEW.description);
+END;
+
+CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks
+BEGIN
+    INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+    VALUES ('delete', OLD.id, OLD.title, OLD.description);
+END;
+
+CREATE TRIGGER tasks_fts_update AFTER UPDATE OF title, description ON tasks
+BEGIN
+    INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+    VALUES ('delete', OLD.id, OLD.title, OLD.description);
+    INSERT INTO tasks_fts (rowid, title, description)
+    VALUES (NEW.id, NEW.title, NEW.description);
+END;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes
+BEGIN
+    INSERT INTO notes_fts (rowid, title, content)
+    VALUES (NEW.id, NEW.title, NEW.content);
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes
+BEGIN
+    INSERT INTO notes_fts (notes_fts, rowid, title, content)
+    VALUES ('delete', OLD.id, OLD.title, OLD.content);
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF title, content ON notes
+BEGIN
+    INSERT INTO notes_fts (notes_fts, rowid, title, content)
+    VALUES ('delete', OLD.id, OLD.title, OLD.content);
+    INSERT INTO notes_fts (rowid, title, content)
+    VALUES (NEW.id, NEW.title, NEW.content);
+END;
+
+-- Index everything that existed before this migration
+INSERT INTO events_fts (events_fts) VALUES ('rebuild');
+INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild');
+INSERT INTO notes_fts (notes_fts) VALUES ('rebuild');
@@ -0,0 +1,18 @@
+-- Saved searches, shown in the sidebar as smart lists
+
+CREATE TABLE saved_searches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    query TEXT NOT NULL, -- search query language, e.g. 'type:task is:overdue priority:1'
+    sort_by TEXT NOT NULL DEFAULT 'RELEVANCE' CHECK (sort_by IN ('RELEVANCE', 'DATE_ASC', 'DATE_DESC', 'PRIORITY', 'TITLE')),
+    group_by TEXT NOT NULL DEFAULT 'NONE' CHECK (group_by IN ('NONE', 'TYPE', 'CATEGORY', 'STATUS', 'PRIORITY', 'DATE')),
+    is_pinned BOOLEAN NOT NULL DEFAULT 1, -- listed in the sidebar
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TRIGGER saved_searches_updated_at AFTER UPDATE ON saved_searches
+BEGIN
+    UPDATE saved_searches SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
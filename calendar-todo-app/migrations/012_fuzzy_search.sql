@@ -0,0 +1,7 @@
+-- Read-only views of the terms in each full-text index, used to find near
+-- misses when a search has too few exact hits. They read the index directly,
+-- so there is nothing to keep in sync.
+
+CREATE VIRTUAL TABLE events_fts_vocab USING fts5vocab('events_fts', 'row');
+CREATE VIRTUAL TABLE tasks_fts_vocab USING fts5vocab('tasks_fts', 'row');
+CREATE VIRTUAL TABLE notes_fts_vocab USING fts5vocab('notes_fts', 'row');
@@ -0,0 +1,182 @@
+-- Index participant names and emails and category names alongside the text
+-- of events and tasks, so searching for "alice" or "work" finds the items
+-- they belong to.
+--
+-- The indexes now read from views that join in this metadata. Every change
+-- that alters a view row removes the row from the index in a BEFORE trigger,
+-- while the view still shows the indexed values, and adds it back in an
+-- AFTER trigger.
+
+DROP TRIGGER events_fts_insert;
+DROP TRIGGER events_fts_delete;
+DROP TRIGGER events_fts_update;
+DROP TRIGGER tasks_fts_insert;
+DROP TRIGGER tasks_fts_delete;
+DROP TRIGGER tasks_fts_update;
+
+DROP TABLE events_fts_vocab;
+DROP TABLE tasks_fts_vocab;
+DROP TABLE events_fts;
+DROP TABLE tasks_fts;
+
+CREATE VIEW events_search AS
+SELECT e.id, e.title, e.description, e.location,
+       (SELECT group_concat(entry, ' ') FROM (
+            SELECT p.name || ' ' || COALESCE(p.email, '') AS entry
+            FROM event_participants ep JOIN participants p ON p.id = ep.participant_id
+            WHERE ep.event_id = e.id
+            ORDER BY p.id
+       )) AS participants,
+       c.name AS category
+FROM events e
+LEFT JOIN categories c ON c.id = e.category_id;
+
+CREATE VIEW tasks_search AS
+SELECT t.id, t.title, t.description, c.name AS category
+FROM tasks t
+LEFT JOIN categories c ON c.id = t.category_id;
+
+CREATE VIRTUAL TABLE events_fts USING fts5(
+    title, description, location, participants, category,
+    content = 'events_search', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    title, description, category,
+    content = 'tasks_search', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE events_fts_vocab USING fts5vocab('events_fts', 'row');
+CREATE VIRTUAL TABLE tasks_fts_vocab USING fts5vocab('tasks_fts', 'row');
+
+-- Events
+
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER events_fts_delete BEFORE DELETE ON events
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER events_fts_unindex BEFORE UPDATE OF title, description, location, category_id ON events
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER events_fts_reindex AFTER UPDATE OF title, description, location, category_id ON events
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER event_participants_fts_unindex_insert BEFORE INSERT ON event_participants
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search WHERE id = NEW.event_id;
+END;
+
+CREATE TRIGGER event_participants_fts_reindex_insert AFTER INSERT ON event_participants
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search WHERE id = NEW.event_id;
+END;
+
+CREATE TRIGGER event_participants_fts_unindex_delete BEFORE DELETE ON event_participants
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search WHERE id = OLD.event_id;
+END;
+
+CREATE TRIGGER event_participants_fts_reindex_delete AFTER DELETE ON event_participants
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search WHERE id = OLD.event_id;
+END;
+
+CREATE TRIGGER participants_fts_unindex BEFORE UPDATE OF name, email ON participants
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search
+    WHERE id IN (SELECT event_id FROM event_participants WHERE participant_id = OLD.id);
+END;
+
+CREATE TRIGGER participants_fts_reindex AFTER UPDATE OF name, email ON participants
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search
+    WHERE id IN (SELECT event_id FROM event_participants WHERE participant_id = NEW.id);
+END;
+
+-- Detach a deleted participant while it still exists, so the triggers above
+-- see the names that were indexed rather than relying on the cascade
+CREATE TRIGGER participants_fts_detach BEFORE DELETE ON participants
+BEGIN
+    DELETE FROM event_participants WHERE participant_id = OLD.id;
+END;
+
+-- Tasks
+
+CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (rowid, title, description, category)
+    SELECT id, title, description, category FROM tasks_search WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER tasks_fts_delete BEFORE DELETE ON tasks
+BEGIN
+    INSERT INTO tasks_fts (tasks_fts, rowid, title, description, category)
+    SELECT 'delete', id, title, description, category FROM tasks_search WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER tasks_fts_unindex BEFORE UPDATE OF title, description, category_id ON tasks
+BEGIN
+    INSERT INTO tasks_fts (tasks_fts, rowid, title, description, category)
+    SELECT 'delete', id, title, description, category FROM tasks_search WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER tasks_fts_reindex AFTER UPDATE OF title, description, category_id ON tasks
+BEGIN
+    INSERT INTO tasks_fts (rowid, title, description, category)
+    SELECT id, title, description, category FROM tasks_search WHERE id = NEW.id;
+END;
+
+-- Categories
+
+CREATE TRIGGER categories_fts_unindex BEFORE UPDATE OF name ON categories
+BEGIN
+    INSERT INTO events_fts (events_fts, rowid, title, description, location, participants, category)
+    SELECT 'delete', id, title, description, location, participants, category FROM events_search
+    WHERE id IN (SELECT id FROM events WHERE category_id = OLD.id);
+    INSERT INTO tasks_fts (tasks_fts, rowid, title, description, category)
+    SELECT 'delete', id, title, description, category FROM tasks_search
+    WHERE id IN (SELECT id FROM tasks WHERE category_id = OLD.id);
+END;
+
+CREATE TRIGGER categories_fts_reindex AFTER UPDATE OF name ON categories
+BEGIN
+    INSERT INTO events_fts (rowid, title, description, location, participants, category)
+    SELECT id, title, description, location, participants, category FROM events_search
+    WHERE id IN (SELECT id FROM events WHERE category_id = NEW.id);
+    INSERT INTO tasks_fts (rowid, title, description, category)
+    SELECT id, title, description, category FROM tasks_search
+    WHERE id IN (SELECT id FROM tasks WHERE category_id = NEW.id);
+END;
+
+-- Clear a deleted category while it still exists, for the same reason as
+-- participants_fts_detach
+CREATE TRIGGER categories_fts_detach BEFORE DELETE ON categories
+BEGIN
+    UPDATE events SET category_id = NULL WHERE category_id = OLD.id;
+    UPDATE tasks SET category_id = NULL WHERE category_id = OLD.id;
+END;
+
+INSERT INTO events_fts (events_fts) VALUES ('rebuild');
+INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild');
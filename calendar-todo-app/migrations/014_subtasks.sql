@@ -0,0 +1,21 @@
+-- Subtasks of any depth and checklist items inside a task
+
+ALTER TABLE tasks ADD COLUMN parent_task_id INTEGER REFERENCES tasks(id) ON DELETE CASCADE;
+ALTER TABLE tasks ADD COLUMN subtask_order INTEGER; -- position among its siblings, NULL for top-level tasks
+
+CREATE INDEX idx_tasks_parent ON tasks(parent_task_id, subtask_order);
+
+CREATE TABLE task_checklist_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    is_checked BOOLEAN NOT NULL DEFAULT 0,
+    position INTEGER NOT NULL DEFAULT 0,
+    checked_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_task_checklist_items_task ON task_checklist_items(task_id, position);
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('auto_complete_parent_tasks', '1'); -- complete a parent once all of its subtasks are done
@@ -0,0 +1,18 @@
+-- Recurring tasks: completing an instance creates the next one
+
+-- FIXED keeps the rule's schedule, AFTER_COMPLETION counts one interval from
+-- the day the previous instance was completed
+ALTER TABLE tasks ADD COLUMN recurrence_mode TEXT NOT NULL DEFAULT 'FIXED'
+    CHECK (recurrence_mode IN ('FIXED', 'AFTER_COMPLETION'));
+ALTER TABLE tasks ADD COLUMN recurrence_series_id INTEGER; -- first instance of the series, NULL until it repeats
+
+CREATE TABLE task_completions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    series_id INTEGER NOT NULL,
+    task_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL,
+    next_task_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL,
+    due_date TEXT,
+    completed_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_task_completions_series ON task_completions(series_id, completed_at);
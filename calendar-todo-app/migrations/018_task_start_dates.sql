@@ -0,0 +1,8 @@
+-- Start dates and someday/maybe for tasks
+
+-- A task stays out of the active lists until its start date, deferring a
+-- task moves the start date
+ALTER TABLE tasks ADD COLUMN start_date DATETIME;
+ALTER TABLE tasks ADD COLUMN is_someday BOOLEAN NOT NULL DEFAULT 0; -- parked with no commitment, hidden until picked up
+
+CREATE INDEX idx_tasks_start_date ON tasks(start_date);
@@ -0,0 +1,18 @@
+-- Trash with restore for events, tasks and notes, and an archive for finished tasks
+
+-- Deleting sets deleted_at, the row is purged once it has been in the trash
+-- longer than trash_retention_days
+ALTER TABLE events ADD COLUMN deleted_at DATETIME;
+ALTER TABLE tasks ADD COLUMN deleted_at DATETIME;
+ALTER TABLE notes ADD COLUMN deleted_at DATETIME;
+
+-- Archived tasks are kept for good but leave the lists and searches
+ALTER TABLE tasks ADD COLUMN archived_at DATETIME;
+
+CREATE INDEX idx_events_deleted_at ON events(deleted_at);
+CREATE INDEX idx_tasks_deleted_at ON tasks(deleted_at);
+CREATE INDEX idx_tasks_archived_at ON tasks(archived_at);
+CREATE INDEX idx_notes_deleted_at ON notes(deleted_at);
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('trash_retention_days', '30'); -- 0 keeps trashed items until the trash is emptied
@@ -0,0 +1,4 @@
+-- How far undo reaches back. The undo log itself is kept in temp tables, one
+-- per app session, so it needs no schema of its own.
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('undo_depth', '50'); -- steps kept for undo, the oldest are dropped first
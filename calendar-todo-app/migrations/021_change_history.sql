@@ -0,0 +1,130 @@
+-- Field-level change history for events, tasks, notes and categories
+
+CREATE TABLE change_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_type TEXT NOT NULL CHECK (item_type IN ('EVENT', 'TASK', 'NOTE', 'CATEGORY')),
+    item_id INTEGER NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('CREATE', 'UPDATE', 'DELETE')),
+    field TEXT, -- the column that changed, set for updates only
+    old_value TEXT,
+    new_value TEXT,
+    changed_at DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now'))
+);
+
+CREATE INDEX idx_change_history_item ON change_history(item_type, item_id, changed_at);
+CREATE INDEX idx_change_history_changed_at ON change_history(changed_at);
+
+-- Entries are only ever added, and removed once past history_retention_days
+CREATE TRIGGER change_history_append_only BEFORE UPDATE ON change_history
+BEGIN
+    SELECT RAISE(ABORT, 'Change history is append-only');
+END;
+
+CREATE TRIGGER events_history_insert AFTER INSERT ON events
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('EVENT', NEW.id, 'CREATE');
+END;
+
+CREATE TRIGGER events_history_update AFTER UPDATE ON events
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action, field, old_value, new_value)
+    SELECT 'EVENT', NEW.id, 'UPDATE', field, old_value, new_value FROM (
+        SELECT 'title' AS field, OLD.title AS old_value, NEW.title AS new_value
+        UNION ALL SELECT 'description', OLD.description, NEW.description
+        UNION ALL SELECT 'start_time', OLD.start_time, NEW.start_time
+        UNION ALL SELECT 'end_time', OLD.end_time, NEW.end_time
+        UNION ALL SELECT 'is_all_day', OLD.is_all_day, NEW.is_all_day
+        UNION ALL SELECT 'location', OLD.location, NEW.location
+        UNION ALL SELECT 'priority', OLD.priority, NEW.priority
+        UNION ALL SELECT 'category_id', OLD.category_id, NEW.category_id
+        UNION ALL SELECT 'recurring_rule_id', OLD.recurring_rule_id, NEW.recurring_rule_id
+        UNION ALL SELECT 'deleted_at', OLD.deleted_at, NEW.deleted_at
+    ) WHERE old_value IS NOT new_value;
+END;
+
+CREATE TRIGGER events_history_delete AFTER DELETE ON events
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('EVENT', OLD.id, 'DELETE');
+END;
+
+CREATE TRIGGER tasks_history_insert AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('TASK', NEW.id, 'CREATE');
+END;
+
+CREATE TRIGGER tasks_history_update AFTER UPDATE ON tasks
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action, field, old_value, new_value)
+    SELECT 'TASK', NEW.id, 'UPDATE', field, old_value, new_value FROM (
+        SELECT 'title' AS field, OLD.title AS old_value, NEW.title AS new_value
+        UNION ALL SELECT 'description', OLD.description, NEW.description
+        UNION ALL SELECT 'due_date', OLD.due_date, NEW.due_date
+        UNION ALL SELECT 'priority', OLD.priority, NEW.priority
+        UNION ALL SELECT 'status', OLD.status, NEW.status
+        UNION ALL SELECT 'category_id', OLD.category_id, NEW.category_id
+        UNION ALL SELECT 'recurring_rule_id', OLD.recurring_rule_id, NEW.recurring_rule_id
+        UNION ALL SELECT 'kanban_column_id', OLD.kanban_column_id, NEW.kanban_column_id
+        UNION ALL SELECT 'kanban_order', OLD.kanban_order, NEW.kanban_order
+        UNION ALL SELECT 'completed_at', OLD.completed_at, NEW.completed_at
+        UNION ALL SELECT 'estimated_minutes', OLD.estimated_minutes, NEW.estimated_minutes
+        UNION ALL SELECT 'parent_task_id', OLD.parent_task_id, NEW.parent_task_id
+        UNION ALL SELECT 'subtask_order', OLD.subtask_order, NEW.subtask_order
+        UNION ALL SELECT 'recurrence_mode', OLD.recurrence_mode, NEW.recurrence_mode
+        UNION ALL SELECT 'recurrence_series_id', OLD.recurrence_series_id, NEW.recurrence_series_id
+        UNION ALL SELECT 'start_date', OLD.start_date, NEW.start_date
+        UNION ALL SELECT 'is_someday', OLD.is_someday, NEW.is_someday
+        UNION ALL SELECT 'deleted_at', OLD.deleted_at, NEW.deleted_at
+        UNION ALL SELECT 'archived_at', OLD.archived_at, NEW.archived_at
+    ) WHERE old_value IS NOT new_value;
+END;
+
+CREATE TRIGGER tasks_history_delete AFTER DELETE ON tasks
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('TASK', OLD.id, 'DELETE');
+END;
+
+CREATE TRIGGER notes_history_insert AFTER INSERT ON notes
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('NOTE', NEW.id, 'CREATE');
+END;
+
+CREATE TRIGGER notes_history_update AFTER UPDATE ON notes
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action, field, old_value, new_value)
+    SELECT 'NOTE', NEW.id, 'UPDATE', field, old_value, new_value FROM (
+        SELECT 'title' AS field, OLD.title AS old_value, NEW.title AS new_value
+        UNION ALL SELECT 'content', OLD.content, NEW.content
+        UNION ALL SELECT 'permissions', OLD.permissions, NEW.permissions
+        UNION ALL SELECT 'deleted_at', OLD.deleted_at, NEW.deleted_at
+    ) WHERE old_value IS NOT new_value;
+END;
+
+CREATE TRIGGER notes_history_delete AFTER DELETE ON notes
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('NOTE', OLD.id, 'DELETE');
+END;
+
+CREATE TRIGGER categories_history_insert AFTER INSERT ON categories
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('CATEGORY', NEW.id, 'CREATE');
+END;
+
+CREATE TRIGGER categories_history_update AFTER UPDATE ON categories
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action, field, old_value, new_value)
+    SELECT 'CATEGORY', NEW.id, 'UPDATE', field, old_value, new_value FROM (
+        SELECT 'name' AS field, OLD.name AS old_value, NEW.name AS new_value
+        UNION ALL SELECT 'color', OLD.color, NEW.color
+        UNION ALL SELECT 'symbol', OLD.symbol, NEW.symbol
+        UNION ALL SELECT 'is_billable', OLD.is_billable, NEW.is_billable
+        UNION ALL SELECT 'hourly_rate_cents', OLD.hourly_rate_cents, NEW.hourly_rate_cents
+    ) WHERE old_value IS NOT new_value;
+END;
+
+CREATE TRIGGER categories_history_delete AFTER DELETE ON categories
+BEGIN
+    INSERT INTO change_history (item_type, item_id, action) VALUES ('CATEGORY', OLD.id, 'DELETE');
+END;
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+    ('history_retention_days', '365'); -- 0 keeps the history for good
@@ -0,0 +1,48 @@
+-- Updates to categories, participants, events, tasks and notes carry the
+-- updated_at they were loaded with and are refused when it no longer matches.
+-- Stamp it to the millisecond, and at least a millisecond past the previous
+-- stamp, so every edit moves it forward even when the clock has not.
+DROP TRIGGER IF EXISTS categories_updated_at;
+CREATE TRIGGER categories_updated_at AFTER UPDATE ON categories
+BEGIN
+    UPDATE categories SET updated_at = MAX(
+        strftime('%Y-%m-%d %H:%M:%f', 'now'),
+        COALESCE(strftime('%Y-%m-%d %H:%M:%f', OLD.updated_at, '+0.001 seconds'), '')
+    ) WHERE id = NEW.id;
+END;
+
+DROP TRIGGER IF EXISTS participants_updated_at;
+CREATE TRIGGER participants_updated_at AFTER UPDATE ON participants
+BEGIN
+    UPDATE participants SET updated_at = MAX(
+        strftime('%Y-%m-%d %H:%M:%f', 'now'),
+        COALESCE(strftime('%Y-%m-%d %H:%M:%f', OLD.updated_at, '+0.001 seconds'), '')
+    ) WHERE id = NEW.id;
+END;
+
+DROP TRIGGER IF EXISTS events_updated_at;
+CREATE TRIGGER events_updated_at AFTER UPDATE ON events
+BEGIN
+    UPDATE events SET updated_at = MAX(
+        strftime('%Y-%m-%d %H:%M:%f', 'now'),
+        COALESCE(strftime('%Y-%m-%d %H:%M:%f', OLD.updated_at, '+0.001 seconds'), '')
+    ) WHERE id = NEW.id;
+END;
+
+DROP TRIGGER IF EXISTS tasks_updated_at;
+CREATE TRIGGER tasks_updated_at AFTER UPDATE ON tasks
+BEGIN
+    UPDATE tasks SET updated_at = MAX(
+        strftime('%Y-%m-%d %H:%M:%f', 'now'),
+        COALESCE(strftime('%Y-%m-%d %H:%M:%f', OLD.updated_at, '+0.001 seconds'), '')
+    ) WHERE id = NEW.id;
+END;
+
+DROP TRIGGER IF EXISTS notes_updated_at;
+CREATE TRIGGER notes_updated_at AFTER UPDATE ON notes
+BEGIN
+    UPDATE notes SET updated_at = MAX(
+        strftime('%Y-%m-%d %H:%M:%f', 'now'),
+        COALESCE(strftime('%Y-%m-%d %H:%M:%f', OLD.updated_at, '+0.001 seconds'), '')
+    ) WHERE id = NEW.id;
+END;
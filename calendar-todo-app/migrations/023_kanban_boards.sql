@@ -0,0 +1,23 @@
+-- Kanban boards, one per project, each with its own columns. A task sits on
+-- the board of the column it is placed in.
+CREATE TABLE kanban_boards (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    board_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TRIGGER kanban_boards_updated_at AFTER UPDATE ON kanban_boards
+BEGIN
+    UPDATE kanban_boards SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
+
+-- Deleting a board takes its columns along, their tasks are left unplaced
+ALTER TABLE kanban_columns ADD COLUMN board_id INTEGER REFERENCES kanban_boards(id) ON DELETE CASCADE;
+
+-- The existing columns become the first board
+INSERT INTO kanban_boards (name, board_order) VALUES ('Tasks', 0);
+UPDATE kanban_columns SET board_id = (SELECT id FROM kanban_boards);
+
+CREATE INDEX idx_kanban_columns_board ON kanban_columns(board_id, column_order);
@@ -0,0 +1,62 @@
+-- Full-text indexes over participants and categories themselves, so a search
+-- lists the people and categories it matches next to the events and tasks
+-- they belong to
+
+CREATE VIRTUAL TABLE participants_fts USING fts5(
+    name, email,
+    content = 'participants', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE categories_fts USING fts5(
+    name,
+    content = 'categories', content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE participants_fts_vocab USING fts5vocab('participants_fts', 'row');
+CREATE VIRTUAL TABLE categories_fts_vocab USING fts5vocab('categories_fts', 'row');
+
+-- Index the rows that already exist
+INSERT INTO participants_fts (participants_fts) VALUES ('rebuild');
+INSERT INTO categories_fts (categories_fts) VALUES ('rebuild');
+
+CREATE TRIGGER participants_fts_insert AFTER INSERT ON participants
+BEGIN
+    INSERT INTO participants_fts (rowid, name, email)
+    VALUES (NEW.id, NEW.name, NEW.email);
+END;
+
+CREATE TRIGGER participants_fts_delete AFTER DELETE ON participants
+BEGIN
+    INSERT INTO participants_fts (participants_fts, rowid, name, email)
+    VALUES ('delete', OLD.id, OLD.name, OLD.email);
+END;
+
+CREATE TRIGGER participants_fts_update AFTER UPDATE OF name, email ON participants
+BEGIN
+    INSERT INTO participants_fts (participants_fts, rowid, name, email)
+    VALUES ('delete', OLD.id, OLD.name, OLD.email);
+    INSERT INTO participants_fts (rowid, name, email)
+    VALUES (NEW.id, NEW.name, NEW.email);
+END;
+
+CREATE TRIGGER categories_fts_insert AFTER INSERT ON categories
+BEGIN
+    INSERT INTO categories_fts (rowid, name)
+    VALUES (NEW.id, NEW.name);
+END;
+
+CREATE TRIGGER categories_fts_delete AFTER DELETE ON categories
+BEGIN
+    INSERT INTO categories_fts (categories_fts, rowid, name)
+    VALUES ('delete', OLD.id, OLD.name);
+END;
+
+CREATE TRIGGER categories_fts_update AFTER UPDATE OF name ON categories
+BEGIN
+    INSERT INTO categories_fts (categories_fts, rowid, name)
+    VALUES ('delete', OLD.id, OLD.name);
+    INSERT INTO categories_fts (rowid, name)
+    VALUES (NEW.id, NEW.name);
+END;
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,6 +14,63 @@ pub enum DatabaseError {
     
     #[error("Data error: {0}")]
     Data(String),
+
+    /// An update carried an `updated_at` that no longer matches the row,
+    /// `current` is the copy now stored
+    #[error("{item_type} {id} has changed since it was loaded")]
+    Conflict {
+        item_type: String,
+        id: i64,
+        current: serde_json::Value,
+    },
+}
+
+impl DatabaseError {
+    /// The error for an update guarded by `updated_at` that matched no row,
+    /// given the lookup of the row as it is now: a Conflict while it is
+    /// still there, not found once it is gone
+    pub fn stale_update<T: Serialize>(item_type: &str, id: i64, current: DbResult<T>) -> Self {
+        match current {
+            Ok(current) => DatabaseError::Conflict {
+                item_type: item_type.to_string(),
+                id,
+                current: serde_json::to_value(current).unwrap_or_default(),
+            },
+            Err(DatabaseError::Sqlite(rusqlite::Error::QueryReturnedNoRows)) => {
+                DatabaseError::Data(format!("{} {} not found", item_type, id))
+            }
+            Err(e) => e,
+        }
+    }
 }
 
 pub type DbResult<T> = Result<T, DatabaseError>;
+
+/// Error of the update commands, serialized with a `kind` the UI can match
+/// on. A CONFLICT carries the stored copy so the edit can be merged or
+/// redone on top of it.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CommandError {
+    Conflict {
+        item_type: String,
+        id: i64,
+        current: serde_json::Value,
+    },
+    Failed { message: String },
+}
+
+impl From<DatabaseError> for CommandError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::Conflict { item_type, id, current } => CommandError::Conflict { item_type, id, current },
+            other => CommandError::Failed { message: other.to_string() },
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(error: rusqlite::Error) -> Self {
+        DatabaseError::from(error).into()
+    }
+}
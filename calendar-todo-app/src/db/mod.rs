@@ -6,10 +6,40 @@ use directories::ProjectDirs;
 
 pub mod models;
 pub mod operations;
+pub mod undo_log;
 pub mod error;
 
 pub use error::{DatabaseError, DbResult};
 
+/// Schema migrations in the order they are applied, built into the binary.
+/// `PRAGMA user_version` records how many of them have already run against
+/// a database. Test databases are built by the same migrations.
+const MIGRATIONS: &[&str] = &[
+    include_str!("../../migrations/001_initial_schema.sql"),
+    include_str!("../../migrations/003_reminder_quiet_hours.sql"),
+    include_str!("../../migrations/004_reminder_delivery_channels.sql"),
+    include_str!("../../migrations/005_pomodoro_sessions.sql"),
+    include_str!("../../migrations/006_timer_conflict_policy.sql"),
+    include_str!("../../migrations/007_time_budgets.sql"),
+    include_str!("../../migrations/008_billing.sql"),
+    include_str!("../../migrations/009_idle_detection.sql"),
+    include_str!("../../migrations/010_full_text_search.sql"),
+    include_str!("../../migrations/011_saved_searches.sql"),
+    include_str!("../../migrations/012_fuzzy_search.sql"),
+    include_str!("../../migrations/013_search_metadata.sql"),
+    include_str!("../../migrations/014_subtasks.sql"),
+    include_str!("../../migrations/015_task_dependencies.sql"),
+    include_str!("../../migrations/016_recurring_tasks.sql"),
+    include_str!("../../migrations/017_tags.sql"),
+    include_str!("../../migrations/018_task_start_dates.sql"),
+    include_str!("../../migrations/019_trash_and_archive.sql"),
+    include_str!("../../migrations/020_undo_depth.sql"),
+    include_str!("../../migrations/021_change_history.sql"),
+    include_str!("../../migrations/022_update_conflicts.sql"),
+    include_str!("../../migrations/023_kanban_boards.sql"),
+    include_str!("../../migrations/024_search_participants_categories.sql"),
+];
+
 pub struct Database {
     pub conn: Connection,
 }
@@ -28,7 +58,7 @@ impl Database {
     pub fn new_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
         let db = Database { conn };
-        db.initialize_in_memory()?;
+        db.initialize()?;
         Ok(db)
     }
 
@@ -44,22 +74,15 @@ impl Database {
         // Enable foreign key constraints
         self.conn.execute("PRAGMA foreign_keys = ON", [])?;
 
-        // Read and execute the migration file
-        let migration_path = std::env::current_dir()?.join("migrations").join("001_initial_schema.sql");
-        let migration_sql = fs::read_to_string(migration_path)?;
-        
-        self.conn.execute_batch(&migration_sql)?;
-        
-        Ok(())
-    }
+        // Apply any migrations that have not run yet
+        let applied: usize = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
 
-    fn initialize_in_memory(&self) -> Result<()> {
-        // Enable foreign key constraints
-        self.conn.execute("PRAGMA foreign_keys = ON", [])?;
+        for (index, migration_sql) in MIGRATIONS.iter().enumerate().skip(applied) {
+            self.conn.execute_batch(migration_sql)?;
+            self.conn.pragma_update(None, "user_version", (index + 1) as i64)?;
+        }
 
-        // Execute in-memory schema for testing
-        self.conn.execute_batch(&Self::get_test_schema())?;
-        
+        self.install_undo_log()?;
         Ok(())
     }
 
@@ -67,79 +90,6 @@ impl Database {
         self.initialize()
     }
 
-    fn get_test_schema() -> &'static str {
-        r#"
-        CREATE TABLE IF NOT EXISTS categories (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL UNIQUE,
-            color TEXT NOT NULL,
-            symbol TEXT NOT NULL,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        );
-
-        CREATE TABLE IF NOT EXISTS recurring_rules (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            frequency TEXT NOT NULL CHECK (frequency IN ('DAILY', 'WEEKLY', 'MONTHLY', 'YEARLY')),
-            interval_value INTEGER NOT NULL DEFAULT 1,
-            days_of_week TEXT,
-            day_of_month INTEGER,
-            month_of_year INTEGER,
-            end_date DATETIME,
-            end_occurrences INTEGER,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        );
-
-        CREATE TABLE IF NOT EXISTS events (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT,
-            start_time DATETIME NOT NULL,
-            end_time DATETIME NOT NULL,
-            is_all_day BOOLEAN NOT NULL DEFAULT 0,
-            location TEXT,
-            priority INTEGER NOT NULL DEFAULT 3,
-            category_id INTEGER,
-            recurring_rule_id INTEGER,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE SET NULL,
-            FOREIGN KEY (recurring_rule_id) REFERENCES recurring_rules(id) ON DELETE SET NULL
-        );
-
-        CREATE TABLE IF NOT EXISTS kanban_columns (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            position INTEGER NOT NULL,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        );
-
-        CREATE TABLE IF NOT EXISTS tasks (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT,
-            due_date DATETIME,
-            priority INTEGER NOT NULL DEFAULT 3,
-            status TEXT NOT NULL DEFAULT 'TODO' CHECK (status IN ('TODO', 'IN_PROGRESS', 'COMPLETED', 'CANCELLED')),
-            category_id INTEGER,
-            recurring_rule_id INTEGER,
-            kanban_column_id INTEGER,
-            kanban_order INTEGER,
-            completed_at DATETIME,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE SET NULL,
-            FOREIGN KEY (recurring_rule_id) REFERENCES recurring_rules(id) ON DELETE SET NULL,
-            FOREIGN KEY (kanban_column_id) REFERENCES kanban_columns(id) ON DELETE SET NULL
-        );
-
-        INSERT OR IGNORE INTO kanban_columns (name, position) VALUES
-            ('To Do', 1),
-            ('In Progress', 2),
-            ('Completed', 3);
-        "#
-    }
-
     pub fn get_connection(&self) -> &Connection {
         &self.conn
     }
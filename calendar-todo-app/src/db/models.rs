@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use rusqlite::Row;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
 use super::error::DbResult;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -97,6 +98,157 @@ impl Task {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub id: Option<i64>,
+    pub task_id: i64,
+    pub title: String,
+    pub is_checked: bool,
+    pub position: i32,
+    pub checked_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl ChecklistItem {
+    pub fn from_row(row: &Row) -> DbResult<Self> {
+        Ok(ChecklistItem {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            title: row.get(2)?,
+            is_checked: row.get(3)?,
+            position: row.get(4)?,
+            checked_at: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }
+}
+
+/// Finished work below a task: every subtask at any depth, and the task's
+/// own checklist
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TaskProgress {
+    pub subtasks_completed: i64,
+    pub subtasks_total: i64,
+    pub checklist_checked: i64,
+    pub checklist_total: i64,
+    pub completed: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskNode {
+    pub task: Task,
+    pub parent_task_id: Option<i64>,
+    pub progress: TaskProgress,
+    pub checklist: Vec<ChecklistItem>,
+    pub subtasks: Vec<TaskNode>,
+}
+
+/// What changing a task's status did besides that: parents completed along
+/// with it, and the next instances of recurring tasks that were completed
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TaskStatusChange {
+    pub completed_parent_ids: Vec<i64>,
+    pub next_task_ids: Vec<i64>,
+}
+
+/// How one task fared in a bulk operation. Without an error the change was
+/// applied.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BulkItemResult {
+    pub task_id: i64,
+    pub error: Option<String>,
+}
+
+/// Outcome of a bulk operation, one result per task in the order given
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BulkResult {
+    pub results: Vec<BulkItemResult>,
+    pub status_change: TaskStatusChange, // when tasks were completed
+}
+
+impl BulkResult {
+    pub fn applied(&self) -> usize {
+        self.results.iter().filter(|r| r.error.is_none()).count()
+    }
+}
+
+/// An event, task or note waiting in the trash. Subtasks trashed along with
+/// their task are not listed on their own.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrashedItem {
+    pub item_type: String,
+    pub id: i64,
+    pub title: String,
+    pub deleted_at: String,
+}
+
+/// A step in the session's undo log, one command's worth of changes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UndoEntry {
+    pub id: i64,
+    pub label: String,
+    pub created_at: String,
+}
+
+/// What undo and redo would apply, each list starting with the next one up
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct UndoHistory {
+    pub undo: Vec<UndoEntry>,
+    pub redo: Vec<UndoEntry>,
+}
+
+/// A Kanban board with columns of its own, one per project
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KanbanBoard {
+    pub id: Option<i64>,
+    pub name: String,
+    pub board_order: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KanbanColumn {
+    pub id: Option<i64>,
+    pub board_id: i64,
+    pub name: String,
+    pub column_order: i32,
+}
+
+/// One completed instance of a recurring task
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskCompletion {
+    pub id: i64,
+    pub series_id: i64,
+    pub task_id: Option<i64>,
+    pub next_task_id: Option<i64>,
+    pub due_date: Option<String>,
+    pub completed_at: String,
+    /// Completed no later than the day it was due
+    pub on_time: bool,
+}
+
+impl TaskCompletion {
+    pub fn from_row(row: &Row) -> DbResult<Self> {
+        Ok(TaskCompletion {
+            id: row.get(0)?,
+            series_id: row.get(1)?,
+            task_id: row.get(2)?,
+            next_task_id: row.get(3)?,
+            due_date: row.get(4)?,
+            completed_at: row.get(5)?,
+            on_time: row.get(6)?,
+        })
+    }
+}
+
+/// Runs of instances completed on time, newest completion last
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TaskStreak {
+    pub current: i64,
+    pub longest: i64,
+    pub completions: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RecurringRule {
     pub id: Option<i64>,
@@ -155,4 +307,95 @@ impl RecurringRule {
             created_at: row.get(8)?,
         })
     }
+
+    /// Weekdays the rule repeats on, 0 being Sunday. Accepts the UI's JSON
+    /// array as well as a plain comma separated list.
+    pub fn weekdays(&self) -> Vec<u32> {
+        self.days_of_week.as_deref().unwrap_or_default()
+            .trim_matches(|c| c == '[' || c == ']')
+            .split(',')
+            .filter_map(|day| day.trim().parse().ok())
+            .filter(|day| *day < 7)
+            .collect()
+    }
+
+    /// The next date on the schedule after `after`, at the same time of day.
+    /// None once the rule has ended.
+    pub fn next_on_schedule(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let interval = self.interval_value.max(1) as i64;
+        let weekdays = self.weekdays();
+
+        let next = if self.frequency == "WEEKLY" && !weekdays.is_empty() {
+            let week_start = |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_sunday() as i64);
+            let anchor = week_start(after.date());
+            (1..=7 * (interval + 1))
+                .map(|days| after + Duration::days(days))
+                .find(|next| {
+                    let weeks = (week_start(next.date()) - anchor).num_days() / 7;
+                    weeks % interval == 0 && weekdays.contains(&next.weekday().num_days_from_sunday())
+                })?
+        } else {
+            self.step(after)?
+        };
+        self.until_end(next)
+    }
+
+    /// The rule with the day of month its series started on filled in when it
+    /// leaves the day open, so a series started on the 31st returns to the
+    /// 31st after a shorter month instead of staying on the 28th.
+    pub fn anchored_to(&self, first: NaiveDateTime) -> RecurringRule {
+        let mut rule = self.clone();
+        if matches!(rule.frequency.as_str(), "MONTHLY" | "ANNUALLY" | "YEARLY") {
+            rule.day_of_month = rule.day_of_month.or(Some(first.day() as i32));
+        }
+        rule
+    }
+
+    /// One interval of the rule after `from`, ignoring which days it falls
+    /// on. None once the rule has ended.
+    pub fn next_after_interval(&self, from: NaiveDateTime) -> Option<NaiveDateTime> {
+        let interval = self.interval_value.max(1);
+        let next = match self.frequency.as_str() {
+            "DAILY" => from + Duration::days(interval as i64),
+            "WEEKLY" => from + Duration::weeks(interval as i64),
+            "MONTHLY" => shift_months(from, interval, None, None)?,
+            "ANNUALLY" | "YEARLY" => shift_months(from, 12 * interval, None, None)?,
+            _ => return None,
+        };
+        self.until_end(next)
+    }
+
+    fn step(&self, from: NaiveDateTime) -> Option<NaiveDateTime> {
+        let interval = self.interval_value.max(1);
+        let day = self.day_of_month.map(|day| day as u32);
+        match self.frequency.as_str() {
+            "DAILY" => Some(from + Duration::days(interval as i64)),
+            "WEEKLY" => Some(from + Duration::weeks(interval as i64)),
+            "MONTHLY" => shift_months(from, interval, None, day),
+            "ANNUALLY" | "YEARLY" => {
+                shift_months(from, 12 * interval, self.month_of_year.map(|month| month as u32), day)
+            }
+            _ => None,
+        }
+    }
+
+    fn until_end(&self, next: NaiveDateTime) -> Option<NaiveDateTime> {
+        let end = self.end_date.as_deref()
+            .and_then(|end| NaiveDate::parse_from_str(end.get(..10)?, "%Y-%m-%d").ok());
+        match end {
+            Some(end) if next.date() > end => None,
+            _ => Some(next),
+        }
+    }
+}
+
+/// `date` moved by `months`, optionally onto another month of that year and
+/// day of that month. Days past the end of the month clamp to its last day.
+fn shift_months(date: NaiveDateTime, months: i32, month: Option<u32>, day: Option<u32>) -> Option<NaiveDateTime> {
+    let total = date.year() * 12 + date.month0() as i32 + months;
+    let year = total.div_euclid(12);
+    let month = month.unwrap_or(total.rem_euclid(12) as u32 + 1);
+    let last_day = (28..=31).rev().find(|&last| NaiveDate::from_ymd_opt(year, month, last).is_some())?;
+    let day = day.unwrap_or(date.day()).clamp(1, last_day);
+    Some(NaiveDate::from_ymd_opt(year, month, day)?.and_time(date.time()))
 }
@@ -1,4 +1,36 @@
 use super::{Database, models::*, error::*};
+use rusqlite::{params, OptionalExtension};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+
+const TASK_COLUMNS: &str = "id, title, description, due_date, priority, status, category_id,
+     recurring_rule_id, kanban_column_id, kanban_order, completed_at, created_at, updated_at";
+
+/// What happens to a task's subtasks when it is deleted or moved: "CASCADE"
+/// takes them along, "PROMOTE" leaves them in its place under its parent
+pub const CHILD_POLICIES: &[&str] = &["CASCADE", "PROMOTE"];
+
+/// How the next instance of a recurring task is dated: "FIXED" keeps the
+/// rule's schedule, "AFTER_COMPLETION" counts one interval from completion
+pub const RECURRENCE_MODES: &[&str] = &["FIXED", "AFTER_COMPLETION"];
+
+/// Columns a new Kanban board can start with, by template name. "BASIC" is
+/// the default board, "REVIEW" adds a review step before done.
+pub const KANBAN_BOARD_TEMPLATES: &[(&str, &[&str])] = &[
+    ("BASIC", &["To Do", "In Progress", "Completed"]),
+    ("REVIEW", &["To Do", "In Progress", "Review", "Done"]),
+];
+
+/// Statuses that count as done for progress and auto-completion
+const FINISHED_STATUSES: &str = "('COMPLETED')";
+
+/// SQL condition on `tasks` that holds while one of its prerequisites is
+/// still open. Prerequisites in the trash no longer count.
+pub const BLOCKED_CONDITION: &str = "EXISTS (
+     SELECT 1 FROM task_dependencies d JOIN tasks p ON p.id = d.depends_on_task_id
+     WHERE d.task_id = tasks.id AND p.status <> 'COMPLETED' AND p.deleted_at IS NULL)";
+
+/// Item types that go to the trash instead of being deleted, with their tables
+pub const TRASHABLE_TABLES: &[(&str, &str)] = &[("EVENT", "events"), ("TASK", "tasks"), ("NOTE", "notes")];
 
 impl Database {
     // Category operations
@@ -12,26 +44,28 @@ impl Database {
     }
 
     pub fn get_category(&self, id: i64) -> DbResult<Category> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, color, symbol, created_at, updated_at FROM categories WHERE id = ?"
+        let category = self.conn.query_row_and_then(
+            "SELECT id, name, color, symbol, created_at, updated_at FROM categories WHERE id = ?",
+            [id],
+            Category::from_row,
         )?;
-        
-        let category = stmt.query_row([id], Category::from_row)?;
         Ok(category)
     }
 
-    pub fn update_category(&self, category: &Category) -> DbResult<()> {
-        let mut stmt = self.conn.prepare(
-            "UPDATE categories SET name = ?1, color = ?2, symbol = ?3 WHERE id = ?4"
+    /// Refused with a Conflict when `updated_at` is set and the stored row has
+    /// changed since, as are the other updates below. Returns the stored copy,
+    /// whose `updated_at` guards the next save
+    pub fn update_category(&self, category: &Category) -> DbResult<Category> {
+        let id = category.id.ok_or_else(|| DatabaseError::Data("Category ID is required".to_string()))?;
+        let updated = self.conn.execute(
+            "UPDATE categories SET name = ?1, color = ?2, symbol = ?3
+             WHERE id = ?4 AND (?5 IS NULL OR updated_at = ?5)",
+            params![category.name, category.color, category.symbol, id, category.updated_at],
         )?;
-        
-        stmt.execute([
-            &category.name,
-            &category.color,
-            &category.symbol,
-            &category.id.ok_or_else(|| DatabaseError::Data("Category ID is required".to_string()))?
-        ])?;
-        Ok(())
+        if updated == 0 {
+            return Err(DatabaseError::stale_update("CATEGORY", id, self.get_category(id)));
+        }
+        self.get_category(id)
     }
 
     pub fn delete_category(&self, id: i64) -> DbResult<()> {
@@ -62,41 +96,44 @@ impl Database {
     }
 
     pub fn get_event(&self, id: i64) -> DbResult<Event> {
-        let mut stmt = self.conn.prepare(
+        let event = self.conn.query_row_and_then(
             "SELECT id, title, description, start_time, end_time, is_all_day, location, 
              priority, category_id, recurring_rule_id, created_at, updated_at 
-             FROM events WHERE id = ?"
+             FROM events WHERE id = ? AND deleted_at IS NULL",
+            [id],
+            Event::from_row,
         )?;
-        
-        let event = stmt.query_row([id], Event::from_row)?;
         Ok(event)
     }
 
-    pub fn update_event(&self, event: &Event) -> DbResult<()> {
-        let mut stmt = self.conn.prepare(
+    pub fn update_event(&self, event: &Event) -> DbResult<Event> {
+        let id = event.id.ok_or_else(|| DatabaseError::Data("Event ID is required".to_string()))?;
+        let updated = self.conn.execute(
             "UPDATE events SET title = ?1, description = ?2, start_time = ?3, end_time = ?4, 
              is_all_day = ?5, location = ?6, priority = ?7, category_id = ?8, recurring_rule_id = ?9 
-             WHERE id = ?10"
+             WHERE id = ?10 AND deleted_at IS NULL AND (?11 IS NULL OR updated_at = ?11)",
+            params![
+                event.title,
+                event.description,
+                event.start_time,
+                event.end_time,
+                event.is_all_day,
+                event.location,
+                event.priority,
+                event.category_id,
+                event.recurring_rule_id,
+                id,
+                event.updated_at,
+            ],
         )?;
-        
-        stmt.execute([
-            &event.title,
-            &event.description,
-            &event.start_time,
-            &event.end_time,
-            &event.is_all_day,
-            &event.location,
-            &event.priority,
-            &event.category_id,
-            &event.recurring_rule_id,
-            &event.id.ok_or_else(|| DatabaseError::Data("Event ID is required".to_string()))?
-        ])?;
-        Ok(())
+        if updated == 0 {
+            return Err(DatabaseError::stale_update("EVENT", id, self.get_event(id)));
+        }
+        self.get_event(id)
     }
 
     pub fn delete_event(&self, id: i64) -> DbResult<()> {
-        self.conn.execute("DELETE FROM events WHERE id = ?", [id])?;
-        Ok(())
+        self.trash_item("EVENT", id)
     }
 
     // Task operations
@@ -122,41 +159,1324 @@ impl Database {
     }
 
     pub fn get_task(&self, id: i64) -> DbResult<Task> {
-        let mut stmt = self.conn.prepare(
+        let task = self.conn.query_row_and_then(
             "SELECT id, title, description, due_date, priority, status, category_id, 
              recurring_rule_id, kanban_column_id, kanban_order, completed_at, created_at, updated_at 
-             FROM tasks WHERE id = ?"
+             FROM tasks WHERE id = ? AND deleted_at IS NULL",
+            [id],
+            Task::from_row,
         )?;
-        
-        let task = stmt.query_row([id], Task::from_row)?;
         Ok(task)
     }
 
-    pub fn update_task(&self, task: &Task) -> DbResult<()> {
-        let mut stmt = self.conn.prepare(
+    pub fn update_task(&self, task: &Task) -> DbResult<Task> {
+        let id = task.id.ok_or_else(|| DatabaseError::Data("Task ID is required".to_string()))?;
+        let updated = self.conn.execute(
             "UPDATE tasks SET title = ?1, description = ?2, due_date = ?3, priority = ?4, 
              status = ?5, category_id = ?6, recurring_rule_id = ?7, kanban_column_id = ?8, 
-             kanban_order = ?9, completed_at = ?10 WHERE id = ?11"
+             kanban_order = ?9, completed_at = ?10
+             WHERE id = ?11 AND deleted_at IS NULL AND (?12 IS NULL OR updated_at = ?12)",
+            params![
+                task.title,
+                task.description,
+                task.due_date,
+                task.priority,
+                task.status,
+                task.category_id,
+                task.recurring_rule_id,
+                task.kanban_column_id,
+                task.kanban_order,
+                task.completed_at,
+                id,
+                task.updated_at,
+            ],
         )?;
-        
-        stmt.execute([
-            &task.title,
-            &task.description,
-            &task.due_date,
-            &task.priority,
-            &task.status,
-            &task.category_id,
-            &task.recurring_rule_id,
-            &task.kanban_column_id,
-            &task.kanban_order,
-            &task.completed_at,
-            &task.id.ok_or_else(|| DatabaseError::Data("Task ID is required".to_string()))?
-        ])?;
-        Ok(())
+        if updated == 0 {
+            return Err(DatabaseError::stale_update("TASK", id, self.get_task(id)));
+        }
+        self.get_task(id)
     }
 
     pub fn delete_task(&self, id: i64) -> DbResult<()> {
-        self.conn.execute("DELETE FROM tasks WHERE id = ?", [id])?;
+        self.trash_item("TASK", id)
+    }
+
+    // Subtask operations
+    pub fn create_subtask(&self, parent_id: i64, task: &Task) -> DbResult<i64> {
+        self.parent_task_id(parent_id)?;
+        let position = self.next_subtask_order(parent_id)?;
+
+        self.conn.execute(
+            "INSERT INTO tasks (title, description, due_date, priority, status, category_id,
+             recurring_rule_id, kanban_column_id, kanban_order, parent_task_id, subtask_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                task.title,
+                task.description,
+                task.due_date,
+                task.priority,
+                task.status,
+                task.category_id,
+                task.recurring_rule_id,
+                task.kanban_column_id,
+                task.kanban_order,
+                parent_id,
+                position,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Direct subtasks of a task, in order
+    pub fn get_subtasks(&self, parent_id: i64) -> DbResult<Vec<Task>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE parent_task_id = ? AND deleted_at IS NULL ORDER BY subtask_order, id",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_and_then([parent_id], Task::from_row)?
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    /// A task with its checklist and every subtask below it
+    pub fn get_task_tree(&self, id: i64) -> DbResult<TaskNode> {
+        let parent_task_id = self.parent_task_id(id)?;
+        let task = self.conn.query_row_and_then(
+            &format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS),
+            [id],
+            Task::from_row,
+        )?;
+        self.build_task_node(task, parent_task_id)
+    }
+
+    fn build_task_node(&self, task: Task, parent_task_id: Option<i64>) -> DbResult<TaskNode> {
+        let id = task.id.unwrap_or_default();
+        let subtasks = self.get_subtasks(id)?
+            .into_iter()
+            .map(|subtask| self.build_task_node(subtask, Some(id)))
+            .collect::<DbResult<Vec<_>>>()?;
+
+        Ok(TaskNode {
+            progress: self.get_task_progress(id)?,
+            checklist: self.get_checklist_items(id)?,
+            task,
+            parent_task_id,
+            subtasks,
+        })
+    }
+
+    pub fn get_task_progress(&self, id: i64) -> DbResult<TaskProgress> {
+        self.parent_task_id(id)?;
+
+        let (subtasks_completed, subtasks_total): (i64, i64) = self.conn.query_row(
+            &format!(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT id FROM tasks WHERE parent_task_id = ?1 AND deleted_at IS NULL
+                     UNION ALL
+                     SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id WHERE t.deleted_at IS NULL
+                 )
+                 SELECT COALESCE(SUM(status IN {}), 0), COUNT(*) FROM tasks WHERE id IN subtree",
+                FINISHED_STATUSES
+            ),
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let (checklist_checked, checklist_total): (i64, i64) = self.conn.query_row(
+            "SELECT COALESCE(SUM(is_checked), 0), COUNT(*) FROM task_checklist_items WHERE task_id = ?",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(TaskProgress {
+            subtasks_completed,
+            subtasks_total,
+            checklist_checked,
+            checklist_total,
+            completed: subtasks_completed + checklist_checked,
+            total: subtasks_total + checklist_total,
+        })
+    }
+
+    /// Sets a task's status. When `auto_complete_parent_tasks` is on, parents
+    /// left with only finished subtasks and checked checklist items are
+    /// completed too, all the way up. Completing a recurring task creates its
+    /// next instance.
+    pub fn set_task_status(&self, id: i64, status: &str) -> DbResult<TaskStatusChange> {
+        let was_completed: bool = self.conn.query_row(
+            "SELECT status = 'COMPLETED' FROM tasks WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))?;
+
+        self.savepoint(|| {
+            self.conn.execute(
+                "UPDATE tasks SET status = ?1,
+                 completed_at = CASE WHEN ?1 = 'COMPLETED' THEN COALESCE(completed_at, datetime('now')) END
+                 WHERE id = ?2",
+                params![status, id],
+            )?;
+
+            let completed = (status == "COMPLETED" && !was_completed).then_some(id);
+            self.complete_and_repeat(completed, self.parent_task_id(id)?)
+        })
+    }
+
+    /// Moves a task under `new_parent_id`, or to the top level when None, at
+    /// `position` among its new siblings (last when None)
+    pub fn move_task(
+        &self,
+        id: i64,
+        new_parent_id: Option<i64>,
+        position: Option<i64>,
+        child_policy: &str,
+    ) -> DbResult<()> {
+        Self::validate_child_policy(child_policy)?;
+        let old_parent_id = self.parent_task_id(id)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        if child_policy == "PROMOTE" {
+            self.promote_subtasks(id)?;
+        }
+
+        if let Some(parent_id) = new_parent_id {
+            self.parent_task_id(parent_id)?;
+            if self.subtree_ids(id)?.contains(&parent_id) {
+                return Err(DatabaseError::Data(format!(
+                    "Task {} cannot be moved under itself or one of its subtasks",
+                    id
+                )));
+            }
+        }
+
+        self.conn.execute(
+            "UPDATE tasks SET parent_task_id = ?1, subtask_order = NULL WHERE id = ?2",
+            params![new_parent_id, id],
+        )?;
+        if let Some(parent_id) = old_parent_id {
+            self.renumber_subtasks(parent_id, None)?;
+        }
+        if let Some(parent_id) = new_parent_id {
+            self.renumber_subtasks(parent_id, Some((id, position)))?;
+        }
+
+        self.complete_finished_parents(old_parent_id)?;
+        tx.commit()?;
         Ok(())
     }
+
+    /// Moves a task to the trash, and under "CASCADE" every subtask below it
+    pub fn delete_task_with_subtasks(&self, id: i64, child_policy: &str) -> DbResult<()> {
+        Self::validate_child_policy(child_policy)?;
+        let parent_id: Option<i64> = match self.conn.query_row(
+            "SELECT parent_task_id FROM tasks WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        ).optional()? {
+            Some(parent_id) => parent_id,
+            None => return Ok(()), // like delete_task, a missing task is not an error
+        };
+
+        self.savepoint(|| {
+            if child_policy == "PROMOTE" {
+                self.promote_subtasks(id)?;
+            }
+
+            self.conn.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT ?1
+                     UNION ALL
+                     SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+                 )
+                 UPDATE tasks SET deleted_at = datetime('now') WHERE id IN subtree AND deleted_at IS NULL",
+                [id],
+            )?;
+            if let Some(parent_id) = parent_id {
+                self.renumber_subtasks(parent_id, None)?;
+            }
+
+            self.complete_finished_parents(parent_id)?;
+            Ok(())
+        })
+    }
+
+    /// Runs `change` inside a savepoint, rolled back when it fails. Unlike a
+    /// transaction this nests, so the same change can run alone or as one
+    /// item of a bulk operation.
+    fn savepoint<T>(&self, change: impl FnOnce() -> DbResult<T>) -> DbResult<T> {
+        self.conn.execute_batch("SAVEPOINT task_change")?;
+        match change() {
+            Ok(value) => {
+                self.conn.execute_batch("RELEASE task_change")?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.conn.execute_batch("ROLLBACK TO task_change; RELEASE task_change")?;
+                Err(e)
+            }
+        }
+    }
+
+    fn validate_child_policy(child_policy: &str) -> DbResult<()> {
+        if CHILD_POLICIES.contains(&child_policy) {
+            Ok(())
+        } else {
+            Err(DatabaseError::Data(format!("Invalid child policy: {}", child_policy)))
+        }
+    }
+
+    /// Parent of a task, failing when the task does not exist
+    fn parent_task_id(&self, id: i64) -> DbResult<Option<i64>> {
+        self.conn.query_row("SELECT parent_task_id FROM tasks WHERE id = ?", [id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))
+    }
+
+    fn next_subtask_order(&self, parent_id: i64) -> DbResult<i64> {
+        let next = self.conn.query_row(
+            "SELECT COALESCE(MAX(subtask_order) + 1, 0) FROM tasks WHERE parent_task_id = ?",
+            [parent_id],
+            |row| row.get(0),
+        )?;
+        Ok(next)
+    }
+
+    fn subtask_ids(&self, parent_id: i64) -> DbResult<Vec<i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id FROM tasks WHERE parent_task_id = ? AND deleted_at IS NULL ORDER BY subtask_order, id"
+        )?;
+        let ids = stmt.query_map([parent_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// A task and every subtask below it
+    fn subtree_ids(&self, id: i64) -> DbResult<Vec<i64>> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT ?1
+                 UNION ALL
+                 SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+             )
+             SELECT id FROM subtree"
+        )?;
+        let ids = stmt.query_map([id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Numbers a parent's subtasks 0, 1, 2… in their current order, first
+    /// putting `placed` (a task and its wanted position) where it belongs
+    fn renumber_subtasks(&self, parent_id: i64, placed: Option<(i64, Option<i64>)>) -> DbResult<()> {
+        let mut ids = self.subtask_ids(parent_id)?;
+        if let Some((id, position)) = placed {
+            ids.retain(|&other| other != id);
+            let index = position.map_or(ids.len(), |position| position.clamp(0, ids.len() as i64) as usize);
+            ids.insert(index, id);
+        }
+
+        for (order, id) in ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE tasks SET subtask_order = ?1 WHERE id = ?2",
+                params![order as i64, id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Hands a task's direct subtasks to its parent, just before the task
+    fn promote_subtasks(&self, id: i64) -> DbResult<()> {
+        let children = self.subtask_ids(id)?;
+        let parent_id = match self.parent_task_id(id)? {
+            Some(parent_id) => parent_id,
+            None => {
+                self.conn.execute(
+                    "UPDATE tasks SET parent_task_id = NULL, subtask_order = NULL WHERE parent_task_id = ?",
+                    [id],
+                )?;
+                return Ok(());
+            }
+        };
+
+        let mut order = Vec::new();
+        for sibling in self.subtask_ids(parent_id)? {
+            if sibling == id {
+                order.extend(children.iter().copied());
+            }
+            order.push(sibling);
+        }
+
+        for (position, child) in order.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE tasks SET parent_task_id = ?1, subtask_order = ?2 WHERE id = ?3",
+                params![parent_id, position as i64, child],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Completes the finished parents from `parent_id` up, then creates the
+    /// next instance of `completed` and of those parents where they recur
+    fn complete_and_repeat(&self, completed: Option<i64>, parent_id: Option<i64>) -> DbResult<TaskStatusChange> {
+        let completed_parent_ids = self.complete_finished_parents(parent_id)?;
+
+        let mut next_task_ids = Vec::new();
+        for id in completed.into_iter().chain(completed_parent_ids.iter().copied()) {
+            next_task_ids.extend(self.create_next_instance(id)?);
+        }
+        Ok(TaskStatusChange { completed_parent_ids, next_task_ids })
+    }
+
+    /// Completes `parent_id` and its ancestors for as long as each one has
+    /// subtasks, all of them finished, and no unchecked checklist items
+    fn complete_finished_parents(&self, parent_id: Option<i64>) -> DbResult<Vec<i64>> {
+        let enabled: Option<String> = self.conn.query_row(
+            "SELECT value FROM settings WHERE key = 'auto_complete_parent_tasks'",
+            [],
+            |row| row.get(0),
+        ).optional()?;
+        if enabled.as_deref() != Some("1") {
+            return Ok(Vec::new());
+        }
+
+        let mut completed = Vec::new();
+        let mut current = parent_id;
+        while let Some(id) = current {
+            let (finished, subtasks, open): (bool, i64, i64) = self.conn.query_row(
+                &format!(
+                    "SELECT status IN {finished},
+                            (SELECT COUNT(*) FROM tasks WHERE parent_task_id = ?1 AND deleted_at IS NULL),
+                            (SELECT COUNT(*) FROM tasks WHERE parent_task_id = ?1 AND deleted_at IS NULL AND status NOT IN {finished})
+                            + (SELECT COUNT(*) FROM task_checklist_items WHERE task_id = ?1 AND NOT is_checked)
+                     FROM tasks WHERE id = ?1",
+                    finished = FINISHED_STATUSES
+                ),
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+            if finished || subtasks == 0 || open > 0 {
+                break;
+            }
+
+            self.conn.execute(
+                "UPDATE tasks SET status = 'COMPLETED', completed_at = datetime('now') WHERE id = ?",
+                [id],
+            )?;
+            completed.push(id);
+            current = self.parent_task_id(id)?;
+        }
+        Ok(completed)
+    }
+
+    // Checklist operations
+    pub fn add_checklist_item(&self, task_id: i64, title: &str) -> DbResult<i64> {
+        if title.trim().is_empty() {
+            return Err(DatabaseError::Data("Checklist item title must not be empty".to_string()));
+        }
+        self.parent_task_id(task_id)?;
+
+        self.conn.execute(
+            "INSERT INTO task_checklist_items (task_id, title, position)
+             SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0) FROM task_checklist_items WHERE task_id = ?1",
+            params![task_id, title.trim()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_checklist_items(&self, task_id: i64) -> DbResult<Vec<ChecklistItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, task_id, title, is_checked, position, checked_at, created_at
+             FROM task_checklist_items WHERE task_id = ? ORDER BY position, id"
+        )?;
+
+        let items = stmt.query_and_then([task_id], ChecklistItem::from_row)?
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(items)
+    }
+
+    /// Saves an item's title and checked state. Checking the last open item
+    /// can complete the task's parents like `set_task_status` does.
+    pub fn update_checklist_item(&self, item: &ChecklistItem) -> DbResult<TaskStatusChange> {
+        let id = item.id.ok_or_else(|| DatabaseError::Data("Checklist item ID is required".to_string()))?;
+        if item.title.trim().is_empty() {
+            return Err(DatabaseError::Data("Checklist item title must not be empty".to_string()));
+        }
+
+        let task_id = self.checklist_task_id(id)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE task_checklist_items SET title = ?1, is_checked = ?2,
+             checked_at = CASE WHEN ?2 THEN COALESCE(checked_at, datetime('now')) END
+             WHERE id = ?3",
+            params![item.title.trim(), item.is_checked, id],
+        )?;
+
+        let change = if item.is_checked {
+            self.complete_and_repeat(None, Some(task_id))?
+        } else {
+            TaskStatusChange::default()
+        };
+        tx.commit()?;
+        Ok(change)
+    }
+
+    pub fn reorder_checklist_item(&self, id: i64, position: i64) -> DbResult<()> {
+        let task_id = self.checklist_task_id(id)?;
+        let mut ids: Vec<i64> = self.get_checklist_items(task_id)?
+            .into_iter()
+            .filter_map(|item| item.id)
+            .filter(|&other| other != id)
+            .collect();
+        ids.insert(position.clamp(0, ids.len() as i64) as usize, id);
+
+        let tx = self.conn.unchecked_transaction()?;
+        for (position, id) in ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE task_checklist_items SET position = ?1 WHERE id = ?2",
+                params![position as i64, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_checklist_item(&self, id: i64) -> DbResult<()> {
+        self.conn.execute("DELETE FROM task_checklist_items WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    fn checklist_task_id(&self, id: i64) -> DbResult<i64> {
+        self.conn.query_row("SELECT task_id FROM task_checklist_items WHERE id = ?", [id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Checklist item {} not found", id)))
+    }
+
+    // Dependency operations
+    /// Makes `task_id` wait for `depends_on_task_id`, refusing edges that
+    /// would close a cycle
+    pub fn add_task_dependency(&self, task_id: i64, depends_on_task_id: i64) -> DbResult<()> {
+        self.parent_task_id(task_id)?;
+        self.parent_task_id(depends_on_task_id)?;
+        if task_id == depends_on_task_id {
+            return Err(DatabaseError::Data(format!("Task {} cannot depend on itself", task_id)));
+        }
+        if let Some(path) = self.dependency_path(depends_on_task_id, task_id)? {
+            return Err(DatabaseError::Data(format!(
+                "Task {} cannot depend on task {}, that would create a cycle: {} → {}",
+                task_id, depends_on_task_id, task_id, path.join(" → ")
+            )));
+        }
+
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_task_id) VALUES (?1, ?2)",
+            params![task_id, depends_on_task_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_task_dependency(&self, task_id: i64, depends_on_task_id: i64) -> DbResult<()> {
+        self.conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1 AND depends_on_task_id = ?2",
+            params![task_id, depends_on_task_id],
+        )?;
+        Ok(())
+    }
+
+    /// Tasks that must be done before this one can start
+    pub fn get_task_dependencies(&self, task_id: i64) -> DbResult<Vec<Task>> {
+        self.dependency_tasks(
+            "SELECT depends_on_task_id FROM task_dependencies WHERE task_id = ?",
+            task_id,
+        )
+    }
+
+    /// Tasks waiting for this one
+    pub fn get_dependent_tasks(&self, task_id: i64) -> DbResult<Vec<Task>> {
+        self.dependency_tasks(
+            "SELECT task_id FROM task_dependencies WHERE depends_on_task_id = ?",
+            task_id,
+        )
+    }
+
+    /// Prerequisites that are still open, empty when the task is not blocked
+    pub fn get_blocking_tasks(&self, task_id: i64) -> DbResult<Vec<Task>> {
+        self.dependency_tasks(
+            &format!(
+                "SELECT d.depends_on_task_id FROM task_dependencies d
+                 JOIN tasks p ON p.id = d.depends_on_task_id
+                 WHERE d.task_id = ? AND p.status NOT IN {} AND p.deleted_at IS NULL",
+                FINISHED_STATUSES
+            ),
+            task_id,
+        )
+    }
+
+    fn dependency_tasks(&self, ids_query: &str, task_id: i64) -> DbResult<Vec<Task>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE id IN ({}) AND deleted_at IS NULL ORDER BY id",
+            TASK_COLUMNS, ids_query
+        ))?;
+
+        let tasks = stmt.query_and_then([task_id], Task::from_row)?
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    /// The chain of prerequisites leading from `from` to `to`, both included
+    fn dependency_path(&self, from: i64, to: i64) -> DbResult<Option<Vec<String>>> {
+        // Existing edges never form a cycle, so the walk always ends
+        let path: Option<String> = self.conn.query_row(
+            "WITH RECURSIVE chain(id, path) AS (
+                 SELECT ?1, CAST(?1 AS TEXT)
+                 UNION ALL
+                 SELECT d.depends_on_task_id, c.path || ',' || d.depends_on_task_id
+                 FROM task_dependencies d JOIN chain c ON d.task_id = c.id
+                 WHERE c.id != ?2
+             )
+             SELECT path FROM chain WHERE id = ?2 LIMIT 1",
+            params![from, to],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(path.map(|path| path.split(',').map(str::to_string).collect()))
+    }
+
+    // Recurring task operations
+    /// Makes a task repeat by `rule_id`, or stop repeating when None
+    pub fn set_task_recurrence(&self, task_id: i64, rule_id: Option<i64>, mode: &str) -> DbResult<()> {
+        if !RECURRENCE_MODES.contains(&mode) {
+            return Err(DatabaseError::Data(format!("Invalid recurrence mode: {}", mode)));
+        }
+        let changed = self.conn.execute(
+            "UPDATE tasks SET recurring_rule_id = ?1, recurrence_mode = ?2 WHERE id = ?3",
+            params![rule_id, mode, task_id],
+        )?;
+        if changed == 0 {
+            return Err(DatabaseError::Data(format!("Task {} not found", task_id)));
+        }
+        Ok(())
+    }
+
+    /// Every completed instance in the task's series, oldest first
+    pub fn get_task_completions(&self, task_id: i64) -> DbResult<Vec<TaskCompletion>> {
+        let series_id: i64 = self.conn.query_row(
+            "SELECT COALESCE(recurrence_series_id, id) FROM tasks WHERE id = ?",
+            [task_id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", task_id)))?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, series_id, task_id, next_task_id, due_date, completed_at,
+                    due_date IS NULL OR date(completed_at) <= date(due_date)
+             FROM task_completions WHERE series_id = ? ORDER BY completed_at, id"
+        )?;
+        let completions = stmt.query_and_then([series_id], TaskCompletion::from_row)?
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(completions)
+    }
+
+    pub fn get_task_streak(&self, task_id: i64) -> DbResult<TaskStreak> {
+        let completions = self.get_task_completions(task_id)?;
+
+        let mut streak = TaskStreak { completions: completions.len() as i64, ..Default::default() };
+        for completion in &completions {
+            streak.current = if completion.on_time { streak.current + 1 } else { 0 };
+            streak.longest = streak.longest.max(streak.current);
+        }
+        Ok(streak)
+    }
+
+    /// Records the completion of a recurring task and creates its next
+    /// instance, unless the task does not recur or its rule has ended
+    fn create_next_instance(&self, id: i64) -> DbResult<Option<i64>> {
+        let (rule_id, mode, series_id, due_date, completed_at): (Option<i64>, String, i64, Option<String>, String) =
+            self.conn.query_row(
+                "SELECT recurring_rule_id, recurrence_mode, COALESCE(recurrence_series_id, id), due_date,
+                        COALESCE(completed_at, datetime('now'))
+                 FROM tasks WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )?;
+        let rule_id = match rule_id {
+            Some(rule_id) => rule_id,
+            None => return Ok(None),
+        };
+
+        self.conn.execute(
+            "INSERT INTO task_completions (series_id, task_id, due_date, completed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![series_id, id, due_date, completed_at],
+        )?;
+        let completion_id = self.conn.last_insert_rowid();
+        self.conn.execute("UPDATE tasks SET recurrence_series_id = ?1 WHERE id = ?2", params![series_id, id])?;
+
+        let rule = self.conn.query_row_and_then(
+            "SELECT id, frequency, interval AS interval_value, days_of_week, day_of_month, month_of_year,
+                    end_date, end_occurrences, created_at
+             FROM recurring_rules WHERE id = ?",
+            [rule_id],
+            RecurringRule::from_row,
+        )?;
+        let completions: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM task_completions WHERE series_id = ?",
+            [series_id],
+            |row| row.get(0),
+        )?;
+        if rule.end_occurrences.is_some_and(|limit| completions >= limit as i64) {
+            return Ok(None);
+        }
+
+        let completed = parse_due_date(&completed_at)
+            .map(|(at, _)| at)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid completion time on task {}", id)))?;
+        let due = due_date.as_deref().and_then(parse_due_date);
+        let base = due.map_or(completed, |(due, _)| due);
+
+        // Months and years are counted from the day the series started on
+        let first_due: Option<String> = self.conn.query_row(
+            "SELECT due_date FROM task_completions WHERE series_id = ? ORDER BY id LIMIT 1",
+            [series_id],
+            |row| row.get(0),
+        )?;
+        let rule = match first_due.as_deref().and_then(parse_due_date) {
+            Some((first, _)) => rule.anchored_to(first),
+            None => rule,
+        };
+
+        let next = if mode == "AFTER_COMPLETION" {
+            // Keep the time of day the task was due at
+            rule.next_after_interval(completed.date().and_time(base.time()))
+        } else {
+            // Occurrences that went by before the task was completed are skipped
+            let mut next = rule.next_on_schedule(base);
+            while let Some(at) = next.filter(|at| at.date() <= completed.date()) {
+                next = rule.next_on_schedule(at);
+            }
+            next
+        };
+        let next = match next {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+
+        let subtask_order = self.parent_task_id(id)?
+            .map(|parent_id| self.next_subtask_order(parent_id))
+            .transpose()?;
+        self.conn.execute(
+            "INSERT INTO tasks (title, description, due_date, start_date, priority, category_id, recurring_rule_id,
+                                estimated_minutes, parent_task_id, subtask_order, recurrence_mode, recurrence_series_id)
+             SELECT title, description, ?1, ?2, priority, category_id, recurring_rule_id,
+                    estimated_minutes, parent_task_id, ?3, recurrence_mode, ?4
+             FROM tasks WHERE id = ?5",
+            params![
+                format_due_date(next, due.is_some_and(|(_, has_time)| has_time)),
+                shift_date(self.task_start_date(id)?, next - base)?,
+                subtask_order,
+                series_id,
+                id,
+            ],
+        )?;
+        let next_id = self.conn.last_insert_rowid();
+
+        self.copy_task_contents(id, next_id, next - base)?;
+        self.conn.execute(
+            "UPDATE task_completions SET next_task_id = ?1 WHERE id = ?2",
+            params![next_id, completion_id],
+        )?;
+        Ok(Some(next_id))
+    }
+
+    /// Carries a task's checklist, notes, reminder and subtasks over to its
+    /// next instance, all reopened and with dates moved by `shift`
+    fn copy_task_contents(&self, from: i64, to: i64, shift: Duration) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT INTO task_checklist_items (task_id, title, position)
+             SELECT ?1, title, position FROM task_checklist_items WHERE task_id = ?2",
+            params![to, from],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_notes (task_id, note_id)
+             SELECT ?1, note_id FROM task_notes WHERE task_id = ?2",
+            params![to, from],
+        )?;
+
+        let reminder: Option<(String, String)> = self.conn.query_row(
+            "SELECT trigger_time, offset_description FROM reminders WHERE item_type = 'TASK' AND item_id = ?",
+            [from],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+        if let Some((trigger_time, offset_description)) = reminder {
+            if let Some((at, _)) = parse_due_date(&trigger_time) {
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO reminders (item_type, item_id, trigger_time, offset_description)
+                     VALUES ('TASK', ?1, ?2, ?3)",
+                    params![to, format_due_date(shift_time(at, shift)?, true), offset_description],
+                )?;
+            }
+        }
+
+        for subtask in self.get_subtasks(from)? {
+            let subtask_id = subtask.id.unwrap_or_default();
+            self.conn.execute(
+                "INSERT INTO tasks (title, description, due_date, start_date, priority, category_id, estimated_minutes,
+                                    parent_task_id, subtask_order)
+                 SELECT title, description, ?1, ?2, priority, category_id, estimated_minutes, ?3, subtask_order
+                 FROM tasks WHERE id = ?4",
+                params![
+                    shift_date(subtask.due_date, shift)?,
+                    shift_date(self.task_start_date(subtask_id)?, shift)?,
+                    to,
+                    subtask_id,
+                ],
+            )?;
+            self.copy_task_contents(subtask_id, self.conn.last_insert_rowid(), shift)?;
+        }
+        Ok(())
+    }
+
+    // Scheduling operations
+    /// Hides a task until `until`, or shows it again right away when None.
+    /// Deferring a someday task to a date picks it back up.
+    pub fn defer_task(&self, id: i64, until: Option<&str>) -> DbResult<()> {
+        if let Some(until) = until {
+            if parse_due_date(until).is_none() {
+                return Err(DatabaseError::Data(format!("Invalid start date: {}", until)));
+            }
+        }
+        let status: String = self.conn.query_row(
+            "SELECT status FROM tasks WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))?;
+        if until.is_some() && status == "COMPLETED" {
+            return Err(DatabaseError::Data(format!("Task {} is already completed", id)));
+        }
+
+        self.conn.execute(
+            "UPDATE tasks SET start_date = ?1, is_someday = is_someday AND ?1 IS NULL,
+                              updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?2",
+            params![until, id],
+        )?;
+        Ok(())
+    }
+
+    /// Parks a task as someday/maybe, or picks it back up
+    pub fn set_task_someday(&self, id: i64, someday: bool) -> DbResult<()> {
+        let changed = self.conn.execute(
+            "UPDATE tasks SET is_someday = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![someday, id],
+        )?;
+        if changed == 0 {
+            return Err(DatabaseError::Data(format!("Task {} not found", id)));
+        }
+        Ok(())
+    }
+
+    fn task_start_date(&self, id: i64) -> DbResult<Option<String>> {
+        Ok(self.conn.query_row("SELECT start_date FROM tasks WHERE id = ?", [id], |row| row.get(0))?)
+    }
+
+    // Kanban operations
+
+    /// What placing a task in a column does to it: past the first column of
+    /// its board work has started, and the last column completes it
+    pub fn kanban_column_effect(&self, column_id: i64) -> DbResult<(bool, bool)> {
+        self.conn.query_row(
+            "SELECT c.column_order > (SELECT MIN(column_order) FROM kanban_columns WHERE board_id = c.board_id),
+                    c.column_order = (SELECT MAX(column_order) FROM kanban_columns WHERE board_id = c.board_id)
+             FROM kanban_columns c WHERE c.id = ?",
+            [column_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Kanban column {} not found", column_id)))
+    }
+
+    pub fn get_kanban_boards(&self) -> DbResult<Vec<KanbanBoard>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, board_order FROM kanban_boards ORDER BY board_order, id"
+        )?;
+        let boards = stmt.query_map([], |row| {
+            Ok(KanbanBoard { id: Some(row.get(0)?), name: row.get(1)?, board_order: row.get(2)? })
+        })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(boards)
+    }
+
+    /// Creates a board without columns
+    pub fn create_kanban_board(&self, board: &KanbanBoard) -> DbResult<i64> {
+        if board.name.trim().is_empty() {
+            return Err(DatabaseError::Data("Board name is required".to_string()));
+        }
+        self.conn.execute(
+            "INSERT INTO kanban_boards (name, board_order) VALUES (?1, ?2)",
+            params![board.name, board.board_order],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Creates a board with the columns of one of `KANBAN_BOARD_TEMPLATES`
+    pub fn create_kanban_board_from_template(&self, board: &KanbanBoard, template: &str) -> DbResult<i64> {
+        let columns = KANBAN_BOARD_TEMPLATES.iter()
+            .find(|(name, _)| *name == template)
+            .map(|(_, columns)| *columns)
+            .ok_or_else(|| DatabaseError::Data(format!("Unknown board template: {}", template)))?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let board_id = self.create_kanban_board(board)?;
+        for (order, name) in columns.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO kanban_columns (board_id, name, column_order) VALUES (?1, ?2, ?3)",
+                params![board_id, name, order as i32],
+            )?;
+        }
+        tx.commit()?;
+        Ok(board_id)
+    }
+
+    pub fn update_kanban_board(&self, board: &KanbanBoard) -> DbResult<()> {
+        let id = board.id.ok_or_else(|| DatabaseError::Data("Board ID is required".to_string()))?;
+        if board.name.trim().is_empty() {
+            return Err(DatabaseError::Data("Board name is required".to_string()));
+        }
+        let updated = self.conn.execute(
+            "UPDATE kanban_boards SET name = ?1, board_order = ?2 WHERE id = ?3",
+            params![board.name, board.board_order, id],
+        )?;
+        if updated == 0 {
+            return Err(DatabaseError::Data(format!("Kanban board {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Deletes the board with its columns. Their tasks stay, off any board.
+    pub fn delete_kanban_board(&self, id: i64) -> DbResult<()> {
+        let deleted = self.conn.execute("DELETE FROM kanban_boards WHERE id = ?", [id])?;
+        if deleted == 0 {
+            return Err(DatabaseError::Data(format!("Kanban board {} not found", id)));
+        }
+        Ok(())
+    }
+
+    pub fn get_kanban_columns(&self, board_id: i64) -> DbResult<Vec<KanbanColumn>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, board_id, name, column_order FROM kanban_columns
+             WHERE board_id = ? ORDER BY column_order ASC"
+        )?;
+        let columns = stmt.query_map([board_id], |row| {
+            Ok(KanbanColumn {
+                id: Some(row.get(0)?),
+                board_id: row.get(1)?,
+                name: row.get(2)?,
+                column_order: row.get(3)?,
+            })
+        })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(columns)
+    }
+
+    // Bulk operations
+    pub fn bulk_set_task_status(&self, ids: &[i64], status: &str) -> DbResult<BulkResult> {
+        let mut status_change = TaskStatusChange::default();
+        let mut result = self.bulk_apply(ids, |id| {
+            let change = self.set_task_status(id, status)?;
+            status_change.completed_parent_ids.extend(change.completed_parent_ids);
+            status_change.next_task_ids.extend(change.next_task_ids);
+            Ok(())
+        })?;
+        result.status_change = status_change;
+        Ok(result)
+    }
+
+    pub fn bulk_set_task_priority(&self, ids: &[i64], priority: i32) -> DbResult<BulkResult> {
+        if !(1..=5).contains(&priority) {
+            return Err(DatabaseError::Data(format!("Priority must be between 1 and 5, got {}", priority)));
+        }
+        self.bulk_apply(ids, |id| self.set_task_field(id, "priority", &priority))
+    }
+
+    /// Moves tasks into a category, or out of theirs when None
+    pub fn bulk_set_task_category(&self, ids: &[i64], category_id: Option<i64>) -> DbResult<BulkResult> {
+        if let Some(category_id) = category_id {
+            let exists: bool = self.conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM categories WHERE id = ?)",
+                [category_id],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                return Err(DatabaseError::Data(format!("Category {} not found", category_id)));
+            }
+        }
+        self.bulk_apply(ids, |id| self.set_task_field(id, "category_id", &category_id))
+    }
+
+    /// Moves due dates by `days`, and start dates with them so tasks keep
+    /// their lead time. Tasks without a due date are reported and left alone.
+    pub fn bulk_shift_due_dates(&self, ids: &[i64], days: i64) -> DbResult<BulkResult> {
+        let shift = Duration::try_days(days)
+            .ok_or_else(|| DatabaseError::Data(format!("Cannot shift due dates by {} days", days)))?;
+        self.bulk_apply(ids, |id| {
+            let (due_date, start_date): (Option<String>, Option<String>) = self.conn.query_row(
+                "SELECT due_date, start_date FROM tasks WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            ).optional()?
+                .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))?;
+            if due_date.is_none() {
+                return Err(DatabaseError::Data(format!("Task {} has no due date", id)));
+            }
+
+            self.conn.execute(
+                "UPDATE tasks SET due_date = ?1, start_date = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+                params![shift_date(due_date, shift)?, shift_date(start_date, shift)?, id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Moves tasks to the end of a Kanban column in the order given. As when
+    /// dragging them one at a time, blocked tasks cannot leave their board's
+    /// first column and its last column completes them.
+    pub fn bulk_move_to_column(&self, ids: &[i64], column_id: i64) -> DbResult<BulkResult> {
+        let (starts_work, completes) = self.kanban_column_effect(column_id)?;
+
+        let mut status_change = TaskStatusChange::default();
+        let mut result = self.bulk_apply(ids, |id| {
+            if starts_work {
+                let blocking = self.get_blocking_tasks(id)?;
+                if !blocking.is_empty() {
+                    let titles: Vec<String> = blocking.into_iter().map(|t| t.title).collect();
+                    return Err(DatabaseError::Data(format!("Task is blocked by: {}", titles.join(", "))));
+                }
+            }
+
+            let status = if completes { "COMPLETED" } else { "IN_PROGRESS" };
+            let change = self.set_task_status(id, status)?;
+            status_change.completed_parent_ids.extend(change.completed_parent_ids);
+            status_change.next_task_ids.extend(change.next_task_ids);
+
+            self.conn.execute(
+                "UPDATE tasks SET kanban_column_id = ?1,
+                     kanban_order = (SELECT COALESCE(MAX(kanban_order), -1) + 1 FROM tasks WHERE kanban_column_id = ?1)
+                 WHERE id = ?2",
+                params![column_id, id],
+            )?;
+            Ok(())
+        })?;
+        result.status_change = status_change;
+        Ok(result)
+    }
+
+    /// `child_policy` is one of `CHILD_POLICIES`, as for a single delete
+    pub fn bulk_delete_tasks(&self, ids: &[i64], child_policy: &str) -> DbResult<BulkResult> {
+        Self::validate_child_policy(child_policy)?;
+        self.bulk_apply(ids, |id| self.delete_task_with_subtasks(id, child_policy))
+    }
+
+    pub fn bulk_archive_tasks(&self, ids: &[i64]) -> DbResult<BulkResult> {
+        self.bulk_apply(ids, |id| self.archive_task(id))
+    }
+
+    /// Applies `change` to each task, all in one transaction. A task whose
+    /// change fails is left as it was and reported, the rest still go through.
+    fn bulk_apply(&self, ids: &[i64], mut change: impl FnMut(i64) -> DbResult<()>) -> DbResult<BulkResult> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut results: Vec<BulkItemResult> = Vec::new();
+        for &id in ids {
+            if results.iter().any(|r| r.task_id == id) {
+                continue;
+            }
+            let error = self.savepoint(|| change(id)).err().map(|e| e.to_string());
+            results.push(BulkItemResult { task_id: id, error });
+        }
+        tx.commit()?;
+        Ok(BulkResult { results, ..Default::default() })
+    }
+
+    fn set_task_field(&self, id: i64, column: &str, value: &dyn rusqlite::ToSql) -> DbResult<()> {
+        let changed = self.conn.execute(
+            &format!("UPDATE tasks SET {} = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2", column),
+            params![value, id],
+        )?;
+        if changed == 0 {
+            return Err(DatabaseError::Data(format!("Task {} not found", id)));
+        }
+        Ok(())
+    }
+
+    // Trash operations
+    fn trash_table(item_type: &str) -> DbResult<&'static str> {
+        TRASHABLE_TABLES.iter()
+            .find(|(name, _)| *name == item_type)
+            .map(|(_, table)| *table)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid item type: {}", item_type)))
+    }
+
+    /// Moves an item to the trash, a task along with its subtasks. Its links
+    /// to notes, participants and tags stay in place for a restore.
+    pub fn trash_item(&self, item_type: &str, id: i64) -> DbResult<()> {
+        let table = Self::trash_table(item_type)?;
+        if item_type == "TASK" {
+            return self.delete_task_with_subtasks(id, "CASCADE");
+        }
+        self.conn.execute(
+            &format!("UPDATE {} SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL", table),
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// Everything in the trash, most recently deleted first
+    pub fn get_trash(&self) -> DbResult<Vec<TrashedItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT 'EVENT', id, title, deleted_at FROM events WHERE deleted_at IS NOT NULL
+             UNION ALL
+             SELECT 'TASK', id, title, deleted_at FROM tasks t
+             WHERE deleted_at IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM tasks p WHERE p.id = t.parent_task_id AND p.deleted_at = t.deleted_at)
+             UNION ALL
+             SELECT 'NOTE', id, title, deleted_at FROM notes WHERE deleted_at IS NOT NULL
+             ORDER BY 4 DESC, 2 DESC"
+        )?;
+        let items = stmt.query_map([], |row| {
+            Ok(TrashedItem { item_type: row.get(0)?, id: row.get(1)?, title: row.get(2)?, deleted_at: row.get(3)? })
+        })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(items)
+    }
+
+    /// Takes an item out of the trash. A task comes back with the subtasks
+    /// trashed along with it, at the top level when its parent is still in
+    /// the trash.
+    pub fn restore_item(&self, item_type: &str, id: i64) -> DbResult<()> {
+        let table = Self::trash_table(item_type)?;
+        let deleted_at = self.deleted_at(table, id)?
+            .ok_or_else(|| DatabaseError::Data(format!("{} {} is not in the trash", item_type, id)))?;
+        if item_type != "TASK" {
+            self.conn.execute(&format!("UPDATE {} SET deleted_at = NULL WHERE id = ?", table), [id])?;
+            return Ok(());
+        }
+
+        self.savepoint(|| {
+            self.conn.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT ?1
+                     UNION ALL
+                     SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id WHERE t.deleted_at = ?2
+                 )
+                 UPDATE tasks SET deleted_at = NULL WHERE id IN subtree",
+                params![id, deleted_at],
+            )?;
+
+            if let Some(parent_id) = self.parent_task_id(id)? {
+                if self.deleted_at("tasks", parent_id)?.is_some() {
+                    self.conn.execute(
+                        "UPDATE tasks SET parent_task_id = NULL, subtask_order = NULL WHERE id = ?",
+                        [id],
+                    )?;
+                } else {
+                    self.renumber_subtasks(parent_id, None)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Deletes an item in the trash for good, a task with everything below it
+    pub fn purge_item(&self, item_type: &str, id: i64) -> DbResult<()> {
+        let table = Self::trash_table(item_type)?;
+        if self.deleted_at(table, id)?.is_none() {
+            return Err(DatabaseError::Data(format!("{} {} is not in the trash", item_type, id)));
+        }
+
+        if item_type == "TASK" {
+            self.conn.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT ?1
+                     UNION ALL
+                     SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+                 )
+                 DELETE FROM tasks WHERE id IN subtree",
+                [id],
+            )?;
+        } else {
+            self.conn.execute(&format!("DELETE FROM {} WHERE id = ?", table), [id])?;
+        }
+        Ok(())
+    }
+
+    /// Purges everything in the trash, returning how many items went
+    pub fn empty_trash(&self) -> DbResult<usize> {
+        self.purge_trash_items(self.get_trash()?)
+    }
+
+    /// Purges what has been in the trash for longer than the
+    /// `trash_retention_days` setting, where 0 keeps it forever. Returns how
+    /// many items went.
+    pub fn purge_expired_trash(&self) -> DbResult<usize> {
+        let days: i64 = self.conn.query_row(
+            "SELECT value FROM settings WHERE key = 'trash_retention_days'",
+            [],
+            |row| row.get::<_, String>(0),
+        ).optional()?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(30);
+        if days <= 0 {
+            return Ok(0);
+        }
+
+        let cutoff: String = self.conn.query_row(
+            "SELECT datetime('now', ?)",
+            [format!("-{} days", days)],
+            |row| row.get(0),
+        )?;
+        let expired = self.get_trash()?
+            .into_iter()
+            .filter(|item| item.deleted_at <= cutoff)
+            .collect();
+        self.purge_trash_items(expired)
+    }
+
+    fn purge_trash_items(&self, items: Vec<TrashedItem>) -> DbResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        for item in &items {
+            self.purge_item(&item.item_type, item.id)?;
+        }
+        tx.commit()?;
+        Ok(items.len())
+    }
+
+    /// When the item went to the trash, None while it is not there
+    fn deleted_at(&self, table: &str, id: i64) -> DbResult<Option<String>> {
+        let deleted_at: Option<Option<String>> = self.conn.query_row(
+            &format!("SELECT deleted_at FROM {} WHERE id = ?", table),
+            [id],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(deleted_at.flatten())
+    }
+
+    // Archive operations
+    /// Archives a finished top-level task with its subtasks. Archived tasks
+    /// leave the lists and searches but, unlike trashed ones, are kept for good.
+    pub fn archive_task(&self, id: i64) -> DbResult<()> {
+        let (finished, trashed, parent_id): (bool, bool, Option<i64>) = self.conn.query_row(
+            &format!("SELECT status IN {}, deleted_at IS NOT NULL, parent_task_id FROM tasks WHERE id = ?", FINISHED_STATUSES),
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))?;
+        if trashed {
+            return Err(DatabaseError::Data(format!("Task {} is in the trash", id)));
+        }
+        if parent_id.is_some() {
+            return Err(DatabaseError::Data(format!("Task {} is a subtask, it is archived along with its task", id)));
+        }
+        if !finished {
+            return Err(DatabaseError::Data(format!("Task {} is not finished yet", id)));
+        }
+
+        self.set_subtree_archived(id, true)
+    }
+
+    pub fn unarchive_task(&self, id: i64) -> DbResult<()> {
+        let archived: bool = self.conn.query_row(
+            "SELECT archived_at IS NOT NULL FROM tasks WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| DatabaseError::Data(format!("Task {} not found", id)))?;
+        if !archived {
+            return Err(DatabaseError::Data(format!("Task {} is not archived", id)));
+        }
+        self.set_subtree_archived(id, false)
+    }
+
+    /// Archived top-level tasks, most recently archived first
+    pub fn get_archived_tasks(&self) -> DbResult<Vec<Task>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks
+             WHERE archived_at IS NOT NULL AND deleted_at IS NULL AND parent_task_id IS NULL
+             ORDER BY archived_at DESC, id DESC",
+            TASK_COLUMNS
+        ))?;
+        let tasks = stmt.query_and_then([], Task::from_row)?
+            .collect::<DbResult<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    /// Archives every finished top-level task completed before `before`.
+    /// Returns how many were archived.
+    pub fn archive_finished_tasks(&self, before: &str) -> DbResult<usize> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM tasks
+             WHERE status IN {} AND COALESCE(completed_at, updated_at) < ?
+               AND parent_task_id IS NULL AND archived_at IS NULL AND deleted_at IS NULL",
+            FINISHED_STATUSES
+        ))?;
+        let ids = stmt.query_map([before], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        for &id in &ids {
+            self.set_subtree_archived(id, true)?;
+        }
+        tx.commit()?;
+        Ok(ids.len())
+    }
+
+    fn set_subtree_archived(&self, id: i64, archived: bool) -> DbResult<()> {
+        self.conn.execute(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT ?1
+                 UNION ALL
+                 SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+             )
+             UPDATE tasks SET archived_at = CASE WHEN ?2 THEN COALESCE(archived_at, datetime('now')) END
+             WHERE id IN subtree",
+            params![id, archived],
+        )?;
+        Ok(())
+    }
+}
+
+/// Due dates are stored either as a date or as a date and time, the flag
+/// tells which
+fn parse_due_date(value: &str) -> Option<(NaiveDateTime, bool)> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|at| at.naive_utc()))
+        .map(|at| (at, true))
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some((date.and_hms_opt(0, 0, 0)?, false))
+        })
+}
+
+/// Moves a stored date by `shift`, keeping whether it has a time. Values
+/// that do not parse are kept as they are.
+fn shift_date(value: Option<String>, shift: Duration) -> DbResult<Option<String>> {
+    match value.as_deref().and_then(parse_due_date) {
+        Some((at, has_time)) => Ok(Some(format_due_date(shift_time(at, shift)?, has_time))),
+        None => Ok(value),
+    }
+}
+
+/// `at` moved by `shift`, failing instead of going past the calendar's range
+fn shift_time(at: NaiveDateTime, shift: Duration) -> DbResult<NaiveDateTime> {
+    at.checked_add_signed(shift)
+        .ok_or_else(|| DatabaseError::Data(format!("{} cannot be moved that far", format_due_date(at, true))))
+}
+
+fn format_due_date(value: NaiveDateTime, has_time: bool) -> String {
+    if has_time {
+        value.format("%Y-%m-%d %H:%M:%S").to_string()
+    } else {
+        value.format("%Y-%m-%d").to_string()
+    }
 }
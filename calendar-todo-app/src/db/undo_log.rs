@@ -0,0 +1,300 @@
+use super::{Database, models::{UndoEntry, UndoHistory}, error::*};
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+
+/// The undo log lives in temp tables, so it is kept per connection and goes
+/// away with the session. Each step holds the before and after image of every
+/// row it changed, written by the temp triggers `install_undo_log` puts on the
+/// app's tables.
+const UNDO_LOG_SCHEMA: &str = "
+    CREATE TEMP TABLE IF NOT EXISTS undo_steps (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        label TEXT NOT NULL,
+        state TEXT NOT NULL DEFAULT 'RECORDING' CHECK (state IN ('RECORDING', 'DONE', 'UNDONE')),
+        created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TEMP TABLE IF NOT EXISTS undo_changes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        step_id INTEGER NOT NULL REFERENCES undo_steps(id) ON DELETE CASCADE,
+        table_name TEXT NOT NULL,
+        row_id INTEGER NOT NULL,
+        before_row TEXT, -- NULL when the row was inserted
+        after_row TEXT   -- NULL when the row was deleted
+    );
+
+    CREATE INDEX IF NOT EXISTS temp.idx_undo_changes_step ON undo_changes(step_id);
+";
+
+/// Maintained by triggers, so a change that only moves it is not recorded and
+/// does not count as a conflict
+const IGNORED_COLUMN: &str = "updated_at";
+
+/// Append-only, undoing a change adds to the history rather than rewinding it
+const UNTRACKED_TABLES: &[&str] = &["change_history"];
+
+/// Changes are recorded for undo until this is dropped, see `Database::undo_step`
+pub struct UndoStep<'a> {
+    db: &'a Database,
+    id: Option<i64>, // None when joining a step that is already recording
+}
+
+impl Drop for UndoStep<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            // A step left recording would swallow every later change
+            if self.db.finish_undo_step(id).is_err() {
+                let _ = self.db.conn.execute("DELETE FROM undo_steps WHERE id = ?", [id]);
+            }
+        }
+    }
+}
+
+/// JSON object holding a row's columns, as seen through `prefix` ("NEW.", "OLD." or "")
+fn row_image(prefix: &str, columns: &[String]) -> String {
+    let pairs: Vec<String> = columns.iter()
+        .map(|column| format!("'{}', {}\"{}\"", column, prefix, column))
+        .collect();
+    format!("json_object({})", pairs.join(", "))
+}
+
+/// Whether two row images hold the same values, leaving out `IGNORED_COLUMN`
+fn same_row(a: Option<&str>, b: Option<&str>) -> DbResult<bool> {
+    let parse = |image: &str| -> DbResult<Value> {
+        let mut value: Value = serde_json::from_str(image)
+            .map_err(|e| DatabaseError::Data(format!("Invalid undo log entry: {}", e)))?;
+        if let Some(row) = value.as_object_mut() {
+            row.remove(IGNORED_COLUMN);
+        }
+        Ok(value)
+    };
+    match (a, b) {
+        (None, None) => Ok(true),
+        (Some(a), Some(b)) => Ok(parse(a)? == parse(b)?),
+        _ => Ok(false),
+    }
+}
+
+impl Database {
+    /// Creates the session's undo log and the triggers recording into it.
+    /// Runs once the schema is in place.
+    pub fn install_undo_log(&self) -> DbResult<()> {
+        self.conn.execute_batch(UNDO_LOG_SCHEMA)?;
+
+        for table in self.undo_tables()?.into_iter().filter(|t| !UNTRACKED_TABLES.contains(&t.as_str())) {
+            let columns = self.table_columns(&table)?;
+            let compared: Vec<String> = columns.iter().filter(|c| *c != IGNORED_COLUMN).cloned().collect();
+            let record = |row_id: &str, before: &str, after: &str| format!(
+                "INSERT INTO undo_changes (step_id, table_name, row_id, before_row, after_row)
+                 SELECT id, '{}', {}, {}, {} FROM undo_steps WHERE state = 'RECORDING';",
+                table, row_id, before, after
+            );
+
+            self.conn.execute_batch(&format!(
+                "DROP TRIGGER IF EXISTS temp.undo_{table}_insert;
+                 CREATE TEMP TRIGGER undo_{table}_insert AFTER INSERT ON main.\"{table}\"
+                 BEGIN {insert} END;
+
+                 DROP TRIGGER IF EXISTS temp.undo_{table}_update;
+                 CREATE TEMP TRIGGER undo_{table}_update AFTER UPDATE ON main.\"{table}\"
+                 WHEN {old_compared} IS NOT {new_compared}
+                 BEGIN {update} END;
+
+                 DROP TRIGGER IF EXISTS temp.undo_{table}_delete;
+                 CREATE TEMP TRIGGER undo_{table}_delete AFTER DELETE ON main.\"{table}\"
+                 BEGIN {delete} END;",
+                table = table,
+                insert = record("NEW.rowid", "NULL", &row_image("NEW.", &columns)),
+                update = record("NEW.rowid", &row_image("OLD.", &columns), &row_image("NEW.", &columns)),
+                delete = record("OLD.rowid", &row_image("OLD.", &columns), "NULL"),
+                old_compared = row_image("OLD.", &compared),
+                new_compared = row_image("NEW.", &compared),
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Starts an undo step: everything changed until the returned guard is
+    /// dropped is undone and redone as one. A step started while another is
+    /// recording joins it, so commands built from other commands stay one step.
+    pub fn undo_step(&self, label: &str) -> DbResult<UndoStep<'_>> {
+        if self.recording_step()?.is_some() {
+            return Ok(UndoStep { db: self, id: None });
+        }
+        self.conn.execute("INSERT INTO undo_steps (label) VALUES (?)", [label])?;
+        Ok(UndoStep { db: self, id: Some(self.conn.last_insert_rowid()) })
+    }
+
+    /// Reverts the latest step. Returns it, or None when there is nothing to undo.
+    pub fn undo(&self) -> DbResult<Option<UndoEntry>> {
+        let entry = self.undo_entries("DONE", "DESC")?.into_iter().next();
+        if let Some(entry) = &entry {
+            self.replay(entry, true)?;
+            self.conn.execute("UPDATE undo_steps SET state = 'UNDONE' WHERE id = ?", [entry.id])?;
+        }
+        Ok(entry)
+    }
+
+    /// Applies the step undone last again. Returns it, or None when there is
+    /// nothing to redo.
+    pub fn redo(&self) -> DbResult<Option<UndoEntry>> {
+        let entry = self.undo_entries("UNDONE", "ASC")?.into_iter().next();
+        if let Some(entry) = &entry {
+            self.replay(entry, false)?;
+            self.conn.execute("UPDATE undo_steps SET state = 'DONE' WHERE id = ?", [entry.id])?;
+        }
+        Ok(entry)
+    }
+
+    pub fn get_undo_history(&self) -> DbResult<UndoHistory> {
+        Ok(UndoHistory {
+            undo: self.undo_entries("DONE", "DESC")?,
+            redo: self.undo_entries("UNDONE", "ASC")?,
+        })
+    }
+
+    fn undo_entries(&self, state: &str, order: &str) -> DbResult<Vec<UndoEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, label, created_at FROM undo_steps WHERE state = ? ORDER BY id {}",
+            order
+        ))?;
+        let entries = stmt.query_map([state], |row| {
+            Ok(UndoEntry { id: row.get(0)?, label: row.get(1)?, created_at: row.get(2)? })
+        })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn recording_step(&self) -> DbResult<Option<i64>> {
+        let id = self.conn.query_row(
+            "SELECT id FROM undo_steps WHERE state = 'RECORDING'",
+            [],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(id)
+    }
+
+    /// Closes a step. One that changed nothing is dropped, otherwise it goes
+    /// on top of the undo stack, clears the redo stack and pushes out the
+    /// steps beyond `undo_depth`.
+    fn finish_undo_step(&self, id: i64) -> DbResult<()> {
+        let changed: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM undo_changes WHERE step_id = ?)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if !changed {
+            self.conn.execute("DELETE FROM undo_steps WHERE id = ?", [id])?;
+            return Ok(());
+        }
+
+        let depth: i64 = self.conn.query_row(
+            "SELECT value FROM settings WHERE key = 'undo_depth'",
+            [],
+            |row| row.get::<_, String>(0),
+        ).optional()?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(50);
+
+        self.conn.execute("UPDATE undo_steps SET state = 'DONE' WHERE id = ?", [id])?;
+        self.conn.execute("DELETE FROM undo_steps WHERE state = 'UNDONE'", [])?;
+        self.conn.execute(
+            "DELETE FROM undo_steps WHERE state = 'DONE' AND id NOT IN (
+                 SELECT id FROM undo_steps WHERE state = 'DONE' ORDER BY id DESC LIMIT ?
+             )",
+            [depth.max(1)],
+        )?;
+        Ok(())
+    }
+
+    /// Puts every row the step changed back to its before image, or forward
+    /// to its after image, in one transaction. A row that has since been
+    /// changed outside the log is a conflict: nothing is applied and the step,
+    /// which can no longer be replayed, is dropped.
+    fn replay(&self, entry: &UndoEntry, undo: bool) -> DbResult<()> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT table_name, row_id, before_row, after_row FROM undo_changes WHERE step_id = ? ORDER BY id {}",
+            if undo { "DESC" } else { "ASC" }
+        ))?;
+        let changes = stmt.query_map([entry.id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?, row.get::<_, Option<String>>(3)?))
+        })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        // Rows come back one at a time, parents and children in either order
+        self.conn.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+        for (table, row_id, before, after) in changes {
+            let (expected, target) = if undo { (after, before) } else { (before, after) };
+            let columns = self.table_columns(&table)?;
+            let current: Option<String> = self.conn.query_row(
+                &format!("SELECT {} FROM main.\"{}\" WHERE rowid = ?", row_image("", &columns), table),
+                [row_id],
+                |row| row.get(0),
+            ).optional()?;
+
+            // Already there, put in place by a trigger or cascade replayed earlier
+            if same_row(current.as_deref(), target.as_deref())? {
+                continue;
+            }
+            if !same_row(current.as_deref(), expected.as_deref())? {
+                drop(tx);
+                self.conn.execute("DELETE FROM undo_steps WHERE id = ?", [entry.id])?;
+                return Err(DatabaseError::Data(format!(
+                    "Cannot {} \"{}\", {} row {} has changed since",
+                    if undo { "undo" } else { "redo" }, entry.label, table, row_id
+                )));
+            }
+
+            match (current, target) {
+                (_, None) => {
+                    self.conn.execute(&format!("DELETE FROM main.\"{}\" WHERE rowid = ?", table), [row_id])?;
+                }
+                (None, Some(image)) => {
+                    let names: Vec<String> = columns.iter().map(|c| format!("\"{}\"", c)).collect();
+                    let values: Vec<String> = columns.iter().map(|c| format!("json_extract(?2, '$.\"{}\"')", c)).collect();
+                    self.conn.execute(
+                        &format!("INSERT INTO main.\"{}\" (rowid, {}) VALUES (?1, {})", table, names.join(", "), values.join(", ")),
+                        params![row_id, image],
+                    )?;
+                }
+                (Some(_), Some(image)) => {
+                    let assignments: Vec<String> = columns.iter()
+                        .map(|c| format!("\"{}\" = json_extract(?2, '$.\"{}\"')", c, c))
+                        .collect();
+                    self.conn.execute(
+                        &format!("UPDATE main.\"{}\" SET {} WHERE rowid = ?1", table, assignments.join(", ")),
+                        params![row_id, image],
+                    )?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Tables whose changes are recorded: every ordinary table of the app,
+    /// leaving out the search indexes, which their own triggers keep in sync
+    fn undo_tables(&self) -> DbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name FROM sqlite_master t
+             WHERE t.type = 'table' AND t.name NOT LIKE 'sqlite_%'
+               AND t.sql NOT LIKE 'CREATE VIRTUAL TABLE%' AND t.sql NOT LIKE '%WITHOUT ROWID%'
+               AND NOT EXISTS (
+                   SELECT 1 FROM sqlite_master v
+                   WHERE v.type = 'table' AND v.sql LIKE 'CREATE VIRTUAL TABLE%' AND t.name LIKE v.name || '\\_%' ESCAPE '\\'
+               )
+             ORDER BY t.name"
+        )?;
+        let tables = stmt.query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(tables)
+    }
+
+    fn table_columns(&self, table: &str) -> DbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA main.table_info(\"{}\")", table))?;
+        let columns = stmt.query_map([], |row| row.get(1))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(columns)
+    }
+}
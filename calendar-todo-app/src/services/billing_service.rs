@@ -0,0 +1,370 @@
+use crate::db::{Database, error::DbResult};
+use crate::services::settings_service::{read_setting, write_setting};
+use crate::services::time_report_service::{csv_field, format_hours, load_report_entries, markdown_cell, split_by_day, ReportEntry};
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BillingRate {
+    pub is_billable: bool,
+    pub hourly_rate_cents: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoundingRule {
+    pub minutes: i64, // 0, 6 or 15
+    pub mode: String, // "ENTRY" or "DAY"
+}
+
+impl RoundingRule {
+    pub fn load(conn: &Connection) -> DbResult<Self> {
+        Ok(RoundingRule {
+            minutes: read_setting(conn, "billing_rounding_minutes")?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            mode: read_setting(conn, "billing_rounding_mode")?.unwrap_or_else(|| "ENTRY".to_string()),
+        })
+    }
+
+    pub fn save(&self, conn: &Connection) -> Result<(), String> {
+        if ![0, 6, 15].contains(&self.minutes) {
+            return Err("Rounding must be 0, 6 or 15 minutes".to_string());
+        }
+        if self.mode != "ENTRY" && self.mode != "DAY" {
+            return Err(format!("Invalid rounding mode: {}", self.mode));
+        }
+
+        write_setting(conn, "billing_rounding_minutes", &self.minutes.to_string()).map_err(|e| e.to_string())?;
+        write_setting(conn, "billing_rounding_mode", &self.mode).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Rounds to the nearest step, halves rounding up. A zero step bills exact time.
+    pub fn apply(&self, seconds: i64) -> i64 {
+        if self.minutes <= 0 {
+            return seconds;
+        }
+        let step = self.minutes * 60;
+        (seconds + step / 2) / step * step
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceQuery {
+    pub client_type: String, // "CATEGORY" or "PARTICIPANT"
+    pub client_id: i64,
+    pub start_date: String, // YYYY-MM-DD, inclusive
+    pub end_date: String, // YYYY-MM-DD, inclusive
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub date: String,
+    pub description: String,
+    pub entry_ids: Vec<i64>,
+    pub tracked_seconds: i64,
+    pub billed_seconds: i64,
+    pub hourly_rate_cents: i64,
+    pub amount_cents: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceStatement {
+    pub client_type: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub currency: String,
+    pub rounding: RoundingRule,
+    pub lines: Vec<InvoiceLine>,
+    pub total_tracked_seconds: i64,
+    pub total_billed_seconds: i64,
+    pub total_amount_cents: i64,
+}
+
+fn client_table(client_type: &str) -> Result<&'static str, String> {
+    match client_type {
+        "CATEGORY" => Ok("categories"),
+        "PARTICIPANT" => Ok("participants"),
+        other => Err(format!("Invalid client type: {}", other)),
+    }
+}
+
+pub fn write_billing_rate(conn: &Connection, client_type: &str, client_id: i64, rate: &BillingRate) -> Result<(), String> {
+    let table = client_table(client_type)?;
+    if rate.hourly_rate_cents.is_some_and(|cents| cents < 0) {
+        return Err("Hourly rate cannot be negative".to_string());
+    }
+
+    let updated = conn.execute(
+        &format!("UPDATE {} SET is_billable = ?1, hourly_rate_cents = ?2 WHERE id = ?3", table),
+        rusqlite::params![rate.is_billable, rate.hourly_rate_cents, client_id],
+    ).map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Client {} not found", client_id));
+    }
+
+    Ok(())
+}
+
+/// Name and billing terms of a client, if it exists.
+pub fn load_client(conn: &Connection, client_type: &str, client_id: i64) -> Result<Option<(String, BillingRate)>, String> {
+    let table = client_table(client_type)?;
+
+    conn.query_row(
+        &format!("SELECT name, is_billable, hourly_rate_cents FROM {} WHERE id = ?1", table),
+        [client_id],
+        |row| Ok((row.get(0)?, BillingRate { is_billable: row.get(1)?, hourly_rate_cents: row.get(2)? })),
+    ).optional().map_err(|e| e.to_string())
+}
+
+fn category_rates(conn: &Connection) -> DbResult<HashMap<i64, i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, hourly_rate_cents FROM categories WHERE hourly_rate_cents IS NOT NULL"
+    )?;
+    let rates = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(rates)
+}
+
+fn participant_event_ids(conn: &Connection, participant_id: i64) -> DbResult<HashSet<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT event_id FROM event_participants WHERE participant_id = ?1"
+    )?;
+    let ids = stmt.query_map([participant_id], |row| row.get(0))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    Ok(ids)
+}
+
+fn line_description(entry: &ReportEntry) -> String {
+    match (&entry.item_title, entry.item_id) {
+        (Some(title), _) => title.clone(),
+        (None, Some(id)) => format!("{} #{}", entry.item_type, id),
+        (None, None) => "Manual entry".to_string(),
+    }
+}
+
+/// Amount for billed time at an hourly rate, rounded to the nearest cent.
+pub fn line_amount(billed_seconds: i64, hourly_rate_cents: i64) -> i64 {
+    (billed_seconds * hourly_rate_cents + 1800) / 3600
+}
+
+fn invoice_line(
+    date: NaiveDate,
+    description: String,
+    entry_ids: Vec<i64>,
+    tracked_seconds: i64,
+    rounding: &RoundingRule,
+    hourly_rate_cents: i64,
+) -> InvoiceLine {
+    let billed_seconds = rounding.apply(tracked_seconds);
+    InvoiceLine {
+        date: date.format("%Y-%m-%d").to_string(),
+        description,
+        entry_ids,
+        tracked_seconds,
+        billed_seconds,
+        hourly_rate_cents,
+        amount_cents: line_amount(billed_seconds, hourly_rate_cents),
+    }
+}
+
+/// (date, item type, item id)
+type DayKey = (NaiveDate, String, Option<i64>);
+/// (description, tracked seconds, entry ids, hourly rate)
+type DayTotal = (String, i64, Vec<i64>, i64);
+
+/// Itemised statement for a client's time in `[start_date, end_date]`.
+///
+/// A category bills every entry tracked against it, its tasks and events. A
+/// participant bills the events they attend, at their own rate or else at
+/// the event category's rate. Entries are split at midnight and only the part
+/// inside the period is billed; rounding applies per entry or per day and item.
+pub fn build_invoice(conn: &Connection, query: &InvoiceQuery) -> Result<InvoiceStatement, String> {
+    let from = NaiveDate::parse_from_str(&query.start_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", query.start_date))?;
+    let to = NaiveDate::parse_from_str(&query.end_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", query.end_date))?;
+    if to < from {
+        return Err("End date must not be before start date".to_string());
+    }
+
+    let (client_name, client_rate) = load_client(conn, &query.client_type, query.client_id)?
+        .ok_or_else(|| format!("Client {} not found", query.client_id))?;
+    if !client_rate.is_billable {
+        return Err(format!("{} is not billable", client_name));
+    }
+
+    let rounding = RoundingRule::load(conn).map_err(|e| e.to_string())?;
+    let currency = read_setting(conn, "billing_currency")
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| "USD".to_string());
+    let fallback_rates = category_rates(conn).map_err(|e| e.to_string())?;
+    let events = if query.client_type == "PARTICIPANT" {
+        participant_event_ids(conn, query.client_id).map_err(|e| e.to_string())?
+    } else {
+        HashSet::new()
+    };
+
+    let entries: Vec<ReportEntry> = load_report_entries(conn, from, to)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|entry| match query.client_type.as_str() {
+            "CATEGORY" => entry.category_id == Some(query.client_id),
+            _ => entry.item_type == "EVENT" && entry.item_id.is_some_and(|id| events.contains(&id)),
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut days: BTreeMap<DayKey, DayTotal> = BTreeMap::new();
+
+    for entry in &entries {
+        let rate = client_rate.hourly_rate_cents
+            .or_else(|| entry.category_id.and_then(|id| fallback_rates.get(&id).copied()))
+            .unwrap_or(0);
+        let segments = split_by_day(entry, from, to);
+
+        if rounding.mode == "ENTRY" {
+            // One line per entry, dated by its first billed day
+            let Some(first) = segments.first() else { continue };
+            let tracked = segments.iter().map(|s| s.seconds).sum();
+            lines.push(invoice_line(first.date, line_description(entry), vec![entry.id], tracked, &rounding, rate));
+        } else {
+            for segment in segments {
+                let day = days
+                    .entry((segment.date, entry.item_type.clone(), entry.item_id))
+                    .or_insert_with(|| (line_description(entry), 0, Vec::new(), rate));
+                day.1 += segment.seconds;
+                if !day.2.contains(&entry.id) {
+                    day.2.push(entry.id);
+                }
+            }
+        }
+    }
+
+    for ((date, _, _), (description, tracked, entry_ids, rate)) in days {
+        lines.push(invoice_line(date, description, entry_ids, tracked, &rounding, rate));
+    }
+
+    Ok(InvoiceStatement {
+        client_type: query.client_type.clone(),
+        client_id: query.client_id,
+        client_name,
+        start_date: query.start_date.clone(),
+        end_date: query.end_date.clone(),
+        currency,
+        rounding,
+        total_tracked_seconds: lines.iter().map(|l| l.tracked_seconds).sum(),
+        total_billed_seconds: lines.iter().map(|l| l.billed_seconds).sum(),
+        total_amount_cents: lines.iter().map(|l| l.amount_cents).sum(),
+        lines,
+    })
+}
+
+/// Formats cents as a decimal amount, e.g. 12345 -> "123.45".
+pub fn format_amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+pub fn invoice_to_csv(statement: &InvoiceStatement) -> String {
+    let mut csv = String::from("date,description,tracked_hours,billed_hours,rate,amount,currency\n");
+
+    for line in &statement.lines {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            line.date,
+            csv_field(&line.description),
+            format_hours(line.tracked_seconds),
+            format_hours(line.billed_seconds),
+            format_amount(line.hourly_rate_cents),
+            format_amount(line.amount_cents),
+            statement.currency
+        ));
+    }
+    csv.push_str(&format!(
+        "total,Total,{},{},,{},{}\n",
+        format_hours(statement.total_tracked_seconds),
+        format_hours(statement.total_billed_seconds),
+        format_amount(statement.total_amount_cents),
+        statement.currency
+    ));
+
+    csv
+}
+
+pub fn invoice_to_markdown(statement: &InvoiceStatement) -> String {
+    let mut markdown = format!(
+        "## Statement for {}\n\n{} to {}\n\n| Date | Description | Hours | Rate | Amount |\n|---|---|---:|---:|---:|\n",
+        markdown_cell(&statement.client_name),
+        statement.start_date,
+        statement.end_date
+    );
+
+    for line in &statement.lines {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            line.date,
+            markdown_cell(&line.description),
+            format_hours(line.billed_seconds),
+            format_amount(line.hourly_rate_cents),
+            format_amount(line.amount_cents)
+        ));
+    }
+    markdown.push_str(&format!(
+        "| | **Total** | **{}** | | **{} {}** |\n",
+        format_hours(statement.total_billed_seconds),
+        format_amount(statement.total_amount_cents),
+        statement.currency
+    ));
+
+    markdown
+}
+
+#[tauri::command]
+pub async fn update_billing_rate(
+    client_type: String,
+    client_id: i64,
+    rate: BillingRate,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let _undo = db.undo_step("Update billing rate").map_err(|e| e.to_string())?;
+    write_billing_rate(db.get_connection(), &client_type, client_id, &rate)
+}
+
+#[tauri::command]
+pub async fn get_billing_rounding(db: State<'_, Database>) -> Result<RoundingRule, String> {
+    RoundingRule::load(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_billing_rounding(rule: RoundingRule, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update billing rounding").map_err(|e| e.to_string())?;
+    rule.save(db.get_connection())
+}
+
+#[tauri::command]
+pub async fn get_invoice_statement(query: InvoiceQuery, db: State<'_, Database>) -> Result<InvoiceStatement, String> {
+    build_invoice(db.get_connection(), &query)
+}
+
+/// Renders a statement as "JSON", "CSV" or "MARKDOWN".
+#[tauri::command]
+pub async fn export_invoice(
+    query: InvoiceQuery,
+    format: String,
+    db: State<'_, Database>
+) -> Result<String, String> {
+    let statement = build_invoice(db.get_connection(), &query)?;
+
+    match format.as_str() {
+        "JSON" => serde_json::to_string_pretty(&statement).map_err(|e| e.to_string()),
+        "CSV" => Ok(invoice_to_csv(&statement)),
+        "MARKDOWN" => Ok(invoice_to_markdown(&statement)),
+        other => Err(format!("Unsupported export format: {}", other)),
+    }
+}
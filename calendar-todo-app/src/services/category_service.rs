@@ -1,4 +1,4 @@
-use crate::db::{Database, models::Category};
+use crate::db::{Database, models::Category, error::CommandError};
 use serde::{Serialize, Deserialize};
 use tauri::State;
 
@@ -29,16 +29,19 @@ pub async fn get_categories(db: State<'_, Database>) -> Result<Vec<Category>, St
 
 #[tauri::command]
 pub async fn create_category(category: Category, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create category").map_err(|e| e.to_string())?;
     db.create_category(&category).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn update_category(category: Category, db: State<'_, Database>) -> Result<(), String> {
-    db.update_category(&category).map_err(|e| e.to_string())
+pub async fn update_category(category: Category, db: State<'_, Database>) -> Result<Category, CommandError> {
+    let _undo = db.undo_step("Update category")?;
+    Ok(db.update_category(&category)?)
 }
 
 #[tauri::command]
 pub async fn delete_category(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete category").map_err(|e| e.to_string())?;
     db.delete_category(id).map_err(|e| e.to_string())
 }
 
@@ -50,6 +53,7 @@ pub async fn export_categories(db: State<'_, Database>) -> Result<String, String
 
 #[tauri::command]
 pub async fn import_categories(json_data: String, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Import categories").map_err(|e| e.to_string())?;
     let categories: Vec<Category> = serde_json::from_str(&json_data)
         .map_err(|e| e.to_string())?;
     
@@ -1,4 +1,4 @@
-use crate::db::{Database, models::Event, error::DbResult};
+use crate::db::{Database, models::Event, error::{CommandError, DbResult}};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use tauri::State;
@@ -28,7 +28,8 @@ pub async fn get_events_in_range(
         "SELECT id, title, description, start_time, end_time, is_all_day, location, 
          priority, category_id, recurring_rule_id, created_at, updated_at 
          FROM events 
-         WHERE (start_time BETWEEN ?1 AND ?2) OR (end_time BETWEEN ?1 AND ?2)"
+         WHERE deleted_at IS NULL
+           AND ((start_time BETWEEN ?1 AND ?2) OR (end_time BETWEEN ?1 AND ?2))"
     ).map_err(|e| e.to_string())?;
     
     let events = stmt
@@ -55,6 +56,7 @@ pub async fn create_event(
     event: Event,
     db: State<'_, Database>,
 ) -> Result<i64, String> {
+    let _undo = db.undo_step("Create event").map_err(|e| e.to_string())?;
     db.create_event(&event).map_err(|e| e.to_string())
 }
 
@@ -62,8 +64,9 @@ pub async fn create_event(
 pub async fn update_event(
     event: Event,
     db: State<'_, Database>,
-) -> Result<(), String> {
-    db.update_event(&event).map_err(|e| e.to_string())
+) -> Result<Event, CommandError> {
+    let _undo = db.undo_step("Update event")?;
+    Ok(db.update_event(&event)?)
 }
 
 #[tauri::command]
@@ -71,5 +74,6 @@ pub async fn delete_event(
     id: i64,
     db: State<'_, Database>,
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Delete event").map_err(|e| e.to_string())?;
     db.delete_event(id).map_err(|e| e.to_string())
 }
@@ -0,0 +1,92 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::State;
+
+/// Item types whose changes are kept, written by the `*_history_*` triggers
+pub const HISTORY_ITEM_TYPES: &[&str] = &["EVENT", "TASK", "NOTE", "CATEGORY"];
+
+/// One entry in an item's timeline. Updates get one entry per changed field,
+/// values as stored, so booleans read "0" and "1".
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub item_type: String,
+    pub item_id: i64,
+    pub action: String, // CREATE, UPDATE or DELETE
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+}
+
+/// An item's changes, oldest first, optionally only those to `field`
+pub fn load_item_history(
+    conn: &Connection,
+    item_type: &str,
+    item_id: i64,
+    field: Option<&str>,
+) -> DbResult<Vec<HistoryEntry>> {
+    if !HISTORY_ITEM_TYPES.contains(&item_type) {
+        return Err(DatabaseError::Data(format!("No history is kept for items of type {}", item_type)));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, item_type, item_id, action, field, old_value, new_value, changed_at
+         FROM change_history
+         WHERE item_type = ?1 AND item_id = ?2 AND (?3 IS NULL OR field = ?3)
+         ORDER BY changed_at, id"
+    )?;
+    let entries = stmt.query_map(params![item_type, item_id, field], |row| {
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            item_type: row.get(1)?,
+            item_id: row.get(2)?,
+            action: row.get(3)?,
+            field: row.get(4)?,
+            old_value: row.get(5)?,
+            new_value: row.get(6)?,
+            changed_at: row.get(7)?,
+        })
+    })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Drops entries older than the `history_retention_days` setting, where 0
+/// keeps them for good. Returns how many went.
+pub fn purge_history(conn: &Connection) -> DbResult<usize> {
+    let days: i64 = conn.query_row(
+        "SELECT value FROM settings WHERE key = 'history_retention_days'",
+        [],
+        |row| row.get::<_, String>(0),
+    ).optional()?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(365);
+    if days <= 0 {
+        return Ok(0);
+    }
+
+    let purged = conn.execute(
+        "DELETE FROM change_history WHERE changed_at < strftime('%Y-%m-%d %H:%M:%f', 'now', ?)",
+        [format!("-{} days", days)],
+    )?;
+    Ok(purged)
+}
+
+#[tauri::command]
+pub async fn get_item_history(
+    item_type: String,
+    item_id: i64,
+    field: Option<String>,
+    db: State<'_, Database>,
+) -> Result<Vec<HistoryEntry>, String> {
+    load_item_history(db.get_connection(), &item_type, item_id, field.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Run on startup and from time to time to apply `history_retention_days`
+#[tauri::command]
+pub async fn purge_expired_history(db: State<'_, Database>) -> Result<usize, String> {
+    purge_history(db.get_connection()).map_err(|e| e.to_string())
+}
@@ -44,6 +44,7 @@ pub async fn get_holiday_feeds(db: State<'_, Database>) -> Result<Vec<HolidayFee
 
 #[tauri::command]
 pub async fn create_holiday_feed(feed: HolidayFeed, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create holiday feed").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
@@ -61,6 +62,7 @@ pub async fn create_holiday_feed(feed: HolidayFeed, db: State<'_, Database>) ->
 
 #[tauri::command]
 pub async fn update_holiday_feed(feed: HolidayFeed, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update holiday feed").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -78,6 +80,7 @@ pub async fn update_holiday_feed(feed: HolidayFeed, db: State<'_, Database>) ->
 
 #[tauri::command]
 pub async fn delete_holiday_feed(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete holiday feed").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     conn.execute("DELETE FROM holiday_feeds WHERE id = ?", [id])
         .map_err(|e| e.to_string())?;
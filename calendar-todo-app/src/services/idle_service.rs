@@ -0,0 +1,359 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::settings_service::{read_setting, write_setting};
+use crate::services::time_tracking_service::{
+    delete_timesheet_entry, format_timestamp, insert_running_entry, insert_timesheet_entry, load_time_entry,
+    parse_timestamp, split_entry, stop_timer_entry, TimeEntry, ENTRY_COLUMNS,
+};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+
+/// The longest idle threshold, a day
+pub const MAX_IDLE_THRESHOLD_MINUTES: i64 = 24 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IdleConfig {
+    pub threshold_minutes: i64,
+    pub action: String, // "PAUSE" or "TRIM"
+}
+
+impl IdleConfig {
+    pub fn load(conn: &Connection) -> DbResult<Self> {
+        Ok(IdleConfig {
+            threshold_minutes: read_setting(conn, "idle_threshold_minutes")?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10)
+                .clamp(1, MAX_IDLE_THRESHOLD_MINUTES),
+            action: read_setting(conn, "idle_action")?.unwrap_or_else(|| "PAUSE".to_string()),
+        })
+    }
+
+    pub fn save(&self, conn: &Connection) -> Result<(), String> {
+        if self.threshold_minutes < 1 {
+            return Err("Idle threshold must be at least one minute".to_string());
+        }
+        if self.threshold_minutes > MAX_IDLE_THRESHOLD_MINUTES {
+            return Err("Idle threshold can be at most a day".to_string());
+        }
+        if self.action != "PAUSE" && self.action != "TRIM" {
+            return Err(format!("Invalid idle action: {}", self.action));
+        }
+
+        write_setting(conn, "idle_threshold_minutes", &self.threshold_minutes.to_string()).map_err(|e| e.to_string())?;
+        write_setting(conn, "idle_action", &self.action).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Time away from the keyboard while a timer was running. Payload of the
+/// `idle-detected` and `idle-ended` events.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IdleGap {
+    pub id: i64,
+    pub time_entry_id: Option<i64>,
+    pub resumed_entry_id: Option<i64>,
+    pub item_type: String,
+    pub item_id: Option<i64>,
+    pub timer_type: String,
+    pub action: String,
+    pub idle_started_at: String,
+    pub idle_ended_at: Option<String>,
+    pub idle_seconds: Option<i64>,
+    pub resolution: Option<String>,
+}
+
+impl IdleGap {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        let idle_started_at: String = row.get(7)?;
+        let idle_ended_at: Option<String> = row.get(8)?;
+        let idle_seconds = match (parse_timestamp(&idle_started_at), idle_ended_at.as_deref().and_then(parse_timestamp)) {
+            (Some(start), Some(end)) => Some((end - start).num_seconds()),
+            _ => None,
+        };
+
+        Ok(IdleGap {
+            id: row.get(0)?,
+            time_entry_id: row.get(1)?,
+            resumed_entry_id: row.get(2)?,
+            item_type: row.get(3)?,
+            item_id: row.get(4)?,
+            timer_type: row.get(5)?,
+            action: row.get(6)?,
+            idle_started_at,
+            idle_ended_at,
+            idle_seconds,
+            resolution: row.get(9)?,
+        })
+    }
+
+    fn span(&self) -> DbResult<(NaiveDateTime, NaiveDateTime)> {
+        let start = parse_timestamp(&self.idle_started_at);
+        let end = self.idle_ended_at.as_deref().and_then(parse_timestamp);
+        match (start, end) {
+            (Some(start), Some(end)) => Ok((start, end)),
+            _ => Err(DatabaseError::Data(format!("Idle gap {} has not ended", self.id))),
+        }
+    }
+
+    /// A time entry for the gap's item, as `start_timer`/timesheet input.
+    fn entry_template(&self, start: NaiveDateTime, end: Option<NaiveDateTime>) -> TimeEntry {
+        TimeEntry {
+            id: None,
+            item_type: self.item_type.clone(),
+            item_id: self.item_id,
+            start_time: format_timestamp(start),
+            end_time: end.map(format_timestamp),
+            duration_seconds: None,
+            timer_type: self.timer_type.clone(),
+            created_at: None,
+        }
+    }
+}
+
+const GAP_COLUMNS: &str = "id, time_entry_id, resumed_entry_id, item_type, item_id, timer_type, action,
+                           idle_started_at, idle_ended_at, resolution";
+
+fn load_idle_gap(conn: &Connection, id: i64) -> DbResult<Option<IdleGap>> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM idle_periods WHERE id = ?1", GAP_COLUMNS),
+        [id],
+        IdleGap::from_row,
+    ).optional()?)
+}
+
+fn open_idle_gap(conn: &Connection) -> DbResult<Option<IdleGap>> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM idle_periods WHERE idle_ended_at IS NULL ORDER BY id DESC LIMIT 1", GAP_COLUMNS),
+        [],
+        IdleGap::from_row,
+    ).optional()?)
+}
+
+/// The running timer idle detection looks after. Pomodoro phases have their own engine.
+fn running_timer(conn: &Connection) -> DbResult<Option<TimeEntry>> {
+    Ok(conn.query_row(
+        &format!(
+            "SELECT {} FROM time_tracking
+             WHERE end_time IS NULL AND timer_type != 'POMODORO'
+             ORDER BY start_time DESC LIMIT 1",
+            ENTRY_COLUMNS
+        ),
+        [],
+        TimeEntry::from_row,
+    ).optional()?)
+}
+
+/// Starts an idle gap once input has stopped for longer than the threshold.
+/// With `PAUSE` the timer is stopped at the last input, with `TRIM` it keeps
+/// running and the gap is only cut out if the user later discards it.
+fn begin_idle(conn: &Connection, last_input_at: NaiveDateTime, now: NaiveDateTime) -> Result<Option<IdleGap>, String> {
+    let config = IdleConfig::load(conn).map_err(|e| e.to_string())?;
+    if now - last_input_at < Duration::minutes(config.threshold_minutes) {
+        return Ok(None);
+    }
+    if open_idle_gap(conn).map_err(|e| e.to_string())?.is_some() {
+        return Ok(None);
+    }
+    let entry = match running_timer(conn).map_err(|e| e.to_string())? {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let entry_id = entry.id.unwrap_or_default();
+    let entry_start = parse_timestamp(&entry.start_time)
+        .ok_or_else(|| format!("Invalid start time on entry {}", entry_id))?;
+    // A timer started while already idle has nothing before the gap
+    let idle_start = last_input_at.max(entry_start);
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut time_entry_id = Some(entry_id);
+    if config.action == "PAUSE" {
+        if idle_start > entry_start {
+            stop_timer_entry(&tx, entry_id, &format_timestamp(idle_start))?;
+        } else {
+            delete_timesheet_entry(&tx, entry_id)?;
+            time_entry_id = None;
+        }
+    }
+    tx.execute(
+        "INSERT INTO idle_periods (time_entry_id, item_type, item_id, timer_type, action, idle_started_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            time_entry_id,
+            entry.item_type,
+            entry.item_id,
+            entry.timer_type,
+            config.action,
+            format_timestamp(idle_start),
+        ],
+    ).map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_idle_gap(conn, id).map_err(|e| e.to_string())
+}
+
+/// Closes the open idle gap when the user is back. A paused timer picks up
+/// again from `returned_at` unless another timer was started meanwhile.
+fn end_idle(conn: &Connection, returned_at: NaiveDateTime) -> Result<Option<IdleGap>, String> {
+    let gap = match open_idle_gap(conn).map_err(|e| e.to_string())? {
+        Some(gap) => gap,
+        None => return Ok(None),
+    };
+    let idle_start = parse_timestamp(&gap.idle_started_at)
+        .ok_or_else(|| format!("Invalid start time on idle gap {}", gap.id))?;
+    let returned_at = returned_at.max(idle_start);
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut resumed_entry_id = None;
+    if gap.action == "PAUSE" && running_timer(&tx).map_err(|e| e.to_string())?.is_none() {
+        resumed_entry_id = Some(insert_running_entry(&tx, &gap.entry_template(returned_at, None))?);
+    }
+    tx.execute(
+        "UPDATE idle_periods SET idle_ended_at = ?1, resumed_entry_id = ?2 WHERE id = ?3",
+        rusqlite::params![format_timestamp(returned_at), resumed_entry_id, gap.id],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_idle_gap(conn, gap.id).map_err(|e| e.to_string())
+}
+
+/// Handles a heartbeat from the UI. `IDLE` reports the time of the last
+/// input, `ACTIVE` the time input resumed. Returns the idle gap that started
+/// or ended, if any. `at` is UTC like the UI's timestamps, `now` is compared
+/// with it in UTC whatever its time zone.
+pub fn record_heartbeat<Tz: TimeZone>(
+    conn: &Connection,
+    state: &str,
+    at: NaiveDateTime,
+    now: &DateTime<Tz>,
+) -> Result<Option<IdleGap>, String> {
+    match state {
+        "IDLE" => begin_idle(conn, at, now.naive_utc()),
+        "ACTIVE" => end_idle(conn, at),
+        other => Err(format!("Unknown activity state: {}", other)),
+    }
+}
+
+/// Ended gaps still waiting for the user to keep or discard them.
+pub fn load_pending_idle_gaps(conn: &Connection) -> DbResult<Vec<IdleGap>> {
+    let mut stmt = conn.prepare(
+        &format!(
+            "SELECT {} FROM idle_periods
+             WHERE idle_ended_at IS NOT NULL AND resolution IS NULL
+             ORDER BY idle_started_at",
+            GAP_COLUMNS
+        )
+    )?;
+    let gaps = stmt.query_map([], IdleGap::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(gaps)
+}
+
+/// Removes `[start, end)` from a trimmed entry, whether it is still running or
+/// not. Runs inside the caller's transaction.
+fn cut_gap_from_entry(conn: &Connection, gap: &IdleGap, start: NaiveDateTime, end: NaiveDateTime) -> Result<(), String> {
+    let entry = match gap.time_entry_id {
+        Some(id) => load_time_entry(conn, id).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return Ok(()),
+    };
+    let entry_id = entry.id.unwrap_or_default();
+
+    match entry.end_time.as_deref().and_then(parse_timestamp) {
+        None => {
+            stop_timer_entry(conn, entry_id, &format_timestamp(start))?;
+            insert_running_entry(conn, &gap.entry_template(end, None))?;
+        }
+        Some(entry_end) if entry_end > start => {
+            let tail_id = split_entry(conn, entry_id, &format_timestamp(start))?;
+            if entry_end > end {
+                // The tail keeps its id for the part before `end`, which is the gap
+                split_entry(conn, tail_id, &format_timestamp(end))?;
+                delete_timesheet_entry(conn, tail_id)?;
+            } else {
+                delete_timesheet_entry(conn, tail_id)?;
+            }
+        }
+        // Stopped before the gap began, nothing to cut
+        Some(_) => {}
+    }
+
+    Ok(())
+}
+
+/// Keeps or discards an ended idle gap. For a paused timer keeping books the
+/// gap as its own entry; for a trimmed one discarding cuts it out.
+pub fn resolve_idle_period(conn: &Connection, id: i64, keep: bool) -> Result<IdleGap, String> {
+    let gap = load_idle_gap(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Idle gap {} not found", id))?;
+    if gap.resolution.is_some() {
+        return Err("Idle gap has already been resolved".to_string());
+    }
+    let (start, end) = gap.span().map_err(|e| e.to_string())?;
+
+    // A rejected edit rolls back with the transaction and leaves the gap pending
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    if end > start {
+        match (gap.action.as_str(), keep) {
+            ("PAUSE", true) => {
+                insert_timesheet_entry(&tx, &gap.entry_template(start, Some(end)), false)?;
+            }
+            ("TRIM", false) => cut_gap_from_entry(&tx, &gap, start, end)?,
+            _ => {}
+        }
+    }
+    tx.execute(
+        "UPDATE idle_periods SET resolution = ?1 WHERE id = ?2",
+        rusqlite::params![if keep { "KEPT" } else { "DISCARDED" }, id],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_idle_gap(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Idle gap {} not found", id))
+}
+
+#[tauri::command]
+pub async fn get_idle_settings(db: State<'_, Database>) -> Result<IdleConfig, String> {
+    IdleConfig::load(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_idle_settings(config: IdleConfig, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update idle settings").map_err(|e| e.to_string())?;
+    config.save(db.get_connection())
+}
+
+#[tauri::command]
+pub async fn idle_heartbeat(
+    state: String,
+    at: String,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<Option<IdleGap>, String> {
+    let at = parse_timestamp(&at).ok_or_else(|| format!("Invalid timestamp: {}", at))?;
+
+    let gap = record_heartbeat(db.get_connection(), &state, at, &Utc::now())?;
+    if let Some(gap) = &gap {
+        let event = if gap.idle_ended_at.is_some() { "idle-ended" } else { "idle-detected" };
+        let _ = app.emit_all(event, gap.clone());
+    }
+
+    Ok(gap)
+}
+
+#[tauri::command]
+pub async fn get_idle_gaps(db: State<'_, Database>) -> Result<Vec<IdleGap>, String> {
+    load_pending_idle_gaps(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn resolve_idle_gap(id: i64, keep: bool, db: State<'_, Database>) -> Result<IdleGap, String> {
+    let _undo = db.undo_step("Resolve idle gap").map_err(|e| e.to_string())?;
+    resolve_idle_period(db.get_connection(), id, keep)
+}
@@ -1,37 +1,73 @@
-use crate::db::{Database, models::Task};
+use crate::db::{Database, models::{KanbanBoard, KanbanColumn}, operations::KANBAN_BOARD_TEMPLATES};
+use rusqlite::params;
 use tauri::State;
 use serde::{Serialize, Deserialize};
 
+/// A template a board can be created from, with its columns in order
 #[derive(Debug, Serialize, Deserialize)]
-pub struct KanbanColumn {
-    pub id: Option<i64>,
+pub struct KanbanBoardTemplate {
     pub name: String,
-    pub column_order: i32,
+    pub columns: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn get_kanban_boards(db: State<'_, Database>) -> Result<Vec<KanbanBoard>, String> {
+    db.get_kanban_boards().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_kanban_board_templates() -> Result<Vec<KanbanBoardTemplate>, String> {
+    Ok(KANBAN_BOARD_TEMPLATES.iter()
+        .map(|(name, columns)| KanbanBoardTemplate {
+            name: name.to_string(),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn create_kanban_board(
+    board: KanbanBoard,
+    db: State<'_, Database>,
+) -> Result<i64, String> {
+    let _undo = db.undo_step("Create kanban board").map_err(|e| e.to_string())?;
+    db.create_kanban_board(&board).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_kanban_board_from_template(
+    board: KanbanBoard,
+    template: String,
+    db: State<'_, Database>,
+) -> Result<i64, String> {
+    let _undo = db.undo_step("Create kanban board").map_err(|e| e.to_string())?;
+    db.create_kanban_board_from_template(&board, &template).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_kanban_board(
+    board: KanbanBoard,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Update kanban board").map_err(|e| e.to_string())?;
+    db.update_kanban_board(&board).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_kanban_board(
+    id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Delete kanban board").map_err(|e| e.to_string())?;
+    db.delete_kanban_board(id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_kanban_columns(
+    board_id: i64,
     db: State<'_, Database>,
 ) -> Result<Vec<KanbanColumn>, String> {
-    let conn = db.get_connection();
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, name, column_order FROM kanban_columns ORDER BY column_order ASC"
-    ).map_err(|e| e.to_string())?;
-    
-    let columns = stmt
-        .query_map([], |row| {
-            Ok(KanbanColumn {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                column_order: row.get(2)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
-
-    Ok(columns)
+    db.get_kanban_columns(board_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -39,26 +75,29 @@ pub async fn create_kanban_column(
     column: KanbanColumn,
     db: State<'_, Database>,
 ) -> Result<i64, String> {
+    let _undo = db.undo_step("Create kanban column").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
-    let id = conn.execute(
-        "INSERT INTO kanban_columns (name, column_order) VALUES (?, ?)",
-        [&column.name, &column.column_order.to_string()]
+    conn.execute(
+        "INSERT INTO kanban_columns (board_id, name, column_order) VALUES (?, ?, ?)",
+        params![column.board_id, column.name, column.column_order]
     ).map_err(|e| e.to_string())?;
     
-    Ok(id as i64)
+    Ok(conn.last_insert_rowid())
 }
 
+/// Also moves the column to another board when `board_id` changes
 #[tauri::command]
 pub async fn update_kanban_column(
     column: KanbanColumn,
     db: State<'_, Database>,
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Update kanban column").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
-        "UPDATE kanban_columns SET name = ?, column_order = ? WHERE id = ?",
-        [&column.name, &column.column_order.to_string(), &column.id.unwrap().to_string()]
+        "UPDATE kanban_columns SET board_id = ?, name = ?, column_order = ? WHERE id = ?",
+        params![column.board_id, column.name, column.column_order, column.id.ok_or("Column ID is required")?]
     ).map_err(|e| e.to_string())?;
     
     Ok(())
@@ -69,6 +108,7 @@ pub async fn delete_kanban_column(
     id: i64,
     db: State<'_, Database>,
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Delete kanban column").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -1,23 +1,49 @@
+pub mod billing_service;
 pub mod category_service;
 pub mod event_service;
+pub mod history_service;
 pub mod holiday_feed_service;
+pub mod idle_service;
 pub mod kanban_service;
 pub mod note_service;
 pub mod participant_service;
+pub mod quick_add_service;
 pub mod recurring_service;
+pub mod reminder_delivery_service;
 pub mod reminder_service;
+pub mod saved_search_service;
+pub mod search_query_service;
 pub mod search_service;
+pub mod settings_service;
+pub mod tag_service;
 pub mod task_service;
+pub mod time_budget_service;
+pub mod time_report_service;
 pub mod time_tracking_service;
+pub mod trash_service;
+pub mod undo_service;
 
+pub use billing_service::*;
 pub use category_service::*;
 pub use event_service::*;
+pub use history_service::*;
 pub use holiday_feed_service::*;
+pub use idle_service::*;
 pub use kanban_service::*;
 pub use note_service::*;
 pub use participant_service::*;
+pub use quick_add_service::*;
 pub use recurring_service::*;
+pub use reminder_delivery_service::*;
 pub use reminder_service::*;
+pub use saved_search_service::*;
+pub use search_query_service::*;
 pub use search_service::*;
+pub use settings_service::*;
+pub use tag_service::*;
 pub use task_service::*;
+pub use time_budget_service::*;
+pub use time_report_service::*;
 pub use time_tracking_service::*;
+pub use trash_service::*;
+pub use undo_service::*;
@@ -1,4 +1,5 @@
-use crate::db::{Database, models::Note};
+use crate::db::{Database, models::Note, error::{CommandError, DatabaseError}};
+use rusqlite::params;
 use serde::{Serialize, Deserialize};
 use tauri::State;
 
@@ -15,7 +16,7 @@ pub async fn get_notes(db: State<'_, Database>) -> Result<Vec<Note>, String> {
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
-        "SELECT id, title, content, created_at, updated_at FROM notes"
+        "SELECT id, title, content, created_at, updated_at FROM notes WHERE deleted_at IS NULL"
     ).map_err(|e| e.to_string())?;
     
     let notes = stmt.query_map([], |row| {
@@ -36,6 +37,7 @@ pub async fn get_notes(db: State<'_, Database>) -> Result<Vec<Note>, String> {
 
 #[tauri::command]
 pub async fn create_note(note: Note, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create note").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
@@ -49,32 +51,42 @@ pub async fn create_note(note: Note, db: State<'_, Database>) -> Result<i64, Str
 }
 
 #[tauri::command]
-pub async fn update_note(note: Note, db: State<'_, Database>) -> Result<(), String> {
+pub async fn update_note(note: Note, db: State<'_, Database>) -> Result<Note, CommandError> {
+    let _undo = db.undo_step("Update note")?;
     let conn = db.get_connection();
+    let id = note.id.ok_or_else(|| DatabaseError::Data("Note ID is required".to_string()))?;
+    
+    let updated = conn.execute(
+        "UPDATE notes SET title = ?1, content = ?2 WHERE id = ?3 AND deleted_at IS NULL AND (?4 IS NULL OR updated_at = ?4)",
+        params![note.title, note.content, id, note.updated_at],
+    )?;
+    let current = conn.query_row(
+        "SELECT id, title, content, created_at, updated_at FROM notes WHERE id = ? AND deleted_at IS NULL",
+        [id],
+        |row| Ok(Note {
+            id: Some(row.get(0)?),
+            title: row.get(1)?,
+            content: row.get(2)?,
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        }),
+    ).map_err(DatabaseError::from);
+    if updated == 0 {
+        return Err(DatabaseError::stale_update("NOTE", id, current).into());
+    }
     
-    conn.execute(
-        "UPDATE notes SET title = ?1, content = ?2 WHERE id = ?3",
-        [
-            &note.title,
-            &note.content,
-            &note.id.ok_or("Note ID is required")?.to_string(),
-        ],
-    )
-    .map_err(|e| e.to_string())?;
-    
-    Ok(())
+    Ok(current?)
 }
 
 #[tauri::command]
 pub async fn delete_note(id: i64, db: State<'_, Database>) -> Result<(), String> {
-    let conn = db.get_connection();
-    conn.execute("DELETE FROM notes WHERE id = ?", [id])
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    let _undo = db.undo_step("Delete note").map_err(|e| e.to_string())?;
+    db.trash_item("NOTE", id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn link_note(link: NoteLink, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Link note").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     let table_name = match link.entity_type.as_str() {
         "event" => "event_notes",
@@ -96,6 +108,7 @@ pub async fn link_note(link: NoteLink, db: State<'_, Database>) -> Result<(), St
 
 #[tauri::command]
 pub async fn unlink_note(link: NoteLink, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Unlink note").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     let table_name = match link.entity_type.as_str() {
         "event" => "event_notes",
@@ -132,7 +145,7 @@ pub async fn get_notes_for_entity(
         "SELECT n.id, n.title, n.content, n.created_at, n.updated_at
          FROM notes n
          JOIN {} en ON n.id = en.note_id
-         WHERE en.{}_id = ?",
+         WHERE en.{}_id = ? AND n.deleted_at IS NULL",
         table_name,
         entity_type
     );
@@ -1,4 +1,5 @@
-use crate::db::{Database, error::DbResult};
+use crate::db::{Database, error::{CommandError, DatabaseError, DbResult}};
+use rusqlite::params;
 use serde::{Serialize, Deserialize};
 use tauri::State;
 
@@ -40,6 +41,7 @@ pub async fn get_participants(db: State<'_, Database>) -> Result<Vec<Participant
 
 #[tauri::command]
 pub async fn create_participant(participant: Participant, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create participant").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
@@ -56,24 +58,38 @@ pub async fn create_participant(participant: Participant, db: State<'_, Database
 }
 
 #[tauri::command]
-pub async fn update_participant(participant: Participant, db: State<'_, Database>) -> Result<(), String> {
+pub async fn update_participant(participant: Participant, db: State<'_, Database>) -> Result<Participant, CommandError> {
+    let _undo = db.undo_step("Update participant")?;
     let conn = db.get_connection();
+    let id = participant.id.ok_or_else(|| DatabaseError::Data("Participant ID is required".to_string()))?;
+    
+    let updated = conn.execute(
+        "UPDATE participants SET name = ?1, email = ?2, avatar_location = ?3
+         WHERE id = ?4 AND (?5 IS NULL OR updated_at = ?5)",
+        params![participant.name, participant.email, participant.avatar_location, id, participant.updated_at],
+    )?;
+    let current = conn.query_row(
+        "SELECT id, name, email, avatar_location, created_at, updated_at FROM participants WHERE id = ?",
+        [id],
+        |row| Ok(Participant {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            email: row.get(2)?,
+            avatar_location: row.get(3)?,
+            created_at: Some(row.get(4)?),
+            updated_at: Some(row.get(5)?),
+        }),
+    ).map_err(DatabaseError::from);
+    if updated == 0 {
+        return Err(DatabaseError::stale_update("PARTICIPANT", id, current).into());
+    }
     
-    conn.execute(
-        "UPDATE participants SET name = ?1, email = ?2, avatar_location = ?3 WHERE id = ?4",
-        [
-            &participant.name,
-            &participant.email,
-            &participant.avatar_location,
-            &participant.id.ok_or("Participant ID is required")?.to_string(),
-        ],
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(())
+    Ok(current?)
 }
 
 #[tauri::command]
 pub async fn delete_participant(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete participant").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     conn.execute("DELETE FROM participants WHERE id = ?", [id])
         .map_err(|e| e.to_string())?;
@@ -115,6 +131,7 @@ pub async fn add_participant_to_event(
     participant_id: i64,
     db: State<'_, Database>
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Add participant to event").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -131,6 +148,7 @@ pub async fn remove_participant_from_event(
     participant_id: i64,
     db: State<'_, Database>
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Remove participant from event").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -143,6 +161,7 @@ pub async fn remove_participant_from_event(
 
 #[tauri::command]
 pub async fn import_participants_csv(csv_data: String, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Import participants csv").map_err(|e| e.to_string())?;
     let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
     let conn = db.get_connection();
     let tx = conn.transaction().map_err(|e| e.to_string())?;
@@ -0,0 +1,713 @@
+use crate::db::{Database, models::RecurringRule, error::{DatabaseError, DbResult}};
+use crate::services::settings_service::read_setting;
+use crate::services::time_report_service::week_start;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Timelike, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::State;
+
+/// Length of an event when the input gives neither an end time nor a duration
+pub const DEFAULT_EVENT_MINUTES: i64 = 60;
+
+const WEEKDAYS: &[(&str, u32)] = &[
+    ("sunday", 0), ("sun", 0),
+    ("monday", 1), ("mon", 1),
+    ("tuesday", 2), ("tues", 2), ("tue", 2),
+    ("wednesday", 3), ("wed", 3),
+    ("thursday", 4), ("thurs", 4), ("thur", 4), ("thu", 4),
+    ("friday", 5), ("fri", 5),
+    ("saturday", 6), ("sat", 6),
+];
+
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// What relative dates and times in quick-add input are resolved against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuickAddContext {
+    pub now: NaiveDateTime, // wall clock time in the user's time zone
+    pub utc_offset: FixedOffset, // of that wall clock
+    pub week_start_day: u32, // 0 for Sunday, 1 for Monday
+}
+
+impl QuickAddContext {
+    pub fn load(conn: &Connection, now: DateTime<FixedOffset>) -> DbResult<Self> {
+        let week_start_day = read_setting(conn, "week_start_day")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1);
+        Ok(QuickAddContext { now: now.naive_local(), utc_offset: *now.offset(), week_start_day })
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.now.date()
+    }
+
+    /// A wall clock time in UTC, in the ISO form the UI stores times in
+    fn utc_time(&self, at: NaiveDateTime) -> String {
+        (at - Duration::seconds(self.utc_offset.local_minus_utc() as i64))
+            .and_utc()
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+}
+
+/// Someone mentioned with `@`. Without an ID no participant matched, and one
+/// is created along with the event.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DraftParticipant {
+    pub id: Option<i64>,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// A task or event read from one line of text, for the user to check
+/// before it is created
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuickAddDraft {
+    pub item_type: String, // "TASK" or "EVENT"
+    pub title: String,
+    pub due_date: Option<String>, // tasks only
+    pub start_time: Option<String>, // events only
+    pub end_time: Option<String>, // events only
+    pub is_all_day: bool,
+    pub priority: Option<i32>,
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>, // as written, kept when no category matched
+    pub recurring_rule: Option<RecurringRule>,
+    pub participants: Vec<DraftParticipant>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickAddResult {
+    pub draft: QuickAddDraft,
+    pub item_id: i64,
+}
+
+/// Everything recognised in the input, before defaults are applied
+#[derive(Default)]
+struct Parsed {
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    end_time: Option<NaiveTime>,
+    duration: Option<Duration>,
+    priority: Option<i32>,
+    category: Option<String>,
+    participants: Vec<String>,
+    rule: Option<RecurringRule>,
+    title: Vec<String>,
+}
+
+fn rule(frequency: &str, interval_value: i32) -> RecurringRule {
+    RecurringRule {
+        id: None,
+        frequency: frequency.to_string(),
+        interval_value,
+        days_of_week: None,
+        day_of_month: None,
+        month_of_year: None,
+        end_date: None,
+        end_occurrences: None,
+        created_at: None,
+    }
+}
+
+fn weekday(word: &str) -> Option<u32> {
+    let word = word.strip_suffix('s').filter(|w| w.len() > 3).unwrap_or(word);
+    WEEKDAYS.iter().find(|(name, _)| *name == word).map(|(_, day)| *day)
+}
+
+fn month(word: &str) -> Option<u32> {
+    let prefix = word.get(..3)?;
+    let index = MONTHS.iter().position(|name| *name == prefix)?;
+    let full = NaiveDate::from_ymd_opt(2000, index as u32 + 1, 1)?.format("%B").to_string().to_lowercase();
+    (word.len() == 3 || full == word || (word == "sept" && index == 8)).then_some(index as u32 + 1)
+}
+
+/// A day of the month written as 3, 3rd or 21st
+fn day_number(word: &str) -> Option<u32> {
+    let digits = ["st", "nd", "rd", "th"].iter()
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .unwrap_or(word);
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// 3pm, 3:30pm, 15:00, noon or midnight. `meridiem` applies to a bare hour,
+/// as in the first half of 3-4pm.
+fn time_of_day(word: &str, meridiem: Option<&str>) -> Option<NaiveTime> {
+    match word {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let (clock, suffix) = match word.strip_suffix("am").or_else(|| word.strip_suffix("pm")) {
+        Some(clock) => (clock, Some(&word[clock.len()..])),
+        None => (word, meridiem),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None if suffix.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+
+    let hour = match suffix {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// 3pm-4pm, 3-4pm or 9:00-10:30
+fn time_range(word: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = word.split_once('-')?;
+    let meridiem = ["am", "pm"].into_iter().find(|suffix| end.ends_with(suffix));
+    let end_time = time_of_day(end, None)?;
+    let start_time = time_of_day(start, None).or_else(|| {
+        // 11-1pm starts in the morning
+        let start = time_of_day(start, meridiem)?;
+        Some(if start > end_time { start - Duration::hours(12) } else { start })
+    })?;
+    Some((start_time, end_time))
+}
+
+/// 30m, 2h, 1.5h or the number and unit as two words. Lengths out of range
+/// are not recognised.
+fn duration(amount: &str, unit: Option<&str>) -> Option<(Duration, usize)> {
+    let split = amount.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(amount.len());
+    let (number, attached) = amount.split_at(split);
+    let number: f64 = number.parse().ok().filter(|n: &f64| *n > 0.0)?;
+    let (unit, used) = if attached.is_empty() { (unit?, 2) } else { (attached, 1) };
+
+    let minutes = match unit {
+        "m" | "min" | "mins" | "minute" | "minutes" => number,
+        "h" | "hr" | "hrs" | "hour" | "hours" => number * 60.0,
+        _ => return None,
+    };
+    Some((Duration::try_minutes(minutes.round() as i64)?, used))
+}
+
+struct Parser<'a> {
+    words: Vec<&'a str>,
+    lower: Vec<String>, // lowercased, without trailing punctuation
+    context: QuickAddContext,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str, context: QuickAddContext) -> Self {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let lower = words.iter()
+            .map(|w| w.trim_end_matches([',', '.', ';']).to_lowercase())
+            .collect();
+        Parser { words, lower, context }
+    }
+
+    fn word(&self, i: usize) -> Option<&str> {
+        self.lower.get(i).map(String::as_str)
+    }
+
+    fn parse(&self) -> Parsed {
+        let mut parsed = Parsed::default();
+        let mut i = 0;
+        while i < self.words.len() {
+            let used = self.match_at(i, &mut parsed);
+            if used == 0 {
+                parsed.title.push(self.words[i].to_string());
+                i += 1;
+            } else {
+                i += used;
+            }
+        }
+        parsed
+    }
+
+    /// Number of words recognised at `i`, 0 when they belong to the title
+    fn match_at(&self, i: usize, parsed: &mut Parsed) -> usize {
+        let word = &self.lower[i];
+
+        if let Some(priority) = word.strip_prefix('!').and_then(|p| p.parse().ok()).filter(|p| (1..=5).contains(p)) {
+            parsed.priority = Some(priority);
+            return 1;
+        }
+        if let Some(category) = self.words[i].strip_prefix('#').filter(|c| !c.is_empty()) {
+            parsed.category = Some(category.trim_end_matches([',', '.', ';']).replace('_', " "));
+            return 1;
+        }
+        if let Some(person) = self.words[i].strip_prefix('@').filter(|p| !p.is_empty()) {
+            parsed.participants.push(person.trim_end_matches([',', '.', ';']).to_string());
+            return 1;
+        }
+        if let Some(used) = self.recurrence(i, parsed) {
+            return used;
+        }
+
+        // "at", "on", "by" and "due" only go when what follows is recognised
+        let filler = matches!(word.as_str(), "at" | "on" | "by" | "due" | "from");
+        let start = if filler { i + 1 } else { i };
+        if start >= self.words.len() {
+            return 0;
+        }
+        match self.when(start, word == "at", parsed) {
+            0 => 0,
+            used => used + (start - i),
+        }
+    }
+
+    /// Dates, times, ranges and durations starting at `i`
+    fn when(&self, i: usize, after_at: bool, parsed: &mut Parsed) -> usize {
+        let word = self.word(i).unwrap_or_default();
+        let next = self.word(i + 1);
+        let today = self.context.today();
+
+        if let Some((start, end)) = time_range(word) {
+            parsed.time = Some(start);
+            parsed.end_time = Some(end);
+            return 1;
+        }
+        if word == "for" {
+            if let Some((length, used)) = duration(next.unwrap_or_default(), self.word(i + 2)) {
+                parsed.duration = Some(length);
+                return used + 1;
+            }
+            return 0;
+        }
+        if let Some(used) = self.time(i, after_at, parsed) {
+            // "3pm to 4pm" and "3pm - 4pm"
+            if let (Some("to" | "until" | "-"), Some(end)) = (self.word(i + used), self.word(i + used + 1)) {
+                if let Some(end) = time_of_day(end, None) {
+                    parsed.end_time = Some(end);
+                    return used + 2;
+                }
+            }
+            return used;
+        }
+
+        let (date, used) = match (word, next) {
+            ("today", _) => (today, 1),
+            ("tonight", _) => {
+                parsed.time = parsed.time.or(NaiveTime::from_hms_opt(20, 0, 0));
+                (today, 1)
+            }
+            ("tomorrow" | "tmrw" | "tmr", _) => (today + Duration::days(1), 1),
+            ("next", Some("week")) => (week_start(today, self.context.week_start_day) + Duration::days(7), 2),
+            ("next", Some("month")) => match today.with_day(1).and_then(|d| d.checked_add_months(Months::new(1))) {
+                Some(date) => (date, 2),
+                None => return 0,
+            },
+            ("this" | "next", Some(day)) if weekday(day).is_some() => {
+                let week = week_start(today, self.context.week_start_day) + Duration::days(if word == "next" { 7 } else { 0 });
+                let offset = (weekday(day).unwrap_or_default() + 7 - self.context.week_start_day % 7) % 7;
+                (week + Duration::days(offset as i64), 2)
+            }
+            ("in", Some(amount)) => return self.relative(amount, self.word(i + 2), parsed),
+            _ => match self.calendar_date(i) {
+                Some(found) => found,
+                None => return 0,
+            },
+        };
+        parsed.date = Some(date);
+        used
+    }
+
+    fn time(&self, i: usize, after_at: bool, parsed: &mut Parsed) -> Option<usize> {
+        let word = self.word(i)?;
+        let (time, used) = match self.word(i + 1) {
+            Some(m @ ("am" | "pm")) => (time_of_day(word, Some(m))?, 2),
+            _ => match time_of_day(word, None) {
+                Some(time) => (time, 1),
+                // "at 9" is nine o'clock
+                None if after_at => (NaiveTime::from_hms_opt(word.parse().ok().filter(|h| *h < 24)?, 0, 0)?, 1),
+                None => return None,
+            },
+        };
+        parsed.time = Some(time);
+        Some(used)
+    }
+
+    /// in 3 days, in 2 weeks, in 1 month, in 2 hours, in 30 minutes. Amounts
+    /// that land out of range are not recognised.
+    fn relative(&self, amount: &str, unit: Option<&str>, parsed: &mut Parsed) -> usize {
+        let (Ok(amount), Some(unit)) = (amount.parse::<i64>(), unit) else { return 0 };
+        let now = self.context.now;
+        let unit = unit.strip_suffix('s').unwrap_or(unit);
+        let later = |length: Option<Duration>| length.and_then(|length| now.checked_add_signed(length));
+        let at = match unit {
+            "day" => later(Duration::try_days(amount)),
+            "week" => later(Duration::try_weeks(amount)),
+            "month" => u32::try_from(amount).ok().and_then(|months| now.checked_add_months(Months::new(months))),
+            "hour" => later(Duration::try_hours(amount)),
+            "minute" | "min" => later(Duration::try_minutes(amount)),
+            _ => None,
+        };
+        let Some(at) = at else { return 0 };
+        if matches!(unit, "hour" | "minute" | "min") {
+            parsed.time = at.time().with_second(0);
+        }
+        parsed.date = Some(at.date());
+        3
+    }
+
+    /// friday, 2023-02-01, feb 3, 3 feb and 3rd of february, optionally with
+    /// a year. Dates without a year are the next ones to come.
+    fn calendar_date(&self, i: usize) -> Option<(NaiveDate, usize)> {
+        let today = self.context.today();
+        let word = self.word(i)?;
+
+        if let Some(day) = weekday(word) {
+            let offset = (day + 7 - today.weekday().num_days_from_sunday()) % 7;
+            return Some((today + Duration::days(offset as i64), 1));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            return Some((date, 1));
+        }
+
+        let (month, day, used) = match (month(word), self.word(i + 1), self.word(i + 2), self.word(i + 3)) {
+            (Some(month), Some(day), _, _) if day_number(day).is_some() => (month, day_number(day)?, 2),
+            (None, Some("of"), Some(name), _) if month(name).is_some() => (month(name)?, day_number(word)?, 3),
+            (None, Some(name), _, _) if month(name).is_some() => (month(name)?, day_number(word)?, 2),
+            _ => return None,
+        };
+
+        let year = self.word(i + used).and_then(|y| y.parse::<i32>().ok()).filter(|y| (1970..=9999).contains(y));
+        match year {
+            Some(year) => Some((NaiveDate::from_ymd_opt(year, month, day)?, used + 1)),
+            None => {
+                let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+                let date = if this_year < today { NaiveDate::from_ymd_opt(today.year() + 1, month, day)? } else { this_year };
+                Some((date, used))
+            }
+        }
+    }
+
+    /// daily, weekly, monthly, yearly and every ... phrases
+    fn recurrence(&self, i: usize, parsed: &mut Parsed) -> Option<usize> {
+        let word = self.word(i)?;
+        let simple = match word {
+            "daily" => Some("DAILY"),
+            "weekly" => Some("WEEKLY"),
+            "monthly" => Some("MONTHLY"),
+            "yearly" | "annually" => Some("ANNUALLY"),
+            _ => None,
+        };
+        if let Some(frequency) = simple {
+            parsed.rule = Some(rule(frequency, 1));
+            return Some(1);
+        }
+        if word != "every" {
+            return None;
+        }
+
+        let mut j = i + 1;
+        let interval = match self.word(j)? {
+            "other" => { j += 1; 2 }
+            count => match count.parse::<i32>() {
+                Ok(count) if count > 0 => { j += 1; count }
+                _ => 1,
+            },
+        };
+
+        let unit = self.word(j)?;
+        let unit = unit.strip_suffix('s').filter(|u| matches!(*u, "day" | "week" | "month" | "year")).unwrap_or(unit);
+        let found = match unit {
+            "day" => rule("DAILY", interval),
+            "week" => rule("WEEKLY", interval),
+            "month" => rule("MONTHLY", interval),
+            "year" => rule("ANNUALLY", interval),
+            "weekday" | "weekdays" => RecurringRule { days_of_week: Some("[1,2,3,4,5]".to_string()), ..rule("WEEKLY", interval) },
+            "weekend" | "weekends" => RecurringRule { days_of_week: Some("[0,6]".to_string()), ..rule("WEEKLY", interval) },
+            _ if day_number(unit).is_some() && unit.parse::<u32>().is_err() => RecurringRule {
+                day_of_month: day_number(unit).map(|day| day as i32),
+                ..rule("MONTHLY", interval)
+            },
+            _ => {
+                // every mon, wed and fri
+                let mut days = Vec::new();
+                while let Some(day) = self.word(j).and_then(weekday) {
+                    if !days.contains(&day) {
+                        days.push(day);
+                    }
+                    j += 1;
+                    if self.word(j) == Some("and") && self.word(j + 1).and_then(weekday).is_some() {
+                        j += 1;
+                    }
+                }
+                if days.is_empty() {
+                    return None;
+                }
+                days.sort();
+                let list = days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(",");
+                parsed.rule = Some(RecurringRule { days_of_week: Some(format!("[{}]", list)), ..rule("WEEKLY", interval) });
+                return Some(j - i);
+            }
+        };
+        parsed.rule = Some(found);
+        Some(j + 1 - i)
+    }
+}
+
+/// First day on or after `from` that `rule` falls on, when the rule names days
+fn first_occurrence(rule: &RecurringRule, from: NaiveDate) -> Option<NaiveDate> {
+    let weekdays = rule.weekdays();
+    if !weekdays.is_empty() {
+        return (0..7).map(|days| from + Duration::days(days))
+            .find(|date| weekdays.contains(&date.weekday().num_days_from_sunday()));
+    }
+    let day = rule.day_of_month? as u32;
+    (0..3).filter_map(|months| from.with_day(1)?.checked_add_months(Months::new(months))?.with_day(day))
+        .find(|date| *date >= from)
+}
+
+fn find_category(conn: &Connection, name: &str) -> DbResult<Option<i64>> {
+    Ok(conn.query_row(
+        "SELECT id FROM categories WHERE name = ?1 COLLATE NOCASE",
+        [name],
+        |row| row.get(0),
+    ).optional()?)
+}
+
+/// Matches `@alice` to a participant by email, the part of it before the @,
+/// full name or first name
+fn find_participant(conn: &Connection, mention: &str) -> DbResult<DraftParticipant> {
+    let found = conn.query_row(
+        "SELECT id, name, email FROM participants
+         WHERE email = ?1 COLLATE NOCASE
+            OR substr(email, 1, instr(email, '@') - 1) = ?1 COLLATE NOCASE
+            OR name = ?1 COLLATE NOCASE
+            OR name LIKE ?1 || ' %'
+         ORDER BY email = ?1 COLLATE NOCASE DESC, name = ?1 COLLATE NOCASE DESC, id
+         LIMIT 1",
+        [mention],
+        |row| Ok(DraftParticipant { id: Some(row.get(0)?), name: row.get(1)?, email: row.get(2)? }),
+    ).optional()?;
+
+    Ok(found.unwrap_or_else(|| {
+        let email = mention.contains('@').then(|| mention.to_string());
+        let name = mention.split('@').next().unwrap_or(mention).to_string();
+        DraftParticipant { id: None, name, email }
+    }))
+}
+
+/// Reads a task or event out of `input`. `item_type` forces one; otherwise
+/// an end time, a duration or participants make it an event.
+pub fn parse_quick_add(
+    conn: &Connection,
+    input: &str,
+    item_type: Option<&str>,
+    context: &QuickAddContext,
+) -> DbResult<QuickAddDraft> {
+    let parsed = Parser::new(input, *context).parse();
+    let mut warnings = Vec::new();
+
+    let item_type = match item_type {
+        Some(item_type @ ("TASK" | "EVENT")) => item_type,
+        Some(other) => return Err(DatabaseError::Data(format!("Quick add cannot create items of type {}", other))),
+        None if parsed.end_time.is_some() || parsed.duration.is_some() || !parsed.participants.is_empty() => "EVENT",
+        None => "TASK",
+    };
+
+    let category_id = match &parsed.category {
+        Some(name) => {
+            let id = find_category(conn, name)?;
+            if id.is_none() {
+                warnings.push(format!("No category named '{}'", name));
+            }
+            id
+        }
+        None => None,
+    };
+
+    let participants = parsed.participants.iter()
+        .map(|mention| find_participant(conn, mention))
+        .collect::<DbResult<Vec<_>>>()?;
+    if item_type == "TASK" && !participants.is_empty() {
+        warnings.push("Tasks have no participants, they are left out".to_string());
+    }
+
+    // Without a date, the next time the clock shows the time given
+    let from = match parsed.time {
+        Some(time) if time <= context.now.time() => context.today() + Duration::days(1),
+        _ => context.today(),
+    };
+    let date = parsed.date
+        .or_else(|| parsed.rule.as_ref().and_then(|rule| first_occurrence(rule, from)))
+        .or_else(|| parsed.time.map(|_| from));
+
+    let mut draft = QuickAddDraft {
+        item_type: item_type.to_string(),
+        title: parsed.title.join(" "),
+        due_date: None,
+        start_time: None,
+        end_time: None,
+        is_all_day: false,
+        priority: parsed.priority,
+        category_id,
+        category_name: parsed.category.clone(),
+        recurring_rule: parsed.rule.clone(),
+        participants: if item_type == "EVENT" { participants } else { Vec::new() },
+        warnings,
+    };
+
+    if item_type == "TASK" {
+        draft.due_date = date.map(|date| match parsed.time {
+            Some(time) => context.utc_time(date.and_time(time)),
+            None => date.format("%Y-%m-%d").to_string(),
+        });
+        if parsed.end_time.is_some() || parsed.duration.is_some() {
+            draft.warnings.push("Tasks have no end time, it is left out".to_string());
+        }
+    } else {
+        let start = match (date, parsed.time) {
+            (Some(date), Some(time)) => date.and_time(time),
+            (Some(date), None) => {
+                draft.is_all_day = true;
+                date.and_time(NaiveTime::MIN)
+            }
+            (None, _) => {
+                draft.warnings.push("No start time given, the event starts at the next full hour".to_string());
+                let hour = context.now.with_minute(0).and_then(|at| at.with_second(0)).unwrap_or(context.now);
+                hour + Duration::hours(1)
+            }
+        };
+        let end = if draft.is_all_day {
+            start.date().and_hms_opt(23, 59, 59).unwrap_or(start)
+        } else if let Some(end) = parsed.end_time {
+            let end = start.date().and_time(end);
+            if end <= start { end + Duration::days(1) } else { end }
+        } else {
+            let default_end = start + Duration::minutes(DEFAULT_EVENT_MINUTES);
+            match parsed.duration {
+                Some(length) => start.checked_add_signed(length).unwrap_or_else(|| {
+                    draft.warnings.push("The event would end too far ahead, it gets the default length".to_string());
+                    default_end
+                }),
+                None => default_end,
+            }
+        };
+        draft.start_time = Some(context.utc_time(start));
+        draft.end_time = Some(context.utc_time(end));
+    }
+
+    if draft.title.is_empty() {
+        draft.warnings.push("Nothing is left for the title".to_string());
+    }
+    Ok(draft)
+}
+
+/// Creates the task or event a draft describes, with its recurring rule and
+/// any participants that do not exist yet
+pub fn create_from_draft(conn: &Connection, draft: &QuickAddDraft) -> DbResult<i64> {
+    if draft.title.trim().is_empty() {
+        return Err(DatabaseError::Data("Title must not be empty".to_string()));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let rule_id = match &draft.recurring_rule {
+        Some(rule) => {
+            tx.execute(
+                "INSERT INTO recurring_rules (frequency, interval, days_of_week, day_of_month, month_of_year,
+                                              end_date, end_occurrences)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    rule.frequency,
+                    rule.interval_value,
+                    rule.days_of_week,
+                    rule.day_of_month,
+                    rule.month_of_year,
+                    rule.end_date,
+                    rule.end_occurrences,
+                ],
+            )?;
+            Some(tx.last_insert_rowid())
+        }
+        None => None,
+    };
+    let priority = draft.priority.unwrap_or(3);
+
+    let id = match draft.item_type.as_str() {
+        "TASK" => {
+            tx.execute(
+                "INSERT INTO tasks (title, due_date, priority, category_id, recurring_rule_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![draft.title.trim(), draft.due_date, priority, draft.category_id, rule_id],
+            )?;
+            tx.last_insert_rowid()
+        }
+        "EVENT" => {
+            let (Some(start), Some(end)) = (&draft.start_time, &draft.end_time) else {
+                return Err(DatabaseError::Data("Events need a start and end time".to_string()));
+            };
+            tx.execute(
+                "INSERT INTO events (title, start_time, end_time, is_all_day, priority, category_id, recurring_rule_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![draft.title.trim(), start, end, draft.is_all_day, priority, draft.category_id, rule_id],
+            )?;
+            let event_id = tx.last_insert_rowid();
+
+            for participant in &draft.participants {
+                let participant_id = match participant.id {
+                    Some(id) => id,
+                    None => {
+                        tx.execute(
+                            "INSERT INTO participants (name, email) VALUES (?1, ?2)",
+                            params![participant.name, participant.email],
+                        )?;
+                        tx.last_insert_rowid()
+                    }
+                };
+                tx.execute(
+                    "INSERT OR IGNORE INTO event_participants (event_id, participant_id) VALUES (?1, ?2)",
+                    params![event_id, participant_id],
+                )?;
+            }
+            event_id
+        }
+        other => return Err(DatabaseError::Data(format!("Quick add cannot create items of type {}", other))),
+    };
+
+    tx.commit()?;
+    Ok(id)
+}
+
+/// The user's wall clock: the machine's local time unless the UI sends its
+/// UTC offset
+fn user_now(utc_offset_minutes: Option<i32>) -> DateTime<FixedOffset> {
+    match utc_offset_minutes.and_then(|minutes| FixedOffset::east_opt(minutes * 60)) {
+        Some(offset) => Utc::now().with_timezone(&offset),
+        None => Local::now().fixed_offset(),
+    }
+}
+
+#[tauri::command]
+pub async fn preview_quick_add(
+    input: String,
+    item_type: Option<String>,
+    utc_offset_minutes: Option<i32>,
+    db: State<'_, Database>
+) -> Result<QuickAddDraft, String> {
+    let conn = db.get_connection();
+    let context = QuickAddContext::load(conn, user_now(utc_offset_minutes)).map_err(|e| e.to_string())?;
+    parse_quick_add(conn, &input, item_type.as_deref(), &context).map_err(|e| e.to_string())
+}
+
+/// Parses and creates in one go
+#[tauri::command]
+pub async fn quick_add(
+    input: String,
+    item_type: Option<String>,
+    utc_offset_minutes: Option<i32>,
+    db: State<'_, Database>
+) -> Result<QuickAddResult, String> {
+    let _undo = db.undo_step("Quick add").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let context = QuickAddContext::load(conn, user_now(utc_offset_minutes)).map_err(|e| e.to_string())?;
+    let draft = parse_quick_add(conn, &input, item_type.as_deref(), &context).map_err(|e| e.to_string())?;
+    let item_id = create_from_draft(conn, &draft).map_err(|e| e.to_string())?;
+    Ok(QuickAddResult { draft, item_id })
+}
+
+/// Creates a draft from `preview_quick_add`, possibly edited by the user
+#[tauri::command]
+pub async fn create_quick_add_draft(draft: QuickAddDraft, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create quick add draft").map_err(|e| e.to_string())?;
+    create_from_draft(db.get_connection(), &draft).map_err(|e| e.to_string())
+}
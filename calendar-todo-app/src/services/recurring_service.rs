@@ -19,6 +19,7 @@ pub async fn create_recurring_rule(
     rule: RecurringRule,
     db: State<'_, Database>,
 ) -> Result<i64, String> {
+    let _undo = db.undo_step("Create recurring rule").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
@@ -62,6 +63,7 @@ pub async fn update_recurring_rule(
     rule: RecurringRule,
     db: State<'_, Database>,
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Update recurring rule").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
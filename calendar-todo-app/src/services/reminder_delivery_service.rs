@@ -0,0 +1,640 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::reminder_service::{collect_due_reminders, Reminder};
+use crate::services::settings_service::read_setting;
+use crate::services::time_tracking_service::{format_timestamp, parse_timestamp};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use tauri::{AppHandle, Manager, State};
+
+const NETWORK_TIMEOUT_SECS: u64 = 10;
+
+/// Connects to the first address `host` resolves to that answers, with
+/// `NETWORK_TIMEOUT_SECS` covering the connect, reads and writes.
+fn connect(host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let timeout = std::time::Duration::from_secs(NETWORK_TIMEOUT_SECS);
+    let mut last_error = None;
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} did not resolve to any address", host))
+    }))
+}
+
+/// What a channel is asked to deliver for a single reminder.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReminderNotification {
+    pub reminder_id: i64,
+    pub item_type: String,
+    pub item_id: i64,
+    pub title: String,
+    pub trigger_time: String,
+    pub offset_description: String,
+    pub digest: bool, // released after quiet hours rather than on time
+}
+
+impl ReminderNotification {
+    pub fn subject(&self) -> String {
+        format!("Reminder: {} ({})", self.title, self.offset_description)
+    }
+}
+
+/// A way of getting a reminder in front of the user.
+pub trait DeliveryChannel {
+    fn channel_type(&self) -> &'static str;
+    fn deliver(&self, notification: &ReminderNotification) -> Result<(), String>;
+}
+
+/// Shows the reminder in the desktop app by emitting a `reminder-notification` event.
+pub struct DesktopChannel {
+    pub app: AppHandle,
+}
+
+impl DeliveryChannel for DesktopChannel {
+    fn channel_type(&self) -> &'static str {
+        "DESKTOP"
+    }
+
+    fn deliver(&self, notification: &ReminderNotification) -> Result<(), String> {
+        self.app
+            .emit_all("reminder-notification", notification.clone())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Sends the reminder through a plain SMTP relay, typically one on the local machine.
+pub struct SmtpChannel {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    pub to: String,
+}
+
+impl SmtpChannel {
+    fn read_reply(reader: &mut impl BufRead) -> Result<u16, String> {
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).map_err(|e| e.to_string())?;
+            if line.len() < 4 {
+                return Err(format!("Malformed SMTP reply: {:?}", line));
+            }
+            let code = line[..3].parse::<u16>()
+                .map_err(|_| format!("Malformed SMTP reply: {:?}", line))?;
+            // Multi-line replies use '-' after the code on every line but the last
+            if &line[3..4] != "-" {
+                return Ok(code);
+            }
+        }
+    }
+
+    fn command(
+        writer: &mut impl Write,
+        reader: &mut impl BufRead,
+        line: &str,
+        expected: &[u16],
+    ) -> Result<(), String> {
+        write!(writer, "{}\r\n", line).map_err(|e| e.to_string())?;
+        let code = Self::read_reply(reader)?;
+        if expected.contains(&code) {
+            Ok(())
+        } else {
+            Err(format!("SMTP server answered {} to {}", code, line.split(':').next().unwrap_or(line)))
+        }
+    }
+}
+
+impl DeliveryChannel for SmtpChannel {
+    fn channel_type(&self) -> &'static str {
+        "EMAIL"
+    }
+
+    fn deliver(&self, notification: &ReminderNotification) -> Result<(), String> {
+        let stream = connect(&self.host, self.port)
+            .map_err(|e| format!("Failed to connect to SMTP server: {}", e))?;
+
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut writer = stream;
+
+        if Self::read_reply(&mut reader)? != 220 {
+            return Err("SMTP server did not greet".to_string());
+        }
+        Self::command(&mut writer, &mut reader, "EHLO localhost", &[250])?;
+        Self::command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", self.from), &[250])?;
+        Self::command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", self.to), &[250, 251])?;
+        Self::command(&mut writer, &mut reader, "DATA", &[354])?;
+
+        let body = format!(
+            "{}\n\nDue: {}\nItem: {} #{}",
+            notification.subject(),
+            notification.trigger_time,
+            notification.item_type,
+            notification.item_id
+        );
+        // A line break in the title would end the header and start one of its own
+        let mut message = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to,
+            notification.subject().replace(['\r', '\n'], " ")
+        );
+        for line in body.lines() {
+            // Dot-stuffing so a line starting with '.' does not end the message early
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        Self::command(&mut writer, &mut reader, &format!("{}.", message), &[250])?;
+        Self::command(&mut writer, &mut reader, "QUIT", &[221])?;
+
+        Ok(())
+    }
+}
+
+/// POSTs the reminder as JSON to an `http://` endpoint.
+pub struct WebhookChannel {
+    pub url: String,
+}
+
+impl WebhookChannel {
+    /// Splits an `http://host[:port]/path` URL into its parts.
+    fn parse_url(&self) -> Result<(String, u16, String), String> {
+        let rest = self.url.strip_prefix("http://")
+            .ok_or_else(|| format!("Only http:// webhook URLs are supported: {}", self.url))?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], rest[index..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host.to_string(),
+                port.parse().map_err(|_| format!("Invalid webhook port: {}", port))?,
+            ),
+            None => (authority.to_string(), 80),
+        };
+
+        Ok((host, port, path))
+    }
+}
+
+impl DeliveryChannel for WebhookChannel {
+    fn channel_type(&self) -> &'static str {
+        "WEBHOOK"
+    }
+
+    fn deliver(&self, notification: &ReminderNotification) -> Result<(), String> {
+        let (host, port, path) = self.parse_url()?;
+        let payload = serde_json::to_string(notification).map_err(|e| e.to_string())?;
+
+        let mut stream = connect(&host, port)
+            .map_err(|e| format!("Failed to connect to webhook: {}", e))?;
+
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            host,
+            port,
+            payload.len(),
+            payload
+        ).map_err(|e| e.to_string())?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).map_err(|e| e.to_string())?;
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| "Malformed webhook response".to_string())?;
+
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(format!("Webhook answered with status {}", status))
+        }
+    }
+}
+
+/// Channel settings as stored in `delivery_channels.config`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum ChannelConfig {
+    Desktop,
+    Email { host: String, port: u16, from: String, to: String },
+    Webhook { url: String },
+}
+
+impl ChannelConfig {
+    pub fn channel_type(&self) -> &'static str {
+        match self {
+            ChannelConfig::Desktop => "DESKTOP",
+            ChannelConfig::Email { .. } => "EMAIL",
+            ChannelConfig::Webhook { .. } => "WEBHOOK",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryChannelConfig {
+    pub id: Option<i64>,
+    pub name: String,
+    pub config: ChannelConfig,
+    pub is_enabled: bool,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryLogEntry {
+    pub id: i64,
+    pub reminder_id: i64,
+    pub channel_id: Option<i64>,
+    pub channel_type: String,
+    pub attempt: i32,
+    pub status: String,
+    pub error: Option<String>,
+    pub attempted_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DispatchSummary {
+    pub sent: usize,
+    pub failed: usize,
+    pub retry_later: usize,
+}
+
+/// Exponential backoff between delivery attempts on the same channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay_seconds: i64,
+}
+
+impl RetryPolicy {
+    pub fn load(conn: &Connection) -> DbResult<Self> {
+        Ok(RetryPolicy {
+            max_attempts: read_setting(conn, "delivery_max_attempts")?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+            base_delay_seconds: read_setting(conn, "delivery_backoff_seconds")?
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+        })
+    }
+
+    /// When the next attempt may run after `failures` failed ones, or `None` once exhausted.
+    pub fn next_attempt_at(&self, failures: i32, last_attempt: NaiveDateTime) -> Option<NaiveDateTime> {
+        if failures >= self.max_attempts {
+            return None;
+        }
+        if failures == 0 {
+            return Some(last_attempt);
+        }
+
+        let factor = 1i64 << (failures - 1).min(20);
+        Some(last_attempt + Duration::seconds(self.base_delay_seconds * factor))
+    }
+}
+
+fn channel_from_row(row: &rusqlite::Row) -> rusqlite::Result<DeliveryChannelConfig> {
+    let config: String = row.get(2)?;
+    Ok(DeliveryChannelConfig {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        config: serde_json::from_str(&config).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        is_enabled: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// Channels to use for a reminder: its own assignments, then its item's
+/// category, falling back to the built-in desktop channel (`None`).
+pub fn resolve_channels(conn: &Connection, reminder: &Reminder) -> DbResult<Vec<Option<DeliveryChannelConfig>>> {
+    let reminder_id = reminder.id
+        .ok_or_else(|| DatabaseError::Data("Reminder ID is required".to_string()))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.name, c.config, c.is_enabled, c.created_at, c.updated_at
+         FROM delivery_channels c
+         JOIN reminder_channels rc ON rc.channel_id = c.id
+         WHERE rc.reminder_id = ? AND c.is_enabled = 1
+         ORDER BY c.id"
+    )?;
+    let mut channels = stmt.query_map([reminder_id], channel_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if channels.is_empty() {
+        let table = match reminder.item_type.as_str() {
+            "EVENT" => "events",
+            _ => "tasks",
+        };
+        let category_id: Option<i64> = conn.query_row(
+            &format!("SELECT category_id FROM {} WHERE id = ?", table),
+            [reminder.item_id],
+            |row| row.get(0)
+        ).optional()?.flatten();
+
+        if let Some(category_id) = category_id {
+            let mut stmt = conn.prepare(
+                "SELECT c.id, c.name, c.config, c.is_enabled, c.created_at, c.updated_at
+                 FROM delivery_channels c
+                 JOIN category_channels cc ON cc.channel_id = c.id
+                 WHERE cc.category_id = ? AND c.is_enabled = 1
+                 ORDER BY c.id"
+            )?;
+            channels = stmt.query_map([category_id], channel_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+    }
+
+    if channels.is_empty() {
+        return Ok(vec![None]);
+    }
+
+    Ok(channels.into_iter().map(Some).collect())
+}
+
+fn build_notification(conn: &Connection, reminder: &Reminder, digest: bool) -> DbResult<ReminderNotification> {
+    let table = match reminder.item_type.as_str() {
+        "EVENT" => "events",
+        _ => "tasks",
+    };
+    let title: Option<String> = conn.query_row(
+        &format!("SELECT title FROM {} WHERE id = ?", table),
+        [reminder.item_id],
+        |row| row.get(0)
+    ).optional()?;
+
+    Ok(ReminderNotification {
+        reminder_id: reminder.id.unwrap_or_default(),
+        item_type: reminder.item_type.clone(),
+        item_id: reminder.item_id,
+        title: title.unwrap_or_else(|| format!("{} #{}", reminder.item_type, reminder.item_id)),
+        trigger_time: reminder.trigger_time.clone(),
+        offset_description: reminder.offset_description.clone(),
+        digest,
+    })
+}
+
+/// Sends one notification through one channel unless it already went out,
+/// respecting the retry policy, and records the attempt in the delivery log.
+fn deliver_once(
+    conn: &Connection,
+    channel: &dyn DeliveryChannel,
+    channel_id: Option<i64>,
+    notification: &ReminderNotification,
+    policy: &RetryPolicy,
+    now: NaiveDateTime,
+    summary: &mut DispatchSummary,
+) -> DbResult<()> {
+    let (sent, failures, last_attempt): (i32, i32, Option<String>) = conn.query_row(
+        "SELECT COALESCE(SUM(status = 'SENT'), 0), COALESCE(SUM(status = 'FAILED'), 0), MAX(attempted_at)
+         FROM reminder_delivery_log
+         WHERE reminder_id = ?1 AND channel_id IS ?2 AND channel_type = ?3",
+        rusqlite::params![notification.reminder_id, channel_id, channel.channel_type()],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    )?;
+
+    if sent > 0 {
+        return Ok(());
+    }
+
+    let last_attempt = last_attempt.as_deref().and_then(parse_timestamp).unwrap_or(now);
+    match policy.next_attempt_at(failures, last_attempt) {
+        Some(next) if next <= now => {}
+        Some(_) => {
+            summary.retry_later += 1;
+            return Ok(());
+        }
+        None => return Ok(()),
+    }
+
+    let result = channel.deliver(notification);
+    conn.execute(
+        "INSERT INTO reminder_delivery_log (reminder_id, channel_id, channel_type, attempt, status, error, attempted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            notification.reminder_id,
+            channel_id,
+            channel.channel_type(),
+            failures + 1,
+            if result.is_ok() { "SENT" } else { "FAILED" },
+            result.as_ref().err(),
+            format_timestamp(now),
+        ],
+    )?;
+
+    match result {
+        Ok(()) => summary.sent += 1,
+        Err(_) => summary.failed += 1,
+    }
+
+    Ok(())
+}
+
+/// Runs the reminder engine at `now` and pushes everything due through the
+/// configured channels. `desktop` handles the built-in desktop channel.
+pub fn dispatch_reminders<Tz: TimeZone>(
+    conn: &Connection,
+    now: &DateTime<Tz>,
+    desktop: &dyn DeliveryChannel,
+) -> DbResult<DispatchSummary> {
+    let policy = RetryPolicy::load(conn)?;
+    let delivery = collect_due_reminders(conn, now)?;
+    // The delivery log keeps UTC times, like the reminders themselves
+    let now = now.naive_utc();
+    let mut summary = DispatchSummary::default();
+
+    let due = delivery.immediate.iter().map(|r| (r, false))
+        .chain(delivery.digest.iter().map(|r| (r, true)));
+
+    for (reminder, digest) in due {
+        let notification = build_notification(conn, reminder, digest)?;
+
+        for channel in resolve_channels(conn, reminder)? {
+            let (channel_id, config) = match channel {
+                Some(channel) => (channel.id, channel.config),
+                None => (None, ChannelConfig::Desktop),
+            };
+
+            match config {
+                ChannelConfig::Desktop => {
+                    deliver_once(conn, desktop, channel_id, &notification, &policy, now, &mut summary)?;
+                }
+                ChannelConfig::Email { host, port, from, to } => {
+                    let smtp = SmtpChannel { host, port, from, to };
+                    deliver_once(conn, &smtp, channel_id, &notification, &policy, now, &mut summary)?;
+                }
+                ChannelConfig::Webhook { url } => {
+                    let webhook = WebhookChannel { url };
+                    deliver_once(conn, &webhook, channel_id, &notification, &policy, now, &mut summary)?;
+                }
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn get_delivery_channels(db: State<'_, Database>) -> Result<Vec<DeliveryChannelConfig>, String> {
+    let conn = db.get_connection();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, config, is_enabled, created_at, updated_at
+         FROM delivery_channels ORDER BY name"
+    ).map_err(|e| e.to_string())?;
+
+    let channels = stmt.query_map([], channel_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(channels)
+}
+
+#[tauri::command]
+pub async fn create_delivery_channel(channel: DeliveryChannelConfig, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create delivery channel").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let config = serde_json::to_string(&channel.config).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO delivery_channels (name, channel_type, config, is_enabled) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![channel.name, channel.config.channel_type(), config, channel.is_enabled],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn update_delivery_channel(channel: DeliveryChannelConfig, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update delivery channel").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let config = serde_json::to_string(&channel.config).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE delivery_channels SET name = ?1, channel_type = ?2, config = ?3, is_enabled = ?4 WHERE id = ?5",
+        rusqlite::params![
+            channel.name,
+            channel.config.channel_type(),
+            config,
+            channel.is_enabled,
+            channel.id.ok_or("Channel ID is required")?,
+        ],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_delivery_channel(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete delivery channel").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    conn.execute("DELETE FROM delivery_channels WHERE id = ?", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_reminder_channels(
+    reminder_id: i64,
+    channel_ids: Vec<i64>,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let _undo = db.undo_step("Set reminder channels").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM reminder_channels WHERE reminder_id = ?", [reminder_id])
+        .map_err(|e| e.to_string())?;
+    for channel_id in channel_ids {
+        tx.execute(
+            "INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)",
+            [reminder_id, channel_id],
+        ).map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_category_channels(
+    category_id: i64,
+    channel_ids: Vec<i64>,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let _undo = db.undo_step("Set category channels").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM category_channels WHERE category_id = ?", [category_id])
+        .map_err(|e| e.to_string())?;
+    for channel_id in channel_ids {
+        tx.execute(
+            "INSERT INTO category_channels (category_id, channel_id) VALUES (?1, ?2)",
+            [category_id, channel_id],
+        ).map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_delivery_log(reminder_id: i64, db: State<'_, Database>) -> Result<Vec<DeliveryLogEntry>, String> {
+    let conn = db.get_connection();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, reminder_id, channel_id, channel_type, attempt, status, error, attempted_at
+         FROM reminder_delivery_log
+         WHERE reminder_id = ?
+         ORDER BY attempted_at ASC, id ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let entries = stmt.query_map([reminder_id], |row| {
+        Ok(DeliveryLogEntry {
+            id: row.get(0)?,
+            reminder_id: row.get(1)?,
+            channel_id: row.get(2)?,
+            channel_type: row.get(3)?,
+            attempt: row.get(4)?,
+            status: row.get(5)?,
+            error: row.get(6)?,
+            attempted_at: row.get(7)?,
+        })
+    })
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+
+    Ok(entries)
+}
+
+/// Runs on a blocking thread, as channels wait on SMTP servers and webhooks
+#[tauri::command]
+pub async fn dispatch_due_reminders(app: AppHandle) -> Result<DispatchSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let desktop = DesktopChannel { app: app.clone() };
+        dispatch_reminders(db.get_connection(), &Local::now(), &desktop)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -1,4 +1,8 @@
 use crate::db::{Database, error::DbResult};
+use crate::services::settings_service::{read_setting, write_setting};
+use crate::services::time_tracking_service::{format_timestamp, running_work_phase_end};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone};
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Serialize, Deserialize};
 use tauri::State;
 
@@ -15,6 +19,7 @@ pub struct Reminder {
 
 #[tauri::command]
 pub async fn create_reminder(reminder: Reminder, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create reminder").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
@@ -58,6 +63,7 @@ pub async fn get_reminder(item_type: String, item_id: i64, db: State<'_, Databas
 
 #[tauri::command]
 pub async fn update_reminder(reminder: Reminder, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update reminder").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -76,6 +82,7 @@ pub async fn update_reminder(reminder: Reminder, db: State<'_, Database>) -> Res
 
 #[tauri::command]
 pub async fn delete_reminder(item_type: String, item_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete reminder").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
     
     conn.execute(
@@ -91,10 +98,14 @@ pub async fn get_pending_reminders(db: State<'_, Database>) -> Result<Vec<Remind
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
-        "SELECT id, item_type, item_id, trigger_time, offset_description, is_dismissed, created_at 
-         FROM reminders 
-         WHERE is_dismissed = 0 AND trigger_time <= datetime('now')
-         ORDER BY trigger_time ASC"
+        "SELECT r.id, r.item_type, r.item_id, r.trigger_time, r.offset_description, r.is_dismissed, r.created_at 
+         FROM reminders r
+         LEFT JOIN events e ON r.item_type = 'EVENT' AND e.id = r.item_id
+         LEFT JOIN tasks t ON r.item_type = 'TASK' AND t.id = r.item_id
+         WHERE r.is_dismissed = 0 AND r.trigger_time <= datetime('now')
+           AND (r.deferred_until IS NULL OR r.deferred_until <= datetime('now'))
+           AND e.deleted_at IS NULL AND t.deleted_at IS NULL
+         ORDER BY r.trigger_time ASC"
     ).map_err(|e| e.to_string())?;
     
     let reminders = stmt.query_map([], |row| {
@@ -114,3 +125,218 @@ pub async fn get_pending_reminders(db: State<'_, Database>) -> Result<Vec<Remind
     
     Ok(reminders)
 }
+
+
+/// Quiet-hours and do-not-disturb rules, persisted as individual keys in `settings`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuietHoursSettings {
+    pub enabled: bool,
+    pub start: String, // HH:MM, local time
+    pub end: String, // HH:MM, earlier than start when the window spans midnight
+    pub mute_during_pomodoro: bool,
+    pub override_priority: i32, // priorities up to this value break through, 0 disables
+    pub suppressed_mode: String, // "DEFER" or "DIGEST"
+}
+
+impl Default for QuietHoursSettings {
+    fn default() -> Self {
+        QuietHoursSettings {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            mute_during_pomodoro: true,
+            override_priority: 1,
+            suppressed_mode: "DIGEST".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderDecision {
+    Deliver,
+    Suppress { until: NaiveDateTime },
+}
+
+/// Reminders ready to be shown, split into ones to notify individually and
+/// ones released together as a digest once a quiet window has ended.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReminderDelivery {
+    pub immediate: Vec<Reminder>,
+    pub digest: Vec<Reminder>,
+}
+
+pub fn load_quiet_hours(conn: &Connection) -> DbResult<QuietHoursSettings> {
+    let defaults = QuietHoursSettings::default();
+
+    Ok(QuietHoursSettings {
+        enabled: read_setting(conn, "quiet_hours_enabled")?
+            .map(|v| v == "1")
+            .unwrap_or(defaults.enabled),
+        start: read_setting(conn, "quiet_hours_start")?.unwrap_or(defaults.start),
+        end: read_setting(conn, "quiet_hours_end")?.unwrap_or(defaults.end),
+        mute_during_pomodoro: read_setting(conn, "dnd_during_pomodoro")?
+            .map(|v| v == "1")
+            .unwrap_or(defaults.mute_during_pomodoro),
+        override_priority: read_setting(conn, "dnd_override_priority")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.override_priority),
+        suppressed_mode: read_setting(conn, "dnd_suppressed_mode")?.unwrap_or(defaults.suppressed_mode),
+    })
+}
+
+pub fn save_quiet_hours(conn: &Connection, settings: &QuietHoursSettings) -> Result<(), String> {
+    parse_clock(&settings.start)?;
+    parse_clock(&settings.end)?;
+    if settings.suppressed_mode != "DEFER" && settings.suppressed_mode != "DIGEST" {
+        return Err(format!("Invalid suppressed mode: {}", settings.suppressed_mode));
+    }
+
+    let flag = |value: bool| if value { "1" } else { "0" };
+    let values = [
+        ("quiet_hours_enabled", flag(settings.enabled).to_string()),
+        ("quiet_hours_start", settings.start.clone()),
+        ("quiet_hours_end", settings.end.clone()),
+        ("dnd_during_pomodoro", flag(settings.mute_during_pomodoro).to_string()),
+        ("dnd_override_priority", settings.override_priority.to_string()),
+        ("dnd_suppressed_mode", settings.suppressed_mode.clone()),
+    ];
+
+    for (key, value) in values.iter() {
+        write_setting(conn, key, value).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn parse_clock(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("Invalid time of day: {}", value))
+}
+
+/// Returns when the quiet window containing `now` ends, or `None` outside quiet hours.
+pub fn quiet_window_end(settings: &QuietHoursSettings, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    if !settings.enabled {
+        return None;
+    }
+
+    let start = parse_clock(&settings.start).ok()?;
+    let end = parse_clock(&settings.end).ok()?;
+    let time = now.time();
+    let today = now.date();
+
+    if start <= end {
+        // Same-day window, e.g. 12:00-13:00
+        (time >= start && time < end).then(|| today.and_time(end))
+    } else if time >= start {
+        // Window spans midnight and we are in the evening part
+        Some((today + Duration::days(1)).and_time(end))
+    } else if time < end {
+        // Window spans midnight and we are in the morning part
+        Some(today.and_time(end))
+    } else {
+        None
+    }
+}
+
+/// Decides whether a reminder for an item of `priority` may fire at `now`.
+/// `pomodoro_until` is the end of the running Pomodoro work session, if any.
+pub fn evaluate_reminder(
+    settings: &QuietHoursSettings,
+    now: NaiveDateTime,
+    priority: i32,
+    pomodoro_until: Option<NaiveDateTime>,
+) -> ReminderDecision {
+    if settings.override_priority > 0 && priority <= settings.override_priority {
+        return ReminderDecision::Deliver;
+    }
+
+    let pomodoro_until = pomodoro_until
+        .filter(|until| settings.mute_during_pomodoro && *until > now);
+
+    match (quiet_window_end(settings, now), pomodoro_until) {
+        (Some(a), Some(b)) => ReminderDecision::Suppress { until: a.max(b) },
+        (Some(until), None) | (None, Some(until)) => ReminderDecision::Suppress { until },
+        (None, None) => ReminderDecision::Deliver,
+    }
+}
+
+/// Collects reminders due at `now`, deferring the ones that fall under quiet
+/// hours or DND. Deferred reminders come back once their window has ended,
+/// either one by one or grouped in the digest depending on `suppressed_mode`.
+///
+/// Trigger and deferral times are UTC, like `datetime('now')`, while quiet
+/// hours and DND follow the clock of `now`'s time zone.
+pub fn collect_due_reminders<Tz: TimeZone>(conn: &Connection, now: &DateTime<Tz>) -> DbResult<ReminderDelivery> {
+    let settings = load_quiet_hours(conn)?;
+    let (local_now, utc_now) = (now.naive_local(), now.naive_utc());
+    // Pomodoro phases are stored in UTC too, DND compares them on the local clock
+    let pomodoro_until = running_work_phase_end(conn)?.map(|until| until + (local_now - utc_now));
+    let now_str = format_timestamp(utc_now);
+
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.item_type, r.item_id, r.trigger_time, r.offset_description, r.is_dismissed,
+                r.created_at, r.deferred_until, COALESCE(e.priority, t.priority, 3)
+         FROM reminders r
+         LEFT JOIN events e ON r.item_type = 'EVENT' AND e.id = r.item_id
+         LEFT JOIN tasks t ON r.item_type = 'TASK' AND t.id = r.item_id
+         WHERE r.is_dismissed = 0 AND r.trigger_time <= ?1
+           AND (r.deferred_until IS NULL OR r.deferred_until <= ?1)
+           AND e.deleted_at IS NULL AND t.deleted_at IS NULL
+         ORDER BY r.trigger_time ASC"
+    )?;
+
+    let due = stmt.query_map([&now_str], |row| {
+        let reminder = Reminder {
+            id: Some(row.get(0)?),
+            item_type: row.get(1)?,
+            item_id: row.get(2)?,
+            trigger_time: row.get(3)?,
+            offset_description: row.get(4)?,
+            is_dismissed: row.get(5)?,
+            created_at: row.get(6)?,
+        };
+        let deferred_until: Option<String> = row.get(7)?;
+        let priority: i32 = row.get(8)?;
+        Ok((reminder, deferred_until, priority))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let mut delivery = ReminderDelivery { immediate: Vec::new(), digest: Vec::new() };
+
+    for (reminder, deferred_until, priority) in due {
+        match evaluate_reminder(&settings, local_now, priority, pomodoro_until) {
+            ReminderDecision::Suppress { until } => {
+                conn.execute(
+                    "UPDATE reminders SET deferred_until = ?1 WHERE id = ?2",
+                    rusqlite::params![format_timestamp(until - (local_now - utc_now)), reminder.id],
+                )?;
+            }
+            ReminderDecision::Deliver => {
+                if deferred_until.is_some() && settings.suppressed_mode == "DIGEST" {
+                    delivery.digest.push(reminder);
+                } else {
+                    delivery.immediate.push(reminder);
+                }
+            }
+        }
+    }
+
+    Ok(delivery)
+}
+
+#[tauri::command]
+pub async fn get_quiet_hours(db: State<'_, Database>) -> Result<QuietHoursSettings, String> {
+    load_quiet_hours(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_quiet_hours(settings: QuietHoursSettings, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update quiet hours").map_err(|e| e.to_string())?;
+    save_quiet_hours(db.get_connection(), &settings)
+}
+
+#[tauri::command]
+pub async fn get_deliverable_reminders(db: State<'_, Database>) -> Result<ReminderDelivery, String> {
+    collect_due_reminders(db.get_connection(), &Local::now())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,289 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::search_query_service::{compile_query_with, QueryContext};
+use crate::services::search_service::{item_type_label, search_sorted, SearchResult, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, SORT_ORDERS};
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use tauri::State;
+
+pub const GROUPINGS: &[&str] = &["NONE", "TYPE", "CATEGORY", "STATUS", "PRIORITY", "DATE"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub id: Option<i64>,
+    pub name: String,
+    pub query: String,
+    pub sort_by: String, // one of SORT_ORDERS
+    pub group_by: String, // one of GROUPINGS
+    pub is_pinned: bool,
+    pub position: i64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResultGroup {
+    pub key: String,
+    pub label: String,
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedSearchResults {
+    pub search: SavedSearch,
+    pub total: i64,
+    pub groups: Vec<ResultGroup>,
+}
+
+/// A pinned saved search with its current number of matches, for the sidebar
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SmartList {
+    pub id: i64,
+    pub name: String,
+    pub count: i64,
+}
+
+const SEARCH_COLUMNS: &str = "id, name, query, sort_by, group_by, is_pinned, position, created_at, updated_at";
+
+impl SavedSearch {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(SavedSearch {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            query: row.get(2)?,
+            sort_by: row.get(3)?,
+            group_by: row.get(4)?,
+            is_pinned: row.get(5)?,
+            position: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+
+    fn validate(&self) -> DbResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(DatabaseError::Data("Saved search name must not be empty".to_string()));
+        }
+        if !SORT_ORDERS.iter().any(|(name, _)| *name == self.sort_by) {
+            return Err(DatabaseError::Data(format!("Invalid sort order: {}", self.sort_by)));
+        }
+        if !GROUPINGS.contains(&self.group_by.as_str()) {
+            return Err(DatabaseError::Data(format!("Invalid grouping: {}", self.group_by)));
+        }
+        compile_query_with(&self.query, &QueryContext::default())
+            .map_err(|e| DatabaseError::Data(format!("Invalid query: {}", e)))?;
+        Ok(())
+    }
+}
+
+pub fn load_saved_searches(conn: &Connection) -> DbResult<Vec<SavedSearch>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM saved_searches ORDER BY position, name COLLATE NOCASE",
+        SEARCH_COLUMNS
+    ))?;
+    let searches = stmt.query_map([], SavedSearch::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(searches)
+}
+
+pub fn load_saved_search(conn: &Connection, id: i64) -> DbResult<SavedSearch> {
+    conn.query_row(
+        &format!("SELECT {} FROM saved_searches WHERE id = ?1", SEARCH_COLUMNS),
+        [id],
+        SavedSearch::from_row,
+    )
+    .optional()?
+    .ok_or_else(|| DatabaseError::Data(format!("Saved search {} not found", id)))
+}
+
+pub fn insert_saved_search(conn: &Connection, search: &SavedSearch) -> DbResult<i64> {
+    search.validate()?;
+    conn.execute(
+        "INSERT INTO saved_searches (name, query, sort_by, group_by, is_pinned, position)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![search.name.trim(), search.query, search.sort_by, search.group_by, search.is_pinned, search.position],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn modify_saved_search(conn: &Connection, search: &SavedSearch) -> DbResult<()> {
+    let id = search.id.ok_or_else(|| DatabaseError::Data("Saved search ID is required".to_string()))?;
+    search.validate()?;
+    let changed = conn.execute(
+        "UPDATE saved_searches SET name = ?1, query = ?2, sort_by = ?3, group_by = ?4, is_pinned = ?5, position = ?6
+         WHERE id = ?7",
+        params![search.name.trim(), search.query, search.sort_by, search.group_by, search.is_pinned, search.position, id],
+    )?;
+    if changed == 0 {
+        return Err(DatabaseError::Data(format!("Saved search {} not found", id)));
+    }
+    Ok(())
+}
+
+pub fn remove_saved_search(conn: &Connection, id: i64) -> DbResult<()> {
+    conn.execute("DELETE FROM saved_searches WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+fn group_key(result: &SearchResult, group_by: &str, categories: &HashMap<i64, String>) -> (String, String) {
+    match group_by {
+        "TYPE" => (result.item_type.clone(), item_type_label(&result.item_type).to_string()),
+        "CATEGORY" => match result.category_id {
+            Some(id) => (id.to_string(), categories.get(&id).cloned().unwrap_or_else(|| id.to_string())),
+            None => ("NONE".to_string(), "No category".to_string()),
+        },
+        "STATUS" => match &result.status {
+            Some(status) => (status.clone(), status.clone()),
+            None => ("NONE".to_string(), "No status".to_string()),
+        },
+        "PRIORITY" => match result.priority {
+            Some(priority) => (priority.to_string(), format!("Priority {}", priority)),
+            None => ("NONE".to_string(), "No priority".to_string()),
+        },
+        "DATE" => match result.date.as_deref().and_then(|date| date.get(..10)) {
+            Some(day) => (day.to_string(), day.to_string()),
+            None => ("NONE".to_string(), "No date".to_string()),
+        },
+        _ => ("ALL".to_string(), "All".to_string()),
+    }
+}
+
+/// Splits sorted results into groups, keeping the order in which each group
+/// first appears.
+pub fn group_results(conn: &Connection, results: Vec<SearchResult>, group_by: &str) -> DbResult<Vec<ResultGroup>> {
+    let mut categories = HashMap::new();
+    if group_by == "CATEGORY" {
+        let mut stmt = conn.prepare("SELECT id, name FROM categories")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, name) = row?;
+            categories.insert(id, name);
+        }
+    }
+
+    let mut groups: Vec<ResultGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for result in results {
+        let (key, label) = group_key(&result, group_by, &categories);
+        let position = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(ResultGroup { key, label, results: Vec::new() });
+            groups.len() - 1
+        });
+        groups[position].results.push(result);
+    }
+
+    Ok(groups)
+}
+
+/// Runs a saved search against the current data. Relative dates in the query
+/// are resolved against `today`.
+pub fn evaluate_search(
+    conn: &Connection,
+    search: &SavedSearch,
+    today: NaiveDate,
+    limit: Option<i64>,
+    offset: i64,
+) -> DbResult<SavedSearchResults> {
+    // Like any list of things to do, smart lists leave out what cannot be started yet
+    let context = QueryContext { hide_unavailable_tasks: true, ..QueryContext::load(conn, today)? };
+    let compiled = compile_query_with(&search.query, &context)
+        .map_err(|e| DatabaseError::Data(format!("Invalid query: {}", e)))?;
+
+    let page = search_sorted(conn, &compiled.text, &compiled.scopes, &search.sort_by, limit, offset)?;
+    let groups = group_results(conn, page.results, &search.group_by)?;
+
+    Ok(SavedSearchResults { search: search.clone(), total: page.total, groups })
+}
+
+pub fn load_smart_lists(conn: &Connection, today: NaiveDate) -> DbResult<Vec<SmartList>> {
+    load_saved_searches(conn)?
+        .into_iter()
+        .filter(|search| search.is_pinned)
+        .map(|search| {
+            let results = evaluate_search(conn, &search, today, Some(0), 0)?;
+            Ok(SmartList { id: search.id.unwrap_or_default(), name: search.name, count: results.total })
+        })
+        .collect()
+}
+
+/// Adds or replaces saved searches by name, all or nothing. Returns how many were imported.
+pub fn import_saved_searches_json(conn: &Connection, json_data: &str) -> DbResult<usize> {
+    let searches: Vec<SavedSearch> = serde_json::from_str(json_data)
+        .map_err(|e| DatabaseError::Data(format!("Invalid saved search JSON: {}", e)))?;
+
+    let tx = conn.unchecked_transaction()?;
+    for search in &searches {
+        let existing: Option<i64> = tx.query_row(
+            "SELECT id FROM saved_searches WHERE name = ?1",
+            [search.name.trim()],
+            |row| row.get(0),
+        ).optional()?;
+
+        match existing {
+            Some(id) => modify_saved_search(&tx, &SavedSearch { id: Some(id), ..search.clone() })?,
+            None => {
+                insert_saved_search(&tx, search)?;
+            }
+        }
+    }
+    tx.commit()?;
+
+    Ok(searches.len())
+}
+
+#[tauri::command]
+pub async fn get_saved_searches(db: State<'_, Database>) -> Result<Vec<SavedSearch>, String> {
+    load_saved_searches(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_saved_search(search: SavedSearch, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create saved search").map_err(|e| e.to_string())?;
+    insert_saved_search(db.get_connection(), &search).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_saved_search(search: SavedSearch, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update saved search").map_err(|e| e.to_string())?;
+    modify_saved_search(db.get_connection(), &search).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_saved_search(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete saved search").map_err(|e| e.to_string())?;
+    remove_saved_search(db.get_connection(), id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn evaluate_saved_search(
+    id: i64,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    db: State<'_, Database>
+) -> Result<SavedSearchResults, String> {
+    let conn = db.get_connection();
+    let search = load_saved_search(conn, id).map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    evaluate_search(conn, &search, Local::now().date_naive(), Some(limit), offset.unwrap_or(0))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_smart_lists(db: State<'_, Database>) -> Result<Vec<SmartList>, String> {
+    load_smart_lists(db.get_connection(), Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_saved_searches(db: State<'_, Database>) -> Result<String, String> {
+    let searches = load_saved_searches(db.get_connection()).map_err(|e| e.to_string())?;
+    serde_json::to_string_pretty(&searches).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_saved_searches(json_data: String, db: State<'_, Database>) -> Result<usize, String> {
+    let _undo = db.undo_step("Import saved searches").map_err(|e| e.to_string())?;
+    import_saved_searches_json(db.get_connection(), &json_data).map_err(|e| e.to_string())
+}
@@ -0,0 +1,429 @@
+use crate::db::{Database, error::DbResult};
+use crate::services::search_service::{
+    escape_like, fts_query, fts_table, search_matching, SearchPage, SearchScope, TextMatch,
+    DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+};
+use crate::services::settings_service::read_setting;
+use crate::services::time_report_service::week_start;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use rusqlite::{types::Value, Connection};
+use serde::{Serialize, Deserialize};
+use tauri::State;
+use thiserror::Error;
+
+const ITEM_TYPES: &[&str] = &["EVENT", "TASK", "NOTE"];
+
+/// `is:` flags about when a task is actionable
+const AVAILABILITY_FLAGS: &[&str] = &["available", "deferred", "someday"];
+
+/// Tasks that are not parked as someday and have reached their start date,
+/// compared against today
+const AVAILABLE_CONDITION: &str = "x.is_someday = 0 AND (x.start_date IS NULL OR substr(x.start_date, 1, 10) <= ?)";
+
+/// A query that could not be parsed, pointing at the token responsible
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
+#[error("{message} at position {position}")]
+pub struct QueryError {
+    pub position: usize, // character offset of the token in the query
+    pub token: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryTerm {
+    Word(String),
+    Phrase(String),
+    Filter { field: String, op: String, value: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryToken {
+    pub position: usize,
+    pub text: String, // the token as written
+    pub negated: bool,
+    pub term: QueryTerm,
+}
+
+impl QueryToken {
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError { position: self.position, token: self.text.clone(), message: message.into() }
+    }
+}
+
+/// Free text and filters of a query, ready to run through the search index
+#[derive(Debug, Clone)]
+pub struct CompiledQuery {
+    pub text: TextMatch,
+    pub scopes: Vec<SearchScope>,
+}
+
+/// What relative dates such as `today` or `this_week` are resolved against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryContext {
+    pub today: NaiveDate,
+    pub week_start_day: u32, // 0 for Sunday, 1 for Monday
+    pub hide_unavailable_tasks: bool, // leave out deferred and someday tasks unless the query asks for them
+}
+
+impl QueryContext {
+    pub fn load(conn: &Connection, today: NaiveDate) -> DbResult<Self> {
+        let week_start_day = read_setting(conn, "week_start_day")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1);
+        Ok(QueryContext { today, week_start_day, hide_unavailable_tasks: false })
+    }
+
+    /// First and last day named by a date value
+    fn date_range(&self, value: &str) -> Option<(NaiveDate, NaiveDate)> {
+        let week = week_start(self.today, self.week_start_day);
+        let month = self.today.with_day(1)?;
+        let range = match value.to_lowercase().as_str() {
+            "today" => (self.today, self.today),
+            "yesterday" => (self.today - Duration::days(1), self.today - Duration::days(1)),
+            "tomorrow" => (self.today + Duration::days(1), self.today + Duration::days(1)),
+            "last_week" => (week - Duration::days(7), week - Duration::days(1)),
+            "this_week" => (week, week + Duration::days(6)),
+            "next_week" => (week + Duration::days(7), week + Duration::days(13)),
+            "this_month" => (month, month.checked_add_months(Months::new(1))? - Duration::days(1)),
+            _ => {
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+                (date, date)
+            }
+        };
+        Some(range)
+    }
+}
+
+impl Default for QueryContext {
+    fn default() -> Self {
+        QueryContext { today: Local::now().date_naive(), week_start_day: 1, hide_unavailable_tasks: false }
+    }
+}
+
+/// A field filter and the item types that have the field
+struct FieldFilter {
+    types: &'static [&'static str],
+    condition: String,
+    params: Vec<Value>,
+}
+
+/// Reads a quoted string starting at `open`, returning its content and the
+/// index just past the closing quote.
+fn read_quoted(chars: &[char], open: usize) -> Result<(String, usize), QueryError> {
+    match chars[open + 1..].iter().position(|&c| c == '"') {
+        Some(length) => Ok((chars[open + 1..open + 1 + length].iter().collect(), open + length + 2)),
+        None => Err(QueryError {
+            position: open,
+            token: chars[open..].iter().collect(),
+            message: "Unterminated quote".to_string(),
+        }),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+fn split_operator(value: &str) -> (&str, &str) {
+    for op in ["<=", ">=", "<", ">", "="] {
+        if let Some(rest) = value.strip_prefix(op) {
+            return (op, rest);
+        }
+    }
+    ("", value)
+}
+
+/// Splits a query into words, quoted phrases and `field:value` filters.
+/// Quotes group whitespace, so `category:"Deep Work"` is a single filter.
+pub fn tokenize_query(input: &str) -> Result<Vec<QueryToken>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let position = i;
+        let negated = chars[i] == '-';
+        if negated {
+            i += 1;
+        }
+
+        let word_start = i;
+        while i < chars.len() && !chars[i].is_whitespace() {
+            i = if chars[i] == '"' { read_quoted(&chars, i)?.1 } else { i + 1 };
+        }
+
+        let raw: String = chars[word_start..i].iter().collect();
+        let mut token = QueryToken {
+            position,
+            text: chars[position..i].iter().collect(),
+            negated,
+            term: QueryTerm::Word(raw.clone()),
+        };
+
+        if raw.is_empty() {
+            return Err(token.error("Expected a term after '-'"));
+        }
+
+        token.term = if raw.starts_with('"') {
+            let phrase = unquote(&raw);
+            if phrase.len() + 2 != raw.len() || phrase.contains('"') {
+                return Err(token.error("Unexpected text after quoted phrase"));
+            }
+            QueryTerm::Phrase(phrase.to_string())
+        } else if let Some((field, rest)) = raw.split_once(':') {
+            let (op, value) = split_operator(rest);
+            let value = unquote(value);
+            if field.is_empty() {
+                return Err(token.error("Missing field name"));
+            }
+            if value.is_empty() {
+                return Err(token.error(format!("Missing value for '{}'", field)));
+            }
+            QueryTerm::Filter { field: field.to_lowercase(), op: op.to_string(), value: value.to_string() }
+        } else if raw.contains('"') {
+            return Err(token.error("Unexpected quote"));
+        } else {
+            QueryTerm::Word(raw)
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn sql_operator(op: &str) -> &str {
+    if op.is_empty() { "=" } else { op }
+}
+
+/// Compares the date part of `column` against a day or a range of days
+fn date_condition(
+    token: &QueryToken,
+    column: &str,
+    op: &str,
+    value: &str,
+    context: &QueryContext,
+) -> Result<(String, Vec<Value>), QueryError> {
+    let (from, to) = context.date_range(value).ok_or_else(|| token.error(format!(
+        "Expected a date as YYYY-MM-DD or today, yesterday, tomorrow, last_week, this_week, next_week or this_month, got '{}'",
+        value
+    )))?;
+    let day = |date: NaiveDate| Value::Text(date.format("%Y-%m-%d").to_string());
+    let column = format!("substr(x.{}, 1, 10)", column);
+
+    Ok(match op {
+        "<" => (format!("{} < ?", column), vec![day(from)]),
+        "<=" => (format!("{} <= ?", column), vec![day(to)]),
+        ">" => (format!("{} > ?", column), vec![day(to)]),
+        ">=" => (format!("{} >= ?", column), vec![day(from)]),
+        _ => (format!("{} BETWEEN ? AND ?", column), vec![day(from), day(to)]),
+    })
+}
+
+fn parse_item_type(token: &QueryToken, value: &str) -> Result<&'static str, QueryError> {
+    let wanted = value.to_uppercase();
+    let wanted = wanted.strip_suffix('S').unwrap_or(&wanted);
+    ITEM_TYPES.iter()
+        .find(|item_type| **item_type == wanted)
+        .copied()
+        .ok_or_else(|| token.error(format!("Unknown type '{}', expected event, task or note", value)))
+}
+
+fn compile_filter(
+    token: &QueryToken,
+    field: &str,
+    op: &str,
+    value: &str,
+    context: &QueryContext,
+) -> Result<FieldFilter, QueryError> {
+    let comparable = matches!(field, "priority" | "due" | "start" | "created");
+    if !op.is_empty() && !comparable {
+        return Err(token.error(format!("Operator '{}' is not supported for '{}'", op, field)));
+    }
+
+    let filter = match field {
+        "status" => FieldFilter {
+            types: &["TASK"],
+            condition: "x.status = ?".to_string(),
+            params: vec![Value::Text(value.to_uppercase())],
+        },
+        "priority" => {
+            let priority: i64 = value.parse()
+                .map_err(|_| token.error(format!("Priority must be a number, got '{}'", value)))?;
+            FieldFilter {
+                types: &["EVENT", "TASK"],
+                condition: format!("x.priority {} ?", sql_operator(op)),
+                params: vec![Value::Integer(priority)],
+            }
+        }
+        "category" => FieldFilter {
+            types: &["EVENT", "TASK"],
+            condition: "x.category_id IN (SELECT id FROM categories WHERE name = ? COLLATE NOCASE)".to_string(),
+            params: vec![Value::Text(value.to_string())],
+        },
+        "due" | "start" | "created" => {
+            let (types, column): (&'static [&'static str], &str) = match field {
+                "due" => (&["TASK"], "due_date"),
+                "start" => (&["EVENT"], "start_time"),
+                _ => (ITEM_TYPES, "created_at"),
+            };
+            let (condition, params) = date_condition(token, column, op, value, context)?;
+            FieldFilter { types, condition, params }
+        }
+        "participant" => {
+            let pattern = format!("%{}%", escape_like(value));
+            FieldFilter {
+                types: &["EVENT"],
+                condition: "EXISTS (SELECT 1 FROM event_participants ep
+                                    JOIN participants p ON p.id = ep.participant_id
+                                    WHERE ep.event_id = x.id
+                                    AND (p.name LIKE ? ESCAPE '\\' OR p.email LIKE ? ESCAPE '\\'))".to_string(),
+                params: vec![Value::Text(pattern.clone()), Value::Text(pattern)],
+            }
+        }
+        "is" => {
+            let today = vec![Value::Text(context.today.format("%Y-%m-%d").to_string())];
+            let (types, condition, params): (&'static [&'static str], &str, Vec<Value>) = match value.to_lowercase().as_str() {
+                "recurring" => (&["EVENT", "TASK"], "x.recurring_rule_id IS NOT NULL", Vec::new()),
+                "completed" => (&["TASK"], "x.status = 'COMPLETED'", Vec::new()),
+                "allday" | "all_day" => (&["EVENT"], "x.is_all_day = 1", Vec::new()),
+                "overdue" => (&["TASK"], "x.status <> 'COMPLETED' AND substr(x.due_date, 1, 10) < date('now', 'localtime')", Vec::new()),
+                "available" => (&["TASK"], AVAILABLE_CONDITION, today),
+                "deferred" => (&["TASK"], "substr(x.start_date, 1, 10) > ?", today),
+                "someday" => (&["TASK"], "x.is_someday = 1", Vec::new()),
+                _ => return Err(token.error(format!(
+                    "Unknown flag '{}', expected recurring, completed, allday, overdue, available, deferred or someday", value
+                ))),
+            };
+            FieldFilter { types, condition: condition.to_string(), params }
+        }
+        _ => return Err(token.error(format!("Unknown field '{}'", field))),
+    };
+
+    Ok(filter)
+}
+
+/// Compiles a query such as `status:in_progress priority:<=2 "exact phrase" -excluded`
+/// into an index match plus one filtered scope per item type that can satisfy
+/// it. Every value travels as a bound parameter.
+pub fn compile_query(input: &str) -> Result<CompiledQuery, QueryError> {
+    compile_query_with(input, &QueryContext::default())
+}
+
+pub fn compile_query_with(input: &str, context: &QueryContext) -> Result<CompiledQuery, QueryError> {
+    let mut types: Vec<&str> = ITEM_TYPES.to_vec();
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    let mut filters = Vec::new();
+    let mut tags = Vec::new();
+    let mut asks_availability = false;
+
+    for token in tokenize_query(input)? {
+        let text = match &token.term {
+            QueryTerm::Word(word) => fts_query(word),
+            QueryTerm::Phrase(phrase) => fts_query(phrase).map(|_| format!("\"{}\"", phrase)),
+            QueryTerm::Filter { field, op, value } if field == "type" => {
+                if !op.is_empty() {
+                    return Err(token.error(format!("Operator '{}' is not supported for 'type'", op)));
+                }
+                let item_type = parse_item_type(&token, value)?;
+                types.retain(|t| (*t == item_type) != token.negated);
+                continue;
+            }
+            QueryTerm::Filter { field, op, value } if field == "tag" => {
+                if !op.is_empty() {
+                    return Err(token.error(format!("Operator '{}' is not supported for 'tag'", op)));
+                }
+                tags.push((token.negated, value.clone()));
+                continue;
+            }
+            QueryTerm::Filter { field, op, value } => {
+                asks_availability |= field == "is" && AVAILABILITY_FLAGS.contains(&value.to_lowercase().as_str());
+                filters.push((token.negated, compile_filter(&token, field, op, value, context)?));
+                continue;
+            }
+        };
+
+        let text = text.ok_or_else(|| token.error("Nothing searchable in term"))?;
+        if token.negated {
+            excluded.push(format!("({})", text));
+        } else {
+            included.push(text);
+        }
+    }
+
+    let mut scopes = Vec::new();
+    'types: for item_type in types {
+        let mut scope = SearchScope::new(item_type);
+
+        for (negated, filter) in &filters {
+            let applies = filter.types.contains(&item_type);
+            if applies && *negated {
+                // Missing values count as not matching, so they survive the exclusion
+                scope = scope.condition(&format!("NOT COALESCE(({}), 0)", filter.condition), filter.params.clone());
+            } else if applies {
+                scope = scope.condition(&filter.condition, filter.params.clone());
+            } else if !negated {
+                continue 'types;
+            }
+        }
+
+        if item_type == "TASK" && context.hide_unavailable_tasks && !asks_availability {
+            scope = scope.condition(AVAILABLE_CONDITION, vec![Value::Text(context.today.format("%Y-%m-%d").to_string())]);
+        }
+
+        // Every item type can be tagged
+        for (negated, tag) in &tags {
+            scope = if *negated { scope.without_tag(tag) } else { scope.tagged(tag) };
+        }
+
+        if let (false, Some(table)) = (excluded.is_empty(), fts_table(item_type)) {
+            scope = scope.condition(
+                &format!("x.id NOT IN (SELECT rowid FROM {table} WHERE {table} MATCH ?)", table = table),
+                vec![Value::Text(excluded.join(" OR "))],
+            );
+        }
+
+        scopes.push(scope);
+    }
+
+    let text = if included.is_empty() { TextMatch::Any } else { TextMatch::Index(included.join(" ")) };
+    Ok(CompiledQuery { text, scopes })
+}
+
+pub fn run_search_query(conn: &Connection, query: &str, limit: Option<i64>, offset: i64) -> Result<SearchPage, String> {
+    let context = QueryContext::load(conn, Local::now().date_naive()).map_err(|e| e.to_string())?;
+    let compiled = compile_query_with(query, &context).map_err(|e| e.to_string())?;
+    search_matching(conn, &compiled.text, &compiled.scopes, limit, offset).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_query(
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    db: State<'_, Database>
+) -> Result<SearchPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = offset.unwrap_or(0);
+    if query.trim().is_empty() {
+        return Ok(SearchPage::empty(Some(limit), offset));
+    }
+
+    run_search_query(db.get_connection(), &query, Some(limit), offset)
+}
+
+/// Lets the search box highlight a bad token before the query is run
+#[tauri::command]
+pub async fn validate_search_query(query: String) -> Result<(), QueryError> {
+    compile_query(&query).map(|_| ())
+}
@@ -1,102 +1,780 @@
-use crate::db::Database;
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::tag_service::tag_condition;
+use chrono::NaiveDate;
+use rusqlite::{types::Value, Connection};
 use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use tauri::State;
 
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Free-text searches with fewer exact hits than this retry with typo-tolerant matching
+pub const FUZZY_MIN_HITS: i64 = 3;
+const FUZZY_MAX_CANDIDATES: usize = 20;
+const FUZZY_MAX_RESULTS: i64 = 200;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: i64,
     pub title: String,
     pub description: Option<String>,
-    pub item_type: String, // "EVENT", "TASK", or "NOTE"
-    pub date: Option<String>, // start_time for events, due_date for tasks, created_at for notes
+    pub item_type: String, // "EVENT", "TASK", "NOTE", "PARTICIPANT" or "CATEGORY"
+    pub date: Option<String>, // start_time for events, due_date for tasks, created_at for notes, None otherwise
     pub category_id: Option<i64>,
     pub priority: Option<i32>,
     pub status: Option<String>,
+    pub rank: Option<f64>, // BM25 score, lower is a better match; None when not matched through the index
+    pub snippet: Option<String>, // matched text with hits wrapped in ** markers
+    pub match_quality: f64, // 1.0 for exact and prefix matches, lower the more a fuzzy match had to bend
 }
 
-#[tauri::command]
-pub async fn search_all(
-    query: String,
-    db: State<'_, Database>
-) -> Result<Vec<SearchResult>, String> {
-    let conn = db.get_connection();
-    let mut results = Vec::new();
-    let query_pattern = format!("%{}%", query);
-
-    // Search events
-    let mut stmt = conn.prepare(
-        "SELECT id, title, description, start_time, category_id, priority
-         FROM events 
-         WHERE title LIKE ?1 
-         OR description LIKE ?1 
-         OR location LIKE ?1
-         ORDER BY start_time DESC"
-    ).map_err(|e| e.to_string())?;
-
-    let events = stmt.query_map([&query_pattern], |row| {
-        Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: row.get(2)?,
-            item_type: "EVENT".to_string(),
-            date: Some(row.get(3)?),
-            category_id: row.get(4)?,
-            priority: Some(row.get(5)?),
-            status: None,
-        })
-    }).map_err(|e| e.to_string())?;
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub total: i64,
+    pub limit: Option<i64>,
+    pub offset: i64,
+    pub fuzzy: bool, // results came from the typo-tolerant fallback
+    pub facets: Option<SearchFacets>, // counts over every match, not just this page; None unless asked for
+}
 
-    results.extend(events.filter_map(|r| r.ok()));
+impl SearchPage {
+    pub fn empty(limit: Option<i64>, offset: i64) -> Self {
+        SearchPage { results: Vec::new(), total: 0, limit, offset, fuzzy: false, facets: None }
+    }
+}
 
-    // Search tasks
-    let mut stmt = conn.prepare(
-        "SELECT id, title, description, due_date, category_id, priority, status
-         FROM tasks 
-         WHERE title LIKE ?1 
-         OR description LIKE ?1
-         ORDER BY due_date DESC"
-    ).map_err(|e| e.to_string())?;
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FacetCount {
+    pub value: String, // what to filter on, e.g. a category ID or "2023-01"
+    pub label: String,
+    pub count: i64,
+}
 
-    let tasks = stmt.query_map([&query_pattern], |row| {
-        Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: row.get(2)?,
-            item_type: "TASK".to_string(),
-            date: row.get(3)?,
-            category_id: row.get(4)?,
-            priority: Some(row.get(5)?),
-            status: Some(row.get(6)?),
+/// How many matches fall under each value of the fields the UI filters on.
+/// Matches without a value for a field (notes have no status) are left out
+/// of that field's counts.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SearchFacets {
+    pub item_types: Vec<FacetCount>,
+    pub categories: Vec<FacetCount>, // most matches first
+    pub statuses: Vec<FacetCount>, // most matches first
+    pub priorities: Vec<FacetCount>, // most important first
+    pub months: Vec<FacetCount>, // by the result date, oldest first
+}
+
+/// Matches sharing the same faceted values
+struct FacetRow {
+    item_type: String,
+    category_id: Option<i64>,
+    status: Option<String>,
+    priority: Option<i32>,
+    month: Option<String>,
+    count: i64,
+}
+
+/// One searchable entity type and the filters applied to it. Conditions
+/// refer to the source table as `x` and take their values from `params`
+/// in order.
+#[derive(Debug, Clone)]
+pub struct SearchScope {
+    pub item_type: String,
+    pub conditions: Vec<String>,
+    pub params: Vec<Value>,
+}
+
+impl SearchScope {
+    pub fn new(item_type: &str) -> Self {
+        SearchScope { item_type: item_type.to_string(), conditions: Vec::new(), params: Vec::new() }
+    }
+
+    pub fn filter(self, condition: &str, value: impl Into<Value>) -> Self {
+        self.condition(condition, vec![value.into()])
+    }
+
+    /// Adds a condition taking any number of values
+    pub fn condition(mut self, condition: &str, params: Vec<Value>) -> Self {
+        self.conditions.push(condition.to_string());
+        self.params.extend(params);
+        self
+    }
+
+    /// Keeps items carrying the tag named `tag`, ignoring case
+    pub fn tagged(self, tag: &str) -> Self {
+        match tag_condition(&self.item_type) {
+            Some(condition) => self.filter(&condition, tag.to_string()),
+            None => self.condition("0", Vec::new()),
+        }
+    }
+
+    /// Drops items carrying the tag named `tag`
+    pub fn without_tag(self, tag: &str) -> Self {
+        match tag_condition(&self.item_type) {
+            Some(condition) => self.filter(&format!("NOT ({})", condition), tag.to_string()),
+            None => self,
+        }
+    }
+
+    /// Every entity type, unfiltered
+    pub fn all() -> Vec<SearchScope> {
+        SOURCES.iter().map(|source| SearchScope::new(source.item_type)).collect()
+    }
+}
+
+/// Restricts `scope` to items carrying every one of `tags`
+fn with_tags(scope: SearchScope, tags: Option<Vec<String>>) -> SearchScope {
+    tags.unwrap_or_default().iter().fold(scope, |scope, tag| scope.tagged(tag))
+}
+
+struct SearchSource {
+    item_type: &'static str,
+    table: &'static str,
+    fts_table: &'static str,
+    columns: &'static str, // aliased to id, title, description, date, category_id, priority, status
+    weights: &'static str, // bm25 weight per indexed column, titles count most and metadata least
+    text_columns: &'static [&'static str],
+    visible: &'static str, // leaves out trashed, and for tasks archived, items
+}
+
+const SOURCES: &[SearchSource] = &[
+    SearchSource {
+        item_type: "EVENT",
+        table: "events",
+        fts_table: "events_fts",
+        columns: "x.id, x.title, x.description, x.start_time AS date, x.category_id, x.priority, NULL AS status",
+        weights: "10.0, 4.0, 2.0, 2.0, 1.0",
+        text_columns: &["title", "description", "location"],
+        visible: "x.deleted_at IS NULL",
+    },
+    SearchSource {
+        item_type: "TASK",
+        table: "tasks",
+        fts_table: "tasks_fts",
+        columns: "x.id, x.title, x.description, x.due_date AS date, x.category_id, x.priority, x.status",
+        weights: "10.0, 4.0, 1.0",
+        text_columns: &["title", "description"],
+        visible: "x.deleted_at IS NULL AND x.archived_at IS NULL",
+    },
+    SearchSource {
+        item_type: "NOTE",
+        table: "notes",
+        fts_table: "notes_fts",
+        columns: "x.id, x.title, x.content AS description, x.created_at AS date, NULL AS category_id, NULL AS priority, NULL AS status",
+        weights: "10.0, 4.0",
+        text_columns: &["title", "content"],
+        visible: "x.deleted_at IS NULL",
+    },
+    SearchSource {
+        item_type: "PARTICIPANT",
+        table: "participants",
+        fts_table: "participants_fts",
+        columns: "x.id, x.name AS title, x.email AS description, NULL AS date, NULL AS category_id, NULL AS priority, NULL AS status",
+        weights: "10.0, 4.0",
+        text_columns: &["name", "email"],
+        visible: "1 = 1",
+    },
+    SearchSource {
+        item_type: "CATEGORY",
+        table: "categories",
+        fts_table: "categories_fts",
+        columns: "x.id, x.name AS title, NULL AS description, NULL AS date, x.id AS category_id, NULL AS priority, NULL AS status",
+        weights: "10.0",
+        text_columns: &["name"],
+        visible: "1 = 1",
+    },
+];
+
+/// Result orderings by name. Priority 1 is the most important, so it sorts first.
+pub const SORT_ORDERS: &[(&str, &str)] = &[
+    ("RELEVANCE", "rank IS NULL, rank, date DESC, id DESC"),
+    ("DATE_ASC", "date IS NULL, date, id"),
+    ("DATE_DESC", "date IS NULL, date DESC, id DESC"),
+    ("PRIORITY", "priority IS NULL, priority, date IS NULL, date, id"),
+    ("TITLE", "title COLLATE NOCASE, id"),
+];
+
+/// How the free text of a query is matched
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextMatch {
+    Any,
+    Index(String), // FTS5 expression
+    Literal(String), // LIKE pattern, already escaped
+}
+
+impl TextMatch {
+    pub fn from_query(text: &str) -> Self {
+        let text = text.trim();
+        if text.is_empty() {
+            TextMatch::Any
+        } else if let Some(fts) = fts_query(text) {
+            TextMatch::Index(fts)
+        } else {
+            // Nothing the tokenizer would index, e.g. "@" or an emoji
+            TextMatch::Literal(format!("%{}%", escape_like(text)))
+        }
+    }
+}
+
+/// Plural display name of an item type
+pub fn item_type_label(item_type: &str) -> &'static str {
+    match item_type {
+        "EVENT" => "Events",
+        "TASK" => "Tasks",
+        "PARTICIPANT" => "Participants",
+        "CATEGORY" => "Categories",
+        _ => "Notes",
+    }
+}
+
+/// Name of the FTS5 table indexing an item type
+pub fn fts_table(item_type: &str) -> Option<&'static str> {
+    SOURCES.iter().find(|source| source.item_type == item_type).map(|source| source.fts_table)
+}
+
+/// Turns free text into an FTS5 expression where every word must match as a
+/// prefix. Words are quoted so FTS5 operators and punctuation in the input
+/// are never interpreted.
+pub fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Escapes LIKE wildcards so `%` and `_` in a query match literally
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn scope_sql(scope: &SearchScope, text: &TextMatch, params: &mut Vec<Value>) -> DbResult<String> {
+    let source = SOURCES.iter()
+        .find(|source| source.item_type == scope.item_type)
+        .ok_or_else(|| DatabaseError::Data(format!("Unknown search type: {}", scope.item_type)))?;
+
+    let mut sql = match text {
+        TextMatch::Index(fts) => {
+            params.push(Value::Text(fts.clone()));
+            format!(
+                "SELECT '{item_type}' AS item_type, {columns},
+                        bm25({fts_table}, {weights}) AS rank,
+                        snippet({fts_table}, -1, '**', '**', '…', 12) AS snippet
+                 FROM {fts_table} JOIN {table} x ON x.id = {fts_table}.rowid
+                 WHERE {fts_table} MATCH ?",
+                item_type = source.item_type,
+                columns = source.columns,
+                fts_table = source.fts_table,
+                weights = source.weights,
+                table = source.table,
+            )
+        }
+        TextMatch::Literal(pattern) => {
+            let clauses: Vec<String> = source.text_columns.iter().map(|column| {
+                params.push(Value::Text(pattern.clone()));
+                format!("x.{} LIKE ? ESCAPE '\\'", column)
+            }).collect();
+            format!(
+                "SELECT '{}' AS item_type, {}, NULL AS rank, NULL AS snippet FROM {} x WHERE ({})",
+                source.item_type, source.columns, source.table, clauses.join(" OR ")
+            )
+        }
+        TextMatch::Any => format!(
+            "SELECT '{}' AS item_type, {}, NULL AS rank, NULL AS snippet FROM {} x WHERE 1 = 1",
+            source.item_type, source.columns, source.table
+        ),
+    };
+
+    sql.push_str(" AND ");
+    sql.push_str(source.visible);
+    for condition in &scope.conditions {
+        sql.push_str(" AND ");
+        sql.push_str(condition);
+    }
+    params.extend(scope.params.iter().cloned());
+
+    Ok(sql)
+}
+
+/// Lowercases and strips accents the way the index tokenizer does, so
+/// "Crème" and "creme" compare equal.
+pub fn normalize_text(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).map(fold_diacritic).collect()
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' => 'h',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'ţ' | 'ť' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => c,
+    }
+}
+
+/// Normalised words of a text, split where the index tokenizer would split
+fn words(text: &str) -> Vec<String> {
+    normalize_text(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Optimal string alignment distance: edits, with swapped neighbours counting once
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Distance from `word` to `term`, or to the start of `term` so that
+/// half-typed words still find longer ones
+fn term_distance(word: &str, term: &str) -> usize {
+    let prefix: String = term.chars().take(word.chars().count()).collect();
+    edit_distance(word, term).min(edit_distance(word, &prefix))
+}
+
+/// How many edits a word of this length may need; short words must be exact
+fn allowed_edits(word: &str) -> usize {
+    match word.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Indexed terms within reach of `word`, closest first
+fn fuzzy_candidates(conn: &Connection, word: &str) -> DbResult<Vec<String>> {
+    let max_edits = allowed_edits(word);
+    if max_edits == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Terms are only read in the ranges starting with the first or second
+    // letter of the word, which keeps the scan short on large indexes while
+    // still catching a typo or swap at the start
+    let sql = SOURCES.iter()
+        .flat_map(|source| [
+            format!("SELECT term FROM {}_vocab WHERE term >= ?1 AND term < ?2", source.fts_table),
+            format!("SELECT term FROM {}_vocab WHERE term >= ?3 AND term < ?4", source.fts_table),
+        ])
+        .collect::<Vec<_>>()
+        .join(" UNION ");
+    let letter_range = |letter: char| {
+        let next = char::from_u32(letter as u32 + 1).unwrap_or(char::MAX);
+        (letter.to_string(), next.to_string())
+    };
+    let mut letters = word.chars();
+    let (first_from, first_to) = letter_range(letters.next().unwrap_or_default());
+    let (second_from, second_to) = letter_range(letters.next().unwrap_or_default());
+    let min_length = word.chars().count().saturating_sub(max_edits);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let terms = stmt.query_map([first_from, first_to, second_from, second_to], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut candidates: Vec<(usize, String)> = terms.into_iter()
+        .filter(|term| term.chars().count() >= min_length && !term.starts_with(word))
+        .map(|term| (term_distance(word, &term), term))
+        .filter(|(distance, _)| *distance <= max_edits)
+        .collect();
+    candidates.sort();
+    candidates.truncate(FUZZY_MAX_CANDIDATES);
+
+    Ok(candidates.into_iter().map(|(_, term)| term).collect())
+}
+
+/// Share of the query that a result matches, 1.0 when every word appears
+/// exactly or as a prefix
+fn match_quality(query_words: &[String], result: &SearchResult) -> f64 {
+    let text = format!("{} {}", result.title, result.description.as_deref().unwrap_or(""));
+    let result_words = words(&text);
+    if query_words.is_empty() {
+        return 1.0;
+    }
+
+    let total: f64 = query_words.iter().map(|word| {
+        let length = word.chars().count().max(1) as f64;
+        result_words.iter()
+            .map(|candidate| 1.0 - term_distance(word, candidate) as f64 / length)
+            .fold(0.0, f64::max)
+    }).sum();
+
+    total / query_words.len() as f64
+}
+
+/// Retries a free-text search allowing each word to match indexed terms a
+/// few edits away. Returns None when no word has any near miss to add.
+fn search_fuzzy(
+    conn: &Connection,
+    text: &str,
+    scopes: &[SearchScope],
+    limit: Option<i64>,
+    offset: i64,
+    with_facets: bool,
+) -> DbResult<Option<SearchPage>> {
+    let query_words = words(text);
+    let mut expanded = false;
+    let mut clauses = Vec::new();
+
+    for word in &query_words {
+        let candidates = fuzzy_candidates(conn, word)?;
+        expanded |= !candidates.is_empty();
+
+        let mut alternatives = vec![format!("\"{}\"*", word)];
+        alternatives.extend(candidates.iter().map(|term| format!("\"{}\"", term)));
+        clauses.push(format!("({})", alternatives.join(" OR ")));
+    }
+
+    if !expanded {
+        return Ok(None);
+    }
+
+    let matched = search_matching(conn, &TextMatch::Index(clauses.join(" AND ")), scopes, Some(FUZZY_MAX_RESULTS), 0)?;
+    let mut results = matched.results;
+    for result in &mut results {
+        result.match_quality = match_quality(&query_words, result);
+    }
+    // Stable, so equally good matches keep their BM25 order
+    results.sort_by(|a, b| b.match_quality.partial_cmp(&a.match_quality).unwrap_or(Ordering::Equal));
+
+    let facets = if with_facets {
+        let rows = results.iter().map(|result| FacetRow {
+            item_type: result.item_type.clone(),
+            category_id: result.category_id,
+            status: result.status.clone(),
+            priority: result.priority,
+            month: result.date.as_ref().and_then(|date| date.get(..7)).map(str::to_string),
+            count: 1,
+        }).collect();
+        Some(build_facets(conn, rows)?)
+    } else {
+        None
+    };
+
+    let offset = offset.max(0);
+    let total = results.len() as i64;
+    let results = results.into_iter()
+        .skip(offset as usize)
+        .take(limit.map_or(usize::MAX, |limit| limit.max(0) as usize))
+        .collect();
+
+    Ok(Some(SearchPage { results, total, limit, offset, fuzzy: true, facets }))
+}
+
+/// Adds up facet rows per field and labels the values
+fn build_facets(conn: &Connection, rows: Vec<FacetRow>) -> DbResult<SearchFacets> {
+    let mut item_types: HashMap<String, i64> = HashMap::new();
+    let mut categories: HashMap<i64, i64> = HashMap::new();
+    let mut statuses: HashMap<String, i64> = HashMap::new();
+    let mut priorities: HashMap<i32, i64> = HashMap::new();
+    let mut months: HashMap<String, i64> = HashMap::new();
+
+    for row in rows {
+        *item_types.entry(row.item_type).or_default() += row.count;
+        if let Some(id) = row.category_id {
+            *categories.entry(id).or_default() += row.count;
+        }
+        if let Some(status) = row.status {
+            *statuses.entry(status).or_default() += row.count;
+        }
+        if let Some(priority) = row.priority {
+            *priorities.entry(priority).or_default() += row.count;
+        }
+        if let Some(month) = row.month {
+            *months.entry(month).or_default() += row.count;
+        }
+    }
+
+    let mut names = HashMap::new();
+    if !categories.is_empty() {
+        let mut stmt = conn.prepare("SELECT id, name FROM categories")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, name) = row?;
+            names.insert(id, name);
+        }
+    }
+
+    let by_count = |a: &FacetCount, b: &FacetCount| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label));
+
+    let item_types = SOURCES.iter()
+        .filter_map(|source| item_types.get(source.item_type).map(|&count| FacetCount {
+            value: source.item_type.to_string(),
+            label: item_type_label(source.item_type).to_string(),
+            count,
+        }))
+        .collect();
+
+    let mut categories: Vec<FacetCount> = categories.into_iter()
+        .map(|(id, count)| FacetCount {
+            value: id.to_string(),
+            label: names.get(&id).cloned().unwrap_or_else(|| id.to_string()),
+            count,
+        })
+        .collect();
+    categories.sort_by(by_count);
+
+    let mut statuses: Vec<FacetCount> = statuses.into_iter()
+        .map(|(status, count)| FacetCount { value: status.clone(), label: status, count })
+        .collect();
+    statuses.sort_by(by_count);
+
+    let mut priorities: Vec<(i32, i64)> = priorities.into_iter().collect();
+    priorities.sort();
+    let priorities = priorities.into_iter()
+        .map(|(priority, count)| FacetCount {
+            value: priority.to_string(),
+            label: format!("Priority {}", priority),
+            count,
         })
-    }).map_err(|e| e.to_string())?;
+        .collect();
+
+    let mut months: Vec<(String, i64)> = months.into_iter().collect();
+    months.sort();
+    let months = months.into_iter()
+        .map(|(month, count)| {
+            let label = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+                .map(|first| first.format("%B %Y").to_string())
+                .unwrap_or_else(|_| month.clone());
+            FacetCount { value: month, label, count }
+        })
+        .collect();
 
-    results.extend(tasks.filter_map(|r| r.ok()));
+    Ok(SearchFacets { item_types, categories, statuses, priorities, months })
+}
 
-    // Search notes
-    let mut stmt = conn.prepare(
-        "SELECT id, title, content, created_at
-         FROM notes 
-         WHERE title LIKE ?1 
-         OR content LIKE ?1
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
+/// Searches the given scopes for free text, best matches first. Results found
+/// without the index (no query text, or text with nothing indexable) come
+/// newest first. `limit` of None returns every match from `offset` on.
+/// When the index finds fewer than `FUZZY_MIN_HITS` items, near misses of
+/// the query words are searched as well.
+pub fn search_index(
+    conn: &Connection,
+    text: &str,
+    scopes: &[SearchScope],
+    limit: Option<i64>,
+    offset: i64,
+) -> DbResult<SearchPage> {
+    search_text(conn, text, scopes, limit, offset, false)
+}
+
+/// Like `search_index`, with facet counts over every match
+pub fn search_faceted(
+    conn: &Connection,
+    text: &str,
+    scopes: &[SearchScope],
+    limit: Option<i64>,
+    offset: i64,
+) -> DbResult<SearchPage> {
+    search_text(conn, text, scopes, limit, offset, true)
+}
+
+fn search_text(
+    conn: &Connection,
+    text: &str,
+    scopes: &[SearchScope],
+    limit: Option<i64>,
+    offset: i64,
+    with_facets: bool,
+) -> DbResult<SearchPage> {
+    let text_match = TextMatch::from_query(text);
+    let page = run_search(conn, &text_match, scopes, "RELEVANCE", limit, offset, with_facets)?;
+    if page.total >= FUZZY_MIN_HITS || !matches!(text_match, TextMatch::Index(_)) {
+        return Ok(page);
+    }
+
+    Ok(search_fuzzy(conn, text, scopes, limit, offset, with_facets)?.unwrap_or(page))
+}
 
-    let notes = stmt.query_map([&query_pattern], |row| {
+pub fn search_matching(
+    conn: &Connection,
+    text: &TextMatch,
+    scopes: &[SearchScope],
+    limit: Option<i64>,
+    offset: i64,
+) -> DbResult<SearchPage> {
+    search_sorted(conn, text, scopes, "RELEVANCE", limit, offset)
+}
+
+/// Like `search_matching`, ordered by one of `SORT_ORDERS`
+pub fn search_sorted(
+    conn: &Connection,
+    text: &TextMatch,
+    scopes: &[SearchScope],
+    sort: &str,
+    limit: Option<i64>,
+    offset: i64,
+) -> DbResult<SearchPage> {
+    run_search(conn, text, scopes, sort, limit, offset, false)
+}
+
+fn run_search(
+    conn: &Connection,
+    text: &TextMatch,
+    scopes: &[SearchScope],
+    sort: &str,
+    limit: Option<i64>,
+    offset: i64,
+    with_facets: bool,
+) -> DbResult<SearchPage> {
+    let order = SORT_ORDERS.iter()
+        .find(|(name, _)| *name == sort)
+        .map(|(_, order)| *order)
+        .ok_or_else(|| DatabaseError::Data(format!("Invalid sort order: {}", sort)))?;
+
+    if scopes.is_empty() {
+        let facets = with_facets.then(SearchFacets::default);
+        return Ok(SearchPage { facets, ..SearchPage::empty(limit, offset) });
+    }
+
+    let mut params = Vec::new();
+    let selects = scopes.iter()
+        .map(|scope| scope_sql(scope, text, &mut params))
+        .collect::<DbResult<Vec<_>>>()?;
+    let union = selects.join(" UNION ALL ");
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM ({})", union),
+        rusqlite::params_from_iter(params.iter()),
+        |row| row.get(0),
+    )?;
+
+    let facets = if with_facets {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT item_type, category_id, status, priority, substr(date, 1, 7), COUNT(*)
+             FROM ({}) GROUP BY 1, 2, 3, 4, 5",
+            union
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            Ok(FacetRow {
+                item_type: row.get(0)?,
+                category_id: row.get(1)?,
+                status: row.get(2)?,
+                priority: row.get(3)?,
+                month: row.get(4)?,
+                count: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+        Some(build_facets(conn, rows)?)
+    } else {
+        None
+    };
+
+    let sql = format!(
+        "SELECT item_type, id, title, description, date, category_id, priority, status, rank, snippet
+         FROM ({}) ORDER BY {} LIMIT ? OFFSET ?",
+        union, order
+    );
+    params.push(Value::Integer(limit.unwrap_or(-1)));
+    params.push(Value::Integer(offset.max(0)));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let results = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
         Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: Some(row.get(2)?),
-            item_type: "NOTE".to_string(),
-            date: Some(row.get(3)?),
-            category_id: None,
-            priority: None,
-            status: None,
+            item_type: row.get(0)?,
+            id: row.get(1)?,
+            title: row.get(2)?,
+            description: row.get(3)?,
+            date: row.get(4)?,
+            category_id: row.get(5)?,
+            priority: row.get(6)?,
+            status: row.get(7)?,
+            rank: row.get(8)?,
+            snippet: row.get(9)?,
+            match_quality: 1.0,
         })
-    }).map_err(|e| e.to_string())?;
+    })?.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SearchPage { results, total, limit, offset: offset.max(0), fuzzy: false, facets })
+}
+
+#[tauri::command]
+pub async fn search_all(
+    query: String,
+    db: State<'_, Database>
+) -> Result<SearchPage, String> {
+    if query.trim().is_empty() {
+        return Ok(SearchPage { facets: Some(SearchFacets::default()), ..SearchPage::empty(None, 0) });
+    }
+
+    search_faceted(db.get_connection(), &query, &SearchScope::all(), None, 0)
+        .map_err(|e| e.to_string())
+}
 
-    results.extend(notes.filter_map(|r| r.ok()));
+#[tauri::command]
+pub async fn search_paged(
+    query: String,
+    item_types: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    db: State<'_, Database>
+) -> Result<SearchPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = offset.unwrap_or(0);
+    if query.trim().is_empty() {
+        return Ok(SearchPage::empty(Some(limit), offset));
+    }
+
+    let scopes = match item_types {
+        Some(types) => types.iter().map(|item_type| SearchScope::new(item_type)).collect(),
+        None => SearchScope::all(),
+    };
+    let scopes = scopes.into_iter().map(|scope| with_tags(scope, tags.clone())).collect::<Vec<_>>();
 
-    Ok(results)
+    search_index(db.get_connection(), &query, &scopes, Some(limit), offset)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -105,55 +783,32 @@ pub async fn search_events(
     start_date: Option<String>,
     end_date: Option<String>,
     category_id: Option<i64>,
+    tags: Option<Vec<String>>,
     db: State<'_, Database>
 ) -> Result<Vec<SearchResult>, String> {
-    let conn = db.get_connection();
-    let query_pattern = format!("%{}%", query);
-    let mut params: Vec<String> = vec![query_pattern];
-    
-    let mut sql = String::from(
-        "SELECT id, title, description, start_time, category_id, priority
-         FROM events 
-         WHERE (title LIKE ?1 OR description LIKE ?1 OR location LIKE ?1)"
-    );
+    let mut scope = SearchScope::new("EVENT");
 
     if let Some(start) = start_date {
-        sql.push_str(" AND start_time >= ?");
-        params.push(start);
+        scope = scope.filter("x.start_time >= ?", start);
     }
 
     if let Some(end) = end_date {
-        sql.push_str(" AND start_time <= ?");
-        params.push(end);
+        scope = scope.filter("x.start_time <= ?", end);
     }
 
     if let Some(cat_id) = category_id {
-        sql.push_str(" AND category_id = ?");
-        params.push(cat_id.to_string());
+        scope = scope.filter("x.category_id = ?", cat_id);
     }
 
-    sql.push_str(" ORDER BY start_time DESC");
-
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
-    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p as _).collect();
-
-    let results = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-        Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: row.get(2)?,
-            item_type: "EVENT".to_string(),
-            date: Some(row.get(3)?),
-            category_id: row.get(4)?,
-            priority: Some(row.get(5)?),
-            status: None,
-        })
-    }).map_err(|e| e.to_string())?;
+    let scope = with_tags(scope, tags);
 
-    Ok(results.filter_map(|r| r.ok()).collect())
+    let page = search_index(db.get_connection(), &query, &[scope], None, 0)
+        .map_err(|e| e.to_string())?;
+    Ok(page.results)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_tasks(
     query: String,
     due_date_start: Option<String>,
@@ -161,92 +816,46 @@ pub async fn search_tasks(
     category_id: Option<i64>,
     status: Option<String>,
     priority: Option<i32>,
+    tags: Option<Vec<String>>,
     db: State<'_, Database>
 ) -> Result<Vec<SearchResult>, String> {
-    let conn = db.get_connection();
-    let query_pattern = format!("%{}%", query);
-    let mut params: Vec<String> = vec![query_pattern];
-    
-    let mut sql = String::from(
-        "SELECT id, title, description, due_date, category_id, priority, status
-         FROM tasks 
-         WHERE (title LIKE ?1 OR description LIKE ?1)"
-    );
+    let mut scope = SearchScope::new("TASK");
 
     if let Some(start) = due_date_start {
-        sql.push_str(" AND due_date >= ?");
-        params.push(start);
+        scope = scope.filter("x.due_date >= ?", start);
     }
 
     if let Some(end) = due_date_end {
-        sql.push_str(" AND due_date <= ?");
-        params.push(end);
+        scope = scope.filter("x.due_date <= ?", end);
     }
 
     if let Some(cat_id) = category_id {
-        sql.push_str(" AND category_id = ?");
-        params.push(cat_id.to_string());
+        scope = scope.filter("x.category_id = ?", cat_id);
     }
 
     if let Some(stat) = status {
-        sql.push_str(" AND status = ?");
-        params.push(stat);
+        scope = scope.filter("x.status = ?", stat);
     }
 
     if let Some(prio) = priority {
-        sql.push_str(" AND priority = ?");
-        params.push(prio.to_string());
+        scope = scope.filter("x.priority = ?", prio);
     }
 
-    sql.push_str(" ORDER BY due_date DESC");
-
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
-    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p as _).collect();
+    let scope = with_tags(scope, tags);
 
-    let results = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-        Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: row.get(2)?,
-            item_type: "TASK".to_string(),
-            date: row.get(3)?,
-            category_id: row.get(4)?,
-            priority: Some(row.get(5)?),
-            status: Some(row.get(6)?),
-        })
-    }).map_err(|e| e.to_string())?;
-
-    Ok(results.filter_map(|r| r.ok()).collect())
+    let page = search_index(db.get_connection(), &query, &[scope], None, 0)
+        .map_err(|e| e.to_string())?;
+    Ok(page.results)
 }
 
 #[tauri::command]
 pub async fn search_notes(
     query: String,
+    tags: Option<Vec<String>>,
     db: State<'_, Database>
 ) -> Result<Vec<SearchResult>, String> {
-    let conn = db.get_connection();
-    let query_pattern = format!("%{}%", query);
-
-    let mut stmt = conn.prepare(
-        "SELECT id, title, content, created_at
-         FROM notes 
-         WHERE title LIKE ?1 
-         OR content LIKE ?1
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
-
-    let results = stmt.query_map([query_pattern], |row| {
-        Ok(SearchResult {
-            id: row.get(0)?,
-            title: row.get(1)?,
-            description: Some(row.get(2)?),
-            item_type: "NOTE".to_string(),
-            date: Some(row.get(3)?),
-            category_id: None,
-            priority: None,
-            status: None,
-        })
-    }).map_err(|e| e.to_string())?;
-
-    Ok(results.filter_map(|r| r.ok()).collect())
+    let scope = with_tags(SearchScope::new("NOTE"), tags);
+    let page = search_index(db.get_connection(), &query, &[scope], None, 0)
+        .map_err(|e| e.to_string())?;
+    Ok(page.results)
 }
@@ -0,0 +1,64 @@
+use crate::db::{Database, error::DbResult};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+    pub updated_at: Option<String>,
+}
+
+pub fn read_setting(conn: &Connection, key: &str) -> DbResult<Option<String>> {
+    let value = conn.query_row(
+        "SELECT value FROM settings WHERE key = ?",
+        [key],
+        |row| row.get(0)
+    ).optional()?;
+
+    Ok(value)
+}
+
+pub fn write_setting(conn: &Connection, key: &str, value: &str) -> DbResult<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_settings(db: State<'_, Database>) -> Result<Vec<Setting>, String> {
+    let conn = db.get_connection();
+
+    let mut stmt = conn.prepare(
+        "SELECT key, value, updated_at FROM settings ORDER BY key"
+    ).map_err(|e| e.to_string())?;
+
+    let settings = stmt.query_map([], |row| {
+        Ok(Setting {
+            key: row.get(0)?,
+            value: row.get(1)?,
+            updated_at: row.get(2)?,
+        })
+    })
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_setting(key: String, db: State<'_, Database>) -> Result<Option<String>, String> {
+    read_setting(db.get_connection(), &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_setting(key: String, value: String, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Update setting").map_err(|e| e.to_string())?;
+    write_setting(db.get_connection(), &key, &value).map_err(|e| e.to_string())
+}
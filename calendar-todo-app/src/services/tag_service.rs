@@ -0,0 +1,310 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Tag {
+    pub id: Option<i64>,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// How many items carry a tag, per item type
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TagUsage {
+    pub tag: Tag,
+    pub events: i64,
+    pub tasks: i64,
+    pub notes: i64,
+    pub total: i64,
+}
+
+/// The table an item type is stored in and the table linking it to tags
+pub struct TagLink {
+    pub item_type: &'static str,
+    pub table: &'static str,
+    pub link_table: &'static str,
+    pub item_column: &'static str,
+}
+
+pub const TAG_LINKS: &[TagLink] = &[
+    TagLink { item_type: "EVENT", table: "events", link_table: "event_tags", item_column: "event_id" },
+    TagLink { item_type: "TASK", table: "tasks", link_table: "task_tags", item_column: "task_id" },
+    TagLink { item_type: "NOTE", table: "notes", link_table: "note_tags", item_column: "note_id" },
+];
+
+const TAG_COLUMNS: &str = "id, name, color, created_at";
+
+impl Tag {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Tag {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            color: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+}
+
+pub fn tag_link(item_type: &str) -> DbResult<&'static TagLink> {
+    TAG_LINKS.iter()
+        .find(|link| link.item_type == item_type)
+        .ok_or_else(|| DatabaseError::Data(format!("Items of type {} cannot be tagged", item_type)))
+}
+
+/// Search condition on an item table aliased `x`, holding when the item
+/// carries the tag whose name is bound to it
+pub fn tag_condition(item_type: &str) -> Option<String> {
+    let link = tag_link(item_type).ok()?;
+    Some(format!(
+        "x.id IN (SELECT l.{column} FROM {table} l JOIN tags t ON t.id = l.tag_id WHERE t.name = ? COLLATE NOCASE)",
+        column = link.item_column,
+        table = link.link_table
+    ))
+}
+
+fn tag_name(name: &str) -> DbResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DatabaseError::Data("Tag name must not be empty".to_string()));
+    }
+    Ok(name)
+}
+
+pub fn load_tags(conn: &Connection) -> DbResult<Vec<Tag>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM tags ORDER BY name COLLATE NOCASE", TAG_COLUMNS))?;
+    let tags = stmt.query_map([], Tag::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+pub fn load_tag(conn: &Connection, id: i64) -> DbResult<Tag> {
+    conn.query_row(&format!("SELECT {} FROM tags WHERE id = ?1", TAG_COLUMNS), [id], Tag::from_row)
+        .optional()?
+        .ok_or_else(|| DatabaseError::Data(format!("Tag {} not found", id)))
+}
+
+/// Tag names are unique regardless of case
+pub fn find_tag(conn: &Connection, name: &str) -> DbResult<Option<Tag>> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM tags WHERE name = ?1 COLLATE NOCASE", TAG_COLUMNS),
+        [name.trim()],
+        Tag::from_row,
+    ).optional()?)
+}
+
+pub fn insert_tag(conn: &Connection, tag: &Tag) -> DbResult<i64> {
+    let name = tag_name(&tag.name)?;
+    if find_tag(conn, name)?.is_some() {
+        return Err(DatabaseError::Data(format!("Tag '{}' already exists", name)));
+    }
+    conn.execute("INSERT INTO tags (name, color) VALUES (?1, ?2)", params![name, tag.color])?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Saves a tag's name and color. Renaming it to the name of another tag
+/// merges the two; returns the ID of the tag that remains.
+pub fn modify_tag(conn: &Connection, tag: &Tag) -> DbResult<i64> {
+    let id = tag.id.ok_or_else(|| DatabaseError::Data("Tag ID is required".to_string()))?;
+    let name = tag_name(&tag.name)?;
+    load_tag(conn, id)?;
+
+    match find_tag(conn, name)? {
+        Some(Tag { id: Some(existing), .. }) if existing != id => {
+            merge_into_tag(conn, &[id], existing)?;
+            Ok(existing)
+        }
+        _ => {
+            conn.execute("UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3", params![name, tag.color, id])?;
+            Ok(id)
+        }
+    }
+}
+
+pub fn remove_tag(conn: &Connection, id: i64) -> DbResult<()> {
+    conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Moves every link of `source_ids` over to `target_id` and deletes the
+/// sources. Items carrying several of them end up with the target once.
+pub fn merge_into_tag(conn: &Connection, source_ids: &[i64], target_id: i64) -> DbResult<()> {
+    load_tag(conn, target_id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    for &source_id in source_ids.iter().filter(|&&id| id != target_id) {
+        load_tag(conn, source_id)?;
+        for link in TAG_LINKS {
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {table} ({column}, tag_id)
+                     SELECT {column}, ?1 FROM {table} WHERE tag_id = ?2",
+                    table = link.link_table,
+                    column = link.item_column
+                ),
+                params![target_id, source_id],
+            )?;
+        }
+        tx.execute("DELETE FROM tags WHERE id = ?1", [source_id])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn load_item_tags(conn: &Connection, item_type: &str, item_id: i64) -> DbResult<Vec<Tag>> {
+    let link = tag_link(item_type)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT t.id, t.name, t.color, t.created_at FROM tags t
+         JOIN {table} l ON l.tag_id = t.id
+         WHERE l.{column} = ?1
+         ORDER BY t.name COLLATE NOCASE",
+        table = link.link_table,
+        column = link.item_column
+    ))?;
+    let tags = stmt.query_map([item_id], Tag::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+pub fn add_item_tag(conn: &Connection, item_type: &str, item_id: i64, tag_id: i64) -> DbResult<()> {
+    let link = tag_link(item_type)?;
+    let exists: Option<i64> = conn.query_row(
+        &format!("SELECT id FROM {} WHERE id = ?1", link.table),
+        [item_id],
+        |row| row.get(0),
+    ).optional()?;
+    if exists.is_none() {
+        return Err(DatabaseError::Data(format!("{} {} not found", item_type, item_id)));
+    }
+    load_tag(conn, tag_id)?;
+
+    conn.execute(
+        &format!("INSERT OR IGNORE INTO {} ({}, tag_id) VALUES (?1, ?2)", link.link_table, link.item_column),
+        params![item_id, tag_id],
+    )?;
+    Ok(())
+}
+
+pub fn remove_item_tag(conn: &Connection, item_type: &str, item_id: i64, tag_id: i64) -> DbResult<()> {
+    let link = tag_link(item_type)?;
+    conn.execute(
+        &format!("DELETE FROM {} WHERE {} = ?1 AND tag_id = ?2", link.link_table, link.item_column),
+        params![item_id, tag_id],
+    )?;
+    Ok(())
+}
+
+/// Replaces an item's tags with the ones named, creating missing tags
+pub fn replace_item_tags(conn: &Connection, item_type: &str, item_id: i64, names: &[String]) -> DbResult<Vec<Tag>> {
+    let link = tag_link(item_type)?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(&format!("DELETE FROM {} WHERE {} = ?1", link.link_table, link.item_column), [item_id])?;
+    for name in names {
+        let tag_id = match find_tag(conn, name)? {
+            Some(tag) => tag.id.unwrap_or_default(),
+            None => insert_tag(conn, &Tag { id: None, name: name.clone(), color: None, created_at: None })?,
+        };
+        add_item_tag(conn, item_type, item_id, tag_id)?;
+    }
+    tx.commit()?;
+
+    load_item_tags(conn, item_type, item_id)
+}
+
+/// Every tag with the number of items carrying it, most used first. Trashed
+/// items and archived tasks are not counted
+pub fn load_tag_usage(conn: &Connection) -> DbResult<Vec<TagUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.color, t.created_at,
+                (SELECT COUNT(*) FROM event_tags et JOIN events e ON e.id = et.event_id
+                 WHERE et.tag_id = t.id AND e.deleted_at IS NULL),
+                (SELECT COUNT(*) FROM task_tags tt JOIN tasks k ON k.id = tt.task_id
+                 WHERE tt.tag_id = t.id AND k.deleted_at IS NULL AND k.archived_at IS NULL),
+                (SELECT COUNT(*) FROM note_tags nt JOIN notes n ON n.id = nt.note_id
+                 WHERE nt.tag_id = t.id AND n.deleted_at IS NULL)
+         FROM tags t"
+    )?;
+    let mut usage = stmt.query_map([], |row| {
+        let (events, tasks, notes) = (row.get(4)?, row.get(5)?, row.get(6)?);
+        Ok(TagUsage { tag: Tag::from_row(row)?, events, tasks, notes, total: events + tasks + notes })
+    })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    usage.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.tag.name.to_lowercase().cmp(&b.tag.name.to_lowercase())));
+    Ok(usage)
+}
+
+#[tauri::command]
+pub async fn get_tags(db: State<'_, Database>) -> Result<Vec<Tag>, String> {
+    load_tags(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_tag(tag: Tag, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Create tag").map_err(|e| e.to_string())?;
+    insert_tag(db.get_connection(), &tag).map_err(|e| e.to_string())
+}
+
+/// Returns the ID of the tag that remains, which differs from `tag.id` when
+/// the new name belonged to another tag and the two were merged
+#[tauri::command]
+pub async fn update_tag(tag: Tag, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Update tag").map_err(|e| e.to_string())?;
+    modify_tag(db.get_connection(), &tag).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_tag(id: i64, name: String, db: State<'_, Database>) -> Result<i64, String> {
+    let _undo = db.undo_step("Rename tag").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let tag = load_tag(conn, id).map_err(|e| e.to_string())?;
+    modify_tag(conn, &Tag { name, ..tag }).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn merge_tags(source_ids: Vec<i64>, target_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Merge tags").map_err(|e| e.to_string())?;
+    merge_into_tag(db.get_connection(), &source_ids, target_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_tag(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete tag").map_err(|e| e.to_string())?;
+    remove_tag(db.get_connection(), id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_item_tags(item_type: String, item_id: i64, db: State<'_, Database>) -> Result<Vec<Tag>, String> {
+    load_item_tags(db.get_connection(), &item_type, item_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn tag_item(item_type: String, item_id: i64, tag_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Tag item").map_err(|e| e.to_string())?;
+    add_item_tag(db.get_connection(), &item_type, item_id, tag_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn untag_item(item_type: String, item_id: i64, tag_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Untag item").map_err(|e| e.to_string())?;
+    remove_item_tag(db.get_connection(), &item_type, item_id, tag_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_item_tags(
+    item_type: String,
+    item_id: i64,
+    names: Vec<String>,
+    db: State<'_, Database>
+) -> Result<Vec<Tag>, String> {
+    let _undo = db.undo_step("Set item tags").map_err(|e| e.to_string())?;
+    replace_item_tags(db.get_connection(), &item_type, item_id, &names).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tag_usage(db: State<'_, Database>) -> Result<Vec<TagUsage>, String> {
+    load_tag_usage(db.get_connection()).map_err(|e| e.to_string())
+}
@@ -1,4 +1,5 @@
-use crate::db::{Database, models::Task, error::DbResult};
+use crate::db::{Database, models::{BulkResult, ChecklistItem, Task, TaskCompletion, TaskNode, TaskProgress, TaskStatusChange, TaskStreak}, error::{CommandError, DbResult}, operations::BLOCKED_CONDITION};
+use rusqlite::{params, Connection};
 use serde::{Serialize, Deserialize};
 use tauri::State;
 
@@ -15,43 +16,93 @@ pub struct TaskResponse {
     pub kanban_column_id: Option<i64>,
     pub kanban_order: Option<i32>,
     pub completed_at: Option<String>,
+    /// Some task it depends on is not done yet
+    pub blocked: bool,
+    /// Hidden from the active lists until then
+    pub start_date: Option<String>,
+    pub is_someday: bool,
+}
+
+impl TaskResponse {
+    fn from_row(row: &rusqlite::Row) -> DbResult<Self> {
+        let task = Task::from_row(row)?;
+        Ok(TaskResponse {
+            id: task.id.unwrap_or_default(),
+            title: task.title,
+            description: task.description,
+            due_date: task.due_date,
+            priority: task.priority,
+            status: task.status,
+            category_id: task.category_id,
+            recurring_rule_id: task.recurring_rule_id,
+            kanban_column_id: task.kanban_column_id,
+            kanban_order: task.kanban_order,
+            completed_at: task.completed_at,
+            blocked: row.get(13)?,
+            start_date: row.get(14)?,
+            is_someday: row.get(15)?,
+        })
+    }
+}
+
+/// Tasks due between `start` and `end` that are not someday and have started
+/// by `end`, leaving out trashed and archived ones. Undated tasks have no
+/// place in a range, they are only included when asked for.
+pub fn load_tasks_in_range(conn: &Connection, start: &str, end: &str, include_undated: bool) -> DbResult<Vec<TaskResponse>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, title, description, due_date, priority, status, category_id,
+                recurring_rule_id, kanban_column_id, kanban_order, completed_at,
+                created_at, updated_at, {} AS blocked, start_date, is_someday
+         FROM tasks 
+         WHERE deleted_at IS NULL AND archived_at IS NULL AND is_someday = 0
+           AND (start_date IS NULL OR datetime(start_date) <= datetime(?2))
+           AND ((datetime(due_date) BETWEEN datetime(?1) AND datetime(?2)) OR (?3 AND due_date IS NULL))",
+        BLOCKED_CONDITION
+    ))?;
+
+    let tasks = stmt
+        .query_and_then(params![start, end, include_undated], TaskResponse::from_row)?
+        .collect::<DbResult<Vec<_>>>()?;
+    Ok(tasks)
 }
 
 #[tauri::command]
 pub async fn get_tasks_in_range(
     start: String,
     end: String,
+    include_undated: Option<bool>,
     db: State<'_, Database>,
 ) -> Result<Vec<TaskResponse>, String> {
-    let conn = db.get_connection();
-    
-    let mut stmt = conn.prepare(
+    load_tasks_in_range(db.get_connection(), &start, &end, include_undated.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Tasks placed on a board, column by column, leaving out trashed and
+/// archived ones
+pub fn load_board_tasks(conn: &Connection, board_id: i64) -> DbResult<Vec<TaskResponse>> {
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, title, description, due_date, priority, status, category_id,
                 recurring_rule_id, kanban_column_id, kanban_order, completed_at,
-                created_at, updated_at
+                created_at, updated_at, {} AS blocked, start_date, is_someday
          FROM tasks 
-         WHERE (due_date BETWEEN ?1 AND ?2) OR (due_date IS NULL)"
-    ).map_err(|e| e.to_string())?;
-    
+         WHERE deleted_at IS NULL AND archived_at IS NULL
+           AND kanban_column_id IN (SELECT id FROM kanban_columns WHERE board_id = ?1)
+         ORDER BY (SELECT column_order FROM kanban_columns WHERE id = kanban_column_id), kanban_order ASC",
+        BLOCKED_CONDITION
+    ))?;
+
     let tasks = stmt
-        .query_map([start, end], |row| Task::from_row(&row))
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        .query_and_then([board_id], TaskResponse::from_row)?
+        .collect::<DbResult<Vec<_>>>()?;
+    Ok(tasks)
+}
 
-    Ok(tasks.into_iter().map(|task| TaskResponse {
-        id: task.id.unwrap_or_default(),
-        title: task.title,
-        description: task.description,
-        due_date: task.due_date,
-        priority: task.priority,
-        status: task.status,
-        category_id: task.category_id,
-        recurring_rule_id: task.recurring_rule_id,
-        kanban_column_id: task.kanban_column_id,
-        kanban_order: task.kanban_order,
-        completed_at: task.completed_at,
-    }).collect())
+#[tauri::command]
+pub async fn get_board_tasks(
+    board_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<TaskResponse>, String> {
+    load_board_tasks(db.get_connection(), board_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -61,34 +112,23 @@ pub async fn get_tasks_by_status(
 ) -> Result<Vec<TaskResponse>, String> {
     let conn = db.get_connection();
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, title, description, due_date, priority, status, category_id,
                 recurring_rule_id, kanban_column_id, kanban_order, completed_at,
-                created_at, updated_at
+                created_at, updated_at, {} AS blocked, start_date, is_someday
          FROM tasks 
-         WHERE status = ?
-         ORDER BY kanban_order ASC"
-    ).map_err(|e| e.to_string())?;
+         WHERE status = ? AND deleted_at IS NULL AND archived_at IS NULL
+         ORDER BY kanban_order ASC",
+        BLOCKED_CONDITION
+    )).map_err(|e| e.to_string())?;
     
     let tasks = stmt
-        .query_map([status], |row| Task::from_row(&row))
+        .query_and_then([status], TaskResponse::from_row)
         .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
+        .collect::<DbResult<Vec<_>>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(tasks.into_iter().map(|task| TaskResponse {
-        id: task.id.unwrap_or_default(),
-        title: task.title,
-        description: task.description,
-        due_date: task.due_date,
-        priority: task.priority,
-        status: task.status,
-        category_id: task.category_id,
-        recurring_rule_id: task.recurring_rule_id,
-        kanban_column_id: task.kanban_column_id,
-        kanban_order: task.kanban_order,
-        completed_at: task.completed_at,
-    }).collect())
+    Ok(tasks)
 }
 
 #[tauri::command]
@@ -97,55 +137,35 @@ pub async fn get_tasks(
 ) -> Result<Vec<TaskResponse>, String> {
     let conn = db.get_connection();
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, title, description, due_date, priority, status, category_id,
                 recurring_rule_id, kanban_column_id, kanban_order, completed_at,
-                created_at, updated_at
+                created_at, updated_at, {} AS blocked, start_date, is_someday
          FROM tasks 
-         ORDER BY kanban_order ASC"
-    ).map_err(|e| e.to_string())?;
+         WHERE deleted_at IS NULL AND archived_at IS NULL
+         ORDER BY kanban_order ASC",
+        BLOCKED_CONDITION
+    )).map_err(|e| e.to_string())?;
     
     let tasks = stmt
-        .query_map([], |row| Task::from_row(&row))
+        .query_and_then([], TaskResponse::from_row)
         .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
+        .collect::<DbResult<Vec<_>>>()
         .map_err(|e| e.to_string())?;
 
-    Ok(tasks.into_iter().map(|task| TaskResponse {
-        id: task.id.unwrap_or_default(),
-        title: task.title,
-        description: task.description,
-        due_date: task.due_date,
-        priority: task.priority,
-        status: task.status,
-        category_id: task.category_id,
-        recurring_rule_id: task.recurring_rule_id,
-        kanban_column_id: task.kanban_column_id,
-        kanban_order: task.kanban_order,
-        completed_at: task.completed_at,
-    }).collect())
+    Ok(tasks)
 }
 
+/// Returns the parent tasks completed along with this one and the next
+/// instances of the recurring ones
 #[tauri::command]
 pub async fn update_task_status(
     id: i64,
     status: String,
     db: State<'_, Database>,
-) -> Result<(), String> {
-    let conn = db.get_connection();
-    
-    let completed_at = if status == "COMPLETED" {
-        "datetime('now')"
-    } else {
-        "NULL"
-    };
-    
-    conn.execute(
-        &format!("UPDATE tasks SET status = ?, completed_at = {} WHERE id = ?", completed_at),
-        [&status, &id.to_string()]
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(())
+) -> Result<TaskStatusChange, String> {
+    let _undo = db.undo_step("Update task status").map_err(|e| e.to_string())?;
+    db.set_task_status(id, &status).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -153,6 +173,7 @@ pub async fn create_task(
     task: Task,
     db: State<'_, Database>,
 ) -> Result<i64, String> {
+    let _undo = db.undo_step("Create task").map_err(|e| e.to_string())?;
     db.create_task(&task).map_err(|e| e.to_string())
 }
 
@@ -160,16 +181,21 @@ pub async fn create_task(
 pub async fn update_task(
     task: Task,
     db: State<'_, Database>,
-) -> Result<(), String> {
-    db.update_task(&task).map_err(|e| e.to_string())
+) -> Result<Task, CommandError> {
+    let _undo = db.undo_step("Update task")?;
+    Ok(db.update_task(&task)?)
 }
 
+/// `child_policy` is one of `CHILD_POLICIES`, "CASCADE" when not given
 #[tauri::command]
 pub async fn delete_task(
     id: i64,
+    child_policy: Option<String>,
     db: State<'_, Database>,
 ) -> Result<(), String> {
-    db.delete_task(id).map_err(|e| e.to_string())
+    let _undo = db.undo_step("Delete task").map_err(|e| e.to_string())?;
+    let child_policy = child_policy.unwrap_or_else(|| "CASCADE".to_string());
+    db.delete_task_with_subtasks(id, &child_policy).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -179,21 +205,285 @@ pub async fn reorder_task(
     new_order: i32,
     db: State<'_, Database>,
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Reorder task").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
+
+    let (starts_work, completes) = db.kanban_column_effect(new_column_id).map_err(|e| e.to_string())?;
+    if starts_work {
+        let blocking = db.get_blocking_tasks(task_id).map_err(|e| e.to_string())?;
+        if !blocking.is_empty() {
+            let titles: Vec<String> = blocking.into_iter().map(|t| t.title).collect();
+            return Err(format!("Task is blocked by: {}", titles.join(", ")));
+        }
+    }
     
     conn.execute(
         "UPDATE tasks 
-         SET kanban_column_id = ?, kanban_order = ?, status = CASE
-             WHEN ? = (SELECT id FROM kanban_columns WHERE name = 'Completed') THEN 'COMPLETED'
+         SET kanban_column_id = ?1, kanban_order = ?2, status = CASE
+             WHEN ?3 THEN 'COMPLETED'
              ELSE 'IN_PROGRESS'
          END,
          completed_at = CASE
-             WHEN ? = (SELECT id FROM kanban_columns WHERE name = 'Completed') THEN datetime('now')
+             WHEN ?3 THEN datetime('now')
              ELSE NULL
          END
-         WHERE id = ?",
-        [new_column_id, new_order, new_column_id, new_column_id, task_id]
+         WHERE id = ?4",
+        params![new_column_id, new_order, completes, task_id]
     ).map_err(|e| e.to_string())?;
     
     Ok(())
 }
+
+#[tauri::command]
+pub async fn add_task_dependency(
+    task_id: i64,
+    depends_on_task_id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Add task dependency").map_err(|e| e.to_string())?;
+    db.add_task_dependency(task_id, depends_on_task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_task_dependency(
+    task_id: i64,
+    depends_on_task_id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Remove task dependency").map_err(|e| e.to_string())?;
+    db.remove_task_dependency(task_id, depends_on_task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_dependencies(
+    task_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<Task>, String> {
+    db.get_task_dependencies(task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_dependent_tasks(
+    task_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<Task>, String> {
+    db.get_dependent_tasks(task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_subtask(
+    parent_id: i64,
+    task: Task,
+    db: State<'_, Database>,
+) -> Result<i64, String> {
+    let _undo = db.undo_step("Create subtask").map_err(|e| e.to_string())?;
+    db.create_subtask(parent_id, &task).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_subtasks(
+    parent_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<Task>, String> {
+    db.get_subtasks(parent_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_tree(
+    id: i64,
+    db: State<'_, Database>,
+) -> Result<TaskNode, String> {
+    db.get_task_tree(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_progress(
+    id: i64,
+    db: State<'_, Database>,
+) -> Result<TaskProgress, String> {
+    db.get_task_progress(id).map_err(|e| e.to_string())
+}
+
+/// Moves a task under another one, or to the top level when `new_parent_id`
+/// is None. `child_policy` is one of `CHILD_POLICIES`, "CASCADE" when not given.
+#[tauri::command]
+pub async fn move_task(
+    id: i64,
+    new_parent_id: Option<i64>,
+    position: Option<i64>,
+    child_policy: Option<String>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Move task").map_err(|e| e.to_string())?;
+    let child_policy = child_policy.unwrap_or_else(|| "CASCADE".to_string());
+    db.move_task(id, new_parent_id, position, &child_policy).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_checklist_item(
+    task_id: i64,
+    title: String,
+    db: State<'_, Database>,
+) -> Result<i64, String> {
+    let _undo = db.undo_step("Add checklist item").map_err(|e| e.to_string())?;
+    db.add_checklist_item(task_id, &title).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_checklist_items(
+    task_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<ChecklistItem>, String> {
+    db.get_checklist_items(task_id).map_err(|e| e.to_string())
+}
+
+/// Returns the parent tasks completed by checking the item
+#[tauri::command]
+pub async fn update_checklist_item(
+    item: ChecklistItem,
+    db: State<'_, Database>,
+) -> Result<TaskStatusChange, String> {
+    let _undo = db.undo_step("Update checklist item").map_err(|e| e.to_string())?;
+    db.update_checklist_item(&item).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reorder_checklist_item(
+    id: i64,
+    position: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Reorder checklist item").map_err(|e| e.to_string())?;
+    db.reorder_checklist_item(id, position).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_checklist_item(
+    id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Delete checklist item").map_err(|e| e.to_string())?;
+    db.delete_checklist_item(id).map_err(|e| e.to_string())
+}
+
+/// `mode` is one of `RECURRENCE_MODES`, "FIXED" when not given
+#[tauri::command]
+pub async fn set_task_recurrence(
+    task_id: i64,
+    rule_id: Option<i64>,
+    mode: Option<String>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Set task recurrence").map_err(|e| e.to_string())?;
+    let mode = mode.unwrap_or_else(|| "FIXED".to_string());
+    db.set_task_recurrence(task_id, rule_id, &mode).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_completions(
+    task_id: i64,
+    db: State<'_, Database>,
+) -> Result<Vec<TaskCompletion>, String> {
+    db.get_task_completions(task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_streak(
+    task_id: i64,
+    db: State<'_, Database>,
+) -> Result<TaskStreak, String> {
+    db.get_task_streak(task_id).map_err(|e| e.to_string())
+}
+
+/// Snoozes a task until `until`, or brings it back now when None
+#[tauri::command]
+pub async fn defer_task(
+    id: i64,
+    until: Option<String>,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Defer task").map_err(|e| e.to_string())?;
+    db.defer_task(id, until.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_task_someday(
+    id: i64,
+    someday: bool,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Set task someday").map_err(|e| e.to_string())?;
+    db.set_task_someday(id, someday).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn bulk_update_task_status(
+    ids: Vec<i64>,
+    status: String,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk update task status").map_err(|e| e.to_string())?;
+    db.bulk_set_task_status(&ids, &status).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn bulk_update_task_priority(
+    ids: Vec<i64>,
+    priority: i32,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk update task priority").map_err(|e| e.to_string())?;
+    db.bulk_set_task_priority(&ids, priority).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn bulk_update_task_category(
+    ids: Vec<i64>,
+    category_id: Option<i64>,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk update task category").map_err(|e| e.to_string())?;
+    db.bulk_set_task_category(&ids, category_id).map_err(|e| e.to_string())
+}
+
+/// Moves due dates by `days`, earlier when negative
+#[tauri::command]
+pub async fn bulk_shift_due_dates(
+    ids: Vec<i64>,
+    days: i64,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk shift due dates").map_err(|e| e.to_string())?;
+    db.bulk_shift_due_dates(&ids, days).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn bulk_move_tasks_to_column(
+    ids: Vec<i64>,
+    column_id: i64,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk move tasks to column").map_err(|e| e.to_string())?;
+    db.bulk_move_to_column(&ids, column_id).map_err(|e| e.to_string())
+}
+
+/// `child_policy` is one of `CHILD_POLICIES`, "CASCADE" when not given
+#[tauri::command]
+pub async fn bulk_delete_tasks(
+    ids: Vec<i64>,
+    child_policy: Option<String>,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk delete tasks").map_err(|e| e.to_string())?;
+    let child_policy = child_policy.unwrap_or_else(|| "CASCADE".to_string());
+    db.bulk_delete_tasks(&ids, &child_policy).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn bulk_archive_tasks(
+    ids: Vec<i64>,
+    db: State<'_, Database>,
+) -> Result<BulkResult, String> {
+    let _undo = db.undo_step("Bulk archive tasks").map_err(|e| e.to_string())?;
+    db.bulk_archive_tasks(&ids).map_err(|e| e.to_string())
+}
@@ -0,0 +1,358 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::settings_service::read_setting;
+use crate::services::time_report_service::{load_report_entries, split_by_day, week_start};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use tauri::{AppHandle, Manager, State};
+
+/// Usage levels, in percent of the estimate or budget, that raise an alert.
+pub const BUDGET_THRESHOLDS: [i64; 2] = [80, 100];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskEstimate {
+    pub task_id: i64,
+    pub title: String,
+    pub estimated_minutes: Option<i64>,
+    pub tracked_seconds: i64,
+    pub remaining_seconds: Option<i64>,
+    pub overrun_seconds: i64,
+    pub percent_used: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryBudget {
+    pub id: Option<i64>,
+    pub category_id: i64,
+    pub period: String, // "WEEKLY" or "MONTHLY"
+    pub budget_minutes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub category_id: i64,
+    pub category_name: String,
+    pub period: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub budget_seconds: i64,
+    pub tracked_seconds: i64,
+    pub remaining_seconds: i64,
+    pub overrun_seconds: i64,
+    pub percent_used: i64,
+}
+
+/// Payload of the `budget-threshold-crossed` event.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetAlert {
+    pub scope: String, // "TASK" or "CATEGORY"
+    pub scope_id: i64,
+    pub label: String,
+    pub period: String,
+    pub period_start: String,
+    pub threshold: i64,
+    pub tracked_seconds: i64,
+    pub budget_seconds: i64,
+}
+
+fn percent_used(tracked_seconds: i64, budget_seconds: i64) -> i64 {
+    if budget_seconds > 0 { tracked_seconds * 100 / budget_seconds } else { 0 }
+}
+
+pub fn write_task_estimate(conn: &Connection, task_id: i64, estimated_minutes: Option<i64>) -> Result<(), String> {
+    if estimated_minutes.is_some_and(|minutes| minutes <= 0) {
+        return Err("Estimate must be a positive number of minutes".to_string());
+    }
+
+    let updated = conn.execute(
+        "UPDATE tasks SET estimated_minutes = ?1 WHERE id = ?2",
+        rusqlite::params![estimated_minutes, task_id],
+    ).map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Task {} not found", task_id));
+    }
+
+    // A new estimate starts a fresh set of alerts
+    conn.execute(
+        "DELETE FROM budget_alerts WHERE scope = 'TASK' AND scope_id = ?1",
+        [task_id],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Estimate against finished time entries booked on the task.
+pub fn load_task_estimate(conn: &Connection, task_id: i64) -> DbResult<Option<TaskEstimate>> {
+    let row = conn.query_row(
+        "SELECT t.title, t.estimated_minutes,
+                (SELECT COALESCE(SUM(duration_seconds), 0) FROM time_tracking
+                 WHERE item_type = 'TASK' AND item_id = t.id AND end_time IS NOT NULL)
+         FROM tasks t WHERE t.id = ?1",
+        [task_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, i64>(2)?)),
+    ).optional()?;
+
+    Ok(row.map(|(title, estimated_minutes, tracked_seconds)| {
+        let budget_seconds = estimated_minutes.map(|minutes| minutes * 60);
+        TaskEstimate {
+            task_id,
+            title,
+            estimated_minutes,
+            tracked_seconds,
+            remaining_seconds: budget_seconds.map(|budget| (budget - tracked_seconds).max(0)),
+            overrun_seconds: budget_seconds.map_or(0, |budget| (tracked_seconds - budget).max(0)),
+            percent_used: budget_seconds.map(|budget| percent_used(tracked_seconds, budget)),
+        }
+    }))
+}
+
+fn estimated_task_ids(conn: &Connection) -> DbResult<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM tasks WHERE estimated_minutes IS NOT NULL ORDER BY id"
+    )?;
+    let ids = stmt.query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+pub fn load_task_estimates(conn: &Connection) -> DbResult<Vec<TaskEstimate>> {
+    let mut estimates = Vec::new();
+    for task_id in estimated_task_ids(conn)? {
+        estimates.extend(load_task_estimate(conn, task_id)?);
+    }
+    Ok(estimates)
+}
+
+pub fn write_category_budget(conn: &Connection, budget: &CategoryBudget) -> Result<i64, String> {
+    if budget.period != "WEEKLY" && budget.period != "MONTHLY" {
+        return Err(format!("Invalid budget period: {}", budget.period));
+    }
+    if budget.budget_minutes <= 0 {
+        return Err("Budget must be a positive number of minutes".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO category_budgets (category_id, period, budget_minutes) VALUES (?1, ?2, ?3)
+         ON CONFLICT(category_id, period) DO UPDATE SET budget_minutes = excluded.budget_minutes",
+        rusqlite::params![budget.category_id, budget.period, budget.budget_minutes],
+    ).map_err(|e| e.to_string())?;
+
+    // A new budget starts a fresh set of alerts
+    conn.execute(
+        "DELETE FROM budget_alerts WHERE scope = 'CATEGORY' AND scope_id = ?1 AND period = ?2",
+        rusqlite::params![budget.category_id, budget.period],
+    ).map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        "SELECT id FROM category_budgets WHERE category_id = ?1 AND period = ?2",
+        rusqlite::params![budget.category_id, budget.period],
+        |row| row.get(0),
+    ).map_err(|e| e.to_string())
+}
+
+pub fn remove_category_budget(conn: &Connection, category_id: i64, period: &str) -> DbResult<()> {
+    conn.execute(
+        "DELETE FROM category_budgets WHERE category_id = ?1 AND period = ?2",
+        rusqlite::params![category_id, period],
+    )?;
+    Ok(())
+}
+
+/// First and last day of the budget period containing `today`.
+pub fn budget_period(period: &str, today: NaiveDate, week_start_day: u32) -> DbResult<(NaiveDate, NaiveDate)> {
+    match period {
+        "WEEKLY" => {
+            let start = week_start(today, week_start_day);
+            Ok((start, start + Duration::days(6)))
+        }
+        "MONTHLY" => {
+            let start = today.with_day(1).unwrap_or(today);
+            let next = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            };
+            let end = next.map(|next| next - Duration::days(1)).unwrap_or(start);
+            Ok((start, end))
+        }
+        other => Err(DatabaseError::Data(format!("Invalid budget period: {}", other))),
+    }
+}
+
+/// Usage of every category budget in the period containing `today`. Entries
+/// are split at midnight so only the part inside the period counts.
+pub fn load_budget_statuses(conn: &Connection, today: NaiveDate) -> DbResult<Vec<BudgetStatus>> {
+    let week_start_day: u32 = read_setting(conn, "week_start_day")?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1);
+
+    let mut stmt = conn.prepare(
+        "SELECT b.category_id, c.name, b.period, b.budget_minutes
+         FROM category_budgets b
+         JOIN categories c ON c.id = b.category_id
+         ORDER BY c.name, b.period"
+    )?;
+    let budgets = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, i64>(3)?,
+        ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let mut statuses = Vec::with_capacity(budgets.len());
+    for (category_id, category_name, period, budget_minutes) in budgets {
+        let (start, end) = budget_period(&period, today, week_start_day)?;
+        let tracked_seconds: i64 = load_report_entries(conn, start, end)?
+            .iter()
+            .filter(|entry| entry.category_id == Some(category_id))
+            .flat_map(|entry| split_by_day(entry, start, end))
+            .map(|segment| segment.seconds)
+            .sum();
+        let budget_seconds = budget_minutes * 60;
+
+        statuses.push(BudgetStatus {
+            category_id,
+            category_name,
+            period,
+            period_start: start.format("%Y-%m-%d").to_string(),
+            period_end: end.format("%Y-%m-%d").to_string(),
+            budget_seconds,
+            tracked_seconds,
+            remaining_seconds: (budget_seconds - tracked_seconds).max(0),
+            overrun_seconds: (tracked_seconds - budget_seconds).max(0),
+            percent_used: percent_used(tracked_seconds, budget_seconds),
+        });
+    }
+
+    Ok(statuses)
+}
+
+/// Records a crossed threshold, returning false if it was already announced.
+fn record_alert(conn: &Connection, alert: &BudgetAlert) -> DbResult<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO budget_alerts (scope, scope_id, period, period_start, threshold)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![alert.scope, alert.scope_id, alert.period, alert.period_start, alert.threshold],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Thresholds newly crossed by task estimates and category budgets. Each one
+/// is returned once per estimate, or once per budget period.
+pub fn check_budget_alerts(conn: &Connection, today: NaiveDate) -> DbResult<Vec<BudgetAlert>> {
+    let mut alerts = Vec::new();
+
+    for estimate in load_task_estimates(conn)? {
+        let budget_seconds = estimate.estimated_minutes.unwrap_or_default() * 60;
+        for threshold in BUDGET_THRESHOLDS {
+            if estimate.tracked_seconds * 100 < budget_seconds * threshold {
+                continue;
+            }
+            let alert = BudgetAlert {
+                scope: "TASK".to_string(),
+                scope_id: estimate.task_id,
+                label: estimate.title.clone(),
+                period: "ESTIMATE".to_string(),
+                period_start: String::new(),
+                threshold,
+                tracked_seconds: estimate.tracked_seconds,
+                budget_seconds,
+            };
+            if record_alert(conn, &alert)? {
+                alerts.push(alert);
+            }
+        }
+    }
+
+    for status in load_budget_statuses(conn, today)? {
+        for threshold in BUDGET_THRESHOLDS {
+            if status.tracked_seconds * 100 < status.budget_seconds * threshold {
+                continue;
+            }
+            let alert = BudgetAlert {
+                scope: "CATEGORY".to_string(),
+                scope_id: status.category_id,
+                label: status.category_name.clone(),
+                period: status.period.clone(),
+                period_start: status.period_start.clone(),
+                threshold,
+                tracked_seconds: status.tracked_seconds,
+                budget_seconds: status.budget_seconds,
+            };
+            if record_alert(conn, &alert)? {
+                alerts.push(alert);
+            }
+        }
+    }
+
+    Ok(alerts)
+}
+
+/// Checks budgets after tracked time changed and emits `budget-threshold-crossed`
+/// for every new crossing. Failures are ignored so they never undo the edit.
+pub fn emit_budget_alerts(app: &AppHandle, conn: &Connection) {
+    if let Ok(alerts) = check_budget_alerts(conn, Local::now().date_naive()) {
+        for alert in alerts {
+            let _ = app.emit_all("budget-threshold-crossed", alert);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn update_task_estimate(
+    task_id: i64,
+    estimated_minutes: Option<i64>,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<Option<TaskEstimate>, String> {
+    let _undo = db.undo_step("Update task estimate").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+
+    write_task_estimate(conn, task_id, estimated_minutes)?;
+    emit_budget_alerts(&app, conn);
+
+    load_task_estimate(conn, task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_estimate(task_id: i64, db: State<'_, Database>) -> Result<Option<TaskEstimate>, String> {
+    load_task_estimate(db.get_connection(), task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_estimates(db: State<'_, Database>) -> Result<Vec<TaskEstimate>, String> {
+    load_task_estimates(db.get_connection()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_category_budget(
+    budget: CategoryBudget,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<i64, String> {
+    let _undo = db.undo_step("Update category budget").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+
+    let id = write_category_budget(conn, &budget)?;
+    emit_budget_alerts(&app, conn);
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn delete_category_budget(
+    category_id: i64,
+    period: String,
+    db: State<'_, Database>
+) -> Result<(), String> {
+    let _undo = db.undo_step("Delete category budget").map_err(|e| e.to_string())?;
+    remove_category_budget(db.get_connection(), category_id, &period).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_budget_statuses(db: State<'_, Database>) -> Result<Vec<BudgetStatus>, String> {
+    load_budget_statuses(db.get_connection(), Local::now().date_naive()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,353 @@
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::settings_service::read_setting;
+use crate::services::time_tracking_service::parse_timestamp;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use rusqlite::Connection;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashSet};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeReportQuery {
+    pub start_date: String, // YYYY-MM-DD, inclusive
+    pub end_date: String, // YYYY-MM-DD, inclusive
+    pub group_by: String, // "DAY", "WEEK", "MONTH", "TASK", "EVENT", "CATEGORY" or "TIMER_TYPE"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TimeReportRow {
+    pub key: String,
+    pub label: String,
+    pub total_seconds: i64,
+    pub entry_count: i64,
+    pub average_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub group_by: String,
+    pub rows: Vec<TimeReportRow>,
+    pub total_seconds: i64,
+    pub entry_count: i64,
+    pub average_seconds: i64,
+}
+
+/// A finished time entry together with what it was tracked against.
+#[derive(Debug, Clone)]
+pub struct ReportEntry {
+    pub id: i64,
+    pub item_type: String,
+    pub item_id: Option<i64>,
+    pub item_title: Option<String>,
+    pub category_id: Option<i64>,
+    pub category_name: Option<String>,
+    pub timer_type: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub duration_seconds: i64,
+}
+
+/// Part of an entry falling on a single calendar day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaySegment {
+    pub date: NaiveDate,
+    pub seconds: i64,
+}
+
+/// Splits an entry at every midnight and keeps the days inside `[from, to]`.
+/// Tracked seconds are shared out in proportion to wall-clock time, so
+/// entries whose duration excludes pauses still add up to their duration.
+pub fn split_by_day(entry: &ReportEntry, from: NaiveDate, to: NaiveDate) -> Vec<DaySegment> {
+    let wall_seconds = (entry.end - entry.start).num_seconds();
+    if wall_seconds <= 0 {
+        return if (from..=to).contains(&entry.start.date()) {
+            vec![DaySegment { date: entry.start.date(), seconds: entry.duration_seconds.max(0) }]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let mut segments = Vec::new();
+    let mut allocated = 0;
+    let mut cursor = entry.start;
+
+    while cursor < entry.end {
+        let next_midnight = (cursor.date() + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap();
+        let segment_end = next_midnight.min(entry.end);
+
+        // The last segment takes the rounding remainder so the parts sum exactly
+        let seconds = if segment_end == entry.end {
+            entry.duration_seconds - allocated
+        } else {
+            entry.duration_seconds * (segment_end - cursor).num_seconds() / wall_seconds
+        };
+        allocated += seconds;
+
+        if (from..=to).contains(&cursor.date()) {
+            segments.push(DaySegment { date: cursor.date(), seconds });
+        }
+        cursor = segment_end;
+    }
+
+    segments
+}
+
+/// First day of the week containing `date`; `week_start_day` is 0 for Sunday, 1 for Monday.
+pub fn week_start(date: NaiveDate, week_start_day: u32) -> NaiveDate {
+    let offset = (date.weekday().num_days_from_sunday() + 7 - week_start_day % 7) % 7;
+    date - Duration::days(offset as i64)
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", value))
+}
+
+/// Finished entries that overlap `[from, to]`, whatever their stored timestamp format.
+pub fn load_report_entries(conn: &Connection, from: NaiveDate, to: NaiveDate) -> DbResult<Vec<ReportEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.id, tt.item_type, tt.item_id,
+                CASE tt.item_type WHEN 'TASK' THEN t.title WHEN 'EVENT' THEN e.title END,
+                c.id, c.name, tt.timer_type, tt.start_time, tt.end_time, tt.duration_seconds
+         FROM time_tracking tt
+         LEFT JOIN tasks t ON tt.item_type = 'TASK' AND t.id = tt.item_id
+         LEFT JOIN events e ON tt.item_type = 'EVENT' AND e.id = tt.item_id
+         LEFT JOIN categories c ON c.id = CASE tt.item_type
+             WHEN 'TASK' THEN t.category_id
+             WHEN 'EVENT' THEN e.category_id
+             WHEN 'CATEGORY' THEN tt.item_id
+         END
+         WHERE tt.end_time IS NOT NULL
+           AND substr(tt.start_time, 1, 10) <= ?2
+           AND substr(tt.end_time, 1, 10) >= ?1
+         ORDER BY tt.start_time"
+    )?;
+
+    let rows = stmt.query_map(
+        [from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+                row.get::<_, String>(8)?,
+                row.get::<_, Option<i64>>(9)?,
+            ))
+        },
+    )?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for (id, item_type, item_id, item_title, category_id, category_name, timer_type, start, end, duration) in rows {
+        let start = parse_timestamp(&start)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid start time on entry {}", id)))?;
+        let end = parse_timestamp(&end)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid end time on entry {}", id)))?;
+
+        entries.push(ReportEntry {
+            id,
+            item_type,
+            item_id,
+            item_title,
+            category_id,
+            category_name,
+            timer_type,
+            start,
+            end,
+            duration_seconds: duration.unwrap_or_else(|| (end - start).num_seconds()),
+        });
+    }
+
+    Ok(entries)
+}
+
+const GROUPINGS: &[&str] = &["DAY", "WEEK", "MONTH", "TASK", "EVENT", "CATEGORY", "TIMER_TYPE"];
+
+pub fn build_time_report(conn: &Connection, query: &TimeReportQuery) -> Result<TimeReport, String> {
+    if !GROUPINGS.contains(&query.group_by.as_str()) {
+        return Err(format!("Invalid grouping: {}", query.group_by));
+    }
+    let from = parse_date(&query.start_date)?;
+    let to = parse_date(&query.end_date)?;
+    if to < from {
+        return Err("End date must not be before start date".to_string());
+    }
+
+    let week_start_day: u32 = read_setting(conn, "week_start_day")
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1);
+    let entries = load_report_entries(conn, from, to).map_err(|e| e.to_string())?;
+
+    // key -> (label, seconds, contributing entry ids)
+    let mut groups: BTreeMap<String, (String, i64, HashSet<i64>)> = BTreeMap::new();
+
+    for entry in &entries {
+        for segment in split_by_day(entry, from, to) {
+            let (key, label) = match query.group_by.as_str() {
+                "DAY" => {
+                    let key = segment.date.format("%Y-%m-%d").to_string();
+                    (key.clone(), key)
+                }
+                "WEEK" => {
+                    let key = week_start(segment.date, week_start_day).format("%Y-%m-%d").to_string();
+                    (key.clone(), format!("Week of {}", key))
+                }
+                "MONTH" => {
+                    (segment.date.format("%Y-%m").to_string(), segment.date.format("%B %Y").to_string())
+                }
+                "TASK" | "EVENT" => {
+                    if entry.item_type != query.group_by {
+                        continue;
+                    }
+                    let id = entry.item_id.unwrap_or_default();
+                    let label = entry.item_title.clone()
+                        .unwrap_or_else(|| format!("{} #{}", entry.item_type, id));
+                    (id.to_string(), label)
+                }
+                "CATEGORY" => match entry.category_id {
+                    Some(id) => (id.to_string(), entry.category_name.clone().unwrap_or_default()),
+                    None => ("none".to_string(), "Uncategorized".to_string()),
+                },
+                _ => (entry.timer_type.clone(), entry.timer_type.clone()),
+            };
+
+            let group = groups.entry(key).or_insert_with(|| (label, 0, HashSet::new()));
+            group.1 += segment.seconds;
+            group.2.insert(entry.id);
+        }
+    }
+
+    let mut counted = HashSet::new();
+    let mut total_seconds = 0;
+    let rows: Vec<TimeReportRow> = groups.into_iter().map(|(key, (label, seconds, ids))| {
+        total_seconds += seconds;
+        counted.extend(ids.iter().copied());
+        let entry_count = ids.len() as i64;
+        TimeReportRow {
+            key,
+            label,
+            total_seconds: seconds,
+            entry_count,
+            average_seconds: if entry_count > 0 { seconds / entry_count } else { 0 },
+        }
+    }).collect();
+
+    let entry_count = counted.len() as i64;
+    Ok(TimeReport {
+        start_date: query.start_date.clone(),
+        end_date: query.end_date.clone(),
+        group_by: query.group_by.clone(),
+        rows,
+        total_seconds,
+        entry_count,
+        average_seconds: if entry_count > 0 { total_seconds / entry_count } else { 0 },
+    })
+}
+
+/// Formats seconds as `H:MM`, e.g. 5400 -> "1:30".
+pub fn format_hours(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+pub fn report_to_csv(report: &TimeReport) -> String {
+    let mut csv = String::from("key,label,total_seconds,total_hours,entries,average_seconds\n");
+
+    for row in &report.rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&row.key),
+            csv_field(&row.label),
+            row.total_seconds,
+            format_hours(row.total_seconds),
+            row.entry_count,
+            row.average_seconds
+        ));
+    }
+    csv.push_str(&format!(
+        "total,Total,{},{},{},{}\n",
+        report.total_seconds,
+        format_hours(report.total_seconds),
+        report.entry_count,
+        report.average_seconds
+    ));
+
+    csv
+}
+
+pub fn report_to_markdown(report: &TimeReport) -> String {
+    let mut markdown = format!(
+        "## Time report {} to {} by {}\n\n| {} | Total | Entries | Average |\n|---|---:|---:|---:|\n",
+        report.start_date,
+        report.end_date,
+        report.group_by.to_lowercase().replace('_', " "),
+        match report.group_by.as_str() {
+            "DAY" => "Day",
+            "WEEK" => "Week",
+            "MONTH" => "Month",
+            "TASK" => "Task",
+            "EVENT" => "Event",
+            "CATEGORY" => "Category",
+            _ => "Timer type",
+        }
+    );
+
+    for row in &report.rows {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            markdown_cell(&row.label),
+            format_hours(row.total_seconds),
+            row.entry_count,
+            format_hours(row.average_seconds)
+        ));
+    }
+    markdown.push_str(&format!(
+        "| **Total** | **{}** | **{}** | **{}** |\n",
+        format_hours(report.total_seconds),
+        report.entry_count,
+        format_hours(report.average_seconds)
+    ));
+
+    markdown
+}
+
+#[tauri::command]
+pub async fn get_time_report(query: TimeReportQuery, db: State<'_, Database>) -> Result<TimeReport, String> {
+    build_time_report(db.get_connection(), &query)
+}
+
+#[tauri::command]
+pub async fn export_time_report(
+    query: TimeReportQuery,
+    format: String,
+    db: State<'_, Database>
+) -> Result<String, String> {
+    let report = build_time_report(db.get_connection(), &query)?;
+
+    match format.as_str() {
+        "JSON" => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
+        "CSV" => Ok(report_to_csv(&report)),
+        "MARKDOWN" => Ok(report_to_markdown(&report)),
+        other => Err(format!("Unsupported export format: {}", other)),
+    }
+}
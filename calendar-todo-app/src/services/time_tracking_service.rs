@@ -1,9 +1,27 @@
-use crate::db::{Database, error::DbResult};
+use crate::db::{Database, error::{DatabaseError, DbResult}};
+use crate::services::settings_service::read_setting;
+use crate::services::time_budget_service::emit_budget_alerts;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Serialize, Deserialize};
-use tauri::State;
-use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Manager, State};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Storage format for timestamps written by the backend, matching SQLite's `datetime('now')`.
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parses a stored timestamp, accepting both the SQLite format and RFC 3339.
+pub fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.naive_utc()))
+}
+
+pub fn format_timestamp(value: NaiveDateTime) -> String {
+    value.format(TIMESTAMP_FORMAT).to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimeEntry {
     pub id: Option<i64>,
     pub item_type: String,
@@ -15,53 +33,401 @@ pub struct TimeEntry {
     pub created_at: Option<String>,
 }
 
+pub const ENTRY_COLUMNS: &str = "id, item_type, item_id, start_time, end_time, duration_seconds, timer_type, created_at";
+
+impl TimeEntry {
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(TimeEntry {
+            id: Some(row.get(0)?),
+            item_type: row.get(1)?,
+            item_id: row.get(2)?,
+            start_time: row.get(3)?,
+            end_time: row.get(4)?,
+            duration_seconds: row.get(5)?,
+            timer_type: row.get(6)?,
+            created_at: Some(row.get(7)?),
+        })
+    }
+
+    fn span(&self) -> DbResult<(NaiveDateTime, Option<NaiveDateTime>)> {
+        let start = parse_timestamp(&self.start_time)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid start time on entry {:?}", self.id)))?;
+        let end = match &self.end_time {
+            Some(value) => Some(parse_timestamp(value)
+                .ok_or_else(|| DatabaseError::Data(format!("Invalid end time on entry {:?}", self.id)))?),
+            None => None,
+        };
+        Ok((start, end))
+    }
+}
+
+fn parse_input_timestamp(value: &str) -> Result<NaiveDateTime, String> {
+    parse_timestamp(value).ok_or_else(|| format!("Invalid timestamp: {}", value))
+}
+
+pub fn load_time_entry(conn: &Connection, id: i64) -> DbResult<Option<TimeEntry>> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM time_tracking WHERE id = ?1", ENTRY_COLUMNS),
+        [id],
+        TimeEntry::from_row,
+    ).optional()?)
+}
+
+/// Sets an entry's span and recomputes its duration from it, so the stored
+/// duration can never disagree with the times the user sees.
+fn write_entry_span(conn: &Connection, id: i64, start: NaiveDateTime, end: NaiveDateTime) -> DbResult<()> {
+    conn.execute(
+        "UPDATE time_tracking SET start_time = ?1, end_time = ?2, duration_seconds = ?3 WHERE id = ?4",
+        rusqlite::params![format_timestamp(start), format_timestamp(end), (end - start).num_seconds(), id],
+    )?;
+    Ok(())
+}
+
+/// Inserts a finished entry for the same item and timer type as `template`.
+fn insert_finished_entry(conn: &Connection, template: &TimeEntry, start: NaiveDateTime, end: NaiveDateTime) -> DbResult<i64> {
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, end_time, duration_seconds, timer_type)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            template.item_type,
+            template.item_id,
+            format_timestamp(start),
+            format_timestamp(end),
+            (end - start).num_seconds(),
+            template.timer_type,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn running_entries(conn: &Connection) -> DbResult<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare(
+        &format!("SELECT {} FROM time_tracking WHERE end_time IS NULL ORDER BY start_time", ENTRY_COLUMNS)
+    )?;
+    let entries = stmt.query_map([], TimeEntry::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Makes room for a timer starting at `at` according to the
+/// `timer_conflict_policy` setting: `AUTO_STOP` stops whatever is running,
+/// `REJECT` refuses to start a second timer. A running Pomodoro work phase
+/// that gets stopped this way is paused rather than left pointing at a
+/// closed entry.
+fn claim_running_slot(conn: &Connection, at: NaiveDateTime) -> DbResult<()> {
+    let running = running_entries(conn)?;
+    if running.is_empty() {
+        return Ok(());
+    }
+
+    let policy = read_setting(conn, "timer_conflict_policy")?.unwrap_or_else(|| "AUTO_STOP".to_string());
+    if policy == "REJECT" {
+        return Err(DatabaseError::Data("Another timer is already running".to_string()));
+    }
+
+    let mut session = load_active_session(conn)?;
+    for entry in running {
+        let id = entry.id.unwrap_or_default();
+        let (start, _) = entry.span()?;
+
+        match session.as_mut() {
+            Some(session) if session.time_entry_id == Some(id) => {
+                let worked = session.phase_elapsed(at)?;
+                close_work_entry(conn, session, at, worked)?;
+                session.elapsed_seconds = worked;
+                session.phase_started_at = format_timestamp(at);
+                session.state = "PAUSED".to_string();
+                save_session(conn, session)?;
+            }
+            // A timer backdated before the running one still must not leave a negative duration
+            _ => write_entry_span(conn, id, start, at.max(start))?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts a running timer, applying the single running timer policy first.
+pub fn start_timer_entry(conn: &Connection, entry: &TimeEntry) -> Result<i64, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let id = insert_running_entry(&tx, entry)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+/// `start_timer_entry` for callers that already hold a transaction.
+pub(crate) fn insert_running_entry(conn: &Connection, entry: &TimeEntry) -> Result<i64, String> {
+    let start = parse_input_timestamp(&entry.start_time)?;
+
+    claim_running_slot(conn, start).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, timer_type)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![entry.item_type, entry.item_id, format_timestamp(start), entry.timer_type],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn stop_timer_entry(conn: &Connection, id: i64, end_time: &str) -> Result<TimeEntry, String> {
+    let entry = load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))?;
+    if entry.end_time.is_some() {
+        return Err("Timer is already stopped".to_string());
+    }
+    let session = load_active_session(conn).map_err(|e| e.to_string())?;
+    if session.is_some_and(|session| session.time_entry_id == Some(id)) {
+        return Err("Use the Pomodoro controls to stop a Pomodoro timer".to_string());
+    }
+
+    let (start, _) = entry.span().map_err(|e| e.to_string())?;
+    let end = parse_input_timestamp(end_time)?;
+    if end <= start {
+        return Err("End time must be after start time".to_string());
+    }
+
+    write_entry_span(conn, id, start, end).map_err(|e| e.to_string())?;
+    load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))
+}
+
+/// Entries overlapping `[start, end)`. A running timer counts as open-ended.
+pub fn find_overlapping_entries(
+    conn: &Connection,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_id: Option<i64>,
+) -> DbResult<Vec<TimeEntry>> {
+    // Narrow down by date in SQL, then compare exactly since stored formats vary
+    let mut stmt = conn.prepare(
+        &format!(
+            "SELECT {} FROM time_tracking
+             WHERE substr(start_time, 1, 10) <= ?1
+               AND (end_time IS NULL OR substr(end_time, 1, 10) >= ?2)
+               AND id != ?3
+             ORDER BY start_time",
+            ENTRY_COLUMNS
+        )
+    )?;
+    let candidates = stmt.query_map(
+        rusqlite::params![
+            end.format("%Y-%m-%d").to_string(),
+            start.format("%Y-%m-%d").to_string(),
+            exclude_id.unwrap_or(-1),
+        ],
+        TimeEntry::from_row,
+    )?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let mut overlaps = Vec::new();
+    for entry in candidates {
+        let (entry_start, entry_end) = entry.span()?;
+        if entry_start < end && !matches!(entry_end, Some(entry_end) if entry_end <= start) {
+            overlaps.push(entry);
+        }
+    }
+
+    Ok(overlaps)
+}
+
+/// Trims, splits or removes existing entries so `[start, end)` is free.
+fn resolve_overlaps(conn: &Connection, start: NaiveDateTime, end: NaiveDateTime, overlaps: &[TimeEntry]) -> Result<(), String> {
+    for entry in overlaps {
+        let id = entry.id.unwrap_or_default();
+        let (entry_start, entry_end) = entry.span().map_err(|e| e.to_string())?;
+        let entry_end = entry_end.ok_or("Entry overlaps the running timer")?;
+
+        let result = if entry_start >= start && entry_end <= end {
+            conn.execute("DELETE FROM time_tracking WHERE id = ?1", [id]).map(|_| ()).map_err(DatabaseError::from)
+        } else if entry_start < start && entry_end > end {
+            write_entry_span(conn, id, entry_start, start)
+                .and_then(|_| insert_finished_entry(conn, entry, end, entry_end).map(|_| ()))
+        } else if entry_start < start {
+            write_entry_span(conn, id, entry_start, start)
+        } else {
+            write_entry_span(conn, id, end, entry_end)
+        };
+        result.map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Checks a finished entry's times and fails on overlaps unless asked to resolve them.
+fn prepare_timesheet_span(
+    conn: &Connection,
+    entry: &TimeEntry,
+    resolve: bool,
+) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+    let start = parse_input_timestamp(&entry.start_time)?;
+    let end = parse_input_timestamp(entry.end_time.as_deref().ok_or("Timesheet entries need an end time")?)?;
+    if end <= start {
+        return Err("End time must be after start time".to_string());
+    }
+
+    let overlaps = find_overlapping_entries(conn, start, end, entry.id).map_err(|e| e.to_string())?;
+    if !overlaps.is_empty() {
+        if !resolve {
+            let ids: Vec<String> = overlaps.iter().filter_map(|e| e.id).map(|id| id.to_string()).collect();
+            return Err(format!("Entry overlaps existing entries: {}", ids.join(", ")));
+        }
+        resolve_overlaps(conn, start, end, &overlaps)?;
+    }
+
+    Ok((start, end))
+}
+
+pub fn create_timesheet_entry(conn: &Connection, entry: &TimeEntry, resolve_overlaps: bool) -> Result<TimeEntry, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let id = insert_timesheet_entry(&tx, entry, resolve_overlaps)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))
+}
+
+/// `create_timesheet_entry` for callers that already hold a transaction.
+/// Returns the new entry's id.
+pub(crate) fn insert_timesheet_entry(conn: &Connection, entry: &TimeEntry, resolve_overlaps: bool) -> Result<i64, String> {
+    let (start, end) = prepare_timesheet_span(conn, entry, resolve_overlaps)?;
+    insert_finished_entry(conn, entry, start, end).map_err(|e| e.to_string())
+}
+
+pub fn update_timesheet_entry(conn: &Connection, entry: &TimeEntry, resolve_overlaps: bool) -> Result<TimeEntry, String> {
+    let id = entry.id.ok_or("Time entry ID is required")?;
+    let existing = load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))?;
+    if existing.end_time.is_none() {
+        return Err("Stop the timer before editing it".to_string());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let (start, end) = prepare_timesheet_span(&tx, entry, resolve_overlaps)?;
+    tx.execute(
+        "UPDATE time_tracking SET item_type = ?1, item_id = ?2, timer_type = ?3 WHERE id = ?4",
+        rusqlite::params![entry.item_type, entry.item_id, entry.timer_type, id],
+    ).map_err(|e| e.to_string())?;
+    write_entry_span(&tx, id, start, end).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))
+}
+
+pub fn delete_timesheet_entry(conn: &Connection, id: i64) -> Result<(), String> {
+    let deleted = conn.execute("DELETE FROM time_tracking WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Time entry {} not found", id));
+    }
+    Ok(())
+}
+
+/// Splits a finished entry at `at` into two back-to-back entries.
+pub fn split_timesheet_entry(conn: &Connection, id: i64, at: &str) -> Result<Vec<TimeEntry>, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let second_id = split_entry(&tx, id, at)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let mut parts = Vec::new();
+    for part_id in [id, second_id] {
+        parts.extend(load_time_entry(conn, part_id).map_err(|e| e.to_string())?);
+    }
+    Ok(parts)
+}
+
+/// `split_timesheet_entry` for callers that already hold a transaction.
+/// Returns the id of the second part, the first keeps `id`.
+pub(crate) fn split_entry(conn: &Connection, id: i64, at: &str) -> Result<i64, String> {
+    let entry = load_time_entry(conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", id))?;
+    let (start, end) = entry.span().map_err(|e| e.to_string())?;
+    let end = end.ok_or("Stop the timer before splitting it")?;
+    let at = parse_input_timestamp(at)?;
+    if at <= start || at >= end {
+        return Err("Split time must fall inside the entry".to_string());
+    }
+
+    write_entry_span(conn, id, start, at).map_err(|e| e.to_string())?;
+    insert_finished_entry(conn, &entry, at, end).map_err(|e| e.to_string())
+}
+
+/// Merges finished entries for the same item into the earliest one, spanning
+/// from the first start to the last end. Gaps between them are included, but
+/// another entry sitting inside the merged span is an error.
+pub fn merge_timesheet_entries(conn: &Connection, ids: &[i64]) -> Result<TimeEntry, String> {
+    if ids.len() < 2 {
+        return Err("At least two entries are needed to merge".to_string());
+    }
+
+    let mut entries = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let entry = load_time_entry(conn, id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Time entry {} not found", id))?;
+        let (start, end) = entry.span().map_err(|e| e.to_string())?;
+        let end = end.ok_or("Stop the timer before merging it")?;
+        entries.push((entry, start, end));
+    }
+
+    let (first, _, _) = &entries[0];
+    if entries.iter().any(|(e, _, _)| {
+        e.item_type != first.item_type || e.item_id != first.item_id || e.timer_type != first.timer_type
+    }) {
+        return Err("Only entries for the same item and timer type can be merged".to_string());
+    }
+
+    entries.sort_by_key(|(_, start, _)| *start);
+    let keep_id = entries[0].0.id.unwrap_or_default();
+    let start = entries[0].1;
+    let end = entries.iter().map(|(_, _, end)| *end).max().unwrap_or(start);
+
+    let others = find_overlapping_entries(conn, start, end, None).map_err(|e| e.to_string())?;
+    if let Some(other) = others.iter().find(|e| e.id.is_some_and(|id| !ids.contains(&id))) {
+        return Err(format!("Entry {} lies between the entries being merged", other.id.unwrap_or_default()));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    for (entry, _, _) in entries.iter().skip(1) {
+        tx.execute("DELETE FROM time_tracking WHERE id = ?1", [entry.id]).map_err(|e| e.to_string())?;
+    }
+    write_entry_span(&tx, keep_id, start, end).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    load_time_entry(conn, keep_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Time entry {} not found", keep_id))
+}
+
 #[tauri::command]
 pub async fn start_timer(
     entry: TimeEntry,
     db: State<'_, Database>
 ) -> Result<i64, String> {
-    let conn = db.get_connection();
-    
-    let mut stmt = conn.prepare(
-        "INSERT INTO time_tracking (item_type, item_id, start_time, timer_type) 
-         VALUES (?1, ?2, ?3, ?4)"
-    ).map_err(|e| e.to_string())?;
-    
-    stmt.execute([
-        &entry.item_type,
-        &entry.item_id.map(|id| id.to_string()).unwrap_or_default(),
-        &entry.start_time,
-        &entry.timer_type,
-    ]).map_err(|e| e.to_string())?;
-    
-    Ok(conn.last_insert_rowid())
+    let _undo = db.undo_step("Start timer").map_err(|e| e.to_string())?;
+    start_timer_entry(db.get_connection(), &entry)
 }
 
 #[tauri::command]
 pub async fn stop_timer(
     id: i64,
     end_time: String,
+    app: AppHandle,
     db: State<'_, Database>
 ) -> Result<(), String> {
+    let _undo = db.undo_step("Stop timer").map_err(|e| e.to_string())?;
     let conn = db.get_connection();
-    
-    // Parse times to calculate duration
-    let start_time: DateTime<Utc> = conn.query_row(
-        "SELECT start_time FROM time_tracking WHERE id = ?",
-        [id],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    let end_time: DateTime<Utc> = end_time.parse()
-        .map_err(|e| e.to_string())?;
-    
-    let duration = end_time.signed_duration_since(start_time).num_seconds() as i32;
-    
-    conn.execute(
-        "UPDATE time_tracking SET end_time = ?, duration_seconds = ? WHERE id = ?",
-        [&end_time.to_string(), &duration.to_string(), &id.to_string()],
-    ).map_err(|e| e.to_string())?;
-    
+
+    stop_timer_entry(conn, id, &end_time)?;
+    emit_budget_alerts(&app, conn);
+
     Ok(())
 }
 
@@ -70,24 +436,17 @@ pub async fn get_active_timer(db: State<'_, Database>) -> Result<Option<TimeEntr
     let conn = db.get_connection();
     
     let mut stmt = conn.prepare(
-        "SELECT id, item_type, item_id, start_time, end_time, duration_seconds, timer_type, created_at
-         FROM time_tracking 
-         WHERE end_time IS NULL
-         ORDER BY start_time DESC LIMIT 1"
+        &format!(
+            "SELECT {} FROM time_tracking
+             WHERE end_time IS NULL
+             ORDER BY start_time DESC LIMIT 1",
+            ENTRY_COLUMNS
+        )
     ).map_err(|e| e.to_string())?;
     
-    let entry = stmt.query_row([], |row| {
-        Ok(TimeEntry {
-            id: Some(row.get(0)?),
-            item_type: row.get(1)?,
-            item_id: row.get(2)?,
-            start_time: row.get(3)?,
-            end_time: row.get(4)?,
-            duration_seconds: row.get(5)?,
-            timer_type: row.get(6)?,
-            created_at: Some(row.get(7)?),
-        })
-    }).optional().map_err(|e| e.to_string())?;
+    let entry = stmt.query_row([], TimeEntry::from_row)
+        .optional()
+        .map_err(|e| e.to_string())?;
     
     Ok(entry)
 }
@@ -102,10 +461,7 @@ pub async fn get_time_entries(
 ) -> Result<Vec<TimeEntry>, String> {
     let conn = db.get_connection();
     
-    let mut query = String::from(
-        "SELECT id, item_type, item_id, start_time, end_time, duration_seconds, timer_type, created_at
-         FROM time_tracking WHERE 1=1"
-    );
+    let mut query = format!("SELECT {} FROM time_tracking WHERE 1=1", ENTRY_COLUMNS);
     let mut params: Vec<String> = vec![];
     
     if let Some(type_str) = item_type {
@@ -138,21 +494,494 @@ pub async fn get_time_entries(
         .map(|p| p as &dyn rusqlite::ToSql)
         .collect();
     
-    let entries = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-        Ok(TimeEntry {
-            id: Some(row.get(0)?),
-            item_type: row.get(1)?,
-            item_id: row.get(2)?,
-            start_time: row.get(3)?,
-            end_time: row.get(4)?,
-            duration_seconds: row.get(5)?,
-            timer_type: row.get(6)?,
-            created_at: Some(row.get(7)?),
-        })
-    })
+    let entries = stmt.query_map(rusqlite::params_from_iter(param_refs), TimeEntry::from_row)
     .map_err(|e| e.to_string())?
     .collect::<Result<Vec<_>, _>>()
     .map_err(|e| e.to_string())?;
     
     Ok(entries)
 }
+
+
+#[tauri::command]
+pub async fn create_time_entry(
+    entry: TimeEntry,
+    resolve_overlaps: bool,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<TimeEntry, String> {
+    let _undo = db.undo_step("Create time entry").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+
+    let saved = create_timesheet_entry(conn, &entry, resolve_overlaps)?;
+    emit_budget_alerts(&app, conn);
+
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn update_time_entry(
+    entry: TimeEntry,
+    resolve_overlaps: bool,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<TimeEntry, String> {
+    let _undo = db.undo_step("Update time entry").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+
+    let saved = update_timesheet_entry(conn, &entry, resolve_overlaps)?;
+    emit_budget_alerts(&app, conn);
+
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn delete_time_entry(id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Delete time entry").map_err(|e| e.to_string())?;
+    delete_timesheet_entry(db.get_connection(), id)
+}
+
+#[tauri::command]
+pub async fn split_time_entry(
+    id: i64,
+    at: String,
+    db: State<'_, Database>
+) -> Result<Vec<TimeEntry>, String> {
+    let _undo = db.undo_step("Split time entry").map_err(|e| e.to_string())?;
+    split_timesheet_entry(db.get_connection(), id, &at)
+}
+
+#[tauri::command]
+pub async fn merge_time_entries(ids: Vec<i64>, db: State<'_, Database>) -> Result<TimeEntry, String> {
+    let _undo = db.undo_step("Merge time entries").map_err(|e| e.to_string())?;
+    merge_timesheet_entries(db.get_connection(), &ids)
+}
+
+#[tauri::command]
+pub async fn get_overlapping_entries(
+    start_time: String,
+    end_time: String,
+    exclude_id: Option<i64>,
+    db: State<'_, Database>
+) -> Result<Vec<TimeEntry>, String> {
+    let start = parse_input_timestamp(&start_time)?;
+    let end = parse_input_timestamp(&end_time)?;
+    find_overlapping_entries(db.get_connection(), start, end, exclude_id).map_err(|e| e.to_string())
+}
+
+/// Phase lengths for the Pomodoro engine, read from the `pomodoro_*` settings.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PomodoroConfig {
+    pub work_minutes: i64,
+    pub short_break_minutes: i64,
+    pub long_break_minutes: i64,
+    pub long_break_interval: i64, // completed work intervals between long breaks
+}
+
+impl PomodoroConfig {
+    pub fn load(conn: &Connection) -> DbResult<Self> {
+        let minutes = |key: &str, default: i64| -> DbResult<i64> {
+            Ok(read_setting(conn, key)?
+                .and_then(|v| v.parse::<i64>().ok())
+                .map(|v| v.max(1))
+                .unwrap_or(default))
+        };
+
+        Ok(PomodoroConfig {
+            work_minutes: minutes("pomodoro_work_minutes", 25)?,
+            short_break_minutes: minutes("pomodoro_break_minutes", 5)?,
+            long_break_minutes: minutes("pomodoro_long_break_minutes", 15)?,
+            long_break_interval: minutes("pomodoro_long_break_interval", 4)?,
+        })
+    }
+
+    pub fn phase_seconds(&self, phase: &str) -> i64 {
+        match phase {
+            "WORK" => self.work_minutes * 60,
+            "LONG_BREAK" => self.long_break_minutes * 60,
+            _ => self.short_break_minutes * 60,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PomodoroSession {
+    pub id: i64,
+    pub item_type: String,
+    pub item_id: Option<i64>,
+    pub phase: String, // "WORK", "SHORT_BREAK" or "LONG_BREAK"
+    pub state: String, // "RUNNING", "PAUSED" or "STOPPED"
+    pub phase_started_at: String,
+    pub elapsed_seconds: i64,
+    pub completed_work_intervals: i64,
+    pub time_entry_id: Option<i64>,
+}
+
+impl PomodoroSession {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(PomodoroSession {
+            id: row.get(0)?,
+            item_type: row.get(1)?,
+            item_id: row.get(2)?,
+            phase: row.get(3)?,
+            state: row.get(4)?,
+            phase_started_at: row.get(5)?,
+            elapsed_seconds: row.get(6)?,
+            completed_work_intervals: row.get(7)?,
+            time_entry_id: row.get(8)?,
+        })
+    }
+
+    fn started_at(&self) -> DbResult<NaiveDateTime> {
+        parse_timestamp(&self.phase_started_at)
+            .ok_or_else(|| DatabaseError::Data(format!("Invalid phase start: {}", self.phase_started_at)))
+    }
+
+    /// Seconds spent in the current phase, excluding pauses.
+    pub fn phase_elapsed(&self, now: NaiveDateTime) -> DbResult<i64> {
+        if self.state == "RUNNING" {
+            Ok(self.elapsed_seconds + (now - self.started_at()?).num_seconds().max(0))
+        } else {
+            Ok(self.elapsed_seconds)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PomodoroStatus {
+    pub session: PomodoroSession,
+    pub phase_seconds: i64,
+    pub remaining_seconds: i64,
+}
+
+/// Payload of the `pomodoro-phase-changed` event. `from_phase` is `None` when
+/// a session starts and `to_phase` is `None` when it stops.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PomodoroPhaseChange {
+    pub session_id: i64,
+    pub from_phase: Option<String>,
+    pub to_phase: Option<String>,
+    pub state: String,
+    pub changed_at: String,
+    pub completed_work_intervals: i64,
+}
+
+fn load_active_session(conn: &Connection) -> DbResult<Option<PomodoroSession>> {
+    let session = conn.query_row(
+        "SELECT id, item_type, item_id, phase, state, phase_started_at, elapsed_seconds,
+                completed_work_intervals, time_entry_id
+         FROM pomodoro_sessions
+         WHERE state != 'STOPPED'
+         ORDER BY id DESC LIMIT 1",
+        [],
+        PomodoroSession::from_row
+    ).optional()?;
+
+    Ok(session)
+}
+
+fn save_session(conn: &Connection, session: &PomodoroSession) -> DbResult<()> {
+    conn.execute(
+        "UPDATE pomodoro_sessions
+         SET phase = ?1, state = ?2, phase_started_at = ?3, elapsed_seconds = ?4,
+             completed_work_intervals = ?5, time_entry_id = ?6
+         WHERE id = ?7",
+        rusqlite::params![
+            session.phase,
+            session.state,
+            session.phase_started_at,
+            session.elapsed_seconds,
+            session.completed_work_intervals,
+            session.time_entry_id,
+            session.id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn phase_change(session: &PomodoroSession, from: Option<&str>, at: NaiveDateTime) -> PomodoroPhaseChange {
+    PomodoroPhaseChange {
+        session_id: session.id,
+        from_phase: from.map(str::to_string),
+        to_phase: (session.state != "STOPPED").then(|| session.phase.clone()),
+        state: session.state.clone(),
+        changed_at: format_timestamp(at),
+        completed_work_intervals: session.completed_work_intervals,
+    }
+}
+
+/// Opens the `time_tracking` entry for the current work phase if there is none
+/// yet, subject to the single running timer policy.
+fn open_work_entry(conn: &Connection, session: &mut PomodoroSession, at: NaiveDateTime) -> DbResult<()> {
+    if session.phase != "WORK" || session.time_entry_id.is_some() {
+        return Ok(());
+    }
+
+    claim_running_slot(conn, at)?;
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, timer_type)
+         VALUES (?1, ?2, ?3, 'POMODORO')",
+        rusqlite::params![session.item_type, session.item_id, format_timestamp(at)],
+    )?;
+    session.time_entry_id = Some(conn.last_insert_rowid());
+
+    Ok(())
+}
+
+/// Closes the work phase entry with the time actually worked, pauses excluded.
+fn close_work_entry(conn: &Connection, session: &mut PomodoroSession, at: NaiveDateTime, worked_seconds: i64) -> DbResult<()> {
+    if let Some(entry_id) = session.time_entry_id.take() {
+        conn.execute(
+            "UPDATE time_tracking SET end_time = ?1, duration_seconds = ?2 WHERE id = ?3",
+            rusqlite::params![format_timestamp(at), worked_seconds, entry_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Moves the session to its next phase at `at`. A work phase that ran to
+/// completion counts towards the long break; one that was skipped does not.
+/// A skipped phase always hands over to a running one, but when a break runs
+/// out the next work phase waits paused, so time the app spent closed is
+/// never booked as work.
+fn advance_phase(
+    conn: &Connection,
+    session: &mut PomodoroSession,
+    config: &PomodoroConfig,
+    at: NaiveDateTime,
+    completed: bool,
+) -> DbResult<PomodoroPhaseChange> {
+    let from = session.phase.clone();
+
+    let next = if from == "WORK" {
+        let worked = if completed {
+            config.phase_seconds("WORK")
+        } else {
+            session.phase_elapsed(at)?
+        };
+        close_work_entry(conn, session, at, worked)?;
+
+        if completed {
+            session.completed_work_intervals += 1;
+        }
+        if completed && session.completed_work_intervals % config.long_break_interval == 0 {
+            "LONG_BREAK"
+        } else {
+            "SHORT_BREAK"
+        }
+    } else {
+        "WORK"
+    };
+
+    session.state = if completed && next == "WORK" { "PAUSED" } else { "RUNNING" }.to_string();
+    session.phase = next.to_string();
+    session.phase_started_at = format_timestamp(at);
+    session.elapsed_seconds = 0;
+    if session.state == "RUNNING" {
+        open_work_entry(conn, session, at)?;
+    }
+
+    Ok(phase_change(session, Some(&from), at))
+}
+
+/// Brings the active session up to `now`, completing every phase whose time
+/// has run out. Also used to catch up after the app was closed.
+pub fn tick_pomodoro(conn: &Connection, now: NaiveDateTime) -> DbResult<Vec<PomodoroPhaseChange>> {
+    let mut changes = Vec::new();
+    let mut session = match load_active_session(conn)? {
+        Some(session) => session,
+        None => return Ok(changes),
+    };
+    let config = PomodoroConfig::load(conn)?;
+
+    while session.state == "RUNNING" {
+        let remaining = config.phase_seconds(&session.phase) - session.elapsed_seconds;
+        let phase_end = session.started_at()? + Duration::seconds(remaining);
+        if phase_end > now {
+            break;
+        }
+        changes.push(advance_phase(conn, &mut session, &config, phase_end, true)?);
+    }
+
+    if !changes.is_empty() {
+        save_session(conn, &session)?;
+    }
+
+    Ok(changes)
+}
+
+pub fn start_pomodoro_session(
+    conn: &Connection,
+    item_type: &str,
+    item_id: Option<i64>,
+    now: NaiveDateTime,
+) -> Result<Vec<PomodoroPhaseChange>, String> {
+    let mut changes = tick_pomodoro(conn, now).map_err(|e| e.to_string())?;
+    if load_active_session(conn).map_err(|e| e.to_string())?.is_some() {
+        return Err("A Pomodoro session is already active".to_string());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO pomodoro_sessions (item_type, item_id, phase, state, phase_started_at)
+         VALUES (?1, ?2, 'WORK', 'RUNNING', ?3)",
+        rusqlite::params![item_type, item_id, format_timestamp(now)],
+    ).map_err(|e| e.to_string())?;
+
+    let mut session = load_active_session(&tx)
+        .map_err(|e| e.to_string())?
+        .ok_or("Failed to start Pomodoro session")?;
+    open_work_entry(&tx, &mut session, now).map_err(|e| e.to_string())?;
+    save_session(&tx, &session).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    changes.push(phase_change(&session, None, now));
+    Ok(changes)
+}
+
+/// Applies a pause/resume/skip/stop action to the active session after catching it up to `now`.
+pub fn control_pomodoro_session(
+    conn: &Connection,
+    action: &str,
+    now: NaiveDateTime,
+) -> Result<Vec<PomodoroPhaseChange>, String> {
+    let mut changes = tick_pomodoro(conn, now).map_err(|e| e.to_string())?;
+    let mut session = load_active_session(conn)
+        .map_err(|e| e.to_string())?
+        .ok_or("No active Pomodoro session")?;
+    let config = PomodoroConfig::load(conn).map_err(|e| e.to_string())?;
+
+    match action {
+        "PAUSE" => {
+            if session.state != "RUNNING" {
+                return Err("Pomodoro session is not running".to_string());
+            }
+            session.elapsed_seconds = session.phase_elapsed(now).map_err(|e| e.to_string())?;
+            session.phase_started_at = format_timestamp(now);
+            session.state = "PAUSED".to_string();
+            changes.push(phase_change(&session, Some(&session.phase), now));
+        }
+        "RESUME" => {
+            if session.state != "PAUSED" {
+                return Err("Pomodoro session is not paused".to_string());
+            }
+            session.phase_started_at = format_timestamp(now);
+            session.state = "RUNNING".to_string();
+            open_work_entry(conn, &mut session, now).map_err(|e| e.to_string())?;
+            changes.push(phase_change(&session, Some(&session.phase), now));
+        }
+        "SKIP" => {
+            let change = advance_phase(conn, &mut session, &config, now, false)
+                .map_err(|e| e.to_string())?;
+            changes.push(change);
+        }
+        "STOP" => {
+            let from = session.phase.clone();
+            let worked = session.phase_elapsed(now).map_err(|e| e.to_string())?;
+            close_work_entry(conn, &mut session, now, worked).map_err(|e| e.to_string())?;
+            session.state = "STOPPED".to_string();
+            changes.push(phase_change(&session, Some(&from), now));
+        }
+        _ => return Err(format!("Unknown Pomodoro action: {}", action)),
+    }
+
+    save_session(conn, &session).map_err(|e| e.to_string())?;
+    Ok(changes)
+}
+
+pub fn pomodoro_status(conn: &Connection, now: NaiveDateTime) -> DbResult<Option<PomodoroStatus>> {
+    let config = PomodoroConfig::load(conn)?;
+
+    match load_active_session(conn)? {
+        Some(session) => {
+            let phase_seconds = config.phase_seconds(&session.phase);
+            let remaining_seconds = (phase_seconds - session.phase_elapsed(now)?).max(0);
+            Ok(Some(PomodoroStatus { session, phase_seconds, remaining_seconds }))
+        }
+        None => Ok(None),
+    }
+}
+
+/// End of the running work phase, used by the reminder engine for DND.
+pub fn running_work_phase_end(conn: &Connection) -> DbResult<Option<NaiveDateTime>> {
+    let config = PomodoroConfig::load(conn)?;
+
+    match load_active_session(conn)? {
+        Some(session) if session.state == "RUNNING" && session.phase == "WORK" => {
+            let remaining = config.phase_seconds("WORK") - session.elapsed_seconds;
+            Ok(Some(session.started_at()? + Duration::seconds(remaining)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn emit_phase_changes(app: &AppHandle, changes: &[PomodoroPhaseChange]) {
+    for change in changes {
+        let _ = app.emit_all("pomodoro-phase-changed", change.clone());
+    }
+}
+
+#[tauri::command]
+pub async fn start_pomodoro(
+    item_type: String,
+    item_id: Option<i64>,
+    app: AppHandle,
+    db: State<'_, Database>
+) -> Result<Option<PomodoroStatus>, String> {
+    let _undo = db.undo_step("Start Pomodoro").map_err(|e| e.to_string())?;
+    let conn = db.get_connection();
+    let now = Utc::now().naive_utc();
+
+    let changes = start_pomodoro_session(conn, &item_type, item_id, now)?;
+    emit_phase_changes(&app, &changes);
+
+    pomodoro_status(conn, now).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pause_pomodoro(app: AppHandle, db: State<'_, Database>) -> Result<Option<PomodoroStatus>, String> {
+    let _undo = db.undo_step("Pause Pomodoro").map_err(|e| e.to_string())?;
+    control_pomodoro(&app, db.get_connection(), "PAUSE")
+}
+
+#[tauri::command]
+pub async fn resume_pomodoro(app: AppHandle, db: State<'_, Database>) -> Result<Option<PomodoroStatus>, String> {
+    let _undo = db.undo_step("Resume Pomodoro").map_err(|e| e.to_string())?;
+    control_pomodoro(&app, db.get_connection(), "RESUME")
+}
+
+#[tauri::command]
+pub async fn skip_pomodoro_phase(app: AppHandle, db: State<'_, Database>) -> Result<Option<PomodoroStatus>, String> {
+    let _undo = db.undo_step("Skip Pomodoro phase").map_err(|e| e.to_string())?;
+    control_pomodoro(&app, db.get_connection(), "SKIP")
+}
+
+#[tauri::command]
+pub async fn stop_pomodoro(app: AppHandle, db: State<'_, Database>) -> Result<Option<PomodoroStatus>, String> {
+    let _undo = db.undo_step("Stop Pomodoro").map_err(|e| e.to_string())?;
+    control_pomodoro(&app, db.get_connection(), "STOP")
+}
+
+fn control_pomodoro(app: &AppHandle, conn: &Connection, action: &str) -> Result<Option<PomodoroStatus>, String> {
+    let now = Utc::now().naive_utc();
+
+    let changes = control_pomodoro_session(conn, action, now)?;
+    emit_phase_changes(app, &changes);
+    emit_budget_alerts(app, conn);
+
+    pomodoro_status(conn, now).map_err(|e| e.to_string())
+}
+
+/// Polled by the UI timer; completes elapsed phases and emits their events.
+#[tauri::command]
+pub async fn get_pomodoro_status(app: AppHandle, db: State<'_, Database>) -> Result<Option<PomodoroStatus>, String> {
+    let conn = db.get_connection();
+    let now = Utc::now().naive_utc();
+
+    let changes = tick_pomodoro(conn, now).map_err(|e| e.to_string())?;
+    emit_phase_changes(&app, &changes);
+
+    pomodoro_status(conn, now).map_err(|e| e.to_string())
+}
@@ -0,0 +1,63 @@
+use crate::db::{Database, models::{Task, TrashedItem}};
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_trash(db: State<'_, Database>) -> Result<Vec<TrashedItem>, String> {
+    db.get_trash().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(
+    item_type: String,
+    item_id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Restore from trash").map_err(|e| e.to_string())?;
+    db.restore_item(&item_type, item_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn purge_from_trash(
+    item_type: String,
+    item_id: i64,
+    db: State<'_, Database>,
+) -> Result<(), String> {
+    let _undo = db.undo_step("Purge from trash").map_err(|e| e.to_string())?;
+    db.purge_item(&item_type, item_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn empty_trash(db: State<'_, Database>) -> Result<usize, String> {
+    let _undo = db.undo_step("Empty trash").map_err(|e| e.to_string())?;
+    db.empty_trash().map_err(|e| e.to_string())
+}
+
+/// Run on startup and from time to time to apply `trash_retention_days`
+#[tauri::command]
+pub async fn purge_expired_trash(db: State<'_, Database>) -> Result<usize, String> {
+    db.purge_expired_trash().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn archive_task(task_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Archive task").map_err(|e| e.to_string())?;
+    db.archive_task(task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unarchive_task(task_id: i64, db: State<'_, Database>) -> Result<(), String> {
+    let _undo = db.undo_step("Unarchive task").map_err(|e| e.to_string())?;
+    db.unarchive_task(task_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_archived_tasks(db: State<'_, Database>) -> Result<Vec<Task>, String> {
+    db.get_archived_tasks().map_err(|e| e.to_string())
+}
+
+/// Archives the finished tasks completed before `before`
+#[tauri::command]
+pub async fn archive_finished_tasks(before: String, db: State<'_, Database>) -> Result<usize, String> {
+    let _undo = db.undo_step("Archive finished tasks").map_err(|e| e.to_string())?;
+    db.archive_finished_tasks(&before).map_err(|e| e.to_string())
+}
@@ -0,0 +1,18 @@
+use crate::db::{Database, models::{UndoEntry, UndoHistory}};
+use tauri::State;
+
+/// Reverts the latest change, returning what was undone
+#[tauri::command]
+pub async fn undo(db: State<'_, Database>) -> Result<Option<UndoEntry>, String> {
+    db.undo().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn redo(db: State<'_, Database>) -> Result<Option<UndoEntry>, String> {
+    db.redo().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_undo_history(db: State<'_, Database>) -> Result<UndoHistory, String> {
+    db.get_undo_history().map_err(|e| e.to_string())
+}
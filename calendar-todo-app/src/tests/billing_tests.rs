@@ -0,0 +1,190 @@
+use crate::services::billing_service::*;
+use super::setup_test_db_with_data;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn add_entry(conn: &Connection, item_type: &str, item_id: Option<i64>, start: &str, end: &str) {
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, end_time, duration_seconds, timer_type)
+         VALUES (?1, ?2, ?3, ?4, strftime('%s', ?4) - strftime('%s', ?3), 'MANUAL')",
+        rusqlite::params![item_type, item_id, start, end],
+    ).unwrap();
+}
+
+fn rounding(conn: &Connection, minutes: i64, mode: &str) {
+    RoundingRule { minutes, mode: mode.to_string() }.save(conn).unwrap();
+}
+
+fn query(client_type: &str, client_id: i64) -> InvoiceQuery {
+    InvoiceQuery {
+        client_type: client_type.to_string(),
+        client_id,
+        start_date: "2024-01-01".to_string(),
+        end_date: "2024-01-31".to_string(),
+    }
+}
+
+/// Work (category 1) billed at 100.00 an hour. Seeded task 1 and event 1 both belong to Work.
+fn seed_work_client(conn: &Connection) {
+    write_billing_rate(conn, "CATEGORY", 1, &BillingRate { is_billable: true, hourly_rate_cents: Some(10000) }).unwrap();
+    add_entry(conn, "TASK", Some(1), "2024-01-15 09:00:00", "2024-01-15 09:50:00");
+    add_entry(conn, "TASK", Some(1), "2024-01-15 09:55:00", "2024-01-15 10:02:00");
+    add_entry(conn, "EVENT", Some(1), "2024-01-16 14:00:00", "2024-01-16 14:20:00");
+    add_entry(conn, "TASK", Some(2), "2024-01-16 15:00:00", "2024-01-16 16:00:00"); // Personal, not billed
+}
+
+#[test]
+fn test_rounding_rule() {
+    let quarter = RoundingRule { minutes: 15, mode: "ENTRY".to_string() };
+    assert_eq!(quarter.apply(50 * 60), 45 * 60);
+    assert_eq!(quarter.apply(7 * 60), 0);
+    assert_eq!(quarter.apply(7 * 60 + 30), 15 * 60, "Halves should round up");
+
+    let tenth = RoundingRule { minutes: 6, mode: "ENTRY".to_string() };
+    assert_eq!(tenth.apply(50 * 60), 48 * 60);
+
+    let exact = RoundingRule { minutes: 0, mode: "ENTRY".to_string() };
+    assert_eq!(exact.apply(1234), 1234);
+}
+
+#[test]
+fn test_line_amount_and_formatting() {
+    assert_eq!(line_amount(45 * 60, 10000), 7500);
+    assert_eq!(line_amount(1, 10000), 3, "Amounts round to the nearest cent");
+    assert_eq!(format_amount(123456), "1234.56");
+    assert_eq!(format_amount(5), "0.05");
+}
+
+#[test]
+#[serial]
+fn test_exact_invoice_totals() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_work_client(conn);
+
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+
+    assert_eq!(statement.client_name, "Work");
+    assert_eq!(statement.lines.len(), 3, "Only Work entries should be billed");
+    assert_eq!(statement.lines[0].description, "Complete project proposal");
+    assert_eq!(statement.lines[2].description, "Morning Standup");
+    assert_eq!(statement.total_tracked_seconds, 77 * 60);
+    assert_eq!(statement.total_billed_seconds, 77 * 60);
+    assert_eq!(statement.total_amount_cents, 12833);
+}
+
+#[test]
+#[serial]
+fn test_rounding_per_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_work_client(conn);
+    rounding(conn, 15, "ENTRY");
+
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+
+    let billed: Vec<i64> = statement.lines.iter().map(|l| l.billed_seconds / 60).collect();
+    assert_eq!(billed, vec![45, 0, 15]);
+    assert_eq!(statement.total_amount_cents, 10000);
+
+    rounding(conn, 6, "ENTRY");
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+    assert_eq!(statement.total_billed_seconds, (48 + 6 + 18) * 60);
+    assert_eq!(statement.total_amount_cents, 12000);
+}
+
+#[test]
+#[serial]
+fn test_rounding_per_day() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_work_client(conn);
+    rounding(conn, 15, "DAY");
+
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+
+    assert_eq!(statement.lines.len(), 2, "Entries for the same item and day share a line");
+    assert_eq!(statement.lines[0].entry_ids.len(), 2);
+    assert_eq!(statement.lines[0].tracked_seconds, 57 * 60);
+    assert_eq!(statement.lines[0].billed_seconds, 60 * 60);
+    assert_eq!(statement.lines[1].billed_seconds, 15 * 60);
+    assert_eq!(statement.total_amount_cents, 12500);
+}
+
+#[test]
+#[serial]
+fn test_entry_across_midnight_is_billed_per_day() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_billing_rate(conn, "CATEGORY", 1, &BillingRate { is_billable: true, hourly_rate_cents: Some(6000) }).unwrap();
+    add_entry(conn, "TASK", Some(1), "2024-01-31 23:00:00", "2024-02-01 01:00:00");
+    rounding(conn, 0, "DAY");
+
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+
+    assert_eq!(statement.lines.len(), 1, "Only the part inside the period is billed");
+    assert_eq!(statement.total_billed_seconds, 3600);
+    assert_eq!(statement.total_amount_cents, 6000);
+}
+
+#[test]
+#[serial]
+fn test_participant_client() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_work_client(conn);
+    conn.execute("INSERT INTO participants (name, email) VALUES ('Acme Corp', 'billing@acme.test')", []).unwrap();
+    let client_id = conn.last_insert_rowid();
+    conn.execute("INSERT INTO event_participants (event_id, participant_id) VALUES (1, ?1)", [client_id]).unwrap();
+
+    assert!(build_invoice(conn, &query("PARTICIPANT", client_id)).is_err(), "Clients must be marked billable");
+
+    write_billing_rate(conn, "PARTICIPANT", client_id, &BillingRate { is_billable: true, hourly_rate_cents: None }).unwrap();
+    let statement = build_invoice(conn, &query("PARTICIPANT", client_id)).unwrap();
+    assert_eq!(statement.lines.len(), 1, "Only events the participant attends are billed");
+    assert_eq!(statement.lines[0].hourly_rate_cents, 10000, "Falls back to the event category's rate");
+
+    write_billing_rate(conn, "PARTICIPANT", client_id, &BillingRate { is_billable: true, hourly_rate_cents: Some(15000) }).unwrap();
+    let statement = build_invoice(conn, &query("PARTICIPANT", client_id)).unwrap();
+    assert_eq!(statement.total_amount_cents, 5000);
+}
+
+#[test]
+#[serial]
+fn test_invoice_validation() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert!(build_invoice(conn, &query("CATEGORY", 2)).is_err(), "Non-billable category");
+    assert!(build_invoice(conn, &query("CATEGORY", 99999)).is_err());
+    assert!(build_invoice(conn, &query("VENDOR", 1)).is_err());
+    assert!(write_billing_rate(conn, "CATEGORY", 1, &BillingRate { is_billable: true, hourly_rate_cents: Some(-1) }).is_err());
+    assert!(RoundingRule { minutes: 10, mode: "ENTRY".to_string() }.save(conn).is_err());
+    assert!(RoundingRule { minutes: 15, mode: "WEEK".to_string() }.save(conn).is_err());
+}
+
+#[test]
+#[serial]
+fn test_invoice_exports() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_work_client(conn);
+    rounding(conn, 15, "DAY");
+    conn.execute("UPDATE tasks SET title = 'Proposal, v2' WHERE id = 1", []).unwrap();
+
+    let statement = build_invoice(conn, &query("CATEGORY", 1)).unwrap();
+
+    let csv = invoice_to_csv(&statement);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "date,description,tracked_hours,billed_hours,rate,amount,currency");
+    assert_eq!(lines[1], "2024-01-15,\"Proposal, v2\",0:57,1:00,100.00,100.00,USD");
+    assert_eq!(lines.last().unwrap(), &"total,Total,1:17,1:15,,125.00,USD");
+
+    let markdown = invoice_to_markdown(&statement);
+    assert!(markdown.starts_with("## Statement for Work"));
+    assert!(markdown.contains("| 2024-01-16 | Morning Standup | 0:15 | 100.00 | 25.00 |"));
+    assert!(markdown.contains("| | **Total** | **1:15** | | **125.00 USD** |"));
+
+    let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&statement).unwrap()).unwrap();
+    assert_eq!(json["total_amount_cents"], 12500);
+}
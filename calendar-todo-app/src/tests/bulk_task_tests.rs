@@ -0,0 +1,121 @@
+use crate::db::{Database, models::BulkResult};
+use super::setup_test_db_with_data;
+use serial_test::serial;
+
+fn errors(result: &BulkResult) -> Vec<(i64, bool)> {
+    result.results.iter().map(|r| (r.task_id, r.error.is_some())).collect()
+}
+
+fn column(db: &Database, id: i64) -> (String, Option<i64>, Option<i64>) {
+    db.get_connection().query_row(
+        "SELECT status, kanban_column_id, kanban_order FROM tasks WHERE id = ?",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).unwrap()
+}
+
+#[test]
+#[serial]
+fn test_bulk_updates_report_each_task() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let result = db.bulk_set_task_priority(&[1, 2, 99, 1], 5).unwrap();
+    assert_eq!(errors(&result), vec![(1, false), (2, false), (99, true)], "Repeated ids are applied once");
+    assert_eq!(result.applied(), 2);
+    let priorities: Vec<i32> = conn.prepare("SELECT priority FROM tasks ORDER BY id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(|p| p.unwrap())
+        .collect();
+    assert_eq!(priorities, vec![5, 5, 2]);
+    assert!(db.bulk_set_task_priority(&[1], 9).is_err());
+
+    let result = db.bulk_set_task_category(&[1, 3], Some(2)).unwrap();
+    assert_eq!(result.applied(), 2);
+    let work: i64 = conn.query_row("SELECT COUNT(*) FROM tasks WHERE category_id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(work, 0);
+    assert!(db.bulk_set_task_category(&[1], Some(99)).is_err());
+    db.bulk_set_task_category(&[2], None).unwrap();
+
+    let result = db.bulk_set_task_status(&[1, 3], "NOT_A_STATUS").unwrap();
+    assert_eq!(errors(&result), vec![(1, true), (3, true)]);
+    assert_eq!(column(&db, 3).0, "IN_PROGRESS", "A failed change leaves the task as it was");
+
+    let result = db.bulk_set_task_status(&[1, 3], "COMPLETED").unwrap();
+    assert_eq!(result.applied(), 2);
+    let completed: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM tasks WHERE status = 'COMPLETED' AND completed_at IS NOT NULL",
+        [],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(completed, 2);
+}
+
+#[test]
+#[serial]
+fn test_bulk_shift_due_dates() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE tasks SET start_date = '2023-01-15' WHERE id = 2", []).unwrap();
+    conn.execute("INSERT INTO tasks (title, priority) VALUES ('Someday', 3)", []).unwrap();
+    let undated = conn.last_insert_rowid();
+
+    let result = db.bulk_shift_due_dates(&[1, 2, undated], 7).unwrap();
+    assert_eq!(errors(&result), vec![(1, false), (2, false), (undated, true)]);
+
+    let dates: Vec<(Option<String>, Option<String>)> = conn.prepare("SELECT due_date, start_date FROM tasks WHERE id IN (1, 2) ORDER BY id").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .map(|d| d.unwrap())
+        .collect();
+    assert_eq!(dates, vec![
+        (Some("2023-02-07 17:00:00".to_string()), None),
+        (Some("2023-01-25 19:00:00".to_string()), Some("2023-01-22".to_string())),
+    ]);
+
+    db.bulk_shift_due_dates(&[1], -7).unwrap();
+    let due_date: String = conn.query_row("SELECT due_date FROM tasks WHERE id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(due_date, "2023-01-31 17:00:00");
+
+    assert!(db.bulk_shift_due_dates(&[1], i64::MAX).is_err());
+    let result = db.bulk_shift_due_dates(&[1], 1_000_000_000).unwrap();
+    assert_eq!(errors(&result), vec![(1, true)], "Dates past the calendar's range are reported");
+}
+
+#[test]
+#[serial]
+fn test_bulk_move_to_column() {
+    let db = setup_test_db_with_data();
+    db.add_task_dependency(2, 1).unwrap();
+
+    let result = db.bulk_move_to_column(&[2, 1], 2).unwrap();
+    assert_eq!(errors(&result), vec![(2, true), (1, false)], "Blocked tasks stay in To Do");
+    assert!(result.results[0].error.as_deref().unwrap().contains("Complete project proposal"));
+    assert_eq!(column(&db, 1), ("IN_PROGRESS".to_string(), Some(2), Some(2)), "Placed after the column's last task");
+    assert_eq!(column(&db, 2), ("PENDING".to_string(), Some(1), Some(2)));
+
+    let result = db.bulk_move_to_column(&[1, 2], 3).unwrap();
+    assert_eq!(result.applied(), 2, "Completing the prerequisite first unblocks the next task");
+    assert_eq!(column(&db, 1), ("COMPLETED".to_string(), Some(3), Some(0)));
+    assert_eq!(column(&db, 2), ("COMPLETED".to_string(), Some(3), Some(1)));
+
+    assert!(db.bulk_move_to_column(&[3], 99).is_err());
+}
+
+#[test]
+#[serial]
+fn test_bulk_delete() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO tasks (title, priority, parent_task_id, subtask_order) VALUES ('Outline', 3, 1, 0)", []).unwrap();
+    let child = conn.last_insert_rowid();
+
+    let result = db.bulk_delete_tasks(&[1, 3], "PROMOTE").unwrap();
+    assert_eq!(result.applied(), 2);
+    let remaining: Vec<i64> = conn.prepare("SELECT id FROM tasks WHERE deleted_at IS NULL ORDER BY id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(|id| id.unwrap())
+        .collect();
+    assert_eq!(remaining, vec![2, child]);
+
+    assert!(db.bulk_delete_tasks(&[2], "ORPHAN").is_err());
+}
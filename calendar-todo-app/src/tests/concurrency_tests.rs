@@ -0,0 +1,98 @@
+use crate::db::error::{CommandError, DatabaseError};
+use super::setup_test_db_with_data;
+use serde_json::json;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn test_stale_task_edit_gets_the_current_copy() {
+    let db = setup_test_db_with_data();
+    let mut first = db.get_task(1).unwrap();
+    let mut second = first.clone();
+
+    first.title = "Send project proposal".to_string();
+    db.update_task(&first).unwrap();
+
+    second.priority = 4;
+    let current = match db.update_task(&second) {
+        Err(DatabaseError::Conflict { item_type, id, current }) => {
+            assert_eq!((item_type.as_str(), id), ("TASK", 1));
+            current
+        }
+        other => panic!("Expected a conflict, got {:?}", other),
+    };
+    assert_eq!(current["title"], "Send project proposal");
+    assert_eq!(current["priority"], 1, "The stale edit is not applied");
+
+    second = serde_json::from_value(current).unwrap();
+    second.priority = 4;
+    db.update_task(&second).unwrap();
+    let task = db.get_task(1).unwrap();
+    assert_eq!((task.title.as_str(), task.priority), ("Send project proposal", 4));
+
+    second.updated_at = None;
+    second.title = "Complete project proposal".to_string();
+    db.update_task(&second).unwrap();
+    assert_eq!(db.get_task(1).unwrap().title, "Complete project proposal", "Without updated_at the update is applied as is");
+}
+
+#[test]
+#[serial]
+fn test_any_change_makes_loaded_copies_stale() {
+    let db = setup_test_db_with_data();
+    let mut task = db.get_task(2).unwrap();
+    let mut category = db.get_category(1).unwrap();
+
+    db.bulk_set_task_priority(&[2], 5).unwrap();
+    task.title = "Buy vegetables".to_string();
+    assert!(matches!(db.update_task(&task), Err(DatabaseError::Conflict { .. })));
+
+    let reloaded = db.get_category(1).unwrap();
+    db.update_category(&reloaded).unwrap();
+    category.color = "#000000".to_string();
+    assert!(
+        matches!(db.update_category(&category), Err(DatabaseError::Conflict { .. })),
+        "Saving the same values still counts as a change"
+    );
+}
+
+#[test]
+#[serial]
+fn test_conflict_errors_reach_the_ui_typed() {
+    let db = setup_test_db_with_data();
+    let mut event = db.get_event(1).unwrap();
+    let mut moved = event.clone();
+    moved.location = Some("Conference Room B".to_string());
+    db.update_event(&moved).unwrap();
+
+    event.title = "Standup".to_string();
+    let error = serde_json::to_value(CommandError::from(db.update_event(&event).unwrap_err())).unwrap();
+    assert_eq!(error["kind"], "CONFLICT");
+    assert_eq!((error["item_type"].clone(), error["id"].clone()), (json!("EVENT"), json!(1)));
+    assert_eq!(error["current"]["location"], "Conference Room B");
+    assert_eq!(error["current"]["title"], "Morning Standup");
+
+    event.id = Some(99);
+    let error = serde_json::to_value(CommandError::from(db.update_event(&event).unwrap_err())).unwrap();
+    assert_eq!(error, json!({ "kind": "FAILED", "message": "Data error: EVENT 99 not found" }));
+}
+
+#[test]
+#[serial]
+fn test_saves_chain_on_the_returned_copy() {
+    let db = setup_test_db_with_data();
+    let mut task = db.get_task(1).unwrap();
+
+    task.title = "Draft project proposal".to_string();
+    let first = db.update_task(&task).unwrap();
+    task = first.clone();
+    task.priority = 3;
+    task = db.update_task(&task).unwrap();
+    assert_eq!((task.title.as_str(), task.priority), ("Draft project proposal", 3));
+    assert!(task.updated_at > first.updated_at, "Back-to-back saves still move the stamp forward");
+    assert!(matches!(db.update_task(&first), Err(DatabaseError::Conflict { .. })));
+
+    let category = db.get_category(1).unwrap();
+    let category = db.update_category(&category).unwrap();
+    db.update_category(&category).unwrap();
+}
@@ -0,0 +1,215 @@
+use crate::services::search_service::*;
+use super::setup_test_db_with_data;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn add_note(conn: &Connection, title: &str, content: &str) -> i64 {
+    conn.execute("INSERT INTO notes (title, content) VALUES (?1, ?2)", [title, content]).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn titles(page: &SearchPage) -> Vec<&str> {
+    page.results.iter().map(|r| r.title.as_str()).collect()
+}
+
+#[test]
+fn test_fts_query_quotes_terms() {
+    assert_eq!(fts_query("proj review").as_deref(), Some("\"proj\"* \"review\"*"));
+    assert_eq!(fts_query("\"NEAR(a OR\" -b").as_deref(), Some("\"NEAR\"* \"a\"* \"OR\"* \"b\"*"));
+    assert_eq!(fts_query("@ ☕"), None);
+    assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+}
+
+#[test]
+#[serial]
+fn test_prefix_match_across_entity_types() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Standup notes", "Blockers raised during the daily meeting");
+
+    let page = search_index(conn, "stand", &SearchScope::all(), None, 0).unwrap();
+
+    assert_eq!(page.total, 2);
+    assert!(page.results.iter().any(|r| r.item_type == "EVENT" && r.title == "Morning Standup"));
+    assert!(page.results.iter().any(|r| r.item_type == "NOTE" && r.description.as_deref() == Some("Blockers raised during the daily meeting")));
+    assert!(page.results.iter().all(|r| r.rank.is_some()));
+}
+
+#[test]
+#[serial]
+fn test_title_hits_rank_above_description_hits() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Groceries", "Milk and eggs");
+    add_note(conn, "Weekend", "Remember the groceries list for Saturday and a long description to dilute it");
+
+    let page = search_index(conn, "groceries", &SearchScope::all(), None, 0).unwrap();
+
+    assert_eq!(titles(&page)[2], "Weekend", "Description-only match should rank last");
+    let ranks: Vec<f64> = page.results.iter().map(|r| r.rank.unwrap()).collect();
+    assert!(ranks.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+#[serial]
+fn test_snippet_highlights_match() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let page = search_index(conn, "proposal", &[SearchScope::new("TASK")], None, 0).unwrap();
+
+    assert_eq!(page.results[0].snippet.as_deref(), Some("Complete project **proposal**"));
+}
+
+#[test]
+#[serial]
+fn test_index_follows_updates_and_deletes() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    conn.execute("UPDATE tasks SET title = 'Buy vegetables' WHERE id = 2", []).unwrap();
+    assert_eq!(search_index(conn, "vegetables", &SearchScope::all(), None, 0).unwrap().total, 1);
+    assert_eq!(search_index(conn, "Buy", &SearchScope::all(), None, 0).unwrap().total, 1);
+
+    conn.execute("DELETE FROM tasks WHERE id = 2", []).unwrap();
+    assert_eq!(search_index(conn, "vegetables", &SearchScope::all(), None, 0).unwrap().total, 0);
+
+    let note_id = add_note(conn, "Draft", "first version");
+    conn.execute("UPDATE notes SET content = 'second version' WHERE id = ?1", [note_id]).unwrap();
+    assert_eq!(search_index(conn, "first", &SearchScope::all(), None, 0).unwrap().total, 0);
+    assert_eq!(search_index(conn, "second", &SearchScope::all(), None, 0).unwrap().total, 1);
+}
+
+#[test]
+#[serial]
+fn test_accents_and_case_are_folded() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Café Résumé", "");
+
+    assert_eq!(search_index(conn, "cafe", &SearchScope::all(), None, 0).unwrap().total, 1);
+    assert_eq!(search_index(conn, "RÉSUMÉ", &SearchScope::all(), None, 0).unwrap().total, 1);
+}
+
+#[test]
+#[serial]
+fn test_wildcards_and_symbols_match_literally() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Discount", "50% off");
+    add_note(conn, "Meeting @ HQ", "");
+
+    let page = search_index(conn, "%", &SearchScope::all(), None, 0).unwrap();
+    assert_eq!(titles(&page), vec!["Discount"], "% must not act as a wildcard");
+
+    assert_eq!(search_index(conn, "_", &SearchScope::all(), None, 0).unwrap().total, 0);
+    assert_eq!(titles(&search_index(conn, "@", &SearchScope::all(), None, 0).unwrap()), vec!["Meeting @ HQ"]);
+    assert_eq!(search_index(conn, "\"unbalanced", &SearchScope::all(), None, 0).unwrap().total, 0);
+}
+
+#[test]
+#[serial]
+fn test_pagination() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    for i in 0..7 {
+        add_note(conn, &format!("Report {}", i), "quarterly report");
+    }
+
+    let first = search_index(conn, "report", &SearchScope::all(), Some(3), 0).unwrap();
+    let last = search_index(conn, "report", &SearchScope::all(), Some(3), 6).unwrap();
+
+    assert_eq!(first.total, 7);
+    assert_eq!(first.results.len(), 3);
+    assert_eq!(last.results.len(), 1);
+    assert!(first.results.iter().all(|r| r.id != last.results[0].id));
+}
+
+#[test]
+#[serial]
+fn test_scope_filters() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let scope = SearchScope::new("TASK").filter("x.status = ?", "IN_PROGRESS".to_string());
+    let page = search_index(conn, "", &[scope], None, 0).unwrap();
+    assert_eq!(titles(&page), vec!["Exercise routine"]);
+
+    assert!(search_index(conn, "x", &[SearchScope::new("CONTACT")], None, 0).is_err());
+}
+
+#[test]
+fn test_normalize_and_edit_distance() {
+    assert_eq!(normalize_text("Crème BRÛLÉE à la Façon"), "creme brulee a la facon");
+    assert_eq!(edit_distance("meeting", "meeting"), 0);
+    assert_eq!(edit_distance("meeting", "meetign"), 1, "Swapped letters count as one edit");
+    assert_eq!(edit_distance("groceries", "groseries"), 1);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+#[serial]
+fn test_accent_differences_are_exact_matches() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Café visit", "");
+    add_note(conn, "Cafe opening", "");
+    add_note(conn, "Internet café", "");
+
+    let page = search_index(conn, "CAFÉ", &SearchScope::all(), None, 0).unwrap();
+
+    assert_eq!(page.total, 3);
+    assert!(!page.fuzzy);
+    assert!(page.results.iter().all(|r| r.match_quality == 1.0));
+}
+
+#[test]
+#[serial]
+fn test_typos_fall_back_to_fuzzy_matching() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let page = search_index(conn, "grocereis", &SearchScope::all(), None, 0).unwrap();
+
+    assert!(page.fuzzy);
+    assert_eq!(titles(&page), vec!["Buy groceries"]);
+    assert!(page.results[0].match_quality < 1.0 && page.results[0].match_quality > 0.7);
+
+    let page = search_index(conn, "excercise rout", &SearchScope::all(), None, 0).unwrap();
+    assert_eq!(titles(&page), vec!["Exercise routine"], "Typos and half-typed words combine");
+}
+
+#[test]
+#[serial]
+fn test_exact_hits_rank_above_fuzzy_hits() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    add_note(conn, "Meting room booked", "");
+    add_note(conn, "Meeting agenda", "");
+
+    let page = search_index(conn, "meeting", &SearchScope::all(), None, 0).unwrap();
+
+    assert!(page.fuzzy, "Only one exact hit, so near misses are added");
+    assert_eq!(titles(&page), vec!["Meeting agenda", "Meting room booked"]);
+    assert_eq!(page.results[0].match_quality, 1.0);
+    assert!(page.results[1].match_quality < 1.0);
+}
+
+#[test]
+#[serial]
+fn test_fuzzy_fallback_only_when_hits_are_few() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    for title in ["Plan", "Plan B", "Plan C", "Plant watering"] {
+        add_note(conn, title, "");
+    }
+    add_note(conn, "Plane tickets", "");
+
+    let page = search_index(conn, "plan", &SearchScope::all(), None, 0).unwrap();
+    assert!(!page.fuzzy);
+    assert_eq!(page.total, 5);
+
+    let page = search_index(conn, "zq", &SearchScope::all(), None, 0).unwrap();
+    assert!(!page.fuzzy, "Short words are never fuzzed");
+    assert_eq!(page.total, 0);
+}
@@ -0,0 +1,97 @@
+use crate::services::history_service::*;
+use super::setup_test_db_with_data;
+use rusqlite::Connection;
+use serial_test::serial;
+
+/// Action, field, old and new value
+type Change = (String, Option<String>, Option<String>, Option<String>);
+
+fn changes(conn: &Connection, item_type: &str, item_id: i64, field: Option<&str>) -> Vec<Change> {
+    load_item_history(conn, item_type, item_id, field).unwrap()
+        .into_iter()
+        .map(|entry| (entry.action, entry.field, entry.old_value, entry.new_value))
+        .collect()
+}
+
+fn change(action: &str, field: Option<&str>, old_value: Option<&str>, new_value: Option<&str>) -> Change {
+    (action.to_string(), field.map(String::from), old_value.map(String::from), new_value.map(String::from))
+}
+
+#[test]
+#[serial]
+fn test_timeline_records_field_changes() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    db.bulk_shift_due_dates(&[2], 2).unwrap();
+    db.bulk_set_task_priority(&[2], 1).unwrap();
+    db.bulk_shift_due_dates(&[2], -1).unwrap();
+
+    assert_eq!(changes(conn, "TASK", 2, Some("due_date")), vec![
+        change("UPDATE", Some("due_date"), Some("2023-01-18 19:00:00"), Some("2023-01-20 19:00:00")),
+        change("UPDATE", Some("due_date"), Some("2023-01-20 19:00:00"), Some("2023-01-19 19:00:00")),
+    ]);
+    assert_eq!(changes(conn, "TASK", 2, None), vec![
+        change("CREATE", None, None, None),
+        change("UPDATE", Some("due_date"), Some("2023-01-18 19:00:00"), Some("2023-01-20 19:00:00")),
+        change("UPDATE", Some("priority"), Some("3"), Some("1")),
+        change("UPDATE", Some("due_date"), Some("2023-01-20 19:00:00"), Some("2023-01-19 19:00:00")),
+    ]);
+
+    conn.execute("UPDATE categories SET color = '#000000', name = name WHERE id = 1", []).unwrap();
+    assert_eq!(changes(conn, "CATEGORY", 1, None)[1..], [change("UPDATE", Some("color"), Some("#FF0000"), Some("#000000"))]);
+
+    db.trash_item("EVENT", 1).unwrap();
+    db.purge_item("EVENT", 1).unwrap();
+    let actions: Vec<(String, Option<String>)> = changes(conn, "EVENT", 1, None).into_iter().map(|c| (c.0, c.1)).collect();
+    assert_eq!(actions, vec![
+        ("CREATE".to_string(), None),
+        ("UPDATE".to_string(), Some("deleted_at".to_string())),
+        ("DELETE".to_string(), None),
+    ], "The timeline outlives the item");
+
+    assert!(load_item_history(conn, "TAG", 1, None).is_err());
+}
+
+#[test]
+#[serial]
+fn test_undo_adds_to_the_history() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    {
+        let _undo = db.undo_step("Rename note").unwrap();
+        conn.execute("INSERT INTO notes (title, content) VALUES ('Draft', 'Agenda')", []).unwrap();
+        conn.execute("UPDATE notes SET title = 'Agenda' WHERE id = 1", []).unwrap();
+    }
+    db.undo().unwrap();
+    db.redo().unwrap();
+
+    assert_eq!(changes(conn, "NOTE", 1, None), vec![
+        change("CREATE", None, None, None),
+        change("UPDATE", Some("title"), Some("Draft"), Some("Agenda")),
+        change("UPDATE", Some("title"), Some("Agenda"), Some("Draft")),
+        change("DELETE", None, None, None),
+        change("CREATE", None, None, None),
+        change("UPDATE", Some("title"), Some("Draft"), Some("Agenda")),
+    ]);
+}
+
+#[test]
+#[serial]
+fn test_history_retention() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute(
+        "INSERT INTO change_history (item_type, item_id, action, field, old_value, new_value, changed_at)
+         VALUES ('TASK', 1, 'UPDATE', 'title', 'Project proposal', 'Complete project proposal', '2020-01-01 00:00:00.000')",
+        [],
+    ).unwrap();
+    assert!(conn.execute("UPDATE change_history SET new_value = 'Rewritten'", []).is_err(), "History is append-only");
+
+    assert_eq!(purge_history(conn).unwrap(), 1);
+    assert_eq!(changes(conn, "TASK", 1, None), vec![change("CREATE", None, None, None)]);
+
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'history_retention_days'", []).unwrap();
+    conn.execute("INSERT INTO change_history (item_type, item_id, action, changed_at) VALUES ('TASK', 1, 'DELETE', '2020-01-01')", []).unwrap();
+    assert_eq!(purge_history(conn).unwrap(), 0, "0 keeps the history for good");
+}
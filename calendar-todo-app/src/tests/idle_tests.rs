@@ -0,0 +1,226 @@
+use crate::services::idle_service::*;
+use crate::services::settings_service::write_setting;
+use crate::services::time_tracking_service::{parse_timestamp, start_pomodoro_session, start_timer_entry, stop_timer_entry, TimeEntry};
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::{FixedOffset, NaiveDateTime};
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn start_task_timer(conn: &Connection, start: &str) -> i64 {
+    start_timer_entry(conn, &TimeEntry {
+        id: None,
+        item_type: "TASK".to_string(),
+        item_id: Some(1),
+        start_time: start.to_string(),
+        end_time: None,
+        duration_seconds: None,
+        timer_type: "MANUAL".to_string(),
+        created_at: None,
+    }).unwrap()
+}
+
+/// (start, end, duration) of every entry, ordered by start time
+fn spans(conn: &Connection) -> Vec<(String, Option<String>, Option<i64>)> {
+    let mut stmt = conn.prepare(
+        "SELECT start_time, end_time, duration_seconds FROM time_tracking ORDER BY start_time"
+    ).unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+fn finished(start: &str, end: &str, duration: i64) -> (String, Option<String>, Option<i64>) {
+    (start.to_string(), Some(end.to_string()), Some(duration))
+}
+
+fn running(start: &str) -> (String, Option<String>, Option<i64>) {
+    (start.to_string(), None, None)
+}
+
+fn use_action(conn: &Connection, action: &str) {
+    IdleConfig { threshold_minutes: 10, action: action.to_string() }.save(conn).unwrap();
+}
+
+/// Timer from 09:00, no input from 09:30 until 10:00
+fn idle_for_half_an_hour(conn: &Connection) -> IdleGap {
+    start_task_timer(conn, "2024-01-15 09:00:00");
+    record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 09:45:00").and_utc())
+        .unwrap()
+        .expect("Idle gap should start");
+    record_heartbeat(conn, "ACTIVE", at("2024-01-15 10:00:00"), &at("2024-01-15 10:00:00").and_utc())
+        .unwrap()
+        .expect("Idle gap should end")
+}
+
+#[test]
+#[serial]
+fn test_idle_below_threshold_is_ignored() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_task_timer(conn, "2024-01-15 09:00:00");
+
+    let gap = record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 09:39:59").and_utc()).unwrap();
+
+    assert!(gap.is_none());
+    assert_eq!(spans(conn), vec![running("2024-01-15 09:00:00")]);
+}
+
+#[test]
+#[serial]
+fn test_heartbeat_compares_times_across_offsets() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_task_timer(conn, "2024-01-15T09:00:00.000Z");
+    let last_input = parse_timestamp("2024-01-15T11:35:00+02:00").unwrap();
+    let clock = |value: &str, offset: FixedOffset| at(value).and_local_timezone(offset).unwrap();
+
+    // 11:40 two hours east of UTC is only five minutes after the last input
+    let east = clock("2024-01-15 11:40:00", FixedOffset::east_opt(2 * 3600).unwrap());
+    assert_eq!(record_heartbeat(conn, "IDLE", last_input, &east).unwrap(), None);
+
+    // 04:50 five hours west of UTC is fifteen minutes after it
+    let west = clock("2024-01-15 04:50:00", FixedOffset::west_opt(5 * 3600).unwrap());
+    let gap = record_heartbeat(conn, "IDLE", last_input, &west).unwrap().expect("Idle gap should start");
+    assert_eq!(gap.idle_started_at, "2024-01-15 09:35:00");
+}
+
+#[test]
+#[serial]
+fn test_idle_without_timer_is_ignored() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert!(record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 10:00:00").and_utc()).unwrap().is_none());
+
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+    assert!(record_heartbeat(conn, "IDLE", at("2024-01-15 09:05:00"), &at("2024-01-15 09:20:00").and_utc()).unwrap().is_none(),
+        "Pomodoro phases are left to the Pomodoro engine");
+}
+
+#[test]
+#[serial]
+fn test_pause_stops_timer_and_resumes_on_return() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "PAUSE");
+    start_task_timer(conn, "2024-01-15 09:00:00");
+
+    let gap = record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 09:45:00").and_utc()).unwrap().unwrap();
+    assert_eq!(gap.idle_ended_at, None);
+    assert_eq!(spans(conn), vec![finished("2024-01-15 09:00:00", "2024-01-15 09:30:00", 1800)],
+        "Timer should stop at the last input");
+
+    assert!(record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 09:50:00").and_utc()).unwrap().is_none(),
+        "Repeated idle heartbeats should not open another gap");
+
+    let gap = record_heartbeat(conn, "ACTIVE", at("2024-01-15 10:00:00"), &at("2024-01-15 10:00:00").and_utc()).unwrap().unwrap();
+    assert_eq!(gap.idle_seconds, Some(1800));
+    assert!(gap.resumed_entry_id.is_some());
+    assert_eq!(spans(conn)[1], running("2024-01-15 10:00:00"), "Timer should pick up again on return");
+}
+
+#[test]
+#[serial]
+fn test_pause_keep_books_the_gap() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "PAUSE");
+    let gap = idle_for_half_an_hour(conn);
+
+    assert_eq!(load_pending_idle_gaps(conn).unwrap().len(), 1);
+    let resolved = resolve_idle_period(conn, gap.id, true).unwrap();
+
+    assert_eq!(resolved.resolution.as_deref(), Some("KEPT"));
+    assert_eq!(spans(conn), vec![
+        finished("2024-01-15 09:00:00", "2024-01-15 09:30:00", 1800),
+        finished("2024-01-15 09:30:00", "2024-01-15 10:00:00", 1800),
+        running("2024-01-15 10:00:00"),
+    ]);
+    assert!(load_pending_idle_gaps(conn).unwrap().is_empty());
+    assert!(resolve_idle_period(conn, gap.id, false).is_err(), "A gap can only be resolved once");
+}
+
+#[test]
+#[serial]
+fn test_pause_discard_leaves_gap_out() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "PAUSE");
+    let gap = idle_for_half_an_hour(conn);
+
+    resolve_idle_period(conn, gap.id, false).unwrap();
+
+    assert_eq!(spans(conn).len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_trim_keeps_timer_running_until_discarded() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "TRIM");
+    let gap = idle_for_half_an_hour(conn);
+
+    assert_eq!(gap.resumed_entry_id, None);
+    assert_eq!(spans(conn), vec![running("2024-01-15 09:00:00")], "TRIM should not touch the timer while away");
+
+    resolve_idle_period(conn, gap.id, false).unwrap();
+
+    assert_eq!(spans(conn), vec![
+        finished("2024-01-15 09:00:00", "2024-01-15 09:30:00", 1800),
+        running("2024-01-15 10:00:00"),
+    ]);
+}
+
+#[test]
+#[serial]
+fn test_trim_keep_changes_nothing() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "TRIM");
+    let gap = idle_for_half_an_hour(conn);
+
+    resolve_idle_period(conn, gap.id, true).unwrap();
+
+    assert_eq!(spans(conn), vec![running("2024-01-15 09:00:00")]);
+}
+
+#[test]
+#[serial]
+fn test_trim_discard_after_timer_stopped() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    use_action(conn, "TRIM");
+    let gap = idle_for_half_an_hour(conn);
+    stop_timer_entry(conn, gap.time_entry_id.unwrap(), "2024-01-15 10:30:00").unwrap();
+
+    resolve_idle_period(conn, gap.id, false).unwrap();
+
+    assert_eq!(spans(conn), vec![
+        finished("2024-01-15 09:00:00", "2024-01-15 09:30:00", 1800),
+        finished("2024-01-15 10:00:00", "2024-01-15 10:30:00", 1800),
+    ], "The gap should be cut out of the stopped entry");
+}
+
+#[test]
+#[serial]
+fn test_idle_validation() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    assert_eq!(IdleConfig::load(conn).unwrap(), IdleConfig { threshold_minutes: 10, action: "PAUSE".to_string() });
+    assert!(IdleConfig { threshold_minutes: 0, action: "PAUSE".to_string() }.save(conn).is_err());
+    assert!(IdleConfig { threshold_minutes: i64::MAX, action: "PAUSE".to_string() }.save(conn).is_err());
+    write_setting(conn, "idle_threshold_minutes", &i64::MAX.to_string()).unwrap();
+    assert_eq!(IdleConfig::load(conn).unwrap().threshold_minutes, MAX_IDLE_THRESHOLD_MINUTES);
+    start_task_timer(conn, "2024-01-15 09:00:00");
+    assert_eq!(record_heartbeat(conn, "IDLE", at("2024-01-15 09:30:00"), &at("2024-01-15 10:00:00").and_utc()).unwrap(), None);
+    assert!(IdleConfig { threshold_minutes: 5, action: "STOP".to_string() }.save(conn).is_err());
+    assert!(record_heartbeat(conn, "AWAY", at("2024-01-15 09:00:00"), &at("2024-01-15 10:00:00").and_utc()).is_err());
+    assert!(resolve_idle_period(conn, 99999, true).is_err());
+}
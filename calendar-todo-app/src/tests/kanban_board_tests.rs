@@ -0,0 +1,81 @@
+use crate::db::{Database, models::KanbanBoard};
+use crate::services::task_service::load_board_tasks;
+use super::setup_test_db_with_data;
+use serial_test::serial;
+
+fn board(name: &str) -> KanbanBoard {
+    KanbanBoard { id: None, name: name.to_string(), board_order: 1 }
+}
+
+fn columns(db: &Database, board_id: i64) -> Vec<String> {
+    db.get_kanban_columns(board_id).unwrap().into_iter().map(|c| c.name).collect()
+}
+
+fn board_tasks(db: &Database, board_id: i64) -> Vec<(String, Option<i64>)> {
+    load_board_tasks(db.get_connection(), board_id).unwrap()
+        .into_iter()
+        .map(|t| (t.title, t.kanban_column_id))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_boards_have_their_own_columns() {
+    let db = setup_test_db_with_data();
+
+    let launch = db.create_kanban_board_from_template(&board("Launch"), "REVIEW").unwrap();
+    let empty = db.create_kanban_board(&board("Ideas")).unwrap();
+    assert_eq!(columns(&db, 1), vec!["To Do", "In Progress", "Completed"]);
+    assert_eq!(columns(&db, launch), vec!["To Do", "In Progress", "Review", "Done"]);
+    assert!(columns(&db, empty).is_empty());
+
+    assert!(db.create_kanban_board_from_template(&board("Launch"), "SCRUM").is_err());
+    assert!(db.create_kanban_board_from_template(&board(" "), "BASIC").is_err());
+    let names: Vec<String> = db.get_kanban_boards().unwrap().into_iter().map(|b| b.name).collect();
+    assert_eq!(names, vec!["Tasks", "Launch", "Ideas"], "Failed creations leave no board behind");
+
+    db.update_kanban_board(&KanbanBoard { id: Some(empty), name: "Backlog".to_string(), board_order: -1 }).unwrap();
+    assert_eq!(db.get_kanban_boards().unwrap()[0].name, "Backlog");
+    assert!(db.update_kanban_board(&KanbanBoard { id: Some(99), ..board("Missing") }).is_err());
+}
+
+#[test]
+#[serial]
+fn test_tasks_are_placed_per_board() {
+    let db = setup_test_db_with_data();
+    let launch = db.create_kanban_board_from_template(&board("Launch"), "REVIEW").unwrap();
+    let launch_columns = db.get_kanban_columns(launch).unwrap();
+    let (review, done) = (launch_columns[2].id.unwrap(), launch_columns[3].id.unwrap());
+
+    db.bulk_move_to_column(&[2], review).unwrap();
+    assert_eq!(board_tasks(&db, launch), vec![("Buy groceries".to_string(), Some(review))]);
+    assert_eq!(board_tasks(&db, 1), vec![
+        ("Complete project proposal".to_string(), Some(1)),
+        ("Exercise routine".to_string(), Some(2)),
+    ]);
+
+    db.add_task_dependency(1, 2).unwrap();
+    let result = db.bulk_move_to_column(&[1], review).unwrap();
+    assert_eq!(result.applied(), 0, "Blocked tasks stay in the first column of any board");
+
+    db.bulk_move_to_column(&[2], done).unwrap();
+    let status: String = db.get_connection().query_row("SELECT status FROM tasks WHERE id = 2", [], |row| row.get(0)).unwrap();
+    assert_eq!(status, "COMPLETED", "The board's last column completes tasks, whatever its name");
+    assert!(db.bulk_move_to_column(&[1], 99).is_err());
+}
+
+#[test]
+#[serial]
+fn test_deleting_a_board_unplaces_its_tasks() {
+    let db = setup_test_db_with_data();
+    let launch = db.create_kanban_board_from_template(&board("Launch"), "BASIC").unwrap();
+    let to_do = db.get_kanban_columns(launch).unwrap()[0].id.unwrap();
+    db.bulk_move_to_column(&[3], to_do).unwrap();
+
+    db.delete_kanban_board(launch).unwrap();
+    assert!(db.get_kanban_columns(launch).unwrap().is_empty());
+    let column: Option<i64> = db.get_connection().query_row("SELECT kanban_column_id FROM tasks WHERE id = 3", [], |row| row.get(0)).unwrap();
+    assert_eq!(column, None);
+    assert_eq!(board_tasks(&db, 1).len(), 2);
+    assert!(db.delete_kanban_board(launch).is_err());
+}
@@ -7,27 +7,35 @@ use serial_test::serial;
 mod kanban_service_tests {
     use super::*;
 
+    /// A board of the test's own, the test schema already gives board 1 its columns
+    fn new_board(scenario: &TestScenario) -> i64 {
+        scenario.get_db()
+            .create_kanban_board(&KanbanBoard { id: None, name: "Test Board".to_string(), board_order: 1 })
+            .expect("Failed to create board")
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_get_kanban_columns_success() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Create default columns
         let default_columns = vec![
-            KanbanColumn { id: None, name: "To Do".to_string(), column_order: 1 },
-            KanbanColumn { id: None, name: "In Progress".to_string(), column_order: 2 },
-            KanbanColumn { id: None, name: "Done".to_string(), column_order: 3 },
+            KanbanColumn { id: None, board_id, name: "To Do".to_string(), column_order: 1 },
+            KanbanColumn { id: None, board_id, name: "In Progress".to_string(), column_order: 2 },
+            KanbanColumn { id: None, board_id, name: "Done".to_string(), column_order: 3 },
         ];
         
         for column in &default_columns {
             let conn = scenario.get_db().get_connection();
             conn.execute(
-                "INSERT INTO kanban_columns (name, column_order) VALUES (?1, ?2)",
-                [&column.name, &column.column_order.to_string()]
+                "INSERT INTO kanban_columns (board_id, name, column_order) VALUES (?1, ?2, ?3)",
+                rusqlite::params![board_id, column.name, column.column_order]
             ).expect("Failed to insert column");
         }
         
-        let result = get_kanban_columns(scenario.get_db().into()).await;
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await;
         
         assert!(result.is_ok(), "Should retrieve columns successfully");
         let columns = result.unwrap();
@@ -41,8 +49,9 @@ mod kanban_service_tests {
     #[serial]
     async fn test_get_kanban_columns_empty() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
-        let result = get_kanban_columns(scenario.get_db().into()).await;
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await;
         
         assert!(result.is_ok(), "Should handle empty columns gracefully");
         let columns = result.unwrap();
@@ -53,6 +62,7 @@ mod kanban_service_tests {
     #[serial]
     async fn test_get_kanban_columns_ordered() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Insert columns in reverse order to test ordering
         let columns_data = vec![
@@ -64,12 +74,12 @@ mod kanban_service_tests {
         for (name, order) in &columns_data {
             let conn = scenario.get_db().get_connection();
             conn.execute(
-                "INSERT INTO kanban_columns (name, column_order) VALUES (?1, ?2)",
-                [name, &order.to_string()]
+                "INSERT INTO kanban_columns (board_id, name, column_order) VALUES (?1, ?2, ?3)",
+                rusqlite::params![board_id, name, order]
             ).expect("Failed to insert column");
         }
         
-        let result = get_kanban_columns(scenario.get_db().into()).await;
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await;
         
         assert!(result.is_ok(), "Should retrieve columns successfully");
         let columns = result.unwrap();
@@ -90,6 +100,7 @@ mod kanban_service_tests {
         let scenario = TestScenario::new();
         let new_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "Review".to_string(),
             column_order: 4,
         };
@@ -119,6 +130,7 @@ mod kanban_service_tests {
         // Test empty name
         let empty_name_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "".to_string(),
             column_order: 1,
         };
@@ -129,6 +141,7 @@ mod kanban_service_tests {
         // Test negative order
         let negative_order_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "Test".to_string(),
             column_order: -1,
         };
@@ -143,6 +156,7 @@ mod kanban_service_tests {
         let scenario = TestScenario::new();
         let special_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "Review & Test 🚀".to_string(),
             column_order: 1,
         };
@@ -167,6 +181,7 @@ mod kanban_service_tests {
         // Create initial column
         let initial_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "To Do".to_string(),
             column_order: 1,
         };
@@ -176,6 +191,7 @@ mod kanban_service_tests {
         // Update column
         let updated_column = KanbanColumn {
             id: Some(column_id),
+            board_id: 1,
             name: "Backlog".to_string(),
             column_order: 1,
         };
@@ -197,6 +213,7 @@ mod kanban_service_tests {
         let scenario = TestScenario::new();
         let nonexistent_column = KanbanColumn {
             id: Some(99999),
+            board_id: 1,
             name: "Nonexistent".to_string(),
             column_order: 1,
         };
@@ -222,6 +239,7 @@ mod kanban_service_tests {
         for (name, order) in &columns {
             let column = KanbanColumn {
                 id: None,
+                board_id: 1,
                 name: name.to_string(),
                 column_order: *order,
             };
@@ -232,6 +250,7 @@ mod kanban_service_tests {
         // Reorder: move "Done" to position 1
         let reordered_column = KanbanColumn {
             id: Some(column_ids[2]),
+            board_id: 1,
             name: "Done".to_string(),
             column_order: 1,
         };
@@ -252,6 +271,7 @@ mod kanban_service_tests {
         let scenario = TestScenario::new();
         let column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "Temporary".to_string(),
             column_order: 1,
         };
@@ -287,6 +307,7 @@ mod kanban_service_tests {
         // Create column
         let column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "With Tasks".to_string(),
             column_order: 1,
         };
@@ -311,6 +332,7 @@ mod kanban_service_tests {
     #[serial]
     async fn test_bulk_column_operations() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Create multiple columns
         let column_names = vec!["Backlog", "To Do", "In Progress", "Review", "Done"];
@@ -319,6 +341,7 @@ mod kanban_service_tests {
         for (i, name) in column_names.iter().enumerate() {
             let column = KanbanColumn {
                 id: None,
+                board_id,
                 name: name.to_string(),
                 column_order: (i + 1) as i32,
             };
@@ -328,7 +351,7 @@ mod kanban_service_tests {
         }
         
         // Verify all columns created
-        let result = get_kanban_columns(scenario.get_db().into()).await;
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await;
         assert!(result.is_ok(), "Should retrieve all columns");
         let columns = result.unwrap();
         assert_eq!(columns.len(), 5, "Should have 5 columns");
@@ -344,10 +367,12 @@ mod kanban_service_tests {
     #[serial]
     async fn test_column_workflow_integration() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Create initial columns
         let column = KanbanColumn {
             id: None,
+            board_id,
             name: "To Do".to_string(),
             column_order: 1,
         };
@@ -356,6 +381,7 @@ mod kanban_service_tests {
         // Update column name
         let updated_column = KanbanColumn {
             id: Some(column_id),
+            board_id,
             name: "Backlog".to_string(),
             column_order: 1,
         };
@@ -373,7 +399,7 @@ mod kanban_service_tests {
         assert_eq!(task_column_id, Some(column_id));
         
         // Get columns and verify structure
-        let result = get_kanban_columns(scenario.get_db().into()).await.unwrap();
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await.unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "Backlog");
     }
@@ -382,6 +408,7 @@ mod kanban_service_tests {
     #[serial]
     async fn test_performance_large_column_set() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Create 100 columns to test performance
         let start_time = std::time::Instant::now();
@@ -389,6 +416,7 @@ mod kanban_service_tests {
         for i in 1..=100 {
             let column = KanbanColumn {
                 id: None,
+                board_id,
                 name: format!("Column {}", i),
                 column_order: i,
             };
@@ -400,7 +428,7 @@ mod kanban_service_tests {
         
         // Retrieve all columns
         let retrieve_start = std::time::Instant::now();
-        let result = get_kanban_columns(scenario.get_db().into()).await.unwrap();
+        let result = get_kanban_columns(board_id, scenario.get_db().into()).await.unwrap();
         let retrieve_time = retrieve_start.elapsed();
         
         assert_eq!(result.len(), 100, "Should create and retrieve 100 columns");
@@ -419,12 +447,14 @@ mod kanban_service_tests {
     #[serial]
     async fn test_concurrent_column_operations() {
         let scenario = TestScenario::new();
+        let board_id = new_board(&scenario);
         
         // Create multiple columns concurrently
         let futures: Vec<_> = (1..=10)
             .map(|i| {
                 let column = KanbanColumn {
                     id: None,
+                    board_id,
                     name: format!("Concurrent Column {}", i),
                     column_order: i,
                 };
@@ -440,7 +470,7 @@ mod kanban_service_tests {
         }
         
         // Verify all columns were created
-        let columns = get_kanban_columns(scenario.get_db().into()).await.unwrap();
+        let columns = get_kanban_columns(board_id, scenario.get_db().into()).await.unwrap();
         assert_eq!(columns.len(), 10, "Should have 10 columns");
     }
 
@@ -453,6 +483,7 @@ mod kanban_service_tests {
         let long_name = "A".repeat(1000);
         let long_name_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: long_name.clone(),
             column_order: 1,
         };
@@ -464,6 +495,7 @@ mod kanban_service_tests {
         // Test maximum column order
         let max_order_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "Max Order".to_string(),
             column_order: i32::MAX,
         };
@@ -474,6 +506,7 @@ mod kanban_service_tests {
         // Test unicode characters
         let unicode_column = KanbanColumn {
             id: None,
+            board_id: 1,
             name: "待办事项".to_string(),
             column_order: 2,
         };
@@ -481,7 +514,7 @@ mod kanban_service_tests {
         let result = create_kanban_column(unicode_column, scenario.get_db().into()).await;
         assert!(result.is_ok(), "Should handle unicode characters");
         
-        let columns = get_kanban_columns(scenario.get_db().into()).await.unwrap();
+        let columns = get_kanban_columns(1, scenario.get_db().into()).await.unwrap();
         let unicode_col = columns.iter().find(|c| c.name == "待办事项").unwrap();
         assert_eq!(unicode_col.name, "待办事项");
     }
@@ -1,18 +1,43 @@
 use crate::db::Database;
 
+pub mod billing_tests;
 pub mod category_tests;
 pub mod event_tests;
+pub mod idle_tests;
 pub mod kanban_tests;
 pub mod note_tests;
 pub mod participant_tests;
+pub mod pomodoro_tests;
 pub mod recurring_tests;
 pub mod task_tests;
+pub mod time_budget_tests;
 pub mod time_tracking_tests;
+pub mod timesheet_tests;
+pub mod time_report_tests;
 pub mod search_tests;
+pub mod full_text_search_tests;
+pub mod search_query_tests;
+pub mod saved_search_tests;
+pub mod search_facets_tests;
+pub mod subtask_tests;
+pub mod task_dependency_tests;
+pub mod recurring_task_tests;
+pub mod tag_tests;
+pub mod quick_add_tests;
+pub mod task_start_date_tests;
+pub mod bulk_task_tests;
+pub mod trash_tests;
+pub mod undo_tests;
+pub mod history_tests;
+pub mod concurrency_tests;
+pub mod kanban_board_tests;
 pub mod reminder_tests;
+pub mod reminder_delivery_tests;
+pub mod settings_tests;
 pub mod holiday_feed_tests;
 pub mod models_tests;
 pub mod operations_tests;
+pub mod performance_tests;
 pub mod test_utilities;
 
 // Helper function to create a test database
@@ -43,7 +68,7 @@ pub fn seed_test_data(db: &Database) {
     
     // Seed recurring rules
     conn.execute(
-        "INSERT INTO recurring_rules (frequency, interval_value, days_of_week) VALUES
+        "INSERT INTO recurring_rules (frequency, interval, days_of_week) VALUES
          ('WEEKLY', 1, '1,3,5'),
          ('MONTHLY', 1, NULL),
          ('DAILY', 2, NULL)",
@@ -62,8 +87,8 @@ pub fn seed_test_data(db: &Database) {
     // Seed tasks
     conn.execute(
         "INSERT INTO tasks (title, description, due_date, priority, status, category_id, kanban_column_id, kanban_order) VALUES
-         ('Complete project proposal', 'Write and submit the Q1 project proposal', '2023-01-31 17:00:00', 1, 'TODO', 1, 1, 1),
-         ('Buy groceries', 'Weekly grocery shopping', '2023-01-18 19:00:00', 3, 'TODO', 2, 1, 2),
+         ('Complete project proposal', 'Write and submit the Q1 project proposal', '2023-01-31 17:00:00', 1, 'PENDING', 1, 1, 1),
+         ('Buy groceries', 'Weekly grocery shopping', '2023-01-18 19:00:00', 3, 'PENDING', 2, 1, 2),
          ('Exercise routine', 'Complete 30-minute workout', '2023-01-16 18:00:00', 2, 'IN_PROGRESS', 3, 2, 1)",
         [],
     ).expect("Failed to seed tasks");
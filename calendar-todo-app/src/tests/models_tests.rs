@@ -118,7 +118,7 @@ async fn test_task_model_creation() {
         description: Some("Finish the calendar app".to_string()),
         due_date: Some("2023-01-31 23:59:59".to_string()),
         priority: 1,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: Some(1),
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -129,7 +129,7 @@ async fn test_task_model_creation() {
     };
 
     assert_eq!(task.title, "Complete Project");
-    assert_eq!(task.status, "TODO");
+    assert_eq!(task.status, "PENDING");
     assert_eq!(task.priority, 1);
     assert!(task.due_date.is_some());
     assert!(task.completed_at.is_none());
@@ -206,14 +206,14 @@ async fn test_recurring_rule_from_row() {
 
     // Insert test recurring rule
     conn.execute(
-        "INSERT INTO recurring_rules (frequency, interval_value, days_of_week, end_date) 
+        "INSERT INTO recurring_rules (frequency, interval, days_of_week, end_date) 
          VALUES (?1, ?2, ?3, ?4)",
         ["MONTHLY", "1", "1,15", "2023-12-31 23:59:59"],
     ).expect("Failed to insert test recurring rule");
 
     // Query and test from_row
     let mut stmt = conn.prepare(
-        "SELECT id, frequency, interval_value, days_of_week, day_of_month, month_of_year, end_date, end_occurrences, created_at 
+        "SELECT id, frequency, interval AS interval_value, days_of_week, day_of_month, month_of_year, end_date, end_occurrences, created_at 
          FROM recurring_rules WHERE frequency = ?"
     ).unwrap();
     let rule = stmt.query_row(["MONTHLY"], RecurringRule::from_row).expect("Failed to query recurring rule");
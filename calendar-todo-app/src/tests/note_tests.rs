@@ -147,11 +147,14 @@ mod note_service_tests {
         
         assert!(result.is_ok(), "Should delete note successfully");
         
-        // Verify deletion
+        // Verify the note went to the trash
         let conn = scenario.get_db().get_connection();
-        let mut stmt = conn.prepare("SELECT COUNT(*) FROM notes WHERE id = ?").unwrap();
+        let mut stmt = conn.prepare("SELECT COUNT(*) FROM notes WHERE id = ? AND deleted_at IS NULL").unwrap();
+        let count: i32 = stmt.query_row([note_id], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0, "Note should no longer be live");
+        let mut stmt = conn.prepare("SELECT COUNT(*) FROM notes WHERE id = ? AND deleted_at IS NOT NULL").unwrap();
         let count: i32 = stmt.query_row([note_id], |row| row.get(0)).unwrap();
-        assert_eq!(count, 0, "Note should be deleted");
+        assert_eq!(count, 1, "Note should be in the trash");
     }
 
     #[tokio::test]
@@ -196,7 +196,7 @@ async fn test_task_create_and_get() {
         description: Some("Task description".to_string()),
         due_date: Some("2023-01-31 23:59:59".to_string()),
         priority: 1,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: Some(category_id),
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -211,7 +211,7 @@ async fn test_task_create_and_get() {
 
     let retrieved = db.get_task(id).expect("Failed to get task");
     assert_eq!(retrieved.title, "Test Task");
-    assert_eq!(retrieved.status, "TODO");
+    assert_eq!(retrieved.status, "PENDING");
     assert_eq!(retrieved.priority, 1);
     assert_eq!(retrieved.category_id, Some(category_id));
     assert!(retrieved.completed_at.is_none());
@@ -228,7 +228,7 @@ async fn test_task_update() {
         description: None,
         due_date: None,
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -265,7 +265,7 @@ async fn test_task_delete() {
         description: None,
         due_date: None,
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -279,7 +279,11 @@ async fn test_task_delete() {
     db.delete_task(id).expect("Failed to delete task");
 
     let result = db.get_task(id);
-    assert!(result.is_err(), "Task should be deleted");
+    assert!(result.is_err(), "Task should be in the trash");
+    let trashed: bool = db.get_connection()
+        .query_row("SELECT deleted_at IS NOT NULL FROM tasks WHERE id = ?", [id], |row| row.get(0))
+        .expect("Trashed task should still be stored");
+    assert!(trashed, "Task should be in the trash");
 }
 
 #[tokio::test]
@@ -335,7 +339,7 @@ async fn test_task_update_without_id_fails() {
         description: None,
         due_date: None,
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
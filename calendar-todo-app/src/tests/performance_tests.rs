@@ -1,42 +1,47 @@
 use super::setup_test_db;
-use crate::services::{event_service, task_service, search_service};
+use crate::services::{task_service, search_service};
 use std::time::Instant;
-use crate::db::models::{Event, Task};
+use crate::db::models::Task;
+use serial_test::serial;
 
 #[tokio::test]
+#[serial]
 async fn test_search_performance() {
     let db = setup_test_db();
-    let db_state = tauri::State::new(db);
-    
-    // Insert test data
-    for i in 0..100 {
-        let event = Event {
-            id: None,
-            title: format!("Test Event {}", i),
-            description: Some(format!("Description for event {}", i)),
-            start_time: "2025-04-11T10:00:00Z".to_string(),
-            end_time: "2025-04-11T11:00:00Z".to_string(),
-            is_all_day: false,
-            location: Some("Test Location".to_string()),
-            priority: Some(1),
-            category_id: None,
-            recurring_rule_id: None,
-            created_at: None,
-            updated_at: None,
-        };
-        event_service::create_event(event, db_state.clone()).await.unwrap();
+
+    // Insert test data, one event in a hundred mentions the search term
+    let conn = db.get_connection();
+    let tx = conn.unchecked_transaction().unwrap();
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO events (title, description, start_time, end_time, location, priority)
+             VALUES (?1, ?2, '2025-04-11T10:00:00Z', '2025-04-11T11:00:00Z', 'Room 101', 1)"
+        ).unwrap();
+        for i in 0..100_000 {
+            let title = if i % 100 == 0 { format!("Test Event {}", i) } else { format!("Event {}", i) };
+            stmt.execute([title, format!("Description for event {}", i)]).unwrap();
+        }
     }
+    tx.commit().unwrap();
 
     // Measure search performance
     let start = Instant::now();
-    let results = search_service::search_all("Test".to_string(), db_state.clone()).await.unwrap();
+    let page = search_service::search_index(
+        conn,
+        "Test",
+        &search_service::SearchScope::all(),
+        Some(search_service::DEFAULT_PAGE_SIZE),
+        0,
+    ).unwrap();
     let duration = start.elapsed();
 
-    assert!(results.len() > 0);
+    assert_eq!(page.total, 1000);
+    assert_eq!(page.results.len() as i64, search_service::DEFAULT_PAGE_SIZE);
     assert!(duration.as_millis() < 100, "Search took longer than 100ms: {:?}", duration);
 }
 
 #[tokio::test]
+#[serial]
 async fn test_database_operations_performance() {
     let db = setup_test_db();
     let db_state = tauri::State::new(db);
@@ -49,10 +54,13 @@ async fn test_database_operations_performance() {
             title: format!("Performance Test Task {}", i),
             description: Some(format!("Description for task {}", i)),
             due_date: Some("2025-04-11T10:00:00Z".to_string()),
+            priority: 1,
             status: "PENDING".to_string(),
-            priority: Some(1),
             category_id: None,
+            recurring_rule_id: None,
             kanban_column_id: None,
+            kanban_order: None,
+            completed_at: None,
             created_at: None,
             updated_at: None,
         };
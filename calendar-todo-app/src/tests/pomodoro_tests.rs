@@ -0,0 +1,272 @@
+use crate::services::time_tracking_service::*;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::NaiveDateTime;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// (item_id, start_time, end_time, duration_seconds)
+type EntryRow = (Option<i64>, String, Option<String>, Option<i64>);
+
+fn pomodoro_entries(conn: &Connection) -> Vec<EntryRow> {
+    let mut stmt = conn.prepare(
+        "SELECT item_id, start_time, end_time, duration_seconds
+         FROM time_tracking WHERE timer_type = 'POMODORO' ORDER BY id"
+    ).unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+fn status(conn: &Connection, now: &str) -> PomodoroStatus {
+    pomodoro_status(conn, at(now)).unwrap().expect("Expected an active session")
+}
+
+#[test]
+#[serial]
+fn test_pomodoro_config_from_settings() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    assert_eq!(PomodoroConfig::load(conn).unwrap(), PomodoroConfig {
+        work_minutes: 25,
+        short_break_minutes: 5,
+        long_break_minutes: 15,
+        long_break_interval: 4,
+    });
+
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'pomodoro_break_minutes'", []).unwrap();
+    assert_eq!(PomodoroConfig::load(conn).unwrap().short_break_minutes, 1, "Phase lengths should never be zero");
+}
+
+#[test]
+#[serial]
+fn test_start_pomodoro_opens_work_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let changes = start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].from_phase, None);
+    assert_eq!(changes[0].to_phase.as_deref(), Some("WORK"));
+
+    let current = status(conn, "2024-01-15 09:10:00");
+    assert_eq!(current.session.state, "RUNNING");
+    assert_eq!(current.remaining_seconds, 15 * 60);
+
+    let entries = pomodoro_entries(conn);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, Some(1), "Entry should be linked to the task");
+    assert_eq!(entries[0].2, None, "Work entry should stay open while the phase runs");
+}
+
+#[test]
+#[serial]
+fn test_only_one_active_session() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+    let second = start_pomodoro_session(conn, "TASK", Some(2), at("2024-01-15 09:05:00"));
+
+    assert!(second.is_err(), "A second session should be rejected while one is active");
+}
+
+#[test]
+#[serial]
+fn test_completed_work_interval_writes_entry_and_starts_break() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    assert!(tick_pomodoro(conn, at("2024-01-15 09:24:59")).unwrap().is_empty());
+
+    let changes = tick_pomodoro(conn, at("2024-01-15 09:26:00")).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].from_phase.as_deref(), Some("WORK"));
+    assert_eq!(changes[0].to_phase.as_deref(), Some("SHORT_BREAK"));
+    assert_eq!(changes[0].changed_at, "2024-01-15 09:25:00", "Phase should end exactly on time");
+    assert_eq!(changes[0].completed_work_intervals, 1);
+
+    let entries = pomodoro_entries(conn);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].2.as_deref(), Some("2024-01-15 09:25:00"));
+    assert_eq!(entries[0].3, Some(1500));
+
+    let current = status(conn, "2024-01-15 09:26:00");
+    assert_eq!(current.session.phase, "SHORT_BREAK");
+    assert_eq!(current.remaining_seconds, 4 * 60);
+}
+
+#[test]
+#[serial]
+fn test_long_break_after_interval() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE settings SET value = '2' WHERE key = 'pomodoro_long_break_interval'", []).unwrap();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    tick_pomodoro(conn, at("2024-01-15 09:25:00")).unwrap(); // work -> short break
+    tick_pomodoro(conn, at("2024-01-15 09:30:00")).unwrap(); // short break -> work (paused)
+    control_pomodoro_session(conn, "RESUME", at("2024-01-15 09:31:00")).unwrap();
+    let changes = tick_pomodoro(conn, at("2024-01-15 09:56:00")).unwrap();
+
+    assert_eq!(changes[0].to_phase.as_deref(), Some("LONG_BREAK"));
+    assert_eq!(status(conn, "2024-01-15 09:56:00").remaining_seconds, 15 * 60);
+}
+
+#[test]
+#[serial]
+fn test_break_end_waits_for_resume() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+    tick_pomodoro(conn, at("2024-01-15 09:25:00")).unwrap();
+
+    let changes = tick_pomodoro(conn, at("2024-01-15 09:30:00")).unwrap();
+    assert_eq!(changes[0].to_phase.as_deref(), Some("WORK"));
+    assert_eq!(changes[0].state, "PAUSED");
+    assert_eq!(pomodoro_entries(conn).len(), 1, "No work entry until the user resumes");
+
+    control_pomodoro_session(conn, "RESUME", at("2024-01-15 09:40:00")).unwrap();
+    let entries = pomodoro_entries(conn);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].1, "2024-01-15 09:40:00");
+}
+
+#[test]
+#[serial]
+fn test_pause_and_resume_exclude_paused_time() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    control_pomodoro_session(conn, "PAUSE", at("2024-01-15 09:10:00")).unwrap();
+    assert_eq!(status(conn, "2024-01-15 09:50:00").remaining_seconds, 15 * 60, "Paused time should not count");
+    assert!(tick_pomodoro(conn, at("2024-01-15 09:50:00")).unwrap().is_empty());
+
+    control_pomodoro_session(conn, "RESUME", at("2024-01-15 10:00:00")).unwrap();
+    let changes = tick_pomodoro(conn, at("2024-01-15 10:20:00")).unwrap();
+
+    assert_eq!(changes[0].changed_at, "2024-01-15 10:15:00");
+    assert_eq!(pomodoro_entries(conn)[0].3, Some(1500), "Entry should hold worked time only");
+}
+
+#[test]
+#[serial]
+fn test_invalid_transitions_are_rejected() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert!(control_pomodoro_session(conn, "PAUSE", at("2024-01-15 09:00:00")).is_err(), "No session to pause");
+
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+    assert!(control_pomodoro_session(conn, "RESUME", at("2024-01-15 09:01:00")).is_err());
+    assert!(control_pomodoro_session(conn, "REWIND", at("2024-01-15 09:01:00")).is_err());
+}
+
+#[test]
+#[serial]
+fn test_skip_work_does_not_count_interval() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    let changes = control_pomodoro_session(conn, "SKIP", at("2024-01-15 09:10:00")).unwrap();
+
+    assert_eq!(changes[0].to_phase.as_deref(), Some("SHORT_BREAK"));
+    assert_eq!(changes[0].completed_work_intervals, 0);
+    assert_eq!(pomodoro_entries(conn)[0].3, Some(600), "Time worked before skipping should be kept");
+
+    let changes = control_pomodoro_session(conn, "SKIP", at("2024-01-15 09:12:00")).unwrap();
+    assert_eq!(changes[0].to_phase.as_deref(), Some("WORK"));
+    assert_eq!(changes[0].state, "RUNNING", "Skipping a break should start work right away");
+    assert_eq!(pomodoro_entries(conn).len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_stop_closes_session_and_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    let changes = control_pomodoro_session(conn, "STOP", at("2024-01-15 09:05:00")).unwrap();
+
+    assert_eq!(changes[0].to_phase, None);
+    assert!(pomodoro_status(conn, at("2024-01-15 09:05:00")).unwrap().is_none());
+    assert_eq!(pomodoro_entries(conn)[0].3, Some(300));
+
+    assert!(start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:06:00")).is_ok(),
+        "A new session can start after stopping");
+}
+
+#[test]
+#[serial]
+fn test_session_catches_up_after_restart() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    // App closed at 09:05 and reopened the next morning
+    let changes = tick_pomodoro(conn, at("2024-01-16 08:00:00")).unwrap();
+
+    assert_eq!(changes.len(), 2, "Work and the following break should complete, then stop");
+    assert_eq!(changes[1].changed_at, "2024-01-15 09:30:00");
+    assert_eq!(pomodoro_entries(conn).len(), 1, "Time spent away must not be booked as work");
+
+    let current = status(conn, "2024-01-16 08:00:00");
+    assert_eq!(current.session.phase, "WORK");
+    assert_eq!(current.session.state, "PAUSED");
+}
+
+#[test]
+#[serial]
+fn test_running_work_phase_end() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    assert_eq!(running_work_phase_end(conn).unwrap(), Some(at("2024-01-15 09:25:00")));
+
+    control_pomodoro_session(conn, "PAUSE", at("2024-01-15 09:10:00")).unwrap();
+    assert_eq!(running_work_phase_end(conn).unwrap(), None, "A paused session should not mute reminders");
+}
+
+#[test]
+#[serial]
+fn test_manual_timers_and_pomodoro_share_one_clock() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    // The UI sends toISOString() values, which are stored as UTC like Pomodoro times
+    let manual = |start: &str| TimeEntry {
+        id: None,
+        item_type: "TASK".to_string(),
+        item_id: Some(1),
+        start_time: start.to_string(),
+        end_time: None,
+        duration_seconds: None,
+        timer_type: "MANUAL".to_string(),
+        created_at: None,
+    };
+    let first = start_timer_entry(conn, &manual("2024-01-15T09:00:00.000Z")).unwrap();
+
+    start_pomodoro_session(conn, "TASK", Some(2), at("2024-01-15 09:30:00")).unwrap();
+    let stopped = load_time_entry(conn, first).unwrap().unwrap();
+    assert_eq!(
+        (stopped.end_time.as_deref(), stopped.duration_seconds),
+        (Some("2024-01-15 09:30:00"), Some(1800)),
+        "The manual timer stops when the Pomodoro starts"
+    );
+
+    start_timer_entry(conn, &manual("2024-01-15T09:40:00.000Z")).unwrap();
+    assert_eq!(pomodoro_entries(conn), vec![
+        (Some(2), "2024-01-15 09:30:00".to_string(), Some("2024-01-15 09:40:00".to_string()), Some(600)),
+    ]);
+    assert_eq!(status(conn, "2024-01-15 09:45:00").session.state, "PAUSED");
+}
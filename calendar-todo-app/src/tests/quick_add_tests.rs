@@ -0,0 +1,288 @@
+use crate::services::quick_add_service::*;
+use super::setup_test_db_with_data;
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use rusqlite::Connection;
+use serial_test::serial;
+
+/// Wednesday 18 January 2023, 10:00 UTC, weeks starting on Monday
+fn context() -> QuickAddContext {
+    QuickAddContext {
+        now: NaiveDate::from_ymd_opt(2023, 1, 18).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+        utc_offset: FixedOffset::east_opt(0).unwrap(),
+        week_start_day: 1,
+    }
+}
+
+fn parse(conn: &Connection, input: &str) -> QuickAddDraft {
+    parse_quick_add(conn, input, None, &context()).unwrap()
+}
+
+#[test]
+#[serial]
+fn test_quick_add_task_phrases() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    // Input, title, due date
+    let corpus = [
+        ("Buy milk", "Buy milk", None),
+        ("Submit report today", "Submit report", Some("2023-01-18")),
+        ("Submit report tomorrow", "Submit report", Some("2023-01-19")),
+        ("Submit report tmrw", "Submit report", Some("2023-01-19")),
+        ("Submit report friday", "Submit report", Some("2023-01-20")),
+        ("Submit report on Wednesday", "Submit report", Some("2023-01-18")),
+        ("Submit report this friday", "Submit report", Some("2023-01-20")),
+        ("Submit report next friday", "Submit report", Some("2023-01-27")),
+        ("Submit report next monday", "Submit report", Some("2023-01-23")),
+        ("Plan sprint next week", "Plan sprint", Some("2023-01-23")),
+        ("Close the books next month", "Close the books", Some("2023-02-01")),
+        ("Renew passport in 3 days", "Renew passport", Some("2023-01-21")),
+        ("Renew passport in 2 weeks", "Renew passport", Some("2023-02-01")),
+        ("Renew passport in 100000000 days", "Renew passport in 100000000 days", None),
+        ("Renew passport in 99999999999999 hours", "Renew passport in 99999999999999 hours", None),
+        ("Check the oven in 30 minutes", "Check the oven", Some("2023-01-18T10:30:00.000Z")),
+        ("Check the oven in 2 hours", "Check the oven", Some("2023-01-18T12:00:00.000Z")),
+        ("File taxes feb 3", "File taxes", Some("2023-02-03")),
+        ("File taxes 3 Feb", "File taxes", Some("2023-02-03")),
+        ("File taxes 3rd of February", "File taxes", Some("2023-02-03")),
+        ("File taxes February 3rd, 2024", "File taxes", Some("2024-02-03")),
+        ("Send cards jan 2", "Send cards", Some("2024-01-02")),
+        ("Ship v2 by 2023-02-01 5pm", "Ship v2", Some("2023-02-01T17:00:00.000Z")),
+        ("Call mom at 9", "Call mom", Some("2023-01-19T09:00:00.000Z")),
+        ("Call mom at 11", "Call mom", Some("2023-01-18T11:00:00.000Z")),
+        ("Call mom 3pm", "Call mom", Some("2023-01-18T15:00:00.000Z")),
+        ("Call mom 3 pm", "Call mom", Some("2023-01-18T15:00:00.000Z")),
+        ("Call mom tomorrow 3:30pm", "Call mom", Some("2023-01-19T15:30:00.000Z")),
+        ("Call mom friday 15:00", "Call mom", Some("2023-01-20T15:00:00.000Z")),
+        ("Call mom at noon", "Call mom", Some("2023-01-18T12:00:00.000Z")),
+        ("Call mom 9am", "Call mom", Some("2023-01-19T09:00:00.000Z")),
+        ("Stretch tonight", "Stretch", Some("2023-01-18T20:00:00.000Z")),
+        ("Look at the garden", "Look at the garden", None),
+        ("Read chapter 3", "Read chapter 3", None),
+        ("Ask if we may go", "Ask if we may go", None),
+    ];
+
+    for (input, title, due_date) in corpus {
+        let draft = parse(conn, input);
+        assert_eq!(draft.item_type, "TASK", "{}", input);
+        assert_eq!(draft.title, title, "{}", input);
+        assert_eq!(draft.due_date.as_deref(), due_date, "{}", input);
+    }
+}
+
+#[test]
+#[serial]
+fn test_quick_add_recurrence_phrases() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    // Input, frequency, interval, days of week, day of month, due date
+    let corpus = [
+        ("Water plants daily", "DAILY", 1, None, None, None),
+        ("Water plants every day", "DAILY", 1, None, None, None),
+        ("Water plants every 3 days", "DAILY", 3, None, None, None),
+        ("Review budget weekly", "WEEKLY", 1, None, None, None),
+        ("Review budget every other week", "WEEKLY", 2, None, None, None),
+        ("Backup monthly", "MONTHLY", 1, None, None, None),
+        ("Renew domain yearly", "ANNUALLY", 1, None, None, None),
+        ("Renew domain every year", "ANNUALLY", 1, None, None, None),
+        ("Pay rent every 1st", "MONTHLY", 1, None, Some(1), Some("2023-02-01")),
+        ("Timesheet every friday", "WEEKLY", 1, Some("[5]"), None, Some("2023-01-20")),
+        ("Timesheet every fridays at 9am", "WEEKLY", 1, Some("[5]"), None, Some("2023-01-20T09:00:00.000Z")),
+        ("Yoga every mon, wed and fri", "WEEKLY", 1, Some("[1,3,5]"), None, Some("2023-01-18")),
+        ("Yoga every mon, wed and fri 8am", "WEEKLY", 1, Some("[1,3,5]"), None, Some("2023-01-20T08:00:00.000Z")),
+        ("Standup every weekday 9:30am", "WEEKLY", 1, Some("[1,2,3,4,5]"), None, Some("2023-01-19T09:30:00.000Z")),
+        ("Hike every weekend", "WEEKLY", 1, Some("[0,6]"), None, Some("2023-01-21")),
+        ("Sprint review every 2 weeks on friday", "WEEKLY", 2, None, None, Some("2023-01-20")),
+    ];
+
+    for (input, frequency, interval, days_of_week, day_of_month, due_date) in corpus {
+        let draft = parse(conn, input);
+        let rule = draft.recurring_rule.unwrap_or_else(|| panic!("no rule for {}", input));
+        assert_eq!(rule.frequency, frequency, "{}", input);
+        assert_eq!(rule.interval_value, interval, "{}", input);
+        assert_eq!(rule.days_of_week.as_deref(), days_of_week, "{}", input);
+        assert_eq!(rule.day_of_month, day_of_month, "{}", input);
+        assert_eq!(draft.due_date.as_deref(), due_date, "{}", input);
+    }
+}
+
+#[test]
+#[serial]
+fn test_quick_add_event_phrases() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    // Input, title, start, end
+    let corpus = [
+        ("Team sync 3-4pm", "Team sync", "2023-01-18T15:00:00.000Z", "2023-01-18T16:00:00.000Z"),
+        ("Team sync 11-1pm", "Team sync", "2023-01-18T11:00:00.000Z", "2023-01-18T13:00:00.000Z"),
+        ("Team sync 3pm-4:30pm friday", "Team sync", "2023-01-20T15:00:00.000Z", "2023-01-20T16:30:00.000Z"),
+        ("Team sync tomorrow from 2pm to 3:30pm", "Team sync", "2023-01-19T14:00:00.000Z", "2023-01-19T15:30:00.000Z"),
+        ("Lunch noon for 90 minutes", "Lunch", "2023-01-18T12:00:00.000Z", "2023-01-18T13:30:00.000Z"),
+        ("Lunch noon for 1.5h", "Lunch", "2023-01-18T12:00:00.000Z", "2023-01-18T13:30:00.000Z"),
+        ("Deploy 11pm for 2 hours", "Deploy", "2023-01-18T23:00:00.000Z", "2023-01-19T01:00:00.000Z"),
+    ];
+
+    for (input, title, start, end) in corpus {
+        let draft = parse(conn, input);
+        assert_eq!(draft.item_type, "EVENT", "{}", input);
+        assert_eq!(draft.title, title, "{}", input);
+        assert_eq!(draft.start_time.as_deref(), Some(start), "{}", input);
+        assert_eq!(draft.end_time.as_deref(), Some(end), "{}", input);
+        assert!(!draft.is_all_day, "{}", input);
+    }
+
+    let party = parse_quick_add(conn, "Party saturday", Some("EVENT"), &context()).unwrap();
+    assert!(party.is_all_day);
+    assert_eq!(party.start_time.as_deref(), Some("2023-01-21T00:00:00.000Z"));
+    assert_eq!(party.end_time.as_deref(), Some("2023-01-21T23:59:59.000Z"));
+
+    let untimed = parse_quick_add(conn, "Coffee", Some("EVENT"), &context()).unwrap();
+    assert_eq!(untimed.start_time.as_deref(), Some("2023-01-18T11:00:00.000Z"));
+    assert_eq!(untimed.end_time.as_deref(), Some("2023-01-18T12:00:00.000Z"));
+    assert_eq!(untimed.warnings.len(), 1);
+
+    assert!(parse_quick_add(conn, "Coffee", Some("NOTE"), &context()).is_err());
+
+    let endless = parse_quick_add(conn, "Retreat noon for 100000000000000000000h", Some("EVENT"), &context()).unwrap();
+    assert_eq!(endless.title, "Retreat for 100000000000000000000h", "Lengths out of range are not recognised");
+    assert_eq!(endless.end_time.as_deref(), Some("2023-01-18T13:00:00.000Z"));
+    let endless = parse_quick_add(conn, "Retreat noon for 100000000000h", Some("EVENT"), &context()).unwrap();
+    assert_eq!(endless.end_time.as_deref(), Some("2023-01-18T13:00:00.000Z"));
+    assert_eq!(endless.warnings.len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_quick_add_priority_category_and_participants() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute(
+        "INSERT INTO participants (name, email) VALUES ('Alice Smith', 'alice@example.com'), ('Bob Jones', 'bjones@example.com')",
+        [],
+    ).unwrap();
+
+    let draft = parse(conn, "Review PR tomorrow 3pm !1 #Work every friday @alice");
+    assert_eq!(draft.item_type, "EVENT");
+    assert_eq!(draft.title, "Review PR");
+    assert_eq!(draft.start_time.as_deref(), Some("2023-01-19T15:00:00.000Z"));
+    assert_eq!(draft.end_time.as_deref(), Some("2023-01-19T16:00:00.000Z"));
+    assert_eq!(draft.priority, Some(1));
+    assert_eq!(draft.category_id, Some(1));
+    assert_eq!(draft.recurring_rule.as_ref().and_then(|r| r.days_of_week.as_deref()), Some("[5]"));
+    assert_eq!(draft.participants.len(), 1);
+    assert_eq!(draft.participants[0].name, "Alice Smith");
+    assert!(draft.warnings.is_empty());
+
+    // Email, first name and someone unknown
+    let draft = parse(conn, "Interview @bjones@example.com @Bob @carol@example.com friday 2pm");
+    let mentioned: Vec<(&str, Option<&str>)> = draft.participants.iter()
+        .map(|p| (p.name.as_str(), p.email.as_deref()))
+        .collect();
+    assert_eq!(mentioned, vec![
+        ("Bob Jones", Some("bjones@example.com")),
+        ("Bob Jones", Some("bjones@example.com")),
+        ("carol", Some("carol@example.com")),
+    ]);
+    assert!(draft.participants[2].id.is_none());
+
+    let draft = parse(conn, "Stretch #health !6");
+    assert_eq!(draft.category_id, Some(3));
+    assert_eq!(draft.priority, None);
+    assert_eq!(draft.title, "Stretch !6");
+
+    let draft = parse(conn, "Paint #Hobbies");
+    assert_eq!(draft.category_id, None);
+    assert_eq!(draft.category_name.as_deref(), Some("Hobbies"));
+    assert_eq!(draft.warnings, vec!["No category named 'Hobbies'".to_string()]);
+
+    // Tasks have no participants
+    let draft = parse_quick_add(conn, "Email @alice tomorrow", Some("TASK"), &context()).unwrap();
+    assert!(draft.participants.is_empty());
+    assert_eq!(draft.warnings.len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_week_start_setting() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'week_start_day'", []).unwrap();
+
+    let now = context().now.and_utc().fixed_offset();
+    let context = QuickAddContext::load(conn, now).unwrap();
+    assert_eq!(context.week_start_day, 0);
+
+    // The week after the one starting Sunday the 15th
+    let draft = parse_quick_add(conn, "Plan sprint next week", None, &context).unwrap();
+    assert_eq!(draft.due_date.as_deref(), Some("2023-01-22"));
+    let draft = parse_quick_add(conn, "Plan sprint next sunday", None, &context).unwrap();
+    assert_eq!(draft.due_date.as_deref(), Some("2023-01-22"));
+}
+
+#[test]
+#[serial]
+fn test_times_resolve_on_the_users_clock_and_are_stored_in_utc() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    // 23:30 on Tuesday the 17th in New York is already Wednesday in UTC
+    let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+    let now = new_york.with_ymd_and_hms(2023, 1, 17, 23, 30, 0).unwrap();
+    let context = QuickAddContext::load(conn, now).unwrap();
+
+    let draft = parse_quick_add(conn, "Call mom tomorrow 3pm", None, &context).unwrap();
+    assert_eq!(draft.due_date.as_deref(), Some("2023-01-18T20:00:00.000Z"));
+    let draft = parse_quick_add(conn, "Submit report today", None, &context).unwrap();
+    assert_eq!(draft.due_date.as_deref(), Some("2023-01-17"), "Dates without a time stay on the user's calendar");
+    let draft = parse_quick_add(conn, "Deploy 11pm for 2 hours", None, &context).unwrap();
+    assert_eq!(
+        (draft.start_time.as_deref(), draft.end_time.as_deref()),
+        (Some("2023-01-19T04:00:00.000Z"), Some("2023-01-19T06:00:00.000Z")),
+        "11pm has passed, so it is the next evening"
+    );
+
+    let task_id = create_from_draft(conn, &parse_quick_add(conn, "Call mom tomorrow 3pm", None, &context).unwrap()).unwrap();
+    let due_date: String = conn.query_row("SELECT due_date FROM tasks WHERE id = ?", [task_id], |row| row.get(0)).unwrap();
+    assert_eq!(due_date, "2023-01-18T20:00:00.000Z");
+}
+
+#[test]
+#[serial]
+fn test_create_from_draft() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let draft = parse(conn, "Timesheet !2 #Work every friday 4pm");
+    let task_id = create_from_draft(conn, &draft).unwrap();
+    let (title, due_date, priority, category_id, frequency, days_of_week): (String, String, i32, i64, String, String) = conn.query_row(
+        "SELECT t.title, t.due_date, t.priority, t.category_id, r.frequency, r.days_of_week
+         FROM tasks t JOIN recurring_rules r ON r.id = t.recurring_rule_id
+         WHERE t.id = ?",
+        [task_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+    ).unwrap();
+    assert_eq!(
+        (title.as_str(), due_date.as_str(), priority, category_id, frequency.as_str(), days_of_week.as_str()),
+        ("Timesheet", "2023-01-20T16:00:00.000Z", 2, 1, "WEEKLY", "[5]"),
+    );
+
+    let draft = parse(conn, "Design review friday 2-3pm @dana@example.com");
+    let event_id = create_from_draft(conn, &draft).unwrap();
+    let (start_time, end_time): (String, String) = conn.query_row(
+        "SELECT start_time, end_time FROM events WHERE id = ?",
+        [event_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert_eq!((start_time.as_str(), end_time.as_str()), ("2023-01-20T14:00:00.000Z", "2023-01-20T15:00:00.000Z"));
+    let attendee: String = conn.query_row(
+        "SELECT p.email FROM event_participants ep JOIN participants p ON p.id = ep.participant_id WHERE ep.event_id = ?",
+        [event_id],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(attendee, "dana@example.com");
+
+    let empty = parse(conn, "tomorrow 3pm");
+    assert!(empty.title.is_empty());
+    assert!(create_from_draft(conn, &empty).is_err());
+}
@@ -0,0 +1,197 @@
+use crate::db::{Database, models::{RecurringRule, Task}};
+use super::setup_test_db_with_data;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::params;
+use serial_test::serial;
+
+fn rule(frequency: &str, days_of_week: Option<&str>, day_of_month: Option<i32>) -> RecurringRule {
+    RecurringRule {
+        id: None,
+        frequency: frequency.to_string(),
+        interval_value: 1,
+        days_of_week: days_of_week.map(str::to_string),
+        day_of_month,
+        month_of_year: None,
+        end_date: None,
+        end_occurrences: None,
+        created_at: None,
+    }
+}
+
+fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn days_from_today(days: i64) -> NaiveDate {
+    Utc::now().date_naive() + Duration::days(days)
+}
+
+/// Makes a task recur by a new rule and sets its due date
+fn recur(db: &Database, task_id: i64, frequency: &str, interval: i32, mode: &str, due_date: &str) -> i64 {
+    let conn = db.get_connection();
+    conn.execute(
+        "INSERT INTO recurring_rules (frequency, interval) VALUES (?1, ?2)",
+        params![frequency, interval],
+    ).unwrap();
+    let rule_id = conn.last_insert_rowid();
+    conn.execute("UPDATE tasks SET due_date = ?1 WHERE id = ?2", params![due_date, task_id]).unwrap();
+    db.set_task_recurrence(task_id, Some(rule_id), mode).unwrap();
+    rule_id
+}
+
+fn get_task(db: &Database, id: i64) -> Task {
+    db.get_connection()
+        .query_row_and_then(
+            "SELECT id, title, description, due_date, priority, status, category_id, recurring_rule_id,
+                    kanban_column_id, kanban_order, completed_at, created_at, updated_at
+             FROM tasks WHERE id = ?",
+            [id],
+            Task::from_row,
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_schedule_follows_the_rule() {
+    let mut weekly = rule("WEEKLY", Some("[2, 4]"), None);
+    weekly.interval_value = 2;
+    assert_eq!(weekly.next_on_schedule(at("2023-01-31 17:00:00")), Some(at("2023-02-02 17:00:00")));
+    assert_eq!(weekly.next_on_schedule(at("2023-02-02 17:00:00")), Some(at("2023-02-14 17:00:00")), "Every other week");
+
+    let monthly = rule("MONTHLY", None, Some(31));
+    assert_eq!(monthly.next_on_schedule(at("2023-01-31 09:00:00")), Some(at("2023-02-28 09:00:00")));
+    assert_eq!(monthly.next_on_schedule(at("2023-02-28 09:00:00")), Some(at("2023-03-31 09:00:00")));
+
+    let mut yearly = rule("YEARLY", None, None);
+    yearly.month_of_year = Some(3);
+    yearly.end_date = Some("2025-01-01".to_string());
+    assert_eq!(yearly.next_on_schedule(at("2023-03-15 00:00:00")), Some(at("2024-03-15 00:00:00")));
+    assert_eq!(yearly.next_on_schedule(at("2024-03-15 00:00:00")), None, "The rule has ended");
+
+    let weekly = rule("WEEKLY", Some("[2, 4]"), None);
+    assert_eq!(weekly.next_after_interval(at("2023-02-01 08:00:00")), Some(at("2023-02-08 08:00:00")));
+}
+
+#[test]
+#[serial]
+fn test_fixed_schedule_spawns_next_instance() {
+    let db = setup_test_db_with_data();
+    let due = days_from_today(3);
+    recur(&db, 1, "WEEKLY", 1, "FIXED", &format!("{} 17:00:00", due));
+
+    let change = db.set_task_status(1, "COMPLETED").unwrap();
+    assert_eq!(change.next_task_ids.len(), 1);
+    let next = get_task(&db, change.next_task_ids[0]);
+    assert_eq!(next.title, "Complete project proposal");
+    assert_eq!(next.status, "PENDING");
+    assert_eq!(next.due_date, Some(format!("{} 17:00:00", due + Duration::days(7))));
+    assert_eq!(next.category_id, Some(1));
+
+    assert!(db.set_task_status(1, "COMPLETED").unwrap().next_task_ids.is_empty(), "Already completed");
+    assert!(db.set_task_status(2, "COMPLETED").unwrap().next_task_ids.is_empty(), "Not recurring");
+}
+
+#[test]
+#[serial]
+fn test_monthly_series_keeps_its_day_past_february() {
+    let db = setup_test_db_with_data();
+    let year = days_from_today(0).year() + 1;
+    let end_of_february = NaiveDate::from_ymd_opt(year, 3, 1).unwrap() - Duration::days(1);
+    recur(&db, 1, "MONTHLY", 1, "FIXED", &format!("{}-01-31 09:00:00", year));
+
+    let mut task_id = 1;
+    let mut due_dates = Vec::new();
+    for _ in 0..3 {
+        task_id = db.set_task_status(task_id, "COMPLETED").unwrap().next_task_ids[0];
+        due_dates.push(get_task(&db, task_id).due_date.unwrap());
+    }
+    assert_eq!(due_dates, vec![
+        format!("{} 09:00:00", end_of_february),
+        format!("{}-03-31 09:00:00", year),
+        format!("{}-04-30 09:00:00", year),
+    ]);
+}
+
+#[test]
+#[serial]
+fn test_missed_occurrences_are_skipped_or_counted_from_completion() {
+    let db = setup_test_db_with_data();
+    recur(&db, 1, "DAILY", 2, "FIXED", &days_from_today(-9).to_string());
+    recur(&db, 2, "DAILY", 3, "AFTER_COMPLETION", &format!("{} 08:00:00", days_from_today(-10)));
+
+    let fixed = db.set_task_status(1, "COMPLETED").unwrap().next_task_ids[0];
+    assert_eq!(get_task(&db, fixed).due_date, Some(days_from_today(1).to_string()), "Next date on the every-other-day schedule");
+
+    let after = db.set_task_status(2, "COMPLETED").unwrap().next_task_ids[0];
+    assert_eq!(get_task(&db, after).due_date, Some(format!("{} 08:00:00", days_from_today(3))));
+}
+
+#[test]
+#[serial]
+fn test_subtasks_notes_and_reminders_carry_over() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let due = days_from_today(1);
+    recur(&db, 1, "DAILY", 1, "FIXED", &format!("{} 17:00:00", due));
+
+    let outline = db.create_subtask(1, &get_task(&db, 2)).unwrap();
+    db.add_checklist_item(1, "Proofread").unwrap();
+    conn.execute("INSERT INTO notes (title, content) VALUES ('Template', 'Use the Q1 layout')", []).unwrap();
+    conn.execute("INSERT INTO task_notes (task_id, note_id) VALUES (1, last_insert_rowid())", []).unwrap();
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description) VALUES ('TASK', 1, ?, '1 hour before')",
+        [format!("{} 16:00:00", due)],
+    ).unwrap();
+    db.set_task_status(outline, "COMPLETED").unwrap();
+
+    let next = db.set_task_status(1, "COMPLETED").unwrap().next_task_ids[0];
+
+    let subtasks = db.get_subtasks(next).unwrap();
+    assert_eq!(subtasks.len(), 1);
+    assert_eq!(subtasks[0].title, "Buy groceries");
+    assert_eq!(subtasks[0].status, "PENDING", "Subtasks start over");
+    assert!(!db.get_checklist_items(next).unwrap()[0].is_checked);
+
+    let notes: i64 = conn.query_row("SELECT COUNT(*) FROM task_notes WHERE task_id = ?", [next], |row| row.get(0)).unwrap();
+    assert_eq!(notes, 1);
+    let trigger_time: String = conn.query_row(
+        "SELECT trigger_time FROM reminders WHERE item_type = 'TASK' AND item_id = ?",
+        [next],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(trigger_time, format!("{} 16:00:00", due + Duration::days(1)));
+}
+
+#[test]
+#[serial]
+fn test_completion_history_and_streaks() {
+    let db = setup_test_db_with_data();
+    recur(&db, 3, "DAILY", 1, "AFTER_COMPLETION", &format!("{} 18:00:00", days_from_today(-2)));
+
+    let mut current = 3;
+    for _ in 0..3 {
+        current = db.set_task_status(current, "COMPLETED").unwrap().next_task_ids[0];
+    }
+
+    let completions = db.get_task_completions(current).unwrap();
+    assert_eq!(completions.iter().map(|c| c.on_time).collect::<Vec<_>>(), vec![false, true, true]);
+    assert_eq!(completions[0].task_id, Some(3));
+    assert_eq!(completions[2].next_task_id, Some(current));
+
+    let streak = db.get_task_streak(3).unwrap();
+    assert_eq!((streak.current, streak.longest, streak.completions), (2, 2, 3));
+}
+
+#[test]
+#[serial]
+fn test_series_stops_after_its_occurrences() {
+    let db = setup_test_db_with_data();
+    let rule_id = recur(&db, 2, "WEEKLY", 1, "FIXED", &days_from_today(0).to_string());
+    db.get_connection().execute("UPDATE recurring_rules SET end_occurrences = 2 WHERE id = ?", [rule_id]).unwrap();
+
+    let next = db.set_task_status(2, "COMPLETED").unwrap().next_task_ids[0];
+    assert!(db.set_task_status(next, "COMPLETED").unwrap().next_task_ids.is_empty());
+    assert_eq!(db.get_task_streak(2).unwrap().completions, 2);
+
+    assert!(db.set_task_recurrence(2, Some(rule_id), "SOMETIMES").is_err());
+}
@@ -22,7 +22,7 @@ mod recurring_service_tests {
         
         // Verify rule was created correctly
         let conn = scenario.get_db().get_connection();
-        let mut stmt = conn.prepare("SELECT frequency, interval_value FROM recurring_rules WHERE id = ?").unwrap();
+        let mut stmt = conn.prepare("SELECT frequency, interval FROM recurring_rules WHERE id = ?").unwrap();
         let (frequency, interval): (String, i32) = stmt.query_row([rule_id], |row| {
             Ok((row.get(0)?, row.get(1)?))
         }).unwrap();
@@ -94,7 +94,7 @@ mod recurring_service_tests {
             Ok((row.get(0)?, row.get(1)?, row.get(2)?))
         }).unwrap();
         
-        assert_eq!(frequency, "YEARLY");
+        assert_eq!(frequency, "ANNUALLY");
         assert_eq!(day_of_month, Some(1));
         assert_eq!(month_of_year, Some(1));
     }
@@ -314,7 +314,7 @@ mod recurring_service_tests {
         // Create yearly rule for February 29th
         let leap_year_rule = RecurringRule {
             id: None,
-            frequency: "YEARLY".to_string(),
+            frequency: "ANNUALLY".to_string(),
             interval_value: 1,
             days_of_week: None,
             day_of_month: Some(29),
@@ -0,0 +1,395 @@
+use crate::services::reminder_delivery_service::*;
+use crate::services::reminder_service::Reminder;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::NaiveDateTime;
+use rusqlite::Connection;
+use serial_test::serial;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// Stands in for the desktop channel and records what it was asked to show
+struct RecordingChannel {
+    delivered: RefCell<Vec<ReminderNotification>>,
+    fail: bool,
+}
+
+impl RecordingChannel {
+    fn new() -> Self {
+        RecordingChannel { delivered: RefCell::new(Vec::new()), fail: false }
+    }
+
+    fn failing() -> Self {
+        RecordingChannel { delivered: RefCell::new(Vec::new()), fail: true }
+    }
+}
+
+impl DeliveryChannel for RecordingChannel {
+    fn channel_type(&self) -> &'static str {
+        "DESKTOP"
+    }
+
+    fn deliver(&self, notification: &ReminderNotification) -> Result<(), String> {
+        if self.fail {
+            return Err("desktop unavailable".to_string());
+        }
+        self.delivered.borrow_mut().push(notification.clone());
+        Ok(())
+    }
+}
+
+/// Minimal local SMTP server that accepts one message and hands back its DATA section
+fn spawn_smtp_stand_in() -> (u16, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writer.write_all(b"220 localhost ready\r\n").unwrap();
+
+        let mut data = String::new();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            if in_data {
+                if line == ".\r\n" {
+                    in_data = false;
+                    writer.write_all(b"250 queued\r\n").unwrap();
+                } else {
+                    data.push_str(&line);
+                }
+                continue;
+            }
+            let reply: &[u8] = match line.split_whitespace().next().unwrap_or("") {
+                "EHLO" => b"250-localhost\r\n250 8BITMIME\r\n",
+                "MAIL" | "RCPT" => b"250 ok\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                }
+                _ => b"500 unknown\r\n",
+            };
+            writer.write_all(reply).unwrap();
+        }
+        sender.send(data).unwrap();
+    });
+
+    (port, receiver)
+}
+
+/// Minimal local HTTP server that answers `requests` requests with `status` and hands back their bodies
+fn spawn_http_stand_in(status: u16, requests: usize) -> (u16, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for _ in 0..requests {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            write!(writer, "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).unwrap();
+            sender.send(String::from_utf8(body).unwrap()).unwrap();
+        }
+    });
+
+    (port, receiver)
+}
+
+fn insert_channel(conn: &Connection, name: &str, config: &ChannelConfig) -> i64 {
+    conn.execute(
+        "INSERT INTO delivery_channels (name, channel_type, config) VALUES (?1, ?2, ?3)",
+        [name, config.channel_type(), &serde_json::to_string(config).unwrap()],
+    ).unwrap();
+    conn.last_insert_rowid()
+}
+
+/// Reminder for seeded task 2 ("Buy groceries", Personal category) due before `at`
+fn insert_due_reminder(conn: &Connection) -> i64 {
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description)
+         VALUES ('TASK', 2, '2023-01-18 18:45:00', '15 minutes before')",
+        [],
+    ).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn log_statuses(conn: &Connection, reminder_id: i64) -> Vec<(String, String)> {
+    let mut stmt = conn.prepare(
+        "SELECT channel_type, status FROM reminder_delivery_log WHERE reminder_id = ? ORDER BY id"
+    ).unwrap();
+    stmt.query_map([reminder_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_retry_policy_backoff() {
+    let policy = RetryPolicy { max_attempts: 4, base_delay_seconds: 60 };
+    let last = at("2023-01-18 19:00:00");
+
+    assert_eq!(policy.next_attempt_at(0, last), Some(last), "First attempt should run immediately");
+    assert_eq!(policy.next_attempt_at(1, last), Some(at("2023-01-18 19:01:00")));
+    assert_eq!(policy.next_attempt_at(2, last), Some(at("2023-01-18 19:02:00")));
+    assert_eq!(policy.next_attempt_at(3, last), Some(at("2023-01-18 19:04:00")));
+    assert_eq!(policy.next_attempt_at(4, last), None, "No attempts left after the maximum");
+}
+
+#[test]
+#[serial]
+fn test_retry_policy_loaded_from_settings() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    assert_eq!(
+        RetryPolicy::load(conn).unwrap(),
+        RetryPolicy { max_attempts: 5, base_delay_seconds: 60 }
+    );
+}
+
+#[test]
+fn test_channel_config_serialization() {
+    let config = ChannelConfig::Webhook { url: "http://127.0.0.1:9000/hook".to_string() };
+    let json = serde_json::to_string(&config).unwrap();
+
+    assert!(json.contains("\"type\":\"WEBHOOK\""));
+    assert_eq!(serde_json::from_str::<ChannelConfig>(&json).unwrap(), config);
+}
+
+#[test]
+#[serial]
+fn test_falls_back_to_desktop_channel() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    let desktop = RecordingChannel::new();
+
+    let summary = dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &desktop).unwrap();
+
+    assert_eq!(summary.sent, 1);
+    let delivered = desktop.delivered.borrow();
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].title, "Buy groceries");
+    assert_eq!(delivered[0].reminder_id, reminder_id);
+    assert_eq!(log_statuses(conn, reminder_id), vec![("DESKTOP".to_string(), "SENT".to_string())]);
+}
+
+#[test]
+#[serial]
+fn test_sent_reminders_are_not_repeated() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    insert_due_reminder(conn);
+    let desktop = RecordingChannel::new();
+
+    dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &desktop).unwrap();
+    let second = dispatch_reminders(conn, &at("2023-01-18 19:05:00").and_utc(), &desktop).unwrap();
+
+    assert_eq!(second.sent, 0, "An already delivered reminder should not be sent again");
+    assert_eq!(desktop.delivered.borrow().len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_email_delivery_through_local_smtp() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    let (port, received) = spawn_smtp_stand_in();
+
+    let channel_id = insert_channel(conn, "Mail", &ChannelConfig::Email {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "calendar@localhost".to_string(),
+        to: "me@localhost".to_string(),
+    });
+    conn.execute("INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)", [reminder_id, channel_id]).unwrap();
+
+    let desktop = RecordingChannel::new();
+    let summary = dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &desktop).unwrap();
+
+    assert_eq!(summary.sent, 1);
+    assert!(desktop.delivered.borrow().is_empty(), "Reminder channels should replace the desktop default");
+
+    let message = received.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+    assert!(message.contains("To: <me@localhost>"));
+    assert!(message.contains("Subject: Reminder: Buy groceries (15 minutes before)"));
+    assert_eq!(log_statuses(conn, reminder_id), vec![("EMAIL".to_string(), "SENT".to_string())]);
+}
+
+#[test]
+#[serial]
+fn test_line_breaks_in_titles_stay_out_of_email_headers() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE tasks SET title = 'Buy groceries\r\nBcc: everyone@example.com' WHERE id = 2", []).unwrap();
+    let reminder_id = insert_due_reminder(conn);
+    let (port, received) = spawn_smtp_stand_in();
+
+    let channel_id = insert_channel(conn, "Mail", &ChannelConfig::Email {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "calendar@localhost".to_string(),
+        to: "me@localhost".to_string(),
+    });
+    conn.execute("INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)", [reminder_id, channel_id]).unwrap();
+    dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &RecordingChannel::new()).unwrap();
+
+    let message = received.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+    let (headers, _) = message.split_once("\r\n\r\n").unwrap();
+    assert!(headers.contains("Subject: Reminder: Buy groceries  Bcc: everyone@example.com (15 minutes before)"));
+    assert!(!headers.lines().any(|line| line.starts_with("Bcc:")));
+}
+
+#[test]
+#[serial]
+fn test_webhook_delivery_through_category_channel() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    let (port, received) = spawn_http_stand_in(204, 1);
+
+    // Seeded category 2 is "Personal", the category of task 2
+    let channel_id = insert_channel(conn, "Hook", &ChannelConfig::Webhook {
+        url: format!("http://127.0.0.1:{}/reminders", port),
+    });
+    conn.execute("INSERT INTO category_channels (category_id, channel_id) VALUES (2, ?1)", [channel_id]).unwrap();
+
+    let desktop = RecordingChannel::new();
+    let summary = dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &desktop).unwrap();
+
+    assert_eq!(summary.sent, 1);
+    let body = received.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+    let payload: ReminderNotification = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload.reminder_id, reminder_id);
+    assert_eq!(payload.title, "Buy groceries");
+}
+
+#[test]
+#[serial]
+fn test_failed_webhook_is_retried_with_backoff() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    let (port, received) = spawn_http_stand_in(500, 2);
+
+    let channel_id = insert_channel(conn, "Hook", &ChannelConfig::Webhook {
+        url: format!("http://127.0.0.1:{}/", port),
+    });
+    conn.execute("INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)", [reminder_id, channel_id]).unwrap();
+    let desktop = RecordingChannel::new();
+
+    let first = dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &desktop).unwrap();
+    assert_eq!(first.failed, 1);
+    received.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+
+    let too_soon = dispatch_reminders(conn, &at("2023-01-18 19:00:30").and_utc(), &desktop).unwrap();
+    assert_eq!(too_soon.retry_later, 1, "Retry should wait for the backoff delay");
+    assert_eq!(too_soon.failed, 0);
+
+    let retry = dispatch_reminders(conn, &at("2023-01-18 19:01:00").and_utc(), &desktop).unwrap();
+    assert_eq!(retry.failed, 1, "Retry should run once the backoff has elapsed");
+    received.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+
+    let log = log_statuses(conn, reminder_id);
+    assert_eq!(log.len(), 2);
+    assert!(log.iter().all(|(kind, status)| kind == "WEBHOOK" && status == "FAILED"));
+}
+
+#[test]
+#[serial]
+fn test_unreachable_webhook_fails_the_attempt() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    let channel_id = insert_channel(conn, "Hook", &ChannelConfig::Webhook {
+        url: format!("http://127.0.0.1:{}/", port),
+    });
+    conn.execute("INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)", [reminder_id, channel_id]).unwrap();
+
+    let summary = dispatch_reminders(conn, &at("2023-01-18 19:00:00").and_utc(), &RecordingChannel::new()).unwrap();
+    assert_eq!(summary.failed, 1);
+    let error: String = conn.query_row(
+        "SELECT error FROM reminder_delivery_log WHERE reminder_id = ?1", [reminder_id], |row| row.get(0)
+    ).unwrap();
+    assert!(error.starts_with("Failed to connect to webhook"), "{}", error);
+}
+
+#[test]
+#[serial]
+fn test_delivery_stops_after_max_attempts() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+    conn.execute("UPDATE settings SET value = '2' WHERE key = 'delivery_max_attempts'", []).unwrap();
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'delivery_backoff_seconds'", []).unwrap();
+    let desktop = RecordingChannel::failing();
+
+    for minute in 0..4 {
+        dispatch_reminders(conn, &at(&format!("2023-01-18 19:0{}:00", minute)).and_utc(), &desktop).unwrap();
+    }
+
+    assert_eq!(log_statuses(conn, reminder_id).len(), 2, "Only max_attempts attempts should be logged");
+}
+
+#[test]
+#[serial]
+fn test_disabled_channels_are_ignored() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let reminder_id = insert_due_reminder(conn);
+
+    let channel_id = insert_channel(conn, "Hook", &ChannelConfig::Webhook {
+        url: "http://127.0.0.1:1/".to_string(),
+    });
+    conn.execute("UPDATE delivery_channels SET is_enabled = 0 WHERE id = ?", [channel_id]).unwrap();
+    conn.execute("INSERT INTO reminder_channels (reminder_id, channel_id) VALUES (?1, ?2)", [reminder_id, channel_id]).unwrap();
+
+    let reminder = Reminder {
+        id: Some(reminder_id),
+        item_type: "TASK".to_string(),
+        item_id: 2,
+        trigger_time: "2023-01-18 18:45:00".to_string(),
+        offset_description: "15 minutes before".to_string(),
+        is_dismissed: false,
+        created_at: None,
+    };
+    let channels = resolve_channels(conn, &reminder).unwrap();
+
+    assert_eq!(channels.len(), 1);
+    assert!(channels[0].is_none(), "Only the built-in desktop channel should remain");
+}
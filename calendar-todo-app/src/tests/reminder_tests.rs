@@ -311,6 +311,28 @@ async fn test_get_pending_reminders_excludes_dismissed() {
     assert!(!result[0].is_dismissed, "Returned reminder should not be dismissed");
 }
 
+#[tokio::test]
+#[serial]
+async fn test_get_pending_reminders_excludes_trashed_items() {
+    let db = setup_test_db_with_data();
+    db.get_connection().execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description) VALUES
+         ('EVENT', 1, '2023-01-16 08:45:00', '15 minutes before'),
+         ('TASK', 2, '2023-01-18 18:00:00', '1 hour before'),
+         ('TASK', 3, '2023-01-16 17:00:00', '1 hour before')",
+        [],
+    ).expect("Failed to insert reminders");
+    db.trash_item("EVENT", 1).expect("Failed to trash event");
+    db.trash_item("TASK", 2).expect("Failed to trash task");
+
+    let result = get_pending_reminders(tauri::State::new(db))
+        .await
+        .expect("Failed to get pending reminders");
+
+    assert_eq!(result.len(), 1, "Reminders of trashed items should not be pending");
+    assert_eq!((result[0].item_type.as_str(), result[0].item_id), ("TASK", 3));
+}
+
 #[tokio::test]
 #[serial]
 async fn test_reminder_business_rule_validation() {
@@ -527,4 +549,244 @@ async fn test_reminder_performance_benchmarks() {
     let retrieval_time = start_time.elapsed();
     assert!(retrieval_time.as_millis() < 1000, "Retrieving 100 reminders should complete within 1 second");
     assert_eq!(pending.len(), 100, "Should retrieve all 100 reminders");
-}
\ No newline at end of file
+}
+fn at(value: &str) -> chrono::NaiveDateTime {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn quiet_hours_enabled() -> QuietHoursSettings {
+    QuietHoursSettings {
+        enabled: true,
+        ..QuietHoursSettings::default()
+    }
+}
+
+#[test]
+fn test_quiet_window_spanning_midnight() {
+    let settings = quiet_hours_enabled();
+
+    assert_eq!(
+        quiet_window_end(&settings, at("2023-01-15 23:30:00")),
+        Some(at("2023-01-16 07:00:00")),
+        "Late evening should be quiet until the next morning"
+    );
+    assert_eq!(
+        quiet_window_end(&settings, at("2023-01-16 06:59:00")),
+        Some(at("2023-01-16 07:00:00")),
+        "Early morning should be quiet until the same morning"
+    );
+    assert_eq!(quiet_window_end(&settings, at("2023-01-16 07:00:00")), None);
+    assert_eq!(quiet_window_end(&settings, at("2023-01-16 12:00:00")), None);
+}
+
+#[test]
+fn test_quiet_window_same_day_and_disabled() {
+    let mut settings = quiet_hours_enabled();
+    settings.start = "12:00".to_string();
+    settings.end = "13:00".to_string();
+
+    assert_eq!(quiet_window_end(&settings, at("2023-01-16 12:30:00")), Some(at("2023-01-16 13:00:00")));
+    assert_eq!(quiet_window_end(&settings, at("2023-01-16 23:30:00")), None);
+
+    settings.enabled = false;
+    assert_eq!(quiet_window_end(&settings, at("2023-01-16 12:30:00")), None);
+}
+
+#[test]
+fn test_priority_one_overrides_dnd() {
+    let settings = quiet_hours_enabled();
+    let now = at("2023-01-15 23:30:00");
+
+    assert_eq!(evaluate_reminder(&settings, now, 1, None), ReminderDecision::Deliver);
+    assert_eq!(
+        evaluate_reminder(&settings, now, 2, None),
+        ReminderDecision::Suppress { until: at("2023-01-16 07:00:00") }
+    );
+
+    let no_override = QuietHoursSettings { override_priority: 0, ..quiet_hours_enabled() };
+    assert!(matches!(evaluate_reminder(&no_override, now, 1, None), ReminderDecision::Suppress { .. }));
+}
+
+#[test]
+fn test_pomodoro_session_mutes_reminders() {
+    let settings = QuietHoursSettings::default();
+    let now = at("2023-01-16 10:10:00");
+    let session_end = at("2023-01-16 10:25:00");
+
+    assert_eq!(
+        evaluate_reminder(&settings, now, 3, Some(session_end)),
+        ReminderDecision::Suppress { until: session_end }
+    );
+    assert_eq!(
+        evaluate_reminder(&settings, at("2023-01-16 10:30:00"), 3, Some(session_end)),
+        ReminderDecision::Deliver,
+        "A session that should already have ended must not mute reminders"
+    );
+
+    let unmuted = QuietHoursSettings { mute_during_pomodoro: false, ..QuietHoursSettings::default() };
+    assert_eq!(evaluate_reminder(&unmuted, now, 3, Some(session_end)), ReminderDecision::Deliver);
+}
+
+#[test]
+#[serial]
+fn test_quiet_hours_settings_round_trip() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    assert_eq!(load_quiet_hours(conn).unwrap(), QuietHoursSettings::default());
+
+    let settings = QuietHoursSettings {
+        enabled: true,
+        start: "21:30".to_string(),
+        end: "06:45".to_string(),
+        mute_during_pomodoro: false,
+        override_priority: 2,
+        suppressed_mode: "DEFER".to_string(),
+    };
+    save_quiet_hours(conn, &settings).expect("Failed to save quiet hours");
+
+    assert_eq!(load_quiet_hours(conn).unwrap(), settings);
+}
+
+#[test]
+#[serial]
+fn test_quiet_hours_settings_validation() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let bad_time = QuietHoursSettings { start: "25:00".to_string(), ..QuietHoursSettings::default() };
+    assert!(save_quiet_hours(conn, &bad_time).is_err(), "Invalid time of day should be rejected");
+
+    let bad_mode = QuietHoursSettings { suppressed_mode: "LATER".to_string(), ..QuietHoursSettings::default() };
+    assert!(save_quiet_hours(conn, &bad_mode).is_err(), "Unknown suppressed mode should be rejected");
+}
+
+#[test]
+#[serial]
+fn test_suppressed_reminders_released_as_digest() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    save_quiet_hours(conn, &quiet_hours_enabled()).unwrap();
+
+    // Task 2 has priority 3, task 1 has priority 1
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description) VALUES
+         ('TASK', 2, '2023-01-15 23:00:00', 'At due time'),
+         ('TASK', 1, '2023-01-15 23:05:00', 'At due time')",
+        [],
+    ).unwrap();
+
+    let night = collect_due_reminders(conn, &at("2023-01-15 23:30:00").and_utc()).unwrap();
+    assert_eq!(night.immediate.len(), 1, "Only the priority 1 reminder should break through");
+    assert_eq!(night.immediate[0].item_id, 1);
+    assert!(night.digest.is_empty());
+
+    let deferred_until: Option<String> = conn.query_row(
+        "SELECT deferred_until FROM reminders WHERE item_id = 2", [], |row| row.get(0)
+    ).unwrap();
+    assert_eq!(deferred_until.as_deref(), Some("2023-01-16 07:00:00"));
+
+    let still_quiet = collect_due_reminders(conn, &at("2023-01-16 03:00:00").and_utc()).unwrap();
+    assert!(still_quiet.digest.is_empty(), "Deferred reminder should stay hidden during the window");
+
+    let morning = collect_due_reminders(conn, &at("2023-01-16 07:00:00").and_utc()).unwrap();
+    assert_eq!(morning.digest.len(), 1, "Deferred reminder should be released in the digest");
+    assert_eq!(morning.digest[0].item_id, 2);
+}
+
+#[test]
+#[serial]
+fn test_suppressed_reminders_deferred_individually() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let settings = QuietHoursSettings { suppressed_mode: "DEFER".to_string(), ..quiet_hours_enabled() };
+    save_quiet_hours(conn, &settings).unwrap();
+
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description)
+         VALUES ('TASK', 2, '2023-01-15 23:00:00', 'At due time')",
+        [],
+    ).unwrap();
+
+    let night = collect_due_reminders(conn, &at("2023-01-15 23:30:00").and_utc()).unwrap();
+    assert!(night.immediate.is_empty());
+
+    let morning = collect_due_reminders(conn, &at("2023-01-16 07:15:00").and_utc()).unwrap();
+    assert_eq!(morning.immediate.len(), 1, "DEFER mode should deliver the reminder on its own");
+    assert!(morning.digest.is_empty());
+}
+
+#[test]
+#[serial]
+fn test_quiet_hours_follow_the_local_clock() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    save_quiet_hours(conn, &quiet_hours_enabled()).unwrap();
+    let utc_plus_two = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+    let local = |value: &str| at(value).and_utc().with_timezone(&utc_plus_two);
+
+    // Trigger times are UTC, task 1 has priority 1 and would break through
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description) VALUES
+         ('TASK', 2, '2023-01-15 21:00:00', 'At due time'),
+         ('TASK', 1, '2023-01-15 22:00:00', 'At due time')",
+        [],
+    ).unwrap();
+
+    // 21:30 UTC is 23:30 on the local clock, inside the quiet window
+    let night = collect_due_reminders(conn, &local("2023-01-15 21:30:00")).unwrap();
+    assert!(night.immediate.is_empty(), "The 22:00 UTC reminder is not due yet");
+    let deferred_until: Option<String> = conn.query_row(
+        "SELECT deferred_until FROM reminders WHERE item_id = 2", [], |row| row.get(0)
+    ).unwrap();
+    assert_eq!(deferred_until.as_deref(), Some("2023-01-16 05:00:00"), "07:00 local, stored in UTC");
+
+    let morning = collect_due_reminders(conn, &local("2023-01-16 05:00:00")).unwrap();
+    assert_eq!(morning.immediate.len(), 1);
+    assert_eq!(morning.digest.len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_running_pomodoro_defers_reminders() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    crate::services::time_tracking_service::start_pomodoro_session(conn, "TASK", Some(1), at("2023-01-16 10:00:00"))
+        .expect("Failed to start Pomodoro session");
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description)
+         VALUES ('TASK', 2, '2023-01-16 10:05:00', 'At due time')",
+        [],
+    ).unwrap();
+
+    let during = collect_due_reminders(conn, &at("2023-01-16 10:10:00").and_utc()).unwrap();
+    assert!(during.immediate.is_empty(), "Reminder should be muted during the work session");
+
+    let after = collect_due_reminders(conn, &at("2023-01-16 10:25:00").and_utc()).unwrap();
+    assert_eq!(after.digest.len(), 1, "Reminder should arrive in the digest after the session");
+}
+
+#[test]
+#[serial]
+fn test_pomodoro_mutes_reminders_on_the_local_clock() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let utc_plus_two = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+
+    // Pomodoro times are UTC like trigger times, 10:10 UTC is 12:10 on the local clock
+    crate::services::time_tracking_service::start_pomodoro_session(conn, "TASK", Some(1), at("2023-01-16 10:00:00"))
+        .expect("Failed to start Pomodoro session");
+    conn.execute(
+        "INSERT INTO reminders (item_type, item_id, trigger_time, offset_description)
+         VALUES ('TASK', 2, '2023-01-16 10:05:00', 'At due time')",
+        [],
+    ).unwrap();
+
+    let during = collect_due_reminders(conn, &at("2023-01-16 10:10:00").and_utc().with_timezone(&utc_plus_two)).unwrap();
+    assert!(during.immediate.is_empty(), "Reminder should be muted during the work session");
+    let deferred_until: Option<String> = conn.query_row(
+        "SELECT deferred_until FROM reminders WHERE item_id = 2", [], |row| row.get(0)
+    ).unwrap();
+    assert_eq!(deferred_until.as_deref(), Some("2023-01-16 10:25:00"));
+}
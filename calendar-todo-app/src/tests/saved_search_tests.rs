@@ -0,0 +1,161 @@
+use crate::services::saved_search_service::*;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::NaiveDate;
+use serial_test::serial;
+
+fn saved(name: &str, query: &str, sort_by: &str, group_by: &str) -> SavedSearch {
+    SavedSearch {
+        id: None,
+        name: name.to_string(),
+        query: query.to_string(),
+        sort_by: sort_by.to_string(),
+        group_by: group_by.to_string(),
+        is_pinned: true,
+        position: 0,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn day(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn titles(results: &SavedSearchResults) -> Vec<Vec<&str>> {
+    results.groups.iter()
+        .map(|group| group.results.iter().map(|r| r.title.as_str()).collect())
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_saved_search_crud() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let id = insert_saved_search(conn, &saved("Overdue", "is:overdue", "DATE_ASC", "NONE")).unwrap();
+    insert_saved_search(conn, &SavedSearch { position: -1, ..saved("Inbox", "type:task", "RELEVANCE", "NONE") }).unwrap();
+
+    let names: Vec<String> = load_saved_searches(conn).unwrap().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, vec!["Inbox", "Overdue"], "Saved searches are listed by position");
+
+    let mut search = load_saved_search(conn, id).unwrap();
+    search.query = "is:overdue priority:1".to_string();
+    modify_saved_search(conn, &search).unwrap();
+    assert_eq!(load_saved_search(conn, id).unwrap().query, "is:overdue priority:1");
+
+    remove_saved_search(conn, id).unwrap();
+    assert!(load_saved_search(conn, id).is_err());
+}
+
+#[test]
+#[serial]
+fn test_saved_search_validation() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let error = insert_saved_search(conn, &saved("Bad", "meeting colour:red", "RELEVANCE", "NONE")).unwrap_err();
+    assert!(error.to_string().contains("at position 8"), "Query errors keep their position: {}", error);
+
+    assert!(insert_saved_search(conn, &saved(" ", "type:task", "RELEVANCE", "NONE")).is_err());
+    assert!(insert_saved_search(conn, &saved("Sorted", "type:task", "NEWEST", "NONE")).is_err());
+    assert!(insert_saved_search(conn, &saved("Grouped", "type:task", "RELEVANCE", "WEEK")).is_err());
+
+    insert_saved_search(conn, &saved("Tasks", "type:task", "RELEVANCE", "NONE")).unwrap();
+    assert!(insert_saved_search(conn, &saved("Tasks", "type:event", "RELEVANCE", "NONE")).is_err(), "Names are unique");
+    assert!(modify_saved_search(conn, &SavedSearch { id: Some(99999), ..saved("Gone", "x", "RELEVANCE", "NONE") }).is_err());
+}
+
+#[test]
+#[serial]
+fn test_evaluate_overdue_top_priority_tasks() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let search = saved("My overdue P1 tasks", "type:task due:<today priority:1", "PRIORITY", "NONE");
+
+    let results = evaluate_search(conn, &search, day("2023-02-15"), None, 0).unwrap();
+    assert_eq!(titles(&results), vec![vec!["Complete project proposal"]]);
+
+    let results = evaluate_search(conn, &search, day("2023-01-20"), None, 0).unwrap();
+    assert_eq!(results.total, 0, "Results are live relative to today");
+}
+
+#[test]
+#[serial]
+fn test_evaluate_this_weeks_work_events() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let search = saved("This week's Work events", "type:event category:work start:this_week", "DATE_ASC", "NONE");
+
+    let results = evaluate_search(conn, &search, day("2023-01-18"), None, 0).unwrap();
+    assert_eq!(titles(&results), vec![vec!["Morning Standup"]]);
+
+    let results = evaluate_search(conn, &search, day("2023-01-23"), None, 0).unwrap();
+    assert_eq!(results.total, 0);
+
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'week_start_day'", []).unwrap();
+    let results = evaluate_search(conn, &search, day("2023-01-15"), None, 0).unwrap();
+    assert_eq!(results.total, 1, "Weeks follow the week_start_day setting");
+}
+
+#[test]
+#[serial]
+fn test_evaluate_sorts_and_groups() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let results = evaluate_search(conn, &saved("Important", "priority:<=2", "DATE_ASC", "CATEGORY"), day("2023-01-18"), None, 0).unwrap();
+
+    let labels: Vec<&str> = results.groups.iter().map(|g| g.label.as_str()).collect();
+    assert_eq!(labels, vec!["Work", "Health"]);
+    assert_eq!(titles(&results), vec![
+        vec!["Morning Standup", "Complete project proposal"],
+        vec!["Exercise routine", "Doctor Appointment"],
+    ]);
+    assert_eq!(results.total, 4);
+
+    let results = evaluate_search(conn, &saved("By type", "priority:<=2", "TITLE", "TYPE"), day("2023-01-18"), None, 0).unwrap();
+    let labels: Vec<&str> = results.groups.iter().map(|g| g.label.as_str()).collect();
+    assert_eq!(labels, vec!["Tasks", "Events"]);
+}
+
+#[test]
+#[serial]
+fn test_smart_list_counts() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    insert_saved_search(conn, &saved("Work", "category:work", "RELEVANCE", "NONE")).unwrap();
+    insert_saved_search(conn, &SavedSearch { is_pinned: false, ..saved("Hidden", "type:note", "RELEVANCE", "NONE") }).unwrap();
+
+    let lists = load_smart_lists(conn, day("2023-01-18")).unwrap();
+
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0].name, "Work");
+    assert_eq!(lists[0].count, 2);
+}
+
+#[test]
+#[serial]
+fn test_export_and_import() {
+    let source = setup_test_db();
+    insert_saved_search(source.get_connection(), &saved("Overdue", "is:overdue", "DATE_ASC", "NONE")).unwrap();
+    insert_saved_search(source.get_connection(), &saved("Work", "category:work", "RELEVANCE", "TYPE")).unwrap();
+    let json = serde_json::to_string(&load_saved_searches(source.get_connection()).unwrap()).unwrap();
+
+    let target = setup_test_db();
+    let conn = target.get_connection();
+    insert_saved_search(conn, &saved("Work", "type:task", "TITLE", "NONE")).unwrap();
+
+    assert_eq!(import_saved_searches_json(conn, &json).unwrap(), 2);
+    let imported = load_saved_searches(conn).unwrap();
+    assert_eq!(imported.len(), 2, "Searches with the same name are replaced");
+    assert_eq!(imported.iter().find(|s| s.name == "Work").unwrap().query, "category:work");
+
+    let broken = serde_json::to_string(&vec![
+        saved("New", "type:task", "RELEVANCE", "NONE"),
+        saved("Broken", "priority:high", "RELEVANCE", "NONE"),
+    ]).unwrap();
+    assert!(import_saved_searches_json(conn, &broken).is_err());
+    assert_eq!(load_saved_searches(conn).unwrap().len(), 2, "A failed import changes nothing");
+    assert!(import_saved_searches_json(conn, "not json").is_err());
+}
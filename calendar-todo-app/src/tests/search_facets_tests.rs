@@ -0,0 +1,138 @@
+use crate::services::search_service::*;
+use super::setup_test_db_with_data;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn titles(conn: &Connection, query: &str) -> Vec<String> {
+    let mut titles: Vec<String> = search_index(conn, query, &SearchScope::all(), None, 0).unwrap()
+        .results.into_iter()
+        .map(|r| r.title)
+        .collect();
+    titles.sort();
+    titles
+}
+
+fn counts(facets: &[FacetCount]) -> Vec<(&str, &str, i64)> {
+    facets.iter().map(|f| (f.value.as_str(), f.label.as_str(), f.count)).collect()
+}
+
+fn assert_index_intact(conn: &Connection) {
+    conn.execute("INSERT INTO events_fts (events_fts, rank) VALUES ('integrity-check', 1)", []).unwrap();
+    conn.execute("INSERT INTO tasks_fts (tasks_fts, rank) VALUES ('integrity-check', 1)", []).unwrap();
+    conn.execute("INSERT INTO participants_fts (participants_fts, rank) VALUES ('integrity-check', 1)", []).unwrap();
+    conn.execute("INSERT INTO categories_fts (categories_fts, rank) VALUES ('integrity-check', 1)", []).unwrap();
+}
+
+#[test]
+#[serial]
+fn test_participants_are_searchable() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO participants (name, email) VALUES ('Alice Smith', 'alice@example.com')", []).unwrap();
+    conn.execute("INSERT INTO event_participants (event_id, participant_id) VALUES (3, last_insert_rowid())", []).unwrap();
+
+    assert_eq!(titles(conn, "smith"), vec!["Alice Smith", "Doctor Appointment"]);
+    assert_eq!(titles(conn, "alice@example.com"), vec!["Alice Smith", "Doctor Appointment"]);
+
+    conn.execute("UPDATE participants SET name = 'Alice Brown' WHERE email = 'alice@example.com'", []).unwrap();
+    assert!(titles(conn, "smith").is_empty());
+    assert_eq!(titles(conn, "brown"), vec!["Alice Brown", "Doctor Appointment"]);
+
+    conn.execute("DELETE FROM participants", []).unwrap();
+    assert!(titles(conn, "brown").is_empty());
+    assert_index_intact(conn);
+}
+
+#[test]
+#[serial]
+fn test_categories_and_locations_are_searchable() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert_eq!(titles(conn, "health"), vec!["Doctor Appointment", "Exercise routine", "Health"]);
+    assert_eq!(titles(conn, "medical"), vec!["Doctor Appointment"]);
+    assert_eq!(titles(conn, "health checkup"), vec!["Doctor Appointment"], "Metadata and text words combine");
+
+    conn.execute("UPDATE categories SET name = 'Wellness' WHERE id = 3", []).unwrap();
+    assert_eq!(titles(conn, "wellness"), vec!["Doctor Appointment", "Exercise routine", "Wellness"]);
+
+    conn.execute("UPDATE tasks SET category_id = 2 WHERE id = 3", []).unwrap();
+    assert_eq!(titles(conn, "wellness"), vec!["Doctor Appointment", "Wellness"]);
+
+    conn.execute("DELETE FROM categories WHERE id = 3", []).unwrap();
+    assert!(titles(conn, "wellness").is_empty());
+    assert_index_intact(conn);
+}
+
+#[test]
+#[serial]
+fn test_participants_and_categories_are_results() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO participants (name, email) VALUES ('Dana Quillfeather', 'dana@example.com')", []).unwrap();
+    conn.execute("INSERT INTO categories (name, color, symbol) VALUES ('Quillwork', '#0000FF', 'W')", []).unwrap();
+
+    let page = search_faceted(conn, "dana", &SearchScope::all(), None, 0).unwrap();
+    let found: Vec<_> = page.results.iter().map(|r| (r.item_type.as_str(), r.title.as_str(), r.description.as_deref())).collect();
+    assert_eq!(found, vec![("PARTICIPANT", "Dana Quillfeather", Some("dana@example.com"))]);
+    assert_eq!(counts(&page.facets.unwrap().item_types), vec![("PARTICIPANT", "Participants", 1)]);
+
+    let page = search_index(conn, "quill", &SearchScope::all(), None, 0).unwrap();
+    let mut found: Vec<_> = page.results.iter().map(|r| (r.item_type.as_str(), r.title.as_str())).collect();
+    found.sort();
+    assert_eq!(found, vec![("CATEGORY", "Quillwork"), ("PARTICIPANT", "Dana Quillfeather")]);
+
+    conn.execute("UPDATE categories SET name = 'Rafting' WHERE name = 'Quillwork'", []).unwrap();
+    conn.execute("DELETE FROM participants WHERE email = 'dana@example.com'", []).unwrap();
+    assert_eq!(search_index(conn, "quill", &SearchScope::all(), None, 0).unwrap().total, 0);
+    assert_eq!(titles(conn, "rafting"), vec!["Rafting"]);
+    assert_index_intact(conn);
+}
+
+#[test]
+#[serial]
+fn test_title_hits_rank_above_metadata_hits() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE tasks SET title = 'Personal budget' WHERE id = 3", []).unwrap();
+
+    let page = search_index(conn, "personal", &[SearchScope::new("TASK")], None, 0).unwrap();
+
+    assert_eq!(page.results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>(), vec!["Personal budget", "Buy groceries"]);
+}
+
+#[test]
+#[serial]
+fn test_facet_counts_cover_every_match() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO notes (title, content, created_at) VALUES ('Work log', 'Notes', '2023-02-03 10:00:00')", []).unwrap();
+
+    // Morning Standup and the project proposal are in Work, the workout
+    // matches by prefix, the note by title, and the Work category itself
+    let page = search_faceted(conn, "work", &SearchScope::all(), Some(1), 0).unwrap();
+    let facets = page.facets.unwrap();
+
+    assert_eq!(page.results.len(), 1);
+    assert_eq!(counts(&facets.item_types), vec![("EVENT", "Events", 1), ("TASK", "Tasks", 2), ("NOTE", "Notes", 1), ("CATEGORY", "Categories", 1)]);
+    assert_eq!(counts(&facets.categories), vec![("1", "Work", 3), ("3", "Health", 1)]);
+    assert_eq!(counts(&facets.statuses), vec![("IN_PROGRESS", "IN_PROGRESS", 1), ("PENDING", "PENDING", 1)]);
+    assert_eq!(counts(&facets.priorities), vec![("1", "Priority 1", 1), ("2", "Priority 2", 2)]);
+    assert_eq!(counts(&facets.months), vec![("2023-01", "January 2023", 3), ("2023-02", "February 2023", 1)]);
+
+    assert!(search_index(conn, "work", &SearchScope::all(), None, 0).unwrap().facets.is_none());
+}
+
+#[test]
+#[serial]
+fn test_fuzzy_results_have_facets() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let page = search_faceted(conn, "grocereis", &SearchScope::all(), None, 0).unwrap();
+
+    assert!(page.fuzzy);
+    let facets = page.facets.unwrap();
+    assert_eq!(counts(&facets.item_types), vec![("TASK", "Tasks", 1)]);
+    assert_eq!(counts(&facets.categories), vec![("2", "Personal", 1)]);
+}
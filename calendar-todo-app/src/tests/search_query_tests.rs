@@ -0,0 +1,128 @@
+use crate::services::search_query_service::*;
+use crate::services::search_service::SearchPage;
+use super::{setup_test_db, setup_test_db_with_data};
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn run(conn: &Connection, query: &str) -> SearchPage {
+    run_search_query(conn, query, None, 0).unwrap()
+}
+
+fn found(page: &SearchPage) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = page.results.iter()
+        .map(|r| (r.item_type.clone(), r.title.clone()))
+        .collect();
+    found.sort();
+    found
+}
+
+fn item(item_type: &str, title: &str) -> (String, String) {
+    (item_type.to_string(), title.to_string())
+}
+
+fn error_at(query: &str) -> (usize, String) {
+    let error = compile_query(query).unwrap_err();
+    (error.position, error.token)
+}
+
+#[test]
+fn test_tokenize_query() {
+    let tokens = tokenize_query("status:in_progress priority:<=2 category:\"Deep Work\" \"exact phrase\" -excluded").unwrap();
+
+    let terms: Vec<&QueryTerm> = tokens.iter().map(|t| &t.term).collect();
+    assert_eq!(terms, vec![
+        &QueryTerm::Filter { field: "status".to_string(), op: String::new(), value: "in_progress".to_string() },
+        &QueryTerm::Filter { field: "priority".to_string(), op: "<=".to_string(), value: "2".to_string() },
+        &QueryTerm::Filter { field: "category".to_string(), op: String::new(), value: "Deep Work".to_string() },
+        &QueryTerm::Phrase("exact phrase".to_string()),
+        &QueryTerm::Word("excluded".to_string()),
+    ]);
+    assert!(tokens[4].negated);
+    assert_eq!(tokens[3].position, 53);
+    assert_eq!(tokens[4].position, 68);
+}
+
+#[test]
+fn test_errors_point_at_bad_token() {
+    assert_eq!(error_at("meeting colour:red"), (8, "colour:red".to_string()));
+    assert_eq!(error_at("priority:high"), (0, "priority:high".to_string()));
+    assert_eq!(error_at("due:<2025-13-01"), (0, "due:<2025-13-01".to_string()));
+    assert_eq!(error_at("status:<todo"), (0, "status:<todo".to_string()));
+    assert_eq!(error_at("is:urgent"), (0, "is:urgent".to_string()));
+    assert_eq!(error_at("type:contact"), (0, "type:contact".to_string()));
+    assert_eq!(error_at("ok \"never closed"), (3, "\"never closed".to_string()));
+    assert_eq!(error_at("a - b"), (2, "-".to_string()));
+    assert_eq!(error_at("café @"), (5, "@".to_string()));
+
+    let error = compile_query("meeting colour:red").unwrap_err();
+    assert_eq!(error.to_string(), "Unknown field 'colour' at position 8");
+}
+
+#[test]
+#[serial]
+fn test_field_filters() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert_eq!(found(&run(conn, "status:in_progress")), vec![item("TASK", "Exercise routine")]);
+    assert_eq!(found(&run(conn, "priority:<=2 category:work")), vec![
+        item("EVENT", "Morning Standup"),
+        item("TASK", "Complete project proposal"),
+    ]);
+    assert_eq!(found(&run(conn, "due:<2023-01-18")), vec![item("TASK", "Exercise routine")]);
+    assert_eq!(found(&run(conn, "start:2023-01-17")), vec![item("EVENT", "Doctor Appointment")]);
+}
+
+#[test]
+#[serial]
+fn test_text_phrases_and_exclusions() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert_eq!(found(&run(conn, "\"project proposal\"")), vec![item("TASK", "Complete project proposal")]);
+    assert!(run(conn, "\"proposal project\"").results.is_empty(), "Phrases keep word order");
+
+    let page = run(conn, "category:Work -proposal");
+    assert_eq!(found(&page), vec![item("EVENT", "Morning Standup")]);
+
+    let page = run(conn, "type:task -status:pending");
+    assert_eq!(found(&page), vec![item("TASK", "Exercise routine")]);
+}
+
+#[test]
+#[serial]
+fn test_negated_filter_keeps_items_without_the_field() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE events SET category_id = NULL WHERE title = 'Lunch Break'", []).unwrap();
+
+    let page = run(conn, "type:event -category:health");
+
+    assert_eq!(found(&page), vec![item("EVENT", "Lunch Break"), item("EVENT", "Morning Standup")]);
+}
+
+#[test]
+#[serial]
+fn test_participant_and_flags() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO participants (name, email) VALUES ('Alice Smith', 'alice@example.com')", []).unwrap();
+    conn.execute("INSERT INTO event_participants (event_id, participant_id) VALUES (3, last_insert_rowid())", []).unwrap();
+    conn.execute("UPDATE tasks SET recurring_rule_id = 1 WHERE id = 2", []).unwrap();
+
+    assert_eq!(found(&run(conn, "participant:alice")), vec![item("EVENT", "Doctor Appointment")]);
+    assert!(run(conn, "participant:%").results.is_empty(), "LIKE wildcards are matched literally");
+    assert_eq!(found(&run(conn, "is:recurring")), vec![item("TASK", "Buy groceries")]);
+    assert_eq!(run(conn, "is:overdue").total, 3);
+}
+
+#[test]
+#[serial]
+fn test_values_are_bound_not_spliced() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let page = run(conn, "category:\"x') OR 1=1 --\" status:'");
+
+    assert_eq!(page.total, 0);
+}
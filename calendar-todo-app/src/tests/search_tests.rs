@@ -34,11 +34,11 @@ fn create_test_data(db: &Database) -> Result<(), String> {
     // Create tasks
     conn.execute(
         "INSERT INTO tasks (id, title, description, due_date, is_completed, priority, status, category_id, kanban_order) VALUES 
-         (1, 'Complete project documentation', 'Write comprehensive project docs', '2024-01-25', 0, 1, 'TODO', 1, 1),
+         (1, 'Complete project documentation', 'Write comprehensive project docs', '2024-01-25', 0, 1, 'PENDING', 1, 1),
          (2, 'Review code changes', 'Review pull requests from team', '2024-01-18', 0, 2, 'IN_PROGRESS', 1, 2),
-         (3, 'Buy groceries', 'Weekly grocery shopping', '2024-01-16', 0, 3, 'TODO', 2, 1),
+         (3, 'Buy groceries', 'Weekly grocery shopping', '2024-01-16', 0, 3, 'PENDING', 2, 1),
          (4, 'Exercise routine', 'Daily workout session', '2024-01-15', 1, 2, 'COMPLETED', 3, 1),
-         (5, 'Plan vacation', 'Research and plan summer vacation', '2024-02-01', 0, 3, 'TODO', 2, 2)",
+         (5, 'Plan vacation', 'Research and plan summer vacation', '2024-02-01', 0, 3, 'PENDING', 2, 2)",
         []
     ).map_err(|e| e.to_string())?;
 
@@ -274,13 +274,13 @@ async fn test_search_tasks_with_status_filter() {
         None,
         None,
         None,
-        Some("TODO".to_string()),
+        Some("PENDING".to_string()),
         None,
         state
     ).await.unwrap();
 
     assert!(!results.is_empty());
-    assert!(results.iter().all(|r| r.status == Some("TODO".to_string())));
+    assert!(results.iter().all(|r| r.status == Some("PENDING".to_string())));
 }
 
 #[tokio::test]
@@ -343,7 +343,7 @@ async fn test_search_tasks_combined_filters() {
         None,
         None,
         Some(1), // Work category
-        Some("TODO".to_string()),
+        Some("PENDING".to_string()),
         Some(1), // High priority
         state
     ).await.unwrap();
@@ -351,7 +351,7 @@ async fn test_search_tasks_combined_filters() {
     assert!(!results.is_empty());
     assert!(results.iter().all(|r| {
         r.category_id == Some(1) && 
-        r.status == Some("TODO".to_string()) && 
+        r.status == Some("PENDING".to_string()) && 
         r.priority == Some(1)
     }));
 }
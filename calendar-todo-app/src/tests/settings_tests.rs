@@ -0,0 +1,55 @@
+use crate::services::settings_service::*;
+use super::setup_test_db;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn test_read_default_setting() {
+    let db = setup_test_db();
+
+    let value = read_setting(db.get_connection(), "pomodoro_work_minutes")
+        .expect("Failed to read setting");
+
+    assert_eq!(value.as_deref(), Some("25"));
+}
+
+#[test]
+#[serial]
+fn test_read_missing_setting() {
+    let db = setup_test_db();
+
+    let value = read_setting(db.get_connection(), "does_not_exist")
+        .expect("Missing settings should not be an error");
+
+    assert!(value.is_none());
+}
+
+#[test]
+#[serial]
+fn test_write_setting_inserts_and_updates() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    write_setting(conn, "theme", "dark").expect("Failed to insert setting");
+    assert_eq!(read_setting(conn, "theme").unwrap().as_deref(), Some("dark"));
+
+    write_setting(conn, "theme", "light").expect("Failed to update setting");
+    assert_eq!(read_setting(conn, "theme").unwrap().as_deref(), Some("light"));
+
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM settings WHERE key = 'theme'", [], |row| row.get(0)
+    ).unwrap();
+    assert_eq!(count, 1, "Updating a setting should not duplicate the key");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_get_settings_command() {
+    let db = setup_test_db();
+
+    let settings = get_settings(tauri::State::new(db))
+        .await
+        .expect("Failed to get settings");
+
+    assert!(settings.iter().any(|s| s.key == "quiet_hours_start" && s.value == "22:00"));
+}
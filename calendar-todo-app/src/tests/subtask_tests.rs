@@ -0,0 +1,176 @@
+use crate::db::{Database, models::Task};
+use super::setup_test_db_with_data;
+use serial_test::serial;
+
+fn task(title: &str) -> Task {
+    Task {
+        id: None,
+        title: title.to_string(),
+        description: None,
+        due_date: None,
+        priority: 3,
+        status: "PENDING".to_string(),
+        category_id: None,
+        recurring_rule_id: None,
+        kanban_column_id: None,
+        kanban_order: None,
+        completed_at: None,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn subtask_titles(db: &Database, parent_id: i64) -> Vec<String> {
+    db.get_subtasks(parent_id).unwrap().into_iter().map(|t| t.title).collect()
+}
+
+fn status(db: &Database, id: i64) -> String {
+    db.get_connection()
+        .query_row("SELECT status FROM tasks WHERE id = ?", [id], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+#[serial]
+fn test_subtask_tree_of_any_depth() {
+    let db = setup_test_db_with_data();
+    let outline = db.create_subtask(1, &task("Outline")).unwrap();
+    db.create_subtask(1, &task("Budget")).unwrap();
+    let sources = db.create_subtask(outline, &task("Collect sources")).unwrap();
+    db.create_subtask(sources, &task("Ask the library")).unwrap();
+
+    assert_eq!(subtask_titles(&db, 1), vec!["Outline", "Budget"]);
+
+    let tree = db.get_task_tree(1).unwrap();
+    assert_eq!(tree.subtasks.len(), 2);
+    assert_eq!(tree.subtasks[0].parent_task_id, Some(1));
+    assert_eq!(tree.subtasks[0].subtasks[0].subtasks[0].task.title, "Ask the library");
+    assert_eq!(tree.progress.subtasks_total, 4, "Progress counts subtasks at every depth");
+
+    assert!(db.create_subtask(999, &task("Orphan")).is_err());
+}
+
+#[test]
+#[serial]
+fn test_progress_rolls_up_subtasks_and_checklist() {
+    let db = setup_test_db_with_data();
+    let first = db.create_subtask(1, &task("First draft")).unwrap();
+    db.create_subtask(1, &task("Review")).unwrap();
+    db.add_checklist_item(1, "Pick a template").unwrap();
+    let checked = db.add_checklist_item(1, "Book a room").unwrap();
+    db.add_checklist_item(1, "Send invites").unwrap();
+
+    db.set_task_status(first, "COMPLETED").unwrap();
+    let mut item = db.get_checklist_items(1).unwrap().into_iter().find(|i| i.id == Some(checked)).unwrap();
+    item.is_checked = true;
+    db.update_checklist_item(&item).unwrap();
+
+    let progress = db.get_task_progress(1).unwrap();
+    assert_eq!((progress.subtasks_completed, progress.subtasks_total), (1, 2));
+    assert_eq!((progress.checklist_checked, progress.checklist_total), (1, 3));
+    assert_eq!((progress.completed, progress.total), (2, 5));
+
+    assert!(db.get_checklist_items(1).unwrap()[1].checked_at.is_some());
+    assert!(db.add_checklist_item(1, "  ").is_err());
+}
+
+#[test]
+#[serial]
+fn test_parent_completes_when_all_subtasks_finish() {
+    let db = setup_test_db_with_data();
+    let chapter = db.create_subtask(1, &task("Chapter")).unwrap();
+    let section = db.create_subtask(chapter, &task("Section")).unwrap();
+    let appendix = db.create_subtask(1, &task("Appendix")).unwrap();
+
+    assert_eq!(db.set_task_status(section, "COMPLETED").unwrap().completed_parent_ids, vec![chapter]);
+    assert_eq!(status(&db, 1), "PENDING", "Appendix is still open");
+
+    let completed = db.set_task_status(appendix, "COMPLETED").unwrap().completed_parent_ids;
+    assert_eq!(completed, vec![1], "Completing the last open subtask completes the root");
+    assert_eq!(status(&db, 1), "COMPLETED");
+}
+
+#[test]
+#[serial]
+fn test_auto_complete_waits_for_checklist_and_can_be_turned_off() {
+    let db = setup_test_db_with_data();
+    let only = db.create_subtask(2, &task("Make a list")).unwrap();
+    let item_id = db.add_checklist_item(2, "Check the fridge").unwrap();
+
+    assert!(db.set_task_status(only, "COMPLETED").unwrap().completed_parent_ids.is_empty());
+    assert_eq!(status(&db, 2), "PENDING", "An unchecked checklist item keeps the parent open");
+
+    let mut item = db.get_checklist_items(2).unwrap().remove(0);
+    assert_eq!(item.id, Some(item_id));
+    item.is_checked = true;
+    assert_eq!(db.update_checklist_item(&item).unwrap().completed_parent_ids, vec![2]);
+
+    let parent = db.create_subtask(1, &task("Other")).unwrap();
+    db.get_connection().execute("UPDATE settings SET value = '0' WHERE key = 'auto_complete_parent_tasks'", []).unwrap();
+    assert!(db.set_task_status(parent, "COMPLETED").unwrap().completed_parent_ids.is_empty());
+    assert_eq!(status(&db, 1), "PENDING");
+}
+
+#[test]
+#[serial]
+fn test_delete_cascades_or_promotes_subtasks() {
+    let db = setup_test_db_with_data();
+    let phase_one = db.create_subtask(1, &task("Phase one")).unwrap();
+    db.create_subtask(phase_one, &task("Kickoff")).unwrap();
+    db.create_subtask(phase_one, &task("Research")).unwrap();
+    db.create_subtask(1, &task("Phase two")).unwrap();
+    let phase_three = db.create_subtask(1, &task("Phase three")).unwrap();
+    let wrap_up = db.create_subtask(phase_three, &task("Wrap up")).unwrap();
+    db.add_checklist_item(wrap_up, "Archive files").unwrap();
+
+    db.delete_task_with_subtasks(phase_one, "PROMOTE").unwrap();
+    assert_eq!(subtask_titles(&db, 1), vec!["Kickoff", "Research", "Phase two", "Phase three"]);
+
+    db.delete_task_with_subtasks(phase_three, "CASCADE").unwrap();
+    assert_eq!(subtask_titles(&db, 1), vec!["Kickoff", "Research", "Phase two"]);
+    db.purge_item("TASK", phase_three).unwrap();
+    assert!(db.get_task_tree(wrap_up).is_err());
+    assert!(db.get_checklist_items(wrap_up).unwrap().is_empty());
+
+    assert!(db.delete_task_with_subtasks(phase_three, "CASCADE").is_ok(), "Deleting a missing task is not an error");
+    assert!(db.delete_task_with_subtasks(1, "ORPHAN").is_err());
+}
+
+#[test]
+#[serial]
+fn test_move_task_and_reject_cycles() {
+    let db = setup_test_db_with_data();
+    let design = db.create_subtask(1, &task("Design")).unwrap();
+    let mockups = db.create_subtask(design, &task("Mockups")).unwrap();
+    db.create_subtask(1, &task("Build")).unwrap();
+
+    assert!(db.move_task(design, Some(mockups), None, "CASCADE").is_err(), "A task cannot move under its own subtask");
+    assert!(db.move_task(design, Some(design), None, "CASCADE").is_err());
+
+    db.move_task(design, Some(2), Some(0), "CASCADE").unwrap();
+    assert_eq!(subtask_titles(&db, 1), vec!["Build"]);
+    assert_eq!(subtask_titles(&db, 2), vec!["Design"]);
+    assert_eq!(subtask_titles(&db, design), vec!["Mockups"], "Subtasks move along");
+
+    db.move_task(design, None, None, "PROMOTE").unwrap();
+    assert_eq!(subtask_titles(&db, 2), vec!["Mockups"]);
+    assert!(db.get_task_tree(design).unwrap().parent_task_id.is_none());
+
+    db.move_task(mockups, Some(1), Some(0), "CASCADE").unwrap();
+    assert_eq!(subtask_titles(&db, 1), vec!["Mockups", "Build"]);
+}
+
+#[test]
+#[serial]
+fn test_reorder_checklist_items() {
+    let db = setup_test_db_with_data();
+    for title in ["Flights", "Hotel", "Visa"] {
+        db.add_checklist_item(3, title).unwrap();
+    }
+    let visa = db.get_checklist_items(3).unwrap()[2].id.unwrap();
+
+    db.reorder_checklist_item(visa, 0).unwrap();
+
+    let titles: Vec<String> = db.get_checklist_items(3).unwrap().into_iter().map(|i| i.title).collect();
+    assert_eq!(titles, vec!["Visa", "Flights", "Hotel"]);
+}
@@ -0,0 +1,127 @@
+use crate::services::search_query_service::run_search_query;
+use crate::services::search_service::{search_index, SearchScope};
+use crate::services::tag_service::*;
+use super::setup_test_db_with_data;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn tag(name: &str) -> Tag {
+    Tag { id: None, name: name.to_string(), color: None, created_at: None }
+}
+
+fn names(tags: Vec<Tag>) -> Vec<String> {
+    tags.into_iter().map(|t| t.name).collect()
+}
+
+fn found(conn: &Connection, query: &str) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = run_search_query(conn, query, None, 0).unwrap()
+        .results.into_iter()
+        .map(|r| (r.item_type, r.title))
+        .collect();
+    found.sort();
+    found
+}
+
+/// Tags the standup and the proposal "q1", the proposal and the first note "planning"
+fn tag_seed_items(conn: &Connection) -> (i64, i64) {
+    conn.execute("INSERT INTO notes (title, content) VALUES ('Roadmap', 'Milestones for the quarter')", []).unwrap();
+    let note_id = conn.last_insert_rowid();
+    let q1 = insert_tag(conn, &tag("Q1")).unwrap();
+    let planning = insert_tag(conn, &tag("planning")).unwrap();
+    add_item_tag(conn, "EVENT", 1, q1).unwrap();
+    add_item_tag(conn, "TASK", 1, q1).unwrap();
+    add_item_tag(conn, "TASK", 1, planning).unwrap();
+    add_item_tag(conn, "NOTE", note_id, planning).unwrap();
+    (q1, planning)
+}
+
+#[test]
+#[serial]
+fn test_tag_crud_and_item_links() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let (q1, _) = tag_seed_items(conn);
+
+    assert!(insert_tag(conn, &tag("q1")).is_err(), "Names are unique regardless of case");
+    assert!(insert_tag(conn, &tag("  ")).is_err());
+    assert!(add_item_tag(conn, "TASK", 999, q1).is_err());
+    assert!(add_item_tag(conn, "CATEGORY", 1, q1).is_err());
+
+    assert_eq!(names(load_item_tags(conn, "TASK", 1).unwrap()), vec!["planning", "Q1"]);
+    remove_item_tag(conn, "TASK", 1, q1).unwrap();
+    assert_eq!(names(load_item_tags(conn, "TASK", 1).unwrap()), vec!["planning"]);
+
+    let tags = replace_item_tags(conn, "TASK", 2, &["errands".to_string(), "Q1".to_string()]).unwrap();
+    assert_eq!(names(tags), vec!["errands", "Q1"]);
+    assert_eq!(names(load_tags(conn).unwrap()), vec!["errands", "planning", "Q1"]);
+
+    remove_tag(conn, q1).unwrap();
+    assert!(load_item_tags(conn, "EVENT", 1).unwrap().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_rename_into_existing_tag_merges_links() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let (q1, planning) = tag_seed_items(conn);
+
+    assert_eq!(modify_tag(conn, &Tag { id: Some(q1), ..tag("Q1 2023") }).unwrap(), q1);
+    assert_eq!(load_tag(conn, q1).unwrap().name, "Q1 2023");
+
+    let kept = modify_tag(conn, &Tag { id: Some(q1), ..tag("Planning") }).unwrap();
+    assert_eq!(kept, planning);
+    assert!(load_tag(conn, q1).is_err());
+    assert_eq!(names(load_item_tags(conn, "EVENT", 1).unwrap()), vec!["planning"]);
+    assert_eq!(names(load_item_tags(conn, "TASK", 1).unwrap()), vec!["planning"], "The proposal keeps one link");
+}
+
+#[test]
+#[serial]
+fn test_merge_and_usage_counts() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let (q1, planning) = tag_seed_items(conn);
+    let unused = insert_tag(conn, &tag("someday")).unwrap();
+    let work = insert_tag(conn, &tag("work")).unwrap();
+    add_item_tag(conn, "EVENT", 2, work).unwrap();
+
+    let usage = load_tag_usage(conn).unwrap();
+    let counts: Vec<(&str, i64, i64, i64, i64)> = usage.iter()
+        .map(|u| (u.tag.name.as_str(), u.events, u.tasks, u.notes, u.total))
+        .collect();
+    assert_eq!(counts, vec![
+        ("planning", 0, 1, 1, 2),
+        ("Q1", 1, 1, 0, 2),
+        ("work", 1, 0, 0, 1),
+        ("someday", 0, 0, 0, 0),
+    ]);
+
+    merge_into_tag(conn, &[planning, work, unused], q1).unwrap();
+    let usage = load_tag_usage(conn).unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!((usage[0].events, usage[0].tasks, usage[0].notes), (2, 1, 1));
+    assert!(merge_into_tag(conn, &[q1], 999).is_err());
+}
+
+#[test]
+#[serial]
+fn test_search_filters_by_tag() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    tag_seed_items(conn);
+
+    assert_eq!(found(conn, "tag:q1"), vec![
+        ("EVENT".to_string(), "Morning Standup".to_string()),
+        ("TASK".to_string(), "Complete project proposal".to_string()),
+    ]);
+    assert_eq!(found(conn, "tag:planning -tag:q1"), vec![("NOTE".to_string(), "Roadmap".to_string())]);
+    assert_eq!(found(conn, "tag:planning type:task"), vec![("TASK".to_string(), "Complete project proposal".to_string())]);
+    assert!(run_search_query(conn, "tag:>q1", None, 0).is_err());
+
+    let scopes = [SearchScope::new("TASK").tagged("Q1").tagged("planning")];
+    let page = search_index(conn, "project", &scopes, None, 0).unwrap();
+    assert_eq!(page.results.len(), 1);
+    let page = search_index(conn, "groceries", &scopes, None, 0).unwrap();
+    assert!(page.results.is_empty());
+}
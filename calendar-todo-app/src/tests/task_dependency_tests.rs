@@ -0,0 +1,64 @@
+use crate::db::{Database, models::Task, operations::BLOCKED_CONDITION};
+use super::setup_test_db_with_data;
+use serial_test::serial;
+
+fn blocked_ids(db: &Database) -> Vec<i64> {
+    let mut stmt = db.get_connection()
+        .prepare(&format!("SELECT id FROM tasks WHERE {} ORDER BY id", BLOCKED_CONDITION))
+        .unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().map(|id| id.unwrap()).collect()
+}
+
+fn titles(tasks: Vec<Task>) -> Vec<String> {
+    tasks.into_iter().map(|t| t.title).collect()
+}
+
+#[test]
+#[serial]
+fn test_task_is_blocked_until_prerequisites_finish() {
+    let db = setup_test_db_with_data();
+    db.add_task_dependency(1, 2).unwrap();
+    db.add_task_dependency(1, 3).unwrap();
+    db.add_task_dependency(1, 2).unwrap();
+
+    assert_eq!(titles(db.get_task_dependencies(1).unwrap()), vec!["Buy groceries", "Exercise routine"]);
+    assert_eq!(titles(db.get_dependent_tasks(2).unwrap()), vec!["Complete project proposal"]);
+    assert_eq!(blocked_ids(&db), vec![1]);
+
+    db.set_task_status(2, "COMPLETED").unwrap();
+    assert_eq!(titles(db.get_blocking_tasks(1).unwrap()), vec!["Exercise routine"]);
+
+    db.set_task_status(3, "COMPLETED").unwrap();
+    assert!(db.get_blocking_tasks(1).unwrap().is_empty());
+    assert!(blocked_ids(&db).is_empty());
+}
+
+#[test]
+#[serial]
+fn test_cycles_are_rejected() {
+    let db = setup_test_db_with_data();
+    db.add_task_dependency(1, 2).unwrap();
+    db.add_task_dependency(2, 3).unwrap();
+
+    let err = db.add_task_dependency(3, 1).unwrap_err().to_string();
+    assert!(err.contains("3 → 1 → 2 → 3"), "Unexpected error: {}", err);
+
+    assert!(db.add_task_dependency(2, 2).is_err());
+    assert!(db.add_task_dependency(2, 999).is_err());
+    assert!(db.get_task_dependencies(3).unwrap().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_removing_edges_and_tasks_unblocks() {
+    let db = setup_test_db_with_data();
+    db.add_task_dependency(1, 2).unwrap();
+    db.add_task_dependency(3, 2).unwrap();
+
+    db.remove_task_dependency(1, 2).unwrap();
+    assert_eq!(blocked_ids(&db), vec![3]);
+
+    db.delete_task_with_subtasks(2, "CASCADE").unwrap();
+    assert!(db.get_task_dependencies(3).unwrap().is_empty());
+    assert!(blocked_ids(&db).is_empty());
+}
@@ -0,0 +1,144 @@
+use crate::services::saved_search_service::*;
+use crate::services::task_service::load_tasks_in_range;
+use super::setup_test_db_with_data;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serial_test::serial;
+
+const JANUARY: (&str, &str) = ("2023-01-01 00:00:00", "2023-01-31 23:59:59");
+
+fn in_january(conn: &Connection, include_undated: bool) -> Vec<String> {
+    let mut titles: Vec<String> = load_tasks_in_range(conn, JANUARY.0, JANUARY.1, include_undated).unwrap()
+        .into_iter()
+        .map(|t| t.title)
+        .collect();
+    titles.sort();
+    titles
+}
+
+fn smart_list(name: &str, query: &str) -> SavedSearch {
+    SavedSearch {
+        id: None,
+        name: name.to_string(),
+        query: query.to_string(),
+        sort_by: "TITLE".to_string(),
+        group_by: "NONE".to_string(),
+        is_pinned: true,
+        position: 0,
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn day(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn counts(conn: &Connection, today: &str) -> Vec<(String, i64)> {
+    load_smart_lists(conn, day(today)).unwrap()
+        .into_iter()
+        .map(|list| (list.name, list.count))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_tasks_in_range_respect_start_dates() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO tasks (title, priority) VALUES ('Read a book', 3)", []).unwrap();
+    let undated = conn.last_insert_rowid();
+
+    let all = vec!["Buy groceries", "Complete project proposal", "Exercise routine"];
+    assert_eq!(in_january(conn, false), all, "Undated tasks are left out unless asked for");
+    assert_eq!(in_january(conn, true), vec!["Buy groceries", "Complete project proposal", "Exercise routine", "Read a book"]);
+
+    db.defer_task(1, Some("2023-02-06")).unwrap();
+    db.defer_task(undated, Some("2023-02-01")).unwrap();
+    assert_eq!(in_january(conn, true), vec!["Buy groceries", "Exercise routine"], "Hidden until after the range");
+
+    db.defer_task(1, Some("2023-01-20")).unwrap();
+    assert_eq!(in_january(conn, false), all, "Starts within the range");
+    db.defer_task(1, Some("2023-01-31T09:00:00")).unwrap();
+    assert_eq!(in_january(conn, false), all, "Start dates compare as dates whatever their format");
+
+    db.set_task_someday(2, true).unwrap();
+    assert_eq!(in_january(conn, false), vec!["Complete project proposal", "Exercise routine"]);
+
+    db.defer_task(undated, None).unwrap();
+    assert_eq!(in_january(conn, true), vec!["Complete project proposal", "Exercise routine", "Read a book"]);
+}
+
+#[test]
+#[serial]
+fn test_defer_and_someday() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let state = |id: i64| -> (Option<String>, bool) {
+        conn.query_row("SELECT start_date, is_someday FROM tasks WHERE id = ?", [id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+    };
+
+    db.set_task_someday(2, true).unwrap();
+    assert_eq!(state(2), (None, true));
+
+    db.defer_task(2, Some("2023-01-23 08:00:00")).unwrap();
+    assert_eq!(state(2), (Some("2023-01-23 08:00:00".to_string()), false), "Snoozing to a date picks a someday task up");
+
+    db.set_task_someday(2, true).unwrap();
+    db.defer_task(2, None).unwrap();
+    assert_eq!(state(2), (None, true), "Clearing the start date leaves it in someday");
+
+    assert!(db.defer_task(2, Some("next monday")).is_err());
+    assert!(db.defer_task(99, Some("2023-01-23")).is_err());
+    assert!(db.set_task_someday(99, true).is_err());
+
+    db.set_task_status(3, "COMPLETED").unwrap();
+    assert!(db.defer_task(3, Some("2023-01-23")).is_err(), "Completed tasks are not deferred");
+}
+
+#[test]
+#[serial]
+fn test_smart_lists_hide_deferred_and_someday_tasks() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    insert_saved_search(conn, &smart_list("Tasks", "type:task")).unwrap();
+    insert_saved_search(conn, &smart_list("Snoozed", "is:deferred")).unwrap();
+    insert_saved_search(conn, &smart_list("Someday", "is:someday")).unwrap();
+    insert_saved_search(conn, &smart_list("Everything else", "type:task -is:available")).unwrap();
+
+    db.defer_task(1, Some("2023-01-20")).unwrap();
+    db.set_task_someday(2, true).unwrap();
+
+    // Smart lists at the same position are listed by name
+    let expected = |tasks: i64, snoozed: i64| vec![
+        ("Everything else".to_string(), 3 - tasks),
+        ("Snoozed".to_string(), snoozed),
+        ("Someday".to_string(), 1),
+        ("Tasks".to_string(), tasks),
+    ];
+    assert_eq!(counts(conn, "2023-01-18"), expected(1, 1));
+    assert_eq!(counts(conn, "2023-01-20"), expected(2, 0), "Shows up on its start date");
+}
+
+#[test]
+#[serial]
+fn test_next_instance_keeps_lead_time() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO recurring_rules (frequency, interval) VALUES ('WEEKLY', 1)", []).unwrap();
+    let rule_id = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE tasks SET due_date = '2099-01-09', start_date = '2099-01-07' WHERE id = ?",
+        params![2],
+    ).unwrap();
+    db.set_task_recurrence(2, Some(rule_id), "FIXED").unwrap();
+
+    let next_id = db.set_task_status(2, "COMPLETED").unwrap().next_task_ids[0];
+    let dates: (String, String) = conn.query_row(
+        "SELECT due_date, start_date FROM tasks WHERE id = ?",
+        [next_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert_eq!(dates, ("2099-01-16".to_string(), "2099-01-14".to_string()));
+}
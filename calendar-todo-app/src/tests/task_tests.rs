@@ -129,7 +129,7 @@ async fn test_delete_task() {
         .await
         .expect("Failed to create task");
 
-    let result = delete_task(id, tauri::State::new(db))
+    let result = delete_task(id, None, tauri::State::new(db))
         .await;
     
     assert!(result.is_ok(), "Task deletion should succeed");
@@ -140,7 +140,7 @@ async fn test_delete_task() {
 async fn test_delete_nonexistent_task() {
     let db = setup_test_db();
 
-    let result = delete_task(999, tauri::State::new(db)).await;
+    let result = delete_task(999, None, tauri::State::new(db)).await;
     assert!(result.is_ok(), "Delete should succeed even for non-existent task");
 }
 
@@ -180,22 +180,22 @@ async fn test_get_tasks_by_status() {
     let db = setup_test_db();
     
     // Create tasks with different statuses
-    let todo_task = TaskFactory::create_default(); // TODO status
+    let todo_task = TaskFactory::create_default(); // PENDING status
     let in_progress_task = TaskFactory::create_in_progress();
     let completed_task = TaskFactory::create_completed();
 
-    create_task(todo_task, tauri::State::new(db.clone())).await.expect("Failed to create TODO task");
+    create_task(todo_task, tauri::State::new(db.clone())).await.expect("Failed to create PENDING task");
     create_task(in_progress_task, tauri::State::new(db.clone())).await.expect("Failed to create IN_PROGRESS task");
     create_task(completed_task, tauri::State::new(db.clone())).await.expect("Failed to create COMPLETED task");
 
-    // Test getting TODO tasks
+    // Test getting PENDING tasks
     let todo_tasks = get_tasks_by_status(
-        "TODO".to_string(),
+        "PENDING".to_string(),
         tauri::State::new(db.clone())
-    ).await.expect("Failed to get TODO tasks");
+    ).await.expect("Failed to get PENDING tasks");
     
-    assert_eq!(todo_tasks.len(), 1, "Should find one TODO task");
-    assert_eq!(todo_tasks[0].status, "TODO");
+    assert_eq!(todo_tasks.len(), 1, "Should find one PENDING task");
+    assert_eq!(todo_tasks[0].status, "PENDING");
 
     // Test getting IN_PROGRESS tasks
     let in_progress_tasks = get_tasks_by_status(
@@ -222,7 +222,7 @@ async fn test_get_tasks_by_status_empty() {
     let db = setup_test_db();
 
     let tasks = get_tasks_by_status(
-        "TODO".to_string(),
+        "PENDING".to_string(),
         tauri::State::new(db)
     ).await.expect("Failed to get tasks by status");
 
@@ -242,7 +242,7 @@ async fn test_get_tasks_in_range() {
             description: None,
             due_date: Some("2023-01-15 17:00:00".to_string()),
             priority: 3,
-            status: "TODO".to_string(),
+            status: "PENDING".to_string(),
             category_id: None,
             recurring_rule_id: None,
             kanban_column_id: Some(1),
@@ -257,7 +257,7 @@ async fn test_get_tasks_in_range() {
             description: None,
             due_date: Some("2023-01-20 17:00:00".to_string()),
             priority: 3,
-            status: "TODO".to_string(),
+            status: "PENDING".to_string(),
             category_id: None,
             recurring_rule_id: None,
             kanban_column_id: Some(1),
@@ -272,7 +272,7 @@ async fn test_get_tasks_in_range() {
             description: None,
             due_date: None,
             priority: 3,
-            status: "TODO".to_string(),
+            status: "PENDING".to_string(),
             category_id: None,
             recurring_rule_id: None,
             kanban_column_id: Some(1),
@@ -291,17 +291,27 @@ async fn test_get_tasks_in_range() {
     let tasks_in_range = get_tasks_in_range(
         "2023-01-14 00:00:00".to_string(),
         "2023-01-16 23:59:59".to_string(),
-        tauri::State::new(db)
+        Some(true),
+        tauri::State::new(db.clone())
     ).await.expect("Failed to get tasks in range");
 
     assert_eq!(tasks_in_range.len(), 2, "Should find task in range plus task with no due date");
+
+    let tasks_in_range = get_tasks_in_range(
+        "2023-01-14 00:00:00".to_string(),
+        "2023-01-16 23:59:59".to_string(),
+        None,
+        tauri::State::new(db)
+    ).await.expect("Failed to get tasks in range");
+
+    assert_eq!(tasks_in_range.len(), 1, "Tasks with no due date are left out by default");
 }
 
 #[tokio::test]
 #[serial]
 async fn test_update_task_status() {
     let db = setup_test_db();
-    let task = TaskFactory::create_default(); // TODO status
+    let task = TaskFactory::create_default(); // PENDING status
 
     let id = create_task(task, tauri::State::new(db.clone()))
         .await
@@ -337,18 +347,18 @@ async fn test_update_task_status_back_to_todo() {
         .await
         .expect("Failed to create completed task");
 
-    // Update back to TODO (should clear completed_at)
-    let result = update_task_status(id, "TODO".to_string(), tauri::State::new(db.clone()))
+    // Update back to PENDING (should clear completed_at)
+    let result = update_task_status(id, "PENDING".to_string(), tauri::State::new(db.clone()))
         .await;
-    assert!(result.is_ok(), "Status update back to TODO should succeed");
+    assert!(result.is_ok(), "Status update back to PENDING should succeed");
 
     let todo_tasks = get_tasks_by_status(
-        "TODO".to_string(),
+        "PENDING".to_string(),
         tauri::State::new(db)
-    ).await.expect("Failed to get TODO tasks");
+    ).await.expect("Failed to get PENDING tasks");
     
-    assert_eq!(todo_tasks.len(), 1, "Should find one TODO task");
-    // Note: Current implementation doesn't clear completed_at when moving back to TODO
+    assert_eq!(todo_tasks.len(), 1, "Should find one PENDING task");
+    // Note: Current implementation doesn't clear completed_at when moving back to PENDING
     // This is a potential improvement area
 }
 
@@ -478,7 +488,7 @@ async fn test_task_kanban_ordering() {
 
     // Tasks should be ordered by kanban_order ASC
     let tasks_by_status = get_tasks_by_status(
-        "TODO".to_string(),
+        "PENDING".to_string(),
         tauri::State::new(db)
     ).await.expect("Failed to get tasks by status");
 
@@ -518,7 +528,7 @@ async fn test_task_performance() {
                 description: None,
                 due_date: if i % 2 == 0 { Some(format!("2023-01-{:02} 17:00:00", 20 + (i % 10))) } else { None },
                 priority: (i % 3) as i32 + 1,
-                status: "TODO".to_string(),
+                status: "PENDING".to_string(),
                 category_id: None,
                 recurring_rule_id: None,
                 kanban_column_id: Some(1),
@@ -554,7 +564,7 @@ async fn test_concurrent_task_operations() {
                 description: None,
                 due_date: None,
                 priority: (i % 3) as i32 + 1,
-                status: "TODO".to_string(),
+                status: "PENDING".to_string(),
                 category_id: None,
                 recurring_rule_id: None,
                 kanban_column_id: Some(1),
@@ -617,7 +627,7 @@ async fn test_task_edge_cases() {
         description: None,
         due_date: None,
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -637,7 +647,7 @@ async fn test_task_edge_cases() {
         description: Some("Description with émojis 🚀 and àccénts".to_string()),
         due_date: None,
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -663,7 +673,7 @@ async fn test_task_date_edge_cases() {
         description: None,
         due_date: Some("2024-02-29 17:00:00".to_string()),
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
@@ -683,7 +693,7 @@ async fn test_task_date_edge_cases() {
         description: None,
         due_date: Some("2023-12-31 23:59:59".to_string()),
         priority: 3,
-        status: "TODO".to_string(),
+        status: "PENDING".to_string(),
         category_id: None,
         recurring_rule_id: None,
         kanban_column_id: Some(1),
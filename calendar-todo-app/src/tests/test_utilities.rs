@@ -161,7 +161,7 @@ impl TaskFactory {
             description: Some("Default task description".to_string()),
             due_date: Some("2023-01-31 17:00:00".to_string()),
             priority: 3,
-            status: "TODO".to_string(),
+            status: "PENDING".to_string(),
             category_id: None,
             recurring_rule_id: None,
             kanban_column_id: Some(1),
@@ -179,7 +179,7 @@ impl TaskFactory {
             description: Some("High priority urgent task".to_string()),
             due_date: Some("2023-01-16 17:00:00".to_string()),
             priority: 1,
-            status: "TODO".to_string(),
+            status: "PENDING".to_string(),
             category_id: None,
             recurring_rule_id: None,
             kanban_column_id: Some(1),
@@ -241,10 +241,10 @@ impl TaskFactory {
                 due_date: if i % 2 == 0 { Some(format!("2023-01-{:02} 17:00:00", 20 + i)) } else { None },
                 priority: (i % 3) as i32 + 1,
                 status: match i % 4 {
-                    0 => "TODO",
+                    0 => "PENDING",
                     1 => "IN_PROGRESS",
                     2 => "COMPLETED",
-                    _ => "TODO",
+                    _ => "PENDING",
                 }.to_string(),
                 category_id: None,
                 recurring_rule_id: None,
@@ -307,7 +307,7 @@ impl RecurringRuleFactory {
     pub fn create_yearly() -> RecurringRule {
         RecurringRule {
             id: None,
-            frequency: "YEARLY".to_string(),
+            frequency: "ANNUALLY".to_string(),
             interval_value: 1,
             days_of_week: None,
             day_of_month: Some(1),
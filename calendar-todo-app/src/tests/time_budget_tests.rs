@@ -0,0 +1,191 @@
+use crate::services::time_budget_service::*;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn add_entry(conn: &Connection, item_type: &str, item_id: i64, start: &str, end: &str) {
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, end_time, duration_seconds, timer_type)
+         VALUES (?1, ?2, ?3, ?4, strftime('%s', ?4) - strftime('%s', ?3), 'MANUAL')",
+        rusqlite::params![item_type, item_id, start, end],
+    ).unwrap();
+}
+
+fn budget(category_id: i64, period: &str, budget_minutes: i64) -> CategoryBudget {
+    CategoryBudget { id: None, category_id, period: period.to_string(), budget_minutes }
+}
+
+#[test]
+#[serial]
+fn test_task_estimate_remaining_and_overrun() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_task_estimate(conn, 1, Some(60)).unwrap();
+    add_entry(conn, "TASK", 1, "2024-01-15 09:00:00", "2024-01-15 09:45:00");
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, timer_type) VALUES ('TASK', 1, '2024-01-15 10:00:00', 'MANUAL')",
+        [],
+    ).unwrap();
+
+    let estimate = load_task_estimate(conn, 1).unwrap().unwrap();
+    assert_eq!(estimate.tracked_seconds, 2700, "Running timers should not count yet");
+    assert_eq!(estimate.remaining_seconds, Some(900));
+    assert_eq!(estimate.overrun_seconds, 0);
+    assert_eq!(estimate.percent_used, Some(75));
+
+    add_entry(conn, "TASK", 1, "2024-01-16 09:00:00", "2024-01-16 09:30:00");
+    let estimate = load_task_estimate(conn, 1).unwrap().unwrap();
+    assert_eq!(estimate.remaining_seconds, Some(0));
+    assert_eq!(estimate.overrun_seconds, 900);
+    assert_eq!(estimate.percent_used, Some(125));
+}
+
+#[test]
+#[serial]
+fn test_task_without_estimate() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let estimate = load_task_estimate(conn, 2).unwrap().unwrap();
+    assert_eq!(estimate.estimated_minutes, None);
+    assert_eq!(estimate.remaining_seconds, None);
+    assert!(load_task_estimate(conn, 99999).unwrap().is_none());
+    assert!(load_task_estimates(conn).unwrap().is_empty(), "Only tasks with an estimate are listed");
+}
+
+#[test]
+#[serial]
+fn test_estimate_validation() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    assert!(write_task_estimate(conn, 1, Some(0)).is_err());
+    assert!(write_task_estimate(conn, 99999, Some(30)).is_err(), "Unknown task should be rejected");
+    assert!(write_task_estimate(conn, 1, None).is_ok(), "Clearing an estimate is allowed");
+}
+
+#[test]
+fn test_budget_periods() {
+    // 2024-02-14 is a Wednesday
+    assert_eq!(budget_period("WEEKLY", date("2024-02-14"), 1).unwrap(), (date("2024-02-12"), date("2024-02-18")));
+    assert_eq!(budget_period("WEEKLY", date("2024-02-14"), 0).unwrap(), (date("2024-02-11"), date("2024-02-17")));
+    assert_eq!(budget_period("MONTHLY", date("2024-02-14"), 1).unwrap(), (date("2024-02-01"), date("2024-02-29")));
+    assert_eq!(budget_period("MONTHLY", date("2024-12-31"), 1).unwrap(), (date("2024-12-01"), date("2024-12-31")));
+    assert!(budget_period("YEARLY", date("2024-02-14"), 1).is_err());
+}
+
+#[test]
+#[serial]
+fn test_category_budget_status() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_category_budget(conn, &budget(1, "WEEKLY", 120)).unwrap();
+
+    // Task 1 and event 1 both belong to Work; the first entry straddles the week boundary
+    add_entry(conn, "TASK", 1, "2024-01-14 23:00:00", "2024-01-15 01:00:00");
+    add_entry(conn, "EVENT", 1, "2024-01-16 09:00:00", "2024-01-16 10:30:00");
+    add_entry(conn, "TASK", 2, "2024-01-16 11:00:00", "2024-01-16 12:00:00");
+
+    let statuses = load_budget_statuses(conn, date("2024-01-17")).unwrap();
+    assert_eq!(statuses.len(), 1);
+    let work = &statuses[0];
+    assert_eq!(work.category_name, "Work");
+    assert_eq!(work.period_start, "2024-01-15");
+    assert_eq!(work.tracked_seconds, 9000, "Only the part inside the week should count");
+    assert_eq!(work.remaining_seconds, 0);
+    assert_eq!(work.overrun_seconds, 1800);
+    assert_eq!(work.percent_used, 125);
+}
+
+#[test]
+#[serial]
+fn test_category_budget_upsert_and_validation() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let id = write_category_budget(conn, &budget(1, "MONTHLY", 600)).unwrap();
+    assert_eq!(write_category_budget(conn, &budget(1, "MONTHLY", 900)).unwrap(), id, "Same period should update");
+    assert_eq!(load_budget_statuses(conn, date("2024-01-17")).unwrap()[0].budget_seconds, 54000);
+
+    assert!(write_category_budget(conn, &budget(1, "DAILY", 60)).is_err());
+    assert!(write_category_budget(conn, &budget(1, "WEEKLY", 0)).is_err());
+
+    remove_category_budget(conn, 1, "MONTHLY").unwrap();
+    assert!(load_budget_statuses(conn, date("2024-01-17")).unwrap().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_budget_alerts_fire_once_per_threshold() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_category_budget(conn, &budget(1, "WEEKLY", 100)).unwrap();
+
+    add_entry(conn, "TASK", 1, "2024-01-15 09:00:00", "2024-01-15 10:00:00");
+    assert!(check_budget_alerts(conn, date("2024-01-17")).unwrap().is_empty(), "60% is below every threshold");
+
+    add_entry(conn, "TASK", 1, "2024-01-16 09:00:00", "2024-01-16 09:20:00");
+    let alerts = check_budget_alerts(conn, date("2024-01-17")).unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].threshold, 80);
+    assert_eq!(alerts[0].label, "Work");
+
+    assert!(check_budget_alerts(conn, date("2024-01-17")).unwrap().is_empty(), "Crossings are announced once");
+
+    add_entry(conn, "TASK", 1, "2024-01-17 09:00:00", "2024-01-17 09:30:00");
+    let alerts = check_budget_alerts(conn, date("2024-01-17")).unwrap();
+    assert_eq!(alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(), vec![100]);
+
+    // A new week starts with a clean slate
+    add_entry(conn, "TASK", 1, "2024-01-22 09:00:00", "2024-01-22 11:00:00");
+    let alerts = check_budget_alerts(conn, date("2024-01-22")).unwrap();
+    assert_eq!(alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(), vec![80, 100]);
+}
+
+#[test]
+#[serial]
+fn test_task_estimate_alerts_reset_with_new_estimate() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_task_estimate(conn, 1, Some(30)).unwrap();
+    add_entry(conn, "TASK", 1, "2024-01-15 09:00:00", "2024-01-15 09:30:00");
+
+    let alerts = check_budget_alerts(conn, date("2024-01-15")).unwrap();
+    assert_eq!(alerts.len(), 2);
+    assert!(alerts.iter().all(|a| a.scope == "TASK" && a.scope_id == 1));
+    assert!(check_budget_alerts(conn, date("2024-01-15")).unwrap().is_empty());
+
+    write_task_estimate(conn, 1, Some(35)).unwrap();
+    let alerts = check_budget_alerts(conn, date("2024-01-15")).unwrap();
+    assert_eq!(alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(), vec![80], "85% of the new estimate");
+}
+
+#[test]
+#[serial]
+fn test_category_alerts_reset_with_new_budget() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    write_category_budget(conn, &budget(1, "WEEKLY", 60)).unwrap();
+    add_entry(conn, "TASK", 1, "2024-01-15 09:00:00", "2024-01-15 10:00:00");
+
+    assert_eq!(check_budget_alerts(conn, date("2024-01-17")).unwrap().len(), 2);
+    assert!(check_budget_alerts(conn, date("2024-01-17")).unwrap().is_empty());
+
+    write_category_budget(conn, &budget(1, "WEEKLY", 70)).unwrap();
+    let alerts = check_budget_alerts(conn, date("2024-01-17")).unwrap();
+    assert_eq!(alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(), vec![80], "85% of the new budget");
+}
+
+#[test]
+#[serial]
+fn test_no_budgets_no_alerts() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    assert!(check_budget_alerts(conn, date("2024-01-15")).unwrap().is_empty());
+}
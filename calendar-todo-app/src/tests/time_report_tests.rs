@@ -0,0 +1,241 @@
+use crate::services::time_report_service::*;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn entry(start: &str, end: &str, duration_seconds: i64) -> ReportEntry {
+    ReportEntry {
+        id: 1,
+        item_type: "TASK".to_string(),
+        item_id: Some(1),
+        item_title: None,
+        category_id: None,
+        category_name: None,
+        timer_type: "MANUAL".to_string(),
+        start: NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S").unwrap(),
+        end: NaiveDateTime::parse_from_str(end, "%Y-%m-%d %H:%M:%S").unwrap(),
+        duration_seconds,
+    }
+}
+
+fn add_entry(conn: &Connection, item_type: &str, item_id: Option<i64>, start: &str, end: &str, timer_type: &str) {
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, item_id, start_time, end_time, duration_seconds, timer_type)
+         VALUES (?1, ?2, ?3, ?4, strftime('%s', ?4) - strftime('%s', ?3), ?5)",
+        rusqlite::params![item_type, item_id, start, end, timer_type],
+    ).unwrap();
+}
+
+/// Seeded tasks: 1 "Complete project proposal" (Work), 2 "Buy groceries" (Personal);
+/// seeded event 1 "Morning Standup" (Work)
+fn seed_entries(conn: &Connection) {
+    add_entry(conn, "TASK", Some(1), "2024-01-15 09:00:00", "2024-01-15 10:30:00", "MANUAL");
+    add_entry(conn, "TASK", Some(1), "2024-01-16 09:00:00", "2024-01-16 09:30:00", "POMODORO");
+    add_entry(conn, "TASK", Some(2), "2024-01-16 23:00:00", "2024-01-17 01:00:00", "MANUAL");
+    add_entry(conn, "EVENT", Some(1), "2024-01-22 09:00:00", "2024-01-22 09:30:00", "MANUAL");
+    add_entry(conn, "MANUAL", None, "2024-02-01 08:00:00", "2024-02-01 09:00:00", "COUNTDOWN");
+}
+
+fn report(conn: &Connection, start: &str, end: &str, group_by: &str) -> TimeReport {
+    build_time_report(conn, &TimeReportQuery {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        group_by: group_by.to_string(),
+    }).expect("Failed to build report")
+}
+
+#[test]
+fn test_split_entry_across_midnight() {
+    let segments = split_by_day(
+        &entry("2024-01-16 23:00:00", "2024-01-17 01:00:00", 7200),
+        date("2024-01-01"),
+        date("2024-01-31"),
+    );
+
+    assert_eq!(segments, vec![
+        DaySegment { date: date("2024-01-16"), seconds: 3600 },
+        DaySegment { date: date("2024-01-17"), seconds: 3600 },
+    ]);
+}
+
+#[test]
+fn test_split_prorates_tracked_duration() {
+    // Two hours on the clock but only one hour tracked, e.g. because of a pause
+    let segments = split_by_day(
+        &entry("2024-01-16 22:00:00", "2024-01-17 00:30:00", 3600),
+        date("2024-01-01"),
+        date("2024-01-31"),
+    );
+
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0].seconds, 2880);
+    assert_eq!(segments.iter().map(|s| s.seconds).sum::<i64>(), 3600, "Parts should add up to the duration");
+}
+
+#[test]
+fn test_split_clips_to_range() {
+    let segments = split_by_day(
+        &entry("2024-01-31 22:00:00", "2024-02-01 02:00:00", 14400),
+        date("2024-02-01"),
+        date("2024-02-29"),
+    );
+
+    assert_eq!(segments, vec![DaySegment { date: date("2024-02-01"), seconds: 7200 }]);
+}
+
+#[test]
+fn test_week_start_respects_setting() {
+    // 2024-01-17 is a Wednesday
+    assert_eq!(week_start(date("2024-01-17"), 1), date("2024-01-15"));
+    assert_eq!(week_start(date("2024-01-17"), 0), date("2024-01-14"));
+    assert_eq!(week_start(date("2024-01-15"), 1), date("2024-01-15"));
+}
+
+#[test]
+#[serial]
+fn test_report_by_day() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_entries(conn);
+
+    let by_day = report(conn, "2024-01-15", "2024-01-17", "DAY");
+
+    let totals: Vec<(&str, i64)> = by_day.rows.iter().map(|r| (r.key.as_str(), r.total_seconds)).collect();
+    assert_eq!(totals, vec![("2024-01-15", 5400), ("2024-01-16", 5400), ("2024-01-17", 3600)]);
+    assert_eq!(by_day.total_seconds, 14400);
+    assert_eq!(by_day.entry_count, 3);
+    assert_eq!(by_day.average_seconds, 4800);
+}
+
+#[test]
+#[serial]
+fn test_report_by_week_and_month() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_entries(conn);
+
+    let by_week = report(conn, "2024-01-01", "2024-02-29", "WEEK");
+    let weeks: Vec<(&str, i64)> = by_week.rows.iter().map(|r| (r.key.as_str(), r.total_seconds)).collect();
+    assert_eq!(weeks, vec![("2024-01-15", 14400), ("2024-01-22", 1800), ("2024-01-29", 3600)]);
+
+    let by_month = report(conn, "2024-01-01", "2024-02-29", "MONTH");
+    assert_eq!(by_month.rows.len(), 2);
+    assert_eq!(by_month.rows[0].label, "January 2024");
+    assert_eq!(by_month.rows[0].total_seconds, 16200);
+    assert_eq!(by_month.total_seconds, 19800);
+}
+
+#[test]
+#[serial]
+fn test_report_by_task_and_event() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_entries(conn);
+
+    let by_task = report(conn, "2024-01-01", "2024-01-31", "TASK");
+    assert_eq!(by_task.rows.len(), 2, "Only task entries should be grouped by task");
+    let proposal = by_task.rows.iter().find(|r| r.key == "1").unwrap();
+    assert_eq!(proposal.label, "Complete project proposal");
+    assert_eq!(proposal.total_seconds, 7200);
+    assert_eq!(proposal.entry_count, 2);
+    assert_eq!(proposal.average_seconds, 3600);
+
+    let by_event = report(conn, "2024-01-01", "2024-01-31", "EVENT");
+    assert_eq!(by_event.rows.len(), 1);
+    assert_eq!(by_event.rows[0].label, "Morning Standup");
+}
+
+#[test]
+#[serial]
+fn test_report_by_category_and_timer_type() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_entries(conn);
+
+    let by_category = report(conn, "2024-01-01", "2024-02-29", "CATEGORY");
+    let categories: Vec<(&str, i64)> = by_category.rows.iter().map(|r| (r.label.as_str(), r.total_seconds)).collect();
+    assert!(categories.contains(&("Work", 9000)), "Task 1 and event 1 both belong to Work");
+    assert!(categories.contains(&("Personal", 7200)));
+    assert!(categories.contains(&("Uncategorized", 3600)));
+
+    let by_timer = report(conn, "2024-01-01", "2024-02-29", "TIMER_TYPE");
+    let timers: Vec<(&str, i64)> = by_timer.rows.iter().map(|r| (r.key.as_str(), r.total_seconds)).collect();
+    assert_eq!(timers, vec![("COUNTDOWN", 3600), ("MANUAL", 14400), ("POMODORO", 1800)]);
+}
+
+#[test]
+#[serial]
+fn test_report_ignores_running_timers_and_handles_rfc3339() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, start_time, timer_type) VALUES ('MANUAL', '2024-01-15 09:00:00', 'MANUAL')",
+        [],
+    ).unwrap();
+    conn.execute(
+        "INSERT INTO time_tracking (item_type, start_time, end_time, duration_seconds, timer_type)
+         VALUES ('MANUAL', '2024-01-15T10:00:00Z', '2024-01-15T10:45:00Z', 2700, 'MANUAL')",
+        [],
+    ).unwrap();
+
+    let by_day = report(conn, "2024-01-15", "2024-01-15", "DAY");
+
+    assert_eq!(by_day.entry_count, 1, "Running timers should not be reported");
+    assert_eq!(by_day.total_seconds, 2700);
+}
+
+#[test]
+#[serial]
+fn test_report_validation() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let invalid_grouping = build_time_report(conn, &TimeReportQuery {
+        start_date: "2024-01-01".to_string(),
+        end_date: "2024-01-31".to_string(),
+        group_by: "YEAR".to_string(),
+    });
+    let reversed = build_time_report(conn, &TimeReportQuery {
+        start_date: "2024-01-31".to_string(),
+        end_date: "2024-01-01".to_string(),
+        group_by: "DAY".to_string(),
+    });
+
+    assert!(invalid_grouping.is_err(), "Unknown grouping should be rejected");
+    assert!(reversed.is_err(), "Reversed range should be rejected");
+}
+
+#[test]
+#[serial]
+fn test_csv_and_markdown_exports() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    seed_entries(conn);
+    conn.execute("UPDATE tasks SET title = 'Proposal, draft | v2' WHERE id = 1", []).unwrap();
+
+    let by_task = report(conn, "2024-01-01", "2024-01-31", "TASK");
+
+    let csv = report_to_csv(&by_task);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "key,label,total_seconds,total_hours,entries,average_seconds");
+    assert_eq!(lines[1], "1,\"Proposal, draft | v2\",7200,2:00,2,3600");
+    assert_eq!(lines.last().unwrap(), &"total,Total,14400,4:00,3,4800");
+
+    let markdown = report_to_markdown(&by_task);
+    assert!(markdown.contains("| Task | Total | Entries | Average |"));
+    assert!(markdown.contains("| Proposal, draft \\| v2 | 2:00 | 2 | 1:00 |"));
+    assert!(markdown.contains("| **Total** | **4:00** | **3** | **1:20** |"));
+}
+
+#[test]
+fn test_format_hours() {
+    assert_eq!(format_hours(0), "0:00");
+    assert_eq!(format_hours(5400), "1:30");
+    assert_eq!(format_hours(36059), "10:00");
+}
@@ -0,0 +1,288 @@
+use crate::services::time_tracking_service::*;
+use super::{setup_test_db, setup_test_db_with_data};
+use chrono::NaiveDateTime;
+use rusqlite::Connection;
+use serial_test::serial;
+
+fn at(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn entry(start: &str, end: Option<&str>) -> TimeEntry {
+    TimeEntry {
+        id: None,
+        item_type: "TASK".to_string(),
+        item_id: Some(1),
+        start_time: start.to_string(),
+        end_time: end.map(|e| e.to_string()),
+        duration_seconds: None,
+        timer_type: "MANUAL".to_string(),
+        created_at: None,
+    }
+}
+
+/// (start, end, duration) of every entry, ordered by start time
+fn spans(conn: &Connection) -> Vec<(String, Option<String>, Option<i64>)> {
+    let mut stmt = conn.prepare(
+        "SELECT start_time, end_time, duration_seconds FROM time_tracking ORDER BY start_time"
+    ).unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+fn span(start: &str, end: &str, duration: i64) -> (String, Option<String>, Option<i64>) {
+    (start.to_string(), Some(end.to_string()), Some(duration))
+}
+
+#[test]
+#[serial]
+fn test_stop_timer_validates_end_time() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let id = start_timer_entry(conn, &entry("2024-01-15 10:00:00", None)).unwrap();
+
+    assert!(stop_timer_entry(conn, id, "2024-01-15 09:00:00").is_err(), "End before start should be rejected");
+    assert!(stop_timer_entry(conn, id, "2024-01-15 10:00:00").is_err(), "Zero-length entries should be rejected");
+    assert!(stop_timer_entry(conn, 99999, "2024-01-15 11:00:00").is_err());
+
+    let stopped = stop_timer_entry(conn, id, "2024-01-15T11:30:00Z").unwrap();
+    assert_eq!(stopped.end_time.as_deref(), Some("2024-01-15 11:30:00"), "End time should be stored normalized");
+    assert_eq!(stopped.duration_seconds, Some(5400));
+
+    assert!(stop_timer_entry(conn, id, "2024-01-15 12:00:00").is_err(), "A stopped timer cannot be stopped again");
+}
+
+#[test]
+#[serial]
+fn test_start_timer_auto_stops_running_timer() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    start_timer_entry(conn, &entry("2024-01-15 10:00:00", None)).unwrap();
+    start_timer_entry(conn, &entry("2024-01-15 10:45:00", None)).unwrap();
+
+    assert_eq!(spans(conn), vec![
+        span("2024-01-15 10:00:00", "2024-01-15 10:45:00", 2700),
+        ("2024-01-15 10:45:00".to_string(), None, None),
+    ]);
+}
+
+#[test]
+#[serial]
+fn test_start_timer_reject_policy() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE settings SET value = 'REJECT' WHERE key = 'timer_conflict_policy'", []).unwrap();
+
+    let first = start_timer_entry(conn, &entry("2024-01-15 10:00:00", None)).unwrap();
+    assert!(start_timer_entry(conn, &entry("2024-01-15 10:30:00", None)).is_err(), "Second timer should be rejected");
+
+    stop_timer_entry(conn, first, "2024-01-15 11:00:00").unwrap();
+    assert!(start_timer_entry(conn, &entry("2024-01-15 11:30:00", None)).is_ok(),
+        "A new timer can start once the previous one stopped");
+}
+
+#[test]
+#[serial]
+fn test_start_timer_rejects_invalid_timestamp() {
+    let db = setup_test_db();
+    let conn = db.get_connection();
+
+    let mut invalid = entry("invalid-date", None);
+    invalid.item_type = "MANUAL".to_string();
+    invalid.item_id = None;
+
+    assert!(start_timer_entry(conn, &invalid).is_err());
+}
+
+#[test]
+#[serial]
+fn test_manual_timer_pauses_pomodoro() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+
+    start_timer_entry(conn, &entry("2024-01-15 09:10:00", None)).unwrap();
+
+    let status = pomodoro_status(conn, at("2024-01-15 09:20:00")).unwrap().unwrap();
+    assert_eq!(status.session.state, "PAUSED", "Starting another timer should pause the Pomodoro");
+    assert_eq!(status.session.time_entry_id, None);
+    assert_eq!(status.remaining_seconds, 15 * 60);
+    assert_eq!(spans(conn)[0], span("2024-01-15 09:00:00", "2024-01-15 09:10:00", 600));
+}
+
+#[test]
+#[serial]
+fn test_create_entry_computes_duration() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+
+    let mut manual = entry("2024-01-15 09:00:00", Some("2024-01-15 10:15:00"));
+    manual.duration_seconds = Some(1);
+    let created = create_timesheet_entry(conn, &manual, false).unwrap();
+
+    assert_eq!(created.duration_seconds, Some(4500), "Client-supplied durations should be ignored");
+
+    assert!(create_timesheet_entry(conn, &entry("2024-01-15 11:00:00", None), false).is_err(),
+        "Timesheet entries need an end time");
+    assert!(create_timesheet_entry(conn, &entry("2024-01-15 11:00:00", Some("2024-01-15 10:00:00")), false).is_err(),
+        "End before start should be rejected");
+}
+
+#[test]
+#[serial]
+fn test_overlap_detection() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 10:00:00")), false).unwrap();
+
+    let overlaps = find_overlapping_entries(conn, at("2024-01-15 09:30:00"), at("2024-01-15 10:30:00"), None).unwrap();
+    assert_eq!(overlaps.len(), 1);
+
+    let adjacent = find_overlapping_entries(conn, at("2024-01-15 10:00:00"), at("2024-01-15 11:00:00"), None).unwrap();
+    assert!(adjacent.is_empty(), "Back-to-back entries do not overlap");
+
+    let result = create_timesheet_entry(conn, &entry("2024-01-15 09:30:00", Some("2024-01-15 10:30:00")), false);
+    assert!(result.is_err(), "Overlapping entry should be rejected");
+    assert_eq!(spans(conn).len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_overlap_auto_resolution() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    create_timesheet_entry(conn, &entry("2024-01-15 08:00:00", Some("2024-01-15 09:00:00")), false).unwrap();
+    create_timesheet_entry(conn, &entry("2024-01-15 09:30:00", Some("2024-01-15 09:45:00")), false).unwrap();
+    create_timesheet_entry(conn, &entry("2024-01-15 10:00:00", Some("2024-01-15 12:00:00")), false).unwrap();
+
+    create_timesheet_entry(conn, &entry("2024-01-15 08:30:00", Some("2024-01-15 10:30:00")), true).unwrap();
+
+    assert_eq!(spans(conn), vec![
+        span("2024-01-15 08:00:00", "2024-01-15 08:30:00", 1800),
+        span("2024-01-15 08:30:00", "2024-01-15 10:30:00", 7200),
+        span("2024-01-15 10:30:00", "2024-01-15 12:00:00", 5400),
+    ], "Overlapped entries should be trimmed and covered ones removed");
+}
+
+#[test]
+#[serial]
+fn test_overlap_resolution_splits_enclosing_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 12:00:00")), false).unwrap();
+
+    create_timesheet_entry(conn, &entry("2024-01-15 10:00:00", Some("2024-01-15 10:30:00")), true).unwrap();
+
+    assert_eq!(spans(conn), vec![
+        span("2024-01-15 09:00:00", "2024-01-15 10:00:00", 3600),
+        span("2024-01-15 10:00:00", "2024-01-15 10:30:00", 1800),
+        span("2024-01-15 10:30:00", "2024-01-15 12:00:00", 5400),
+    ]);
+}
+
+#[test]
+#[serial]
+fn test_overlap_with_running_timer_is_not_resolved() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    start_timer_entry(conn, &entry("2024-01-15 09:00:00", None)).unwrap();
+
+    let result = create_timesheet_entry(conn, &entry("2024-01-15 10:00:00", Some("2024-01-15 11:00:00")), true);
+
+    assert!(result.is_err(), "The running timer should never be trimmed");
+    assert_eq!(spans(conn).len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_update_entry_recomputes_duration() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let mut created = create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 10:00:00")), false).unwrap();
+    create_timesheet_entry(conn, &entry("2024-01-15 11:00:00", Some("2024-01-15 12:00:00")), false).unwrap();
+
+    created.end_time = Some("2024-01-15 10:20:00".to_string());
+    created.item_id = Some(2);
+    let updated = update_timesheet_entry(conn, &created, false).unwrap();
+    assert_eq!(updated.duration_seconds, Some(4800));
+    assert_eq!(updated.item_id, Some(2));
+
+    created.end_time = Some("2024-01-15 11:30:00".to_string());
+    assert!(update_timesheet_entry(conn, &created, false).is_err(), "Edit should not overlap another entry");
+
+    let running = start_timer_entry(conn, &entry("2024-01-15 13:00:00", None)).unwrap();
+    let mut running_entry = entry("2024-01-15 13:00:00", Some("2024-01-15 14:00:00"));
+    running_entry.id = Some(running);
+    assert!(update_timesheet_entry(conn, &running_entry, false).is_err(), "Running timers are not edited here");
+}
+
+#[test]
+#[serial]
+fn test_split_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let created = create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 11:00:00")), false).unwrap();
+    let id = created.id.unwrap();
+
+    assert!(split_timesheet_entry(conn, id, "2024-01-15 11:00:00").is_err(), "Split must fall inside the entry");
+
+    let parts = split_timesheet_entry(conn, id, "2024-01-15 09:45:00").unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].id, Some(id));
+    assert_eq!(parts[0].duration_seconds, Some(2700));
+    assert_eq!(parts[1].start_time, "2024-01-15 09:45:00");
+    assert_eq!(parts[1].duration_seconds, Some(4500));
+    assert_eq!(parts[1].item_id, Some(1), "Both parts should keep the item");
+}
+
+#[test]
+#[serial]
+fn test_merge_entries() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let first = create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 09:30:00")), false).unwrap();
+    let second = create_timesheet_entry(conn, &entry("2024-01-15 09:40:00", Some("2024-01-15 10:00:00")), false).unwrap();
+    let mut other_task = entry("2024-01-15 11:00:00", Some("2024-01-15 11:30:00"));
+    other_task.item_id = Some(2);
+    let other = create_timesheet_entry(conn, &other_task, false).unwrap();
+
+    assert!(merge_timesheet_entries(conn, &[first.id.unwrap()]).is_err());
+    assert!(merge_timesheet_entries(conn, &[second.id.unwrap(), other.id.unwrap()]).is_err(),
+        "Entries for different items cannot be merged");
+
+    let merged = merge_timesheet_entries(conn, &[second.id.unwrap(), first.id.unwrap()]).unwrap();
+    assert_eq!(merged.id, first.id, "The earliest entry should be kept");
+    assert_eq!(merged.end_time.as_deref(), Some("2024-01-15 10:00:00"));
+    assert_eq!(merged.duration_seconds, Some(3600));
+    assert_eq!(spans(conn).len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_merge_rejects_entries_in_between() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let first = create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 09:30:00")), false).unwrap();
+    let mut between = entry("2024-01-15 09:30:00", Some("2024-01-15 10:00:00"));
+    between.item_id = Some(2);
+    create_timesheet_entry(conn, &between, false).unwrap();
+    let last = create_timesheet_entry(conn, &entry("2024-01-15 10:00:00", Some("2024-01-15 10:30:00")), false).unwrap();
+
+    assert!(merge_timesheet_entries(conn, &[first.id.unwrap(), last.id.unwrap()]).is_err());
+}
+
+#[test]
+#[serial]
+fn test_delete_entry() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let created = create_timesheet_entry(conn, &entry("2024-01-15 09:00:00", Some("2024-01-15 10:00:00")), false).unwrap();
+
+    delete_timesheet_entry(conn, created.id.unwrap()).unwrap();
+
+    assert!(spans(conn).is_empty());
+    assert!(delete_timesheet_entry(conn, created.id.unwrap()).is_err());
+}
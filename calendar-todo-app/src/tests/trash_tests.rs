@@ -0,0 +1,193 @@
+use crate::db::Database;
+use crate::services::reminder_service::collect_due_reminders;
+use crate::services::search_service::{search_index, SearchScope};
+use crate::services::tag_service::{add_item_tag, insert_tag, load_tag_usage, Tag};
+use crate::services::task_service::load_tasks_in_range;
+use super::setup_test_db_with_data;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+use serial_test::serial;
+
+fn add_subtask(conn: &Connection, parent_id: i64, title: &str, order: i64) -> i64 {
+    conn.execute(
+        "INSERT INTO tasks (title, priority, parent_task_id, subtask_order) VALUES (?1, 3, ?2, ?3)",
+        params![title, parent_id, order],
+    ).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn trash(db: &Database) -> Vec<(String, i64)> {
+    db.get_trash().unwrap().into_iter().map(|item| (item.item_type, item.id)).collect()
+}
+
+fn subtasks(db: &Database, parent_id: i64) -> Vec<String> {
+    db.get_subtasks(parent_id).unwrap().into_iter().map(|t| t.title).collect()
+}
+
+fn backdate(conn: &Connection, table: &str, id: i64, days: i64) {
+    conn.execute(
+        &format!("UPDATE {} SET deleted_at = datetime('now', ?1) WHERE deleted_at = (SELECT deleted_at FROM {} WHERE id = ?2)", table, table),
+        params![format!("-{} days", days), id],
+    ).unwrap();
+}
+
+#[test]
+#[serial]
+fn test_trashed_task_comes_back_with_its_subtasks() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let outline = add_subtask(conn, 1, "Outline", 0);
+    let sources = add_subtask(conn, outline, "Sources", 0);
+    let budget = add_subtask(conn, 1, "Budget", 1);
+
+    db.trash_item("TASK", budget).unwrap();
+    backdate(conn, "tasks", budget, 1);
+    db.trash_item("TASK", 1).unwrap();
+
+    let open: Vec<i64> = conn.prepare("SELECT id FROM tasks WHERE deleted_at IS NULL ORDER BY id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(|id| id.unwrap())
+        .collect();
+    assert_eq!(open, vec![2, 3]);
+    assert!(db.get_task(1).is_err(), "Trashed tasks no longer load");
+    assert_eq!(trash(&db), vec![("TASK".to_string(), 1), ("TASK".to_string(), budget)], "Subtasks trashed along with their task are not listed");
+
+    db.restore_item("TASK", 1).unwrap();
+    assert_eq!(subtasks(&db, 1), vec!["Outline"], "The subtask trashed on its own stays in the trash");
+    assert_eq!(subtasks(&db, outline), vec!["Sources"]);
+
+    db.restore_item("TASK", budget).unwrap();
+    assert_eq!(subtasks(&db, 1), vec!["Outline", "Budget"]);
+    assert!(db.restore_item("TASK", budget).is_err());
+
+    db.trash_item("TASK", 1).unwrap();
+    db.restore_item("TASK", sources).unwrap();
+    let placement: (Option<i64>, Option<i64>) = conn.query_row(
+        "SELECT parent_task_id, subtask_order FROM tasks WHERE id = ?",
+        [sources],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert_eq!(placement, (None, None), "Restored to the top level while its parent is in the trash");
+}
+
+#[test]
+#[serial]
+fn test_trashed_items_leave_lists_and_search() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO notes (title, content) VALUES ('Standup notes', 'Blockers')", []).unwrap();
+    let note = conn.last_insert_rowid();
+    conn.execute("INSERT INTO reminders (item_type, item_id, trigger_time, offset_description) VALUES ('EVENT', 1, '2023-01-16 08:45:00', '15 minutes before')", []).unwrap();
+
+    let found = |query: &str| search_index(conn, query, &SearchScope::all(), None, 0).unwrap().total;
+    assert_eq!(found("standup"), 2);
+
+    db.trash_item("EVENT", 1).unwrap();
+    db.trash_item("NOTE", note).unwrap();
+    assert_eq!(found("standup"), 0);
+    assert!(db.get_event(1).is_err());
+    let now = NaiveDateTime::parse_from_str("2023-01-16 08:50:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    assert!(collect_due_reminders(conn, &now.and_utc()).unwrap().immediate.is_empty(), "Trashed events do not remind");
+
+    db.restore_item("EVENT", 1).unwrap();
+    assert_eq!(found("standup"), 1);
+
+    db.purge_item("NOTE", note).unwrap();
+    let notes: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+    assert_eq!(notes, 0);
+    assert!(db.purge_item("EVENT", 1).is_err(), "Only items in the trash are purged");
+    assert!(db.trash_item("CATEGORY", 1).is_err());
+}
+
+#[test]
+#[serial]
+fn test_trash_retention() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let outline = add_subtask(conn, 1, "Outline", 0);
+    db.trash_item("TASK", 1).unwrap();
+    db.trash_item("EVENT", 2).unwrap();
+    db.trash_item("EVENT", 3).unwrap();
+    backdate(conn, "tasks", 1, 40);
+    conn.execute("UPDATE events SET deleted_at = datetime('now', '-10 days') WHERE id = 2", []).unwrap();
+
+    assert_eq!(db.purge_expired_trash().unwrap(), 1);
+    let purged: i64 = conn.query_row("SELECT COUNT(*) FROM tasks WHERE id IN (1, ?)", [outline], |row| row.get(0)).unwrap();
+    assert_eq!(purged, 0, "The whole subtree goes");
+    assert_eq!(trash(&db), vec![("EVENT".to_string(), 3), ("EVENT".to_string(), 2)]);
+
+    conn.execute("UPDATE settings SET value = '0' WHERE key = 'trash_retention_days'", []).unwrap();
+    backdate(conn, "events", 2, 400);
+    assert_eq!(db.purge_expired_trash().unwrap(), 0, "0 keeps trashed items");
+
+    assert_eq!(db.empty_trash().unwrap(), 2);
+    assert!(trash(&db).is_empty());
+}
+
+#[test]
+#[serial]
+fn test_archive_finished_tasks() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let outline = add_subtask(conn, 1, "Outline", 0);
+    let january = |conn| -> Vec<String> {
+        let mut titles: Vec<String> = load_tasks_in_range(conn, "2023-01-01", "2023-01-31 23:59:59", true).unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        titles.sort();
+        titles
+    };
+
+    assert!(db.archive_task(1).is_err(), "Open tasks are not archived");
+    db.set_task_status(1, "COMPLETED").unwrap();
+    assert!(db.archive_task(outline).is_err(), "Subtasks go with their task");
+
+    db.archive_task(1).unwrap();
+    assert_eq!(january(conn), vec!["Buy groceries", "Exercise routine"]);
+    let archived: Vec<String> = db.get_archived_tasks().unwrap().into_iter().map(|t| t.title).collect();
+    assert_eq!(archived, vec!["Complete project proposal"]);
+    assert_eq!(search_index(conn, "outline", &SearchScope::all(), None, 0).unwrap().total, 0);
+
+    db.unarchive_task(1).unwrap();
+    assert!(db.unarchive_task(1).is_err());
+    assert_eq!(january(conn).len(), 4);
+
+    db.set_task_status(3, "COMPLETED").unwrap();
+    assert_eq!(db.archive_finished_tasks("2999-01-01").unwrap(), 2);
+    assert_eq!(db.archive_finished_tasks("2999-01-01").unwrap(), 0);
+
+    let result = db.bulk_archive_tasks(&[2, 99]).unwrap();
+    assert_eq!(result.applied(), 0);
+}
+
+#[test]
+#[serial]
+fn test_trashed_items_are_not_edited_or_counted() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let q1 = insert_tag(conn, &Tag { id: None, name: "Q1".to_string(), color: None, created_at: None }).unwrap();
+    for (item_type, id) in [("EVENT", 1), ("TASK", 1), ("TASK", 2)] {
+        add_item_tag(conn, item_type, id, q1).unwrap();
+    }
+    let mut task = db.get_task(1).unwrap();
+    let mut event = db.get_event(1).unwrap();
+
+    db.trash_item("TASK", 1).unwrap();
+    db.trash_item("EVENT", 1).unwrap();
+    conn.execute("UPDATE tasks SET status = 'COMPLETED' WHERE id = 2", []).unwrap();
+    db.archive_task(2).unwrap();
+    let usage = load_tag_usage(conn).unwrap();
+    assert_eq!((usage[0].events, usage[0].tasks, usage[0].total), (0, 0, 0));
+
+    task.title = "Edited in the trash".to_string();
+    event.title = "Edited in the trash".to_string();
+    assert!(db.update_task(&task).is_err());
+    assert!(db.update_event(&event).is_err());
+    task.updated_at = None;
+    assert!(db.update_task(&task).is_err(), "Trashed items are not edited even without a guard");
+
+    db.restore_item("TASK", 1).unwrap();
+    assert_ne!(db.get_task(1).unwrap().title, "Edited in the trash");
+    assert_eq!(load_tag_usage(conn).unwrap()[0].tasks, 1);
+}
@@ -0,0 +1,171 @@
+use crate::db::Database;
+use crate::services::search_service::{search_index, SearchScope};
+use crate::services::time_tracking_service::{control_pomodoro_session, start_pomodoro_session};
+use super::setup_test_db_with_data;
+use chrono::NaiveDateTime;
+use rusqlite::{Connection, OptionalExtension};
+use serial_test::serial;
+
+fn labels(db: &Database) -> (Vec<String>, Vec<String>) {
+    let history = db.get_undo_history().unwrap();
+    (
+        history.undo.into_iter().map(|entry| entry.label).collect(),
+        history.redo.into_iter().map(|entry| entry.label).collect(),
+    )
+}
+
+fn task_row(conn: &Connection, id: i64) -> Option<(String, String, Option<i64>, Option<i64>)> {
+    conn.query_row(
+        "SELECT title, status, kanban_column_id, kanban_order FROM tasks WHERE id = ?",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    ).ok()
+}
+
+fn rename_task(db: &Database, id: i64, title: &str) {
+    let _undo = db.undo_step("Rename task").unwrap();
+    db.get_connection().execute("UPDATE tasks SET title = ?1 WHERE id = ?2", rusqlite::params![title, id]).unwrap();
+}
+
+#[test]
+#[serial]
+fn test_undo_and_redo_inserts_updates_and_deletes() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let before = task_row(conn, 1);
+
+    let added = {
+        let _undo = db.undo_step("Edit").unwrap();
+        conn.execute("UPDATE tasks SET title = 'Send project proposal' WHERE id = 1", []).unwrap();
+        conn.execute("INSERT INTO tasks (title, priority) VALUES ('Book flights', 2)", []).unwrap();
+        let added = conn.last_insert_rowid();
+        conn.execute("DELETE FROM events WHERE id = 1", []).unwrap();
+        added
+    };
+    let standups = || search_index(conn, "standup", &SearchScope::all(), None, 0).unwrap().total;
+    assert_eq!(standups(), 0);
+
+    assert_eq!(db.undo().unwrap().map(|entry| entry.label).as_deref(), Some("Edit"));
+    assert_eq!(task_row(conn, 1), before);
+    assert_eq!(task_row(conn, added), None);
+    let event: String = conn.query_row("SELECT title FROM events WHERE id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(event, "Morning Standup");
+    assert_eq!(standups(), 1, "Restored rows are searchable again");
+    assert_eq!(labels(&db), (vec![], vec!["Edit".to_string()]));
+    assert!(db.undo().unwrap().is_none());
+
+    db.redo().unwrap();
+    assert_eq!(task_row(conn, 1).unwrap().0, "Send project proposal");
+    assert_eq!(task_row(conn, added).unwrap().0, "Book flights");
+    assert_eq!(standups(), 0);
+    assert!(db.redo().unwrap().is_none());
+}
+
+#[test]
+#[serial]
+fn test_multi_row_changes_undo_as_one() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("INSERT INTO tasks (title, priority, parent_task_id, subtask_order) VALUES ('Outline', 3, 1, 0)", []).unwrap();
+    let subtask = conn.last_insert_rowid();
+    db.add_checklist_item(subtask, "Draft headings").unwrap();
+    let before: Vec<_> = (1..=3).map(|id| task_row(conn, id)).collect();
+
+    {
+        let _undo = db.undo_step("Move tasks").unwrap();
+        db.bulk_move_to_column(&[1, 2], 3).unwrap();
+    }
+    {
+        let _undo = db.undo_step("Delete task").unwrap();
+        db.trash_item("TASK", 1).unwrap();
+        db.purge_item("TASK", 1).unwrap();
+    }
+    assert_eq!(task_row(conn, subtask), None);
+
+    db.undo().unwrap();
+    assert!(db.get_subtasks(1).unwrap().iter().any(|t| t.id == Some(subtask)), "Rows removed by cascade come back");
+    assert_eq!(db.get_checklist_items(subtask).unwrap().len(), 1);
+    let deleted_at: Option<String> = conn.query_row("SELECT deleted_at FROM tasks WHERE id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(deleted_at, None);
+
+    db.undo().unwrap();
+    let after: Vec<_> = (1..=3).map(|id| task_row(conn, id)).collect();
+    assert_eq!(after, before, "Every task in the bulk move goes back");
+}
+
+#[test]
+#[serial]
+fn test_undo_stack_is_bounded_and_new_steps_clear_redo() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    conn.execute("UPDATE settings SET value = '2' WHERE key = 'undo_depth'", []).unwrap();
+
+    rename_task(&db, 1, "First");
+    rename_task(&db, 1, "Second");
+    {
+        let _undo = db.undo_step("Rename twice").unwrap();
+        rename_task(&db, 2, "Nested steps join the outer one");
+        rename_task(&db, 3, "So both renames undo together");
+    }
+    {
+        let _undo = db.undo_step("Nothing").unwrap();
+        conn.execute("UPDATE tasks SET title = title WHERE id = 1", []).unwrap();
+    }
+    assert_eq!(labels(&db).0, vec!["Rename twice", "Rename task"], "Steps that change nothing are not kept");
+
+    db.undo().unwrap();
+    assert_eq!(task_row(conn, 2).unwrap().0, "Buy groceries");
+    assert_eq!(task_row(conn, 3).unwrap().0, "Exercise routine");
+    assert_eq!(labels(&db), (vec!["Rename task".to_string()], vec!["Rename twice".to_string()]));
+
+    rename_task(&db, 2, "Third");
+    assert_eq!(labels(&db), (vec!["Rename task".to_string(), "Rename task".to_string()], vec![]));
+}
+
+#[test]
+#[serial]
+fn test_undo_refuses_rows_changed_since() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    rename_task(&db, 1, "First");
+    rename_task(&db, 2, "Groceries");
+    conn.execute("UPDATE tasks SET title = 'Changed elsewhere' WHERE id = 2", []).unwrap();
+
+    let error = db.undo().unwrap_err().to_string();
+    assert!(error.contains("tasks row 2"), "{}", error);
+    assert_eq!(task_row(conn, 2).unwrap().0, "Changed elsewhere");
+
+    db.undo().unwrap();
+    assert_eq!(task_row(conn, 1).unwrap().0, "Complete project proposal", "The conflicting step is dropped");
+}
+
+#[test]
+#[serial]
+fn test_pomodoro_controls_undo_one_at_a_time() {
+    let db = setup_test_db_with_data();
+    let conn = db.get_connection();
+    let at = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap();
+    let session = || conn.query_row(
+        "SELECT state, (SELECT COUNT(*) FROM time_tracking) FROM pomodoro_sessions",
+        [],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+    ).optional().unwrap();
+    let entries: i64 = conn.query_row("SELECT COUNT(*) FROM time_tracking", [], |row| row.get(0)).unwrap();
+
+    {
+        let _undo = db.undo_step("Start Pomodoro").unwrap();
+        start_pomodoro_session(conn, "TASK", Some(1), at("2024-01-15 09:00:00")).unwrap();
+    }
+    {
+        let _undo = db.undo_step("Pause Pomodoro").unwrap();
+        control_pomodoro_session(conn, "PAUSE", at("2024-01-15 09:10:00")).unwrap();
+    }
+    assert_eq!(session(), Some(("PAUSED".to_string(), entries + 1)));
+
+    assert_eq!(db.undo().unwrap().map(|entry| entry.label).as_deref(), Some("Pause Pomodoro"));
+    assert_eq!(session(), Some(("RUNNING".to_string(), entries + 1)));
+    db.undo().unwrap();
+    assert_eq!(session(), None, "Undoing the start removes the session and its work entry");
+    let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM time_tracking", [], |row| row.get(0)).unwrap();
+    assert_eq!(remaining, entries);
+}
@@ -102,9 +102,9 @@ describe('KanbanBoard Component', () => {
   });
 
   const mockColumns: KanbanColumn[] = [
-    { id: 1, name: 'To Do', column_order: 0 },
-    { id: 2, name: 'In Progress', column_order: 1 },
-    { id: 3, name: 'Done', column_order: 2 }
+    { id: 1, board_id: 1, name: 'To Do', column_order: 0 },
+    { id: 2, board_id: 1, name: 'In Progress', column_order: 1 },
+    { id: 3, board_id: 1, name: 'Done', column_order: 2 }
   ];
 
   const mockTasks: Task[] = [
@@ -135,6 +135,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -153,6 +154,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -164,6 +166,7 @@ describe('KanbanBoard Component', () => {
         expect(screen.getByText('In Progress')).toBeInTheDocument();
         expect(screen.getByText('Done')).toBeInTheDocument();
       });
+      expect((globalThis as any).mockTauriInvoke).toHaveBeenCalledWith('get_kanban_columns', { boardId: 1 });
     });
 
     it('should render tasks in appropriate columns', async () => {
@@ -174,6 +177,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -194,6 +198,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -215,6 +220,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -230,6 +236,7 @@ describe('KanbanBoard Component', () => {
           'create_kanban_column',
           expect.objectContaining({
             column: expect.objectContaining({
+              board_id: 1,
               name: 'Review',
               column_order: 0
             })
@@ -244,6 +251,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -268,6 +276,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -303,6 +312,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -326,6 +336,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -347,6 +358,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -370,6 +382,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -385,6 +398,7 @@ describe('KanbanBoard Component', () => {
 
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={mockTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
@@ -416,6 +430,7 @@ describe('KanbanBoard Component', () => {
       
       render(
         <KanbanBoard 
+          boardId={1}
           tasks={largeTasks} 
           onTaskUpdate={mockOnTaskUpdate} 
           onTaskDelete={mockOnTaskDelete} 
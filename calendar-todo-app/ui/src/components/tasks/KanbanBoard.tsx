@@ -6,12 +6,14 @@ import { TaskCard } from './TaskCard';
 import './Tasks.css';
 
 interface KanbanBoardProps {
+  boardId: number;
   tasks: Task[];
   onTaskUpdate: (task: Task) => Promise<void>;
   onTaskDelete: (id: number) => Promise<void>;
 }
 
 export const KanbanBoard: React.FC<KanbanBoardProps> = ({
+  boardId,
   tasks,
   onTaskUpdate,
   onTaskDelete,
@@ -22,11 +24,11 @@ export const KanbanBoard: React.FC<KanbanBoardProps> = ({
 
   useEffect(() => {
     loadColumns();
-  }, []);
+  }, [boardId]);
 
   const loadColumns = async () => {
     try {
-      const loadedColumns = await kanbanService.getColumns();
+      const loadedColumns = await kanbanService.getColumns(boardId);
       setColumns(loadedColumns);
     } catch (error) {
       console.error('Failed to load Kanban columns:', error);
@@ -38,6 +40,7 @@ export const KanbanBoard: React.FC<KanbanBoardProps> = ({
     
     try {
       const newColumn: KanbanColumn = {
+        board_id: boardId,
         name: newColumnName.trim(),
         column_order: columns.length
       };
@@ -27,7 +27,7 @@ export const categoryService = {
     return invoke('create_category', { category });
   },
 
-  async updateCategory(category: Category): Promise<void> {
+  async updateCategory(category: Category): Promise<Category> {
     return invoke('update_category', { category });
   },
 
@@ -21,7 +21,7 @@ export const eventService = {
     return invoke('create_event', { event });
   },
 
-  async updateEvent(event: Event): Promise<void> {
+  async updateEvent(event: Event): Promise<Event> {
     return invoke('update_event', { event });
   },
 
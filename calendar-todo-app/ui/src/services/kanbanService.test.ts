@@ -14,21 +14,76 @@ describe('Kanban Service', () => {
   });
 
   describe('CRUD Operations', () => {
+    describe('boards', () => {
+      it('should fetch kanban boards successfully', async () => {
+        const mockBoards = [
+          { id: 1, name: 'Default', board_order: 0 },
+          { id: 2, name: 'Release', board_order: 1 }
+        ];
+
+        globalThis.setMockResponse('get_kanban_boards', mockBoards);
+
+        const result = await kanbanService.getBoards();
+        expect(result).toEqual(mockBoards);
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('get_kanban_boards');
+      });
+
+      it('should fetch board templates', async () => {
+        const mockTemplates = [
+          { name: 'Basic', columns: ['To Do', 'In Progress', 'Done'] }
+        ];
+
+        globalThis.setMockResponse('get_kanban_board_templates', mockTemplates);
+
+        const result = await kanbanService.getBoardTemplates();
+        expect(result).toEqual(mockTemplates);
+      });
+
+      it('should create a board from a template', async () => {
+        const board = { name: 'Release', board_order: 1 };
+
+        globalThis.setMockResponse('create_kanban_board_from_template', 2);
+
+        const result = await kanbanService.createBoardFromTemplate(board, 'Basic');
+        expect(result).toBe(2);
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('create_kanban_board_from_template', { board, template: 'Basic' });
+      });
+
+      it('should create, update and delete a board', async () => {
+        const board = { name: 'Sprint', board_order: 2 };
+
+        globalThis.setMockResponse('create_kanban_board', 3);
+        globalThis.setMockResponse('update_kanban_board', undefined);
+        globalThis.setMockResponse('delete_kanban_board', undefined);
+
+        const id = await kanbanService.createBoard(board);
+        await kanbanService.updateBoard({ ...board, id, name: 'Sprint 2' });
+        await kanbanService.deleteBoard(id);
+
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('create_kanban_board', { board });
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('update_kanban_board', { board: { ...board, id: 3, name: 'Sprint 2' } });
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('delete_kanban_board', { id: 3 });
+      });
+    });
+
     describe('getColumns', () => {
       it('should fetch kanban columns successfully', async () => {
         const mockColumns = [
           {
             id: 1,
+            board_id: 1,
             name: 'To Do',
             column_order: 1
           },
           {
             id: 2,
+            board_id: 1,
             name: 'In Progress',
             column_order: 2
           },
           {
             id: 3,
+            board_id: 1,
             name: 'Done',
             column_order: 3
           }
@@ -36,15 +91,15 @@ describe('Kanban Service', () => {
 
         globalThis.setMockResponse('get_kanban_columns', mockColumns);
 
-        const result = await kanbanService.getColumns();
+        const result = await kanbanService.getColumns(1);
         expect(result).toEqual(mockColumns);
-        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('get_kanban_columns');
+        expect(globalThis.mockTauriInvoke).toHaveBeenCalledWith('get_kanban_columns', { boardId: 1 });
       });
 
       it('should handle empty column list', async () => {
         globalThis.setMockResponse('get_kanban_columns', []);
 
-        const result = await kanbanService.getColumns();
+        const result = await kanbanService.getColumns(1);
         expect(result).toEqual([]);
         expect(result).toHaveLength(0);
       });
@@ -52,19 +107,20 @@ describe('Kanban Service', () => {
       it('should handle network errors', async () => {
         globalThis.setMockError('get_kanban_columns', 'Failed to fetch columns');
 
-        await expect(kanbanService.getColumns()).rejects.toThrow('Failed to fetch columns');
+        await expect(kanbanService.getColumns(1)).rejects.toThrow('Failed to fetch columns');
       });
 
       it('should handle database connection errors', async () => {
         globalThis.setMockError('get_kanban_columns', 'Database connection failed');
 
-        await expect(kanbanService.getColumns()).rejects.toThrow('Database connection failed');
+        await expect(kanbanService.getColumns(1)).rejects.toThrow('Database connection failed');
       });
     });
 
     describe('createColumn', () => {
       it('should create a column successfully', async () => {
         const newColumn = {
+          board_id: 1,
           name: 'Review',
           column_order: 4
         };
@@ -80,6 +136,7 @@ describe('Kanban Service', () => {
       it('should handle column creation with all fields', async () => {
         const newColumn = {
           id: 5,
+          board_id: 1,
           name: 'Testing',
           column_order: 5
         };
@@ -93,6 +150,7 @@ describe('Kanban Service', () => {
 
       it('should handle creation errors', async () => {
         const newColumn = {
+          board_id: 1,
           name: 'Invalid Column',
           column_order: -1
         };
@@ -104,6 +162,7 @@ describe('Kanban Service', () => {
 
       it('should handle duplicate column names', async () => {
         const duplicateColumn = {
+          board_id: 1,
           name: 'To Do',
           column_order: 6
         };
@@ -118,6 +177,7 @@ describe('Kanban Service', () => {
       it('should update a column successfully', async () => {
         const columnToUpdate = {
           id: 1,
+          board_id: 1,
           name: 'Updated To Do',
           column_order: 1
         };
@@ -131,6 +191,7 @@ describe('Kanban Service', () => {
       it('should handle update errors', async () => {
         const columnToUpdate = {
           id: 999,
+          board_id: 1,
           name: 'Non-existent',
           column_order: 1
         };
@@ -143,6 +204,7 @@ describe('Kanban Service', () => {
       it('should handle column order conflicts', async () => {
         const columnToUpdate = {
           id: 1,
+          board_id: 1,
           name: 'To Do',
           column_order: 2
         };
@@ -182,6 +244,7 @@ describe('Kanban Service', () => {
     describe('Column Name Validation', () => {
       it('should handle columns with special characters', async () => {
         const specialColumn = {
+          board_id: 1,
           name: 'Review & Test 🚀',
           column_order: 4
         };
@@ -195,6 +258,7 @@ describe('Kanban Service', () => {
 
       it('should handle very long column names', async () => {
         const longNameColumn = {
+          board_id: 1,
           name: 'A'.repeat(255),
           column_order: 5
         };
@@ -207,6 +271,7 @@ describe('Kanban Service', () => {
 
       it('should handle empty name validation', async () => {
         const emptyNameColumn = {
+          board_id: 1,
           name: '',
           column_order: 6
         };
@@ -218,6 +283,7 @@ describe('Kanban Service', () => {
 
       it('should handle whitespace-only names', async () => {
         const whitespaceColumn = {
+          board_id: 1,
           name: '   ',
           column_order: 7
         };
@@ -234,6 +300,7 @@ describe('Kanban Service', () => {
         
         for (let i = 0; i < validOrders.length; i++) {
           const column = {
+            board_id: 1,
             name: `Column ${i + 1}`,
             column_order: validOrders[i]
           };
@@ -246,6 +313,7 @@ describe('Kanban Service', () => {
 
       it('should handle negative column orders', async () => {
         const negativeOrderColumn = {
+          board_id: 1,
           name: 'Invalid Order',
           column_order: -1
         };
@@ -257,6 +325,7 @@ describe('Kanban Service', () => {
 
       it('should handle zero column order', async () => {
         const zeroOrderColumn = {
+          board_id: 1,
           name: 'Zero Order',
           column_order: 0
         };
@@ -272,10 +341,10 @@ describe('Kanban Service', () => {
     describe('Column Ordering', () => {
       it('should maintain column order after creation', async () => {
         const columns = [
-          { name: 'To Do', column_order: 1 },
-          { name: 'In Progress', column_order: 2 },
-          { name: 'Review', column_order: 3 },
-          { name: 'Done', column_order: 4 }
+          { board_id: 1, name: 'To Do', column_order: 1 },
+          { board_id: 1, name: 'In Progress', column_order: 2 },
+          { board_id: 1, name: 'Review', column_order: 3 },
+          { board_id: 1, name: 'Done', column_order: 4 }
         ];
 
         // Create columns
@@ -288,7 +357,7 @@ describe('Kanban Service', () => {
         const orderedColumns = columns.map((col, i) => ({ ...col, id: i + 1 }));
         globalThis.setMockResponse('get_kanban_columns', orderedColumns);
 
-        const result = await kanbanService.getColumns();
+        const result = await kanbanService.getColumns(1);
         expect(result).toHaveLength(4);
         expect(result[0].column_order).toBe(1);
         expect(result[1].column_order).toBe(2);
@@ -299,6 +368,7 @@ describe('Kanban Service', () => {
       it('should handle column reordering', async () => {
         const originalColumn = {
           id: 1,
+          board_id: 1,
           name: 'To Do',
           column_order: 1
         };
@@ -318,14 +388,14 @@ describe('Kanban Service', () => {
     describe('Default Columns', () => {
       it('should handle default kanban setup', async () => {
         const defaultColumns = [
-          { id: 1, name: 'To Do', column_order: 1 },
-          { id: 2, name: 'In Progress', column_order: 2 },
-          { id: 3, name: 'Done', column_order: 3 }
+          { id: 1, board_id: 1, name: 'To Do', column_order: 1 },
+          { id: 2, board_id: 1, name: 'In Progress', column_order: 2 },
+          { id: 3, board_id: 1, name: 'Done', column_order: 3 }
         ];
 
         globalThis.setMockResponse('get_kanban_columns', defaultColumns);
 
-        const result = await kanbanService.getColumns();
+        const result = await kanbanService.getColumns(1);
         expect(result).toEqual(defaultColumns);
         expect(result).toHaveLength(3);
         expect(result[0].name).toBe('To Do');
@@ -339,13 +409,14 @@ describe('Kanban Service', () => {
     it('should handle timeout errors', async () => {
       globalThis.setMockError('get_kanban_columns', 'Request timeout');
 
-      await expect(kanbanService.getColumns()).rejects.toThrow('Request timeout');
+      await expect(kanbanService.getColumns(1)).rejects.toThrow('Request timeout');
     });
 
     it('should handle server errors', async () => {
       globalThis.setMockError('create_kanban_column', 'Internal server error');
 
       const column = {
+        board_id: 1,
         name: 'Test Column',
         column_order: 1
       };
@@ -356,6 +427,7 @@ describe('Kanban Service', () => {
     it('should handle concurrent modification errors', async () => {
       const column = {
         id: 1,
+        board_id: 1,
         name: 'Concurrent Test',
         column_order: 1
       };
@@ -375,6 +447,7 @@ describe('Kanban Service', () => {
   describe('Performance and Edge Cases', () => {
     it('should handle bulk column operations', async () => {
       const columns = Array.from({ length: 20 }, (_, i) => ({
+        board_id: 1,
         name: `Column ${i + 1}`,
         column_order: i + 1
       }));
@@ -390,7 +463,7 @@ describe('Kanban Service', () => {
     it('should handle rapid successive API calls', async () => {
       globalThis.setMockResponse('get_kanban_columns', []);
 
-      const promises = Array.from({ length: 10 }, () => kanbanService.getColumns());
+      const promises = Array.from({ length: 10 }, () => kanbanService.getColumns(1));
       const results = await Promise.all(promises);
       
       results.forEach(result => {
@@ -402,23 +475,24 @@ describe('Kanban Service', () => {
     it('should handle large column datasets', async () => {
       const largeDataset = Array.from({ length: 100 }, (_, i) => ({
         id: i + 1,
+        board_id: 1,
         name: `Column ${i + 1}`,
         column_order: i + 1
       }));
 
       globalThis.setMockResponse('get_kanban_columns', largeDataset);
 
-      const result = await kanbanService.getColumns();
+      const result = await kanbanService.getColumns(1);
       expect(result).toEqual(largeDataset);
       expect(result).toHaveLength(100);
     });
 
     it('should handle unicode column names', async () => {
       const unicodeColumns = [
-        { name: '待办事项', column_order: 1 },
-        { name: 'En Cours', column_order: 2 },
-        { name: 'Готово', column_order: 3 },
-        { name: '🚀 Launch', column_order: 4 }
+        { board_id: 1, name: '待办事项', column_order: 1 },
+        { board_id: 1, name: 'En Cours', column_order: 2 },
+        { board_id: 1, name: 'Готово', column_order: 3 },
+        { board_id: 1, name: '🚀 Launch', column_order: 4 }
       ];
 
       for (let i = 0; i < unicodeColumns.length; i++) {
@@ -433,6 +507,7 @@ describe('Kanban Service', () => {
     it('should handle complete column workflow', async () => {
       // Create a new column
       const newColumn = {
+        board_id: 1,
         name: 'Testing',
         column_order: 4
       };
@@ -443,6 +518,7 @@ describe('Kanban Service', () => {
       // Update the column
       const updatedColumn = {
         id: columnId,
+        board_id: 1,
         name: 'QA Testing',
         column_order: 4
       };
@@ -451,13 +527,13 @@ describe('Kanban Service', () => {
 
       // Verify update
       const allColumns = [
-        { id: 1, name: 'To Do', column_order: 1 },
-        { id: 2, name: 'In Progress', column_order: 2 },
-        { id: 3, name: 'Done', column_order: 3 },
-        { id: 4, name: 'QA Testing', column_order: 4 }
+        { id: 1, board_id: 1, name: 'To Do', column_order: 1 },
+        { id: 2, board_id: 1, name: 'In Progress', column_order: 2 },
+        { id: 3, board_id: 1, name: 'Done', column_order: 3 },
+        { id: 4, board_id: 1, name: 'QA Testing', column_order: 4 }
       ];
       globalThis.setMockResponse('get_kanban_columns', allColumns);
-      const result = await kanbanService.getColumns();
+      const result = await kanbanService.getColumns(1);
       expect(result).toHaveLength(4);
       expect(result[3].name).toBe('QA Testing');
 
@@ -469,13 +545,13 @@ describe('Kanban Service', () => {
     it('should handle column reordering workflow', async () => {
       // Initial columns
       const initialColumns = [
-        { id: 1, name: 'To Do', column_order: 1 },
-        { id: 2, name: 'In Progress', column_order: 2 },
-        { id: 3, name: 'Done', column_order: 3 }
+        { id: 1, board_id: 1, name: 'To Do', column_order: 1 },
+        { id: 2, board_id: 1, name: 'In Progress', column_order: 2 },
+        { id: 3, board_id: 1, name: 'Done', column_order: 3 }
       ];
       globalThis.setMockResponse('get_kanban_columns', initialColumns);
       
-      let result = await kanbanService.getColumns();
+      let result = await kanbanService.getColumns(1);
       expect(result[0].column_order).toBe(1);
       expect(result[1].column_order).toBe(2);
       expect(result[2].column_order).toBe(3);
@@ -484,28 +560,31 @@ describe('Kanban Service', () => {
       globalThis.setMockResponse('update_kanban_column', undefined);
       await kanbanService.updateColumn({
         id: 3,
+        board_id: 1,
         name: 'Done',
         column_order: 1
       });
       await kanbanService.updateColumn({
         id: 1,
+        board_id: 1,
         name: 'To Do',
         column_order: 2
       });
       await kanbanService.updateColumn({
         id: 2,
+        board_id: 1,
         name: 'In Progress',
         column_order: 3
       });
 
       // Verify new order
       const reorderedColumns = [
-        { id: 3, name: 'Done', column_order: 1 },
-        { id: 1, name: 'To Do', column_order: 2 },
-        { id: 2, name: 'In Progress', column_order: 3 }
+        { id: 3, board_id: 1, name: 'Done', column_order: 1 },
+        { id: 1, board_id: 1, name: 'To Do', column_order: 2 },
+        { id: 2, board_id: 1, name: 'In Progress', column_order: 3 }
       ];
       globalThis.setMockResponse('get_kanban_columns', reorderedColumns);
-      result = await kanbanService.getColumns();
+      result = await kanbanService.getColumns(1);
       expect(result[0].name).toBe('Done');
       expect(result[1].name).toBe('To Do');
       expect(result[2].name).toBe('In Progress');
@@ -515,6 +594,7 @@ describe('Kanban Service', () => {
   describe('Type Safety and Interface Compliance', () => {
     it('should enforce KanbanColumn interface compliance', async () => {
       const validColumn = {
+        board_id: 1,
         name: 'Type Test',
         column_order: 1
       };
@@ -529,6 +609,7 @@ describe('Kanban Service', () => {
     it('should handle optional id field correctly', async () => {
       const columnWithId = {
         id: 1,
+        board_id: 1,
         name: 'Optional ID Test',
         column_order: 1
       };
@@ -541,6 +622,7 @@ describe('Kanban Service', () => {
 
     it('should validate required fields', async () => {
       const incompleteColumn = {
+        board_id: 1,
         name: 'Incomplete Column'
         // Missing column_order
       } as any;
@@ -1,14 +1,50 @@
 import { invoke } from '@tauri-apps/api/tauri';
 
+export interface KanbanBoard {
+  id?: number;
+  name: string;
+  board_order: number;
+}
+
+export interface KanbanBoardTemplate {
+  name: string;
+  columns: string[];
+}
+
 export interface KanbanColumn {
   id?: number;
+  board_id: number;
   name: string;
   column_order: number;
 }
 
 export const kanbanService = {
-  async getColumns(): Promise<KanbanColumn[]> {
-    return invoke('get_kanban_columns');
+  async getBoards(): Promise<KanbanBoard[]> {
+    return invoke('get_kanban_boards');
+  },
+
+  async getBoardTemplates(): Promise<KanbanBoardTemplate[]> {
+    return invoke('get_kanban_board_templates');
+  },
+
+  async createBoard(board: KanbanBoard): Promise<number> {
+    return invoke('create_kanban_board', { board });
+  },
+
+  async createBoardFromTemplate(board: KanbanBoard, template: string): Promise<number> {
+    return invoke('create_kanban_board_from_template', { board, template });
+  },
+
+  async updateBoard(board: KanbanBoard): Promise<void> {
+    return invoke('update_kanban_board', { board });
+  },
+
+  async deleteBoard(id: number): Promise<void> {
+    return invoke('delete_kanban_board', { id });
+  },
+
+  async getColumns(boardId: number): Promise<KanbanColumn[]> {
+    return invoke('get_kanban_columns', { boardId });
   },
 
   async createColumn(column: KanbanColumn): Promise<number> {
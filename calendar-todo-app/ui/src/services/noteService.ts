@@ -24,7 +24,7 @@ export const noteService = {
     return invoke('create_note', { note });
   },
 
-  async updateNote(note: Note): Promise<void> {
+  async updateNote(note: Note): Promise<Note> {
     return invoke('update_note', { note });
   },
 
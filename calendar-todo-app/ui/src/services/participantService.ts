@@ -18,7 +18,7 @@ export const participantService = {
     return invoke('create_participant', { participant });
   },
 
-  async updateParticipant(participant: Participant): Promise<void> {
+  async updateParticipant(participant: Participant): Promise<Participant> {
     return invoke('update_participant', { participant });
   },
 
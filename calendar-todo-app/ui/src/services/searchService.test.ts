@@ -7,6 +7,9 @@ declare global {
   var resetMocks: () => void;
 }
 
+// search_all answers with a page of results and facet counts
+const searchPage = (results: any) => ({ results, total: Array.isArray(results) ? results.length : 0, facets: null });
+
 describe('Search Service', () => {
   beforeEach(() => {
     global.resetMocks();
@@ -43,11 +46,11 @@ describe('Search Service', () => {
         }
       ];
 
-      global.setMockResponse('search_all', mockResults.map(r => ({
+      global.setMockResponse('search_all', searchPage(mockResults.map(r => ({
         ...r,
         item_type: r.itemType,
         category_id: r.categoryId
-      })));
+      }))));
 
       const result = await searchService.searchAll('project');
 
@@ -57,7 +60,7 @@ describe('Search Service', () => {
     });
 
     it('should handle empty search results', async () => {
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       const result = await searchService.searchAll('nonexistent');
 
@@ -84,7 +87,7 @@ describe('Search Service', () => {
         }
       ];
 
-      global.setMockResponse('search_all', backendResponse);
+      global.setMockResponse('search_all', searchPage(backendResponse));
 
       const result = await searchService.searchAll('test');
 
@@ -110,7 +113,7 @@ describe('Search Service', () => {
         }
       ];
 
-      global.setMockResponse('search_all', backendResponse);
+      global.setMockResponse('search_all', searchPage(backendResponse));
 
       const result = await searchService.searchAll('minimal');
 
@@ -402,7 +405,7 @@ describe('Search Service', () => {
 
   describe('Query Validation and Edge Cases', () => {
     it('should handle empty query strings', async () => {
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       const result = await searchService.searchAll('');
 
@@ -413,7 +416,7 @@ describe('Search Service', () => {
     });
 
     it('should handle whitespace-only queries', async () => {
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       const result = await searchService.searchAll('   ');
 
@@ -431,7 +434,7 @@ describe('Search Service', () => {
       ];
 
       for (const query of specialQueries) {
-        global.setMockResponse('search_all', []);
+        global.setMockResponse('search_all', searchPage([]));
         const result = await searchService.searchAll(query);
         expect(result).toEqual([]);
       }
@@ -439,7 +442,7 @@ describe('Search Service', () => {
 
     it('should handle very long query strings', async () => {
       const longQuery = 'a'.repeat(1000);
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       const result = await searchService.searchAll(longQuery);
 
@@ -490,7 +493,7 @@ describe('Search Service', () => {
         priority: (i % 3) + 1
       }));
 
-      global.setMockResponse('search_all', largeResultSet);
+      global.setMockResponse('search_all', searchPage(largeResultSet));
 
       const result = await searchService.searchAll('result');
 
@@ -499,7 +502,7 @@ describe('Search Service', () => {
     });
 
     it('should handle concurrent search requests', async () => {
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       const promises = Array.from({ length: 10 }, (_, i) => 
         searchService.searchAll(`query${i}`)
@@ -512,7 +515,7 @@ describe('Search Service', () => {
     });
 
     it('should handle rapid successive searches', async () => {
-      global.setMockResponse('search_all', []);
+      global.setMockResponse('search_all', searchPage([]));
 
       for (let i = 0; i < 50; i++) {
         const result = await searchService.searchAll(`rapid${i}`);
@@ -537,7 +540,7 @@ describe('Search Service', () => {
         { id: 2, title: 'Second', item_type: 'NOTE', date: '2024-01-16T00:00:00Z' }
       ];
 
-      global.setMockResponse('search_all', orderedResults);
+      global.setMockResponse('search_all', searchPage(orderedResults));
 
       const result = await searchService.searchAll('ordered');
 
@@ -556,7 +559,7 @@ describe('Search Service', () => {
         priority: 2
       };
 
-      global.setMockResponse('search_all', [completeResult]);
+      global.setMockResponse('search_all', searchPage([completeResult]));
 
       const result = await searchService.searchAll('complete');
 
@@ -578,7 +581,7 @@ describe('Search Service', () => {
         { id: 3, title: 'Note', item_type: 'NOTE' }
       ];
 
-      global.setMockResponse('search_all', mixedResults);
+      global.setMockResponse('search_all', searchPage(mixedResults));
 
       const result = await searchService.searchAll('mixed');
 
@@ -609,7 +612,7 @@ describe('Search Service', () => {
     });
 
     it('should handle malformed backend responses', async () => {
-      global.setMockResponse('search_all', 'invalid-json-response');
+      global.setMockResponse('search_all', searchPage('invalid-json-response'));
 
       // This should not throw, just return the invalid response
       const result = await searchService.searchAll('malformed');
@@ -617,7 +620,7 @@ describe('Search Service', () => {
     });
 
     it('should handle null backend responses', async () => {
-      global.setMockResponse('search_all', null);
+      global.setMockResponse('search_all', searchPage(null));
 
       const result = await searchService.searchAll('null-response');
       expect(result).toBeNull();
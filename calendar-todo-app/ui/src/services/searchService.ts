@@ -4,21 +4,56 @@ export interface SearchResult {
   id: number;
   title: string;
   description?: string;
-  itemType: 'EVENT' | 'TASK' | 'NOTE';
+  itemType: 'EVENT' | 'TASK' | 'NOTE' | 'PARTICIPANT' | 'CATEGORY';
   date?: string;
   categoryId?: number;
   priority?: number;
   status?: string;
 }
 
+export interface FacetCount {
+  value: string;
+  label: string;
+  count: number;
+}
+
+export interface SearchFacets {
+  itemTypes: FacetCount[];
+  categories: FacetCount[];
+  statuses: FacetCount[];
+  priorities: FacetCount[];
+  months: FacetCount[];
+}
+
+export interface FacetedSearchResults {
+  results: SearchResult[];
+  total: number;
+  facets?: SearchFacets;
+}
+
 export const searchService = {
   async searchAll(query: string): Promise<SearchResult[]> {
-    const results: any[] = await invoke('search_all', { query });
-    return results.map(r => ({
-      ...r,
-      itemType: r.item_type,
-      categoryId: r.category_id,
-    }));
+    const { results } = await searchService.searchAllWithFacets(query);
+    return results;
+  },
+
+  async searchAllWithFacets(query: string): Promise<FacetedSearchResults> {
+    const page: any = await invoke('search_all', { query });
+    return {
+      results: page.results.map((r: any) => ({
+        ...r,
+        itemType: r.item_type,
+        categoryId: r.category_id,
+      })),
+      total: page.total,
+      facets: page.facets && {
+        itemTypes: page.facets.item_types,
+        categories: page.facets.categories,
+        statuses: page.facets.statuses,
+        priorities: page.facets.priorities,
+        months: page.facets.months,
+      },
+    };
   },
 
   async searchEvents(params: {
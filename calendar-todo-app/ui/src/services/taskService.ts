@@ -12,6 +12,9 @@ export interface Task {
   kanban_column_id?: number;
   kanban_order?: number;
   completed_at?: string;
+  blocked?: boolean;
+  start_date?: string;
+  is_someday?: boolean;
 }
 
 export const taskService = {
@@ -23,11 +26,15 @@ export const taskService = {
     return invoke('get_tasks_in_column', { columnId });
   },
 
+  async getBoardTasks(boardId: number): Promise<Task[]> {
+    return invoke('get_board_tasks', { boardId });
+  },
+
   async createTask(task: Task): Promise<number> {
     return invoke('create_task', { task });
   },
 
-  async updateTask(task: Task): Promise<void> {
+  async updateTask(task: Task): Promise<Task> {
     return invoke('update_task', { task });
   },
 